@@ -0,0 +1,157 @@
+use chrono::{Duration, Utc};
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use rust::assets;
+use rust::db::Repository;
+use rust::functionality::{
+    parse_models, Answer, Method, ProbabilityComputer, Score, Selection, Service,
+};
+use std::path::Path;
+use tokio::runtime::Runtime;
+
+const SIZES: [usize; 3] = [1_000, 10_000, 100_000];
+/// Answers per question in the synthetic databases.
+const ANSWERS: usize = 5;
+/// Questions per session.
+const NUM: usize = 20;
+
+fn question_set(size: usize) -> String {
+    let mut yaml =
+        String::from("name: synthetic\ntype_: default\ndata:\n  question_prefix: ''\nitems:\n");
+    for i in 0..size {
+        yaml.push_str(&format!(
+            "- id: q{i}\n  question: Question {i}\n  answers:\n  - Answer {i}\n"
+        ));
+    }
+    yaml
+}
+
+/// Gives every question `ANSWERS` answers of varying age and score.
+const ANSWERS_SQL: &str = "
+    INSERT INTO answers(question_id, time, correct, score)
+    WITH RECURSIVE k(i) AS (SELECT 0 UNION ALL SELECT i + 1 FROM k WHERE i < $1)
+    SELECT
+        id,
+        strftime('%Y-%m-%dT%H:%M:%S+00:00', 'now', printf('-%d hours', (id * 7 + i * 13) % 5000)),
+        (id + i) % 3 != 0,
+        ((id + i) % 3 != 0) * 1.0
+    FROM questions, k;
+";
+
+/// Identifies what a database is built from, the schema and the generated
+/// data, so a cached one is rebuilt once either changes.
+fn fingerprint(questions: &str) -> String {
+    let mut input = Vec::new();
+    for migration in sqlx::migrate!("../sql/migrations").iter() {
+        input.extend(migration.version.to_le_bytes());
+        input.extend(&*migration.checksum);
+    }
+    input.extend(questions.as_bytes());
+    input.extend(ANSWERS_SQL.as_bytes());
+    input.extend(ANSWERS.to_le_bytes());
+    assets::hash(&input)[..16].to_string()
+}
+
+/// A database with `size` questions in one set, each with a few answers of
+/// varying age and score. Built once per size, schema and generator, and kept
+/// in the target directory for later runs.
+fn database(rt: &Runtime, size: usize) -> String {
+    let questions = question_set(size);
+    let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join(format!(
+        "bench-{}-{}.db",
+        size,
+        fingerprint(&questions)
+    ));
+    let url = format!("sqlite://{}?mode=rwc", path.display());
+    if path.exists() {
+        return url;
+    }
+    rt.block_on(async {
+        let repo = Repository::new(&url).await.unwrap();
+        let mut service = Service::new(&repo).await.unwrap();
+        let models = parse_models(&[questions]).unwrap();
+        service.import(&models, false).await.unwrap();
+        let pool = sqlx::SqlitePool::connect(&url).await.unwrap();
+        sqlx::query(ANSWERS_SQL)
+            .bind(ANSWERS as i64 - 1)
+            .execute(&pool)
+            .await
+            .unwrap();
+    });
+    url
+}
+
+fn startup(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let mut group = c.benchmark_group("service_new");
+    group.sample_size(10);
+    for size in SIZES {
+        let url = database(&rt, size);
+        let repo = rt.block_on(Repository::new(&url)).unwrap();
+        group.bench_with_input(BenchmarkId::from_parameter(size), &repo, |b, repo| {
+            b.to_async(&rt)
+                .iter(|| async { Service::new(repo).await.unwrap() })
+        });
+    }
+    group.finish();
+}
+
+fn selection(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    for size in SIZES {
+        let url = database(&rt, size);
+        let repo = rt.block_on(Repository::new(&url)).unwrap();
+        let mut service = rt.block_on(Service::new(&repo)).unwrap();
+        service.seed(0);
+        let mut group = c.benchmark_group(format!("select_{}", size));
+        for method in Method::ALL {
+            group.bench_function(method.to_string(), |b| {
+                b.iter(|| service.select("synthetic", method, NUM, Selection::All))
+            });
+        }
+        group.finish();
+    }
+}
+
+fn probability(c: &mut Criterion) {
+    let mut group = c.benchmark_group("probability_computer");
+    for size in SIZES {
+        let ids = (0..size as i64).collect::<Vec<i64>>();
+        let start = Utc::now() - Duration::days(365);
+        let answers = || {
+            (0..size * ANSWERS)
+                .map(|i| Answer {
+                    question_id: (i % size) as i64,
+                    time: start + Duration::minutes((i * 7 % 500_000) as i64),
+                    score: Score::from(i % 3 != 0),
+                })
+                .collect::<Vec<Answer>>()
+        };
+        group.bench_with_input(BenchmarkId::new("new", size), &size, |b, _| {
+            b.iter_batched(
+                answers,
+                |a| ProbabilityComputer::new(a, &ids),
+                BatchSize::LargeInput,
+            )
+        });
+        // Every answer starts from the same computer, instead of one that
+        // piles up the answers of the earlier iterations.
+        group.bench_with_input(BenchmarkId::new("add_answer", size), &size, |b, _| {
+            b.iter_batched(
+                || ProbabilityComputer::new(answers(), &ids),
+                |mut computer| {
+                    computer.add_answer(Answer {
+                        question_id: size as i64 / 2,
+                        time: Utc::now(),
+                        score: Score::CORRECT,
+                    });
+                    computer
+                },
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, startup, selection, probability);
+criterion_main!(benches);
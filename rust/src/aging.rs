@@ -0,0 +1,76 @@
+use crate::storage::Storage;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, HashSet};
+
+/// How long ago the questions of a set were answered.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SetAge {
+    pub set: String,
+    pub questions: usize,
+    /// Names of the questions never answered.
+    pub never_answered: Vec<String>,
+    /// Names of the questions last answered before the cutoff, with when.
+    pub stale: Vec<(String, DateTime<Utc>)>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct AgingReport {
+    /// Every set with questions of its own that isn't archived, by name.
+    pub sets: Vec<SetAge>,
+    /// Sets in the database that none of the question set files has any
+    /// more, so `trivial load` no longer updates them.
+    pub missing_sources: Vec<String>,
+}
+
+/// Finds the questions of each set that were never answered or not since
+/// `stale_before`, and, given the names of the sets in the question set
+/// files, the sets whose file is gone.
+pub async fn aging_report(
+    repo: &dyn Storage,
+    stale_before: DateTime<Utc>,
+    sources: Option<&[String]>,
+) -> Result<AgingReport> {
+    let archived = repo
+        .get_archived_sets()
+        .await?
+        .into_iter()
+        .collect::<HashSet<_>>();
+    let mut sets = BTreeMap::<String, SetAge>::new();
+    for q in repo.get_all_questions().await? {
+        if archived.contains(&q.factory) {
+            continue;
+        }
+        let age = sets.entry(q.factory.clone()).or_insert_with(|| SetAge {
+            set: q.factory.clone(),
+            ..Default::default()
+        });
+        age.questions += 1;
+        match q.last_answered_at {
+            None => age.never_answered.push(q.name),
+            Some(last) if last < stale_before => age.stale.push((q.name, last)),
+            Some(_) => {}
+        }
+    }
+    for age in sets.values_mut() {
+        age.never_answered.sort();
+        age.stale
+            .sort_by(|a, b| a.1.cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
+    }
+
+    let mut missing_sources = Vec::new();
+    if let Some(sources) = sources {
+        missing_sources = repo
+            .get_all_question_factories()
+            .await?
+            .into_iter()
+            .map(|f| f.name)
+            .filter(|name| !sources.contains(name))
+            .collect();
+        missing_sources.sort();
+    }
+    Ok(AgingReport {
+        sets: sets.into_values().collect(),
+        missing_sources,
+    })
+}
@@ -0,0 +1,147 @@
+use crate::encoding;
+use crate::expr;
+use crate::functionality::{
+    FactoryOptions, QuestionFactory, QuestionKey, QuestionRunner, QuestionSetFactory, Score,
+    SetIndex,
+};
+use crate::hints::Hints;
+use crate::input::is_shortcut;
+use crate::output;
+use anyhow::{bail, Context, Result};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct ArithmeticData {
+    #[serde(default)]
+    question_prefix: String,
+    /// Relative difference allowed between the answer and the exact value,
+    /// e.g. 0.1 for estimating.
+    #[serde(default)]
+    tolerance: f64,
+    #[serde(flatten)]
+    options: FactoryOptions,
+    #[serde(skip)]
+    depends: Vec<String>,
+}
+
+impl QuestionFactory for ArithmeticData {
+    fn build(&self, data: &[u8]) -> Result<Box<dyn QuestionRunner>> {
+        let mut question = encoding::decode::<Arithmetic>(data)?;
+        for (name, [min, max]) in &question.vars {
+            if min > max {
+                bail!("{}: the range of {} is empty", question.id, name);
+            }
+        }
+        let lowest = question.vars.iter().map(|(n, [min, _])| (n.clone(), *min));
+        expr::eval_with(&question.answer, &lookup(&lowest.collect()))
+            .with_context(|| format!("{}: answer {:?}", question.id, question.answer))?;
+        question.tolerance = question.tolerance.or(Some(self.tolerance));
+        question.question = format!("{}{}", self.question_prefix, question.question);
+        Ok(Box::new(question) as Box<dyn QuestionRunner>)
+    }
+}
+
+impl QuestionSetFactory for ArithmeticData {
+    fn build_set(&self, s: &SetIndex, set_name: &str) -> Vec<QuestionKey> {
+        s.get_factory(set_name).to_vec()
+    }
+
+    fn depends_on(&self) -> &Vec<String> {
+        &self.depends
+    }
+}
+
+/// A template asked with new numbers every time, e.g. two-digit
+/// multiplication:
+///
+/// ```yaml
+/// - id: two-digit-multiplication
+///   question: '{a} × {b}'
+///   answer: a * b
+///   vars:
+///     a: [10, 99]
+///     b: [10, 99]
+/// ```
+///
+/// The answers are tracked for the template, however the numbers came out.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Arithmetic {
+    id: String,
+    /// The question, with `{var}` standing for the variable's value.
+    question: String,
+    /// Expression of the variables giving the answer, see `expr::eval`.
+    answer: String,
+    /// The smallest and largest value of each variable, both included.
+    vars: BTreeMap<String, [i64; 2]>,
+    /// Overrides the set's, see `ArithmeticData::tolerance`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tolerance: Option<f64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    hints: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    /// Questions to know before this one is introduced, see
+    /// `Question::requires`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    requires: Vec<String>,
+}
+
+fn lookup(values: &BTreeMap<String, i64>) -> impl Fn(&str) -> Option<f64> + '_ {
+    |name| values.get(name).map(|&v| v as f64)
+}
+
+impl Arithmetic {
+    /// The question with the values filled in.
+    fn render(&self, values: &BTreeMap<String, i64>) -> String {
+        let mut question = self.question.clone();
+        for (name, value) in values {
+            question = question.replace(&format!("{{{}}}", name), &value.to_string());
+        }
+        question
+    }
+}
+
+impl QuestionRunner for Arithmetic {
+    fn run(&self) -> Result<Score> {
+        let mut rng = rand::thread_rng();
+        let values = self
+            .vars
+            .iter()
+            .map(|(name, [min, max])| (name.clone(), rng.gen_range(*min..=*max)))
+            .collect::<BTreeMap<_, _>>();
+        let expected = expr::eval_with(&self.answer, &lookup(&values))?;
+        let validator = |input: &str| match expr::eval(input) {
+            _ if is_shortcut(input) => Ok(()),
+            Ok(_) => Ok(()),
+            Err(err) => Err(format!("{:#}", err)),
+        };
+
+        let mut hints = Hints::new(&self.hints);
+        let question = self.render(&values);
+        let answer = hints.prompt(&question, Some(validator))?;
+        let value = expr::eval(&answer)?;
+        let tolerance = self.tolerance.unwrap_or_default().max(1e-9);
+        let correct = (value - expected).abs() <= tolerance * expected.abs().max(1.);
+        if correct {
+            output::feedback(output::correct(&format!("Correct! It's {}", expected)));
+        } else {
+            output::feedback(output::wrong(&format!("Wrong. The answer is {}", expected)));
+        }
+        println!();
+        Ok(hints.score(correct.into()))
+    }
+
+    fn name(&self) -> String {
+        self.id.clone()
+    }
+
+    fn question_text(&self) -> String {
+        self.question.clone()
+    }
+
+    fn answer_text(&self) -> String {
+        self.answer.clone()
+    }
+}
@@ -0,0 +1,98 @@
+use crate::db::Asset;
+use crate::encoding;
+use crate::functionality::Models;
+use anyhow::{bail, Context, Result};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::RwLock;
+
+/// Directory next to the set files with the images, sounds and other files
+/// questions refer to in one of `FIELDS`, e.g. `assets: [assets/flags/fr.png]`.
+pub const DIR: &str = "assets";
+
+/// The fields of a question that name assets, each either a path or a list
+/// of them: `assets` of default questions, and `image` or `sound` of the
+/// types that pass their fields on, such as scripts.
+pub const FIELDS: &[&str] = &["assets", "image", "sound"];
+
+/// The directory of the sets, which the paths of the assets are relative to.
+static ROOT: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// Sets the directory the assets are shown from, see `locate`.
+pub fn set_root(dir: &Path) {
+    *ROOT.write().unwrap() = Some(dir.to_path_buf());
+}
+
+/// Where to find the asset, in the directory of the sets if it's known.
+pub fn locate(path: &str) -> String {
+    match &*ROOT.read().unwrap() {
+        Some(dir) => dir.join(path).display().to_string(),
+        None => path.to_string(),
+    }
+}
+
+/// The paths of the assets the questions refer to, relative to the directory
+/// of the sets.
+pub fn references(models: &Models) -> Result<BTreeSet<String>> {
+    let mut paths = BTreeSet::new();
+    for q in &models.questions {
+        let data = encoding::decode::<Value>(&q.data)?;
+        for field in FIELDS {
+            match data.get(field) {
+                Some(Value::String(path)) => {
+                    paths.insert(path.clone());
+                }
+                Some(Value::Array(values)) => {
+                    paths.extend(values.iter().filter_map(Value::as_str).map(str::to_string));
+                }
+                _ => {}
+            }
+        }
+    }
+    for path in &paths {
+        let path = Path::new(path);
+        let inside =
+            path.starts_with(DIR) && path.components().all(|c| matches!(c, Component::Normal(_)));
+        if !inside {
+            bail!("{} is outside of the {} directory", path.display(), DIR);
+        }
+    }
+    Ok(paths)
+}
+
+pub fn hash(data: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(data))
+}
+
+/// Reads the assets from the directory of the sets, failing if any of them
+/// is missing.
+pub fn scan(dir: &Path, paths: &BTreeSet<String>) -> Result<Vec<Asset>> {
+    paths
+        .iter()
+        .map(|path| {
+            let data = fs::read(dir.join(path)).with_context(|| format!("reading {}", path))?;
+            Ok(Asset {
+                path: path.clone(),
+                sha256: hash(&data),
+                size: data.len() as i64,
+            })
+        })
+        .collect()
+}
+
+/// Checks that the assets in the directory of the sets are still the ones
+/// recorded, returning a description of each that isn't.
+pub fn verify(dir: &Path, assets: &[Asset]) -> Vec<String> {
+    let mut problems = Vec::new();
+    for asset in assets {
+        match fs::read(dir.join(&asset.path)) {
+            Ok(data) if hash(&data) == asset.sha256 => {}
+            Ok(_) => problems.push(format!("{} changed since it was loaded", asset.path)),
+            Err(err) => problems.push(format!("{} can't be read: {}", asset.path, err)),
+        }
+    }
+    problems
+}
@@ -0,0 +1,217 @@
+use crate::db::AnswerDetails;
+use crate::storage::Storage;
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+/// Answers waiting to be written before `Autosave::save` waits too.
+const QUEUE: usize = 64;
+
+/// An answer on its way to the database.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PendingAnswer {
+    pub question_id: i64,
+    pub time: DateTime<Utc>,
+    pub correct: bool,
+    pub score: f64,
+    #[serde(default)]
+    pub error: Option<f64>,
+    #[serde(default)]
+    pub text: Option<String>,
+    #[serde(default)]
+    pub steps: Option<String>,
+    #[serde(default)]
+    pub params: Option<String>,
+    #[serde(default)]
+    pub tolerance: Option<f64>,
+    /// The question's probability with the answer.
+    pub probability: f64,
+}
+
+impl PendingAnswer {
+    fn details(&self) -> AnswerDetails {
+        AnswerDetails {
+            error: self.error,
+            text: self.text.clone(),
+            steps: self.steps.clone(),
+            params: self.params.clone(),
+            tolerance: self.tolerance,
+        }
+    }
+}
+
+enum Message {
+    Answer(PendingAnswer),
+    Flush(oneshot::Sender<()>),
+}
+
+/// The journal file and how many of its answers aren't in the database yet.
+struct Journal {
+    file: File,
+    pending: usize,
+    /// Whether writing an answer failed, which keeps the journal around so
+    /// the answer is retried on the next start.
+    failed: bool,
+}
+
+/// Writes answers to the database on a background task, so a slow disk
+/// doesn't hold up the next question. Every answer is first appended to a
+/// journal file and synced, which is quick, and the journal is emptied once
+/// the database has caught up. Answers still in the journal when the
+/// process was killed are written by the next `Autosave::start`.
+///
+/// Dropping it lets the task finish the queue in the background while the
+/// runtime lives; `close` waits for it.
+pub struct Autosave {
+    tx: mpsc::Sender<Message>,
+    task: JoinHandle<Result<()>>,
+    journal: Arc<Mutex<Journal>>,
+}
+
+impl Autosave {
+    /// Writes the answers left in the journal by an earlier run, returning
+    /// how many there were, and starts writing new ones.
+    pub async fn start(repo: Arc<dyn Storage>, journal: &Path) -> Result<(Autosave, usize)> {
+        let recovered = recover(repo.as_ref(), journal).await?;
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(journal)
+            .with_context(|| format!("opening {}", journal.display()))?;
+        file.set_len(0)?;
+        let journal = Arc::new(Mutex::new(Journal {
+            file,
+            pending: 0,
+            failed: false,
+        }));
+        let (tx, rx) = mpsc::channel(QUEUE);
+        let task = tokio::spawn(write(repo, rx, journal.clone()));
+        Ok((Autosave { tx, task, journal }, recovered))
+    }
+
+    /// Journals the answer and queues it to be written to the database.
+    pub async fn save(&self, answer: PendingAnswer) -> Result<()> {
+        {
+            let mut journal = self.journal.lock().unwrap();
+            writeln!(journal.file, "{}", serde_json::to_string(&answer)?)?;
+            journal.file.sync_data()?;
+            journal.pending += 1;
+        }
+        self.tx
+            .send(Message::Answer(answer))
+            .await
+            .map_err(|_| anyhow!("the answers can't be saved anymore, see the log"))
+    }
+
+    /// Waits until the answers saved so far are in the database, e.g. before
+    /// reading them back.
+    pub async fn flush(&self) -> Result<()> {
+        let (done, wait) = oneshot::channel();
+        self.tx
+            .send(Message::Flush(done))
+            .await
+            .map_err(|_| anyhow!("the answers can't be saved anymore, see the log"))?;
+        wait.await?;
+        Ok(())
+    }
+
+    /// Writes the remaining answers, failing if any couldn't be written.
+    /// Those stay in the journal for the next start.
+    pub async fn close(self) -> Result<()> {
+        drop(self.tx);
+        self.task.await?
+    }
+}
+
+async fn write(
+    repo: Arc<dyn Storage>,
+    mut rx: mpsc::Receiver<Message>,
+    journal: Arc<Mutex<Journal>>,
+) -> Result<()> {
+    let mut first_error = None;
+    while let Some(message) = rx.recv().await {
+        let answer = match message {
+            Message::Answer(answer) => answer,
+            Message::Flush(done) => {
+                let _ = done.send(());
+                continue;
+            }
+        };
+        let res = repo
+            .add_answer(
+                answer.question_id,
+                answer.time,
+                answer.correct,
+                answer.score,
+                &answer.details(),
+                answer.probability,
+            )
+            .await;
+        let mut journal = journal.lock().unwrap();
+        journal.pending -= 1;
+        if let Err(err) = res {
+            warn!(
+                "saving an answer to question {}: {:#}",
+                answer.question_id, err
+            );
+            journal.failed = true;
+            first_error.get_or_insert(err);
+        }
+        if journal.pending == 0 && !journal.failed {
+            journal.file.set_len(0)?;
+        }
+    }
+    match first_error {
+        Some(err) => Err(err.context("saving answers, they are kept in the journal")),
+        None => Ok(()),
+    }
+}
+
+/// Writes the answers in the journal that aren't in the database.
+async fn recover(repo: &dyn Storage, journal: &Path) -> Result<usize> {
+    if !journal.exists() {
+        return Ok(0);
+    }
+    let text =
+        fs::read_to_string(journal).with_context(|| format!("reading {}", journal.display()))?;
+    let mut recovered = 0;
+    for line in text.lines() {
+        // The last line is cut short if the process was killed writing it,
+        // and then the answer never made it past the journal.
+        let Ok(answer) = serde_json::from_str::<PendingAnswer>(line) else {
+            continue;
+        };
+        if repo.get_question_by_id(answer.question_id).await.is_err() {
+            continue;
+        }
+        let saved = repo.get_answers(answer.question_id).await?;
+        if saved.iter().any(|a| a.time == answer.time) {
+            continue;
+        }
+        repo.add_answer(
+            answer.question_id,
+            answer.time,
+            answer.correct,
+            answer.score,
+            &answer.details(),
+            answer.probability,
+        )
+        .await?;
+        recovered += 1;
+    }
+    Ok(recovered)
+}
+
+/// Where the journal of the database at `db` is kept.
+pub fn journal_path(db: &Path) -> PathBuf {
+    let mut path = db.as_os_str().to_owned();
+    path.push(".pending");
+    PathBuf::from(path)
+}
@@ -0,0 +1,94 @@
+use std::path::PathBuf;
+
+use anyhow::{bail, Result};
+use chrono::{Months, Utc};
+use rust::aging::aging_report;
+use rust::functionality::load_models_dir;
+use rust::{db, output};
+
+/// Lists the questions never answered or not answered in a long time, and
+/// the sets whose question set file is gone, to keep a large question bank
+/// healthy. Archived sets are left out.
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    /// Questions not answered in this many months are stale
+    #[arg(short, long, default_value_t = 6)]
+    months: u32,
+    /// List the questions instead of counting them per set
+    #[arg(short, long)]
+    list: bool,
+    /// Path to the question set files, to find sets whose file is gone.
+    /// Defaults to the one in the config file
+    #[arg(short, long)]
+    pub path: Option<PathBuf>,
+}
+
+pub async fn run(repo: &db::Repository, args: Args) -> Result<()> {
+    let Some(stale_before) = Utc::now().checked_sub_months(Months::new(args.months)) else {
+        bail!("{} months is too long ago", args.months);
+    };
+    let sources = match &args.path {
+        Some(path) => Some(
+            load_models_dir(path)?
+                .factories
+                .into_iter()
+                .map(|f| f.name)
+                .collect::<Vec<_>>(),
+        ),
+        None => None,
+    };
+    let report = aging_report(repo, stale_before, sources.as_deref()).await?;
+
+    if args.list {
+        for set in &report.sets {
+            for name in &set.never_answered {
+                println!("{}/{} {}", set.set, name, output::muted("never answered"));
+            }
+            for (name, last) in &set.stale {
+                println!(
+                    "{}/{} {}",
+                    set.set,
+                    name,
+                    output::muted(&format!("last answered {}", last.format("%Y-%m-%d")))
+                );
+            }
+        }
+    } else {
+        println!(
+            "{:<30} {:>9} {:>14} {:>9}",
+            output::header("Set"),
+            output::header("Questions"),
+            output::header("Never answered"),
+            output::header("Stale")
+        );
+        for set in report
+            .sets
+            .iter()
+            .filter(|s| !s.never_answered.is_empty() || !s.stale.is_empty())
+        {
+            println!(
+                "{:<30} {:>9} {:>14} {:>9}",
+                set.set,
+                set.questions,
+                set.never_answered.len(),
+                set.stale.len()
+            );
+        }
+    }
+
+    match &args.path {
+        Some(path) if !report.missing_sources.is_empty() => {
+            println!();
+            println!(
+                "{}",
+                output::header(&format!("Sets with no file in {}:", path.display()))
+            );
+            for name in &report.missing_sources {
+                println!("  {}", name);
+            }
+        }
+        Some(_) => {}
+        None => println!("Pass --path to also find sets whose file is gone"),
+    }
+    Ok(())
+}
@@ -0,0 +1,33 @@
+use anyhow::{bail, Result};
+use rust::{db, functionality::Service, storage::Storage};
+
+/// Hides a set from the set picker and the forecast, keeping its questions
+/// and answers. Lists the archived sets when no set is given.
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    set: Option<String>,
+}
+
+pub async fn run(repo: &db::Repository, args: Args) -> Result<()> {
+    let Some(set) = args.set else {
+        let archived = repo.get_archived_sets().await?;
+        if archived.is_empty() {
+            println!("No archived sets");
+        }
+        for name in archived {
+            println!("{}", name);
+        }
+        return Ok(());
+    };
+    if !Service::new(repo).await?.has_set(&set) {
+        bail!("there is no set named {}", set);
+    }
+    match repo.set_archived(&set, true).await? {
+        true => println!(
+            "Archived {}, `trivial unarchive {}` brings it back",
+            set, set
+        ),
+        false => println!("{} is already archived", set),
+    }
+    Ok(())
+}
@@ -0,0 +1,62 @@
+use anyhow::{bail, Result};
+use rust::functionality::{Answer, ProbabilityComputer, Score, Service};
+use rust::model::{ModelKind, ModelParams};
+use rust::{db, storage::Storage};
+
+/// Compares how well each probability model predicts the answers given so
+/// far, with the parameters of the questions' sets.
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    /// Only use the answers to this set
+    #[arg(short, long)]
+    set: Option<String>,
+}
+
+pub async fn run(repo: &db::Repository, args: Args) -> Result<()> {
+    let service = Service::new(repo).await?;
+    let ids = match &args.set {
+        Some(set) => {
+            if !service.get_sets().contains(&set) {
+                bail!("there is no set named {}", set);
+            }
+            service.get_set(set).to_vec()
+        }
+        None => service.get_questions().iter().map(|q| q.id).collect(),
+    };
+    let params = ids
+        .iter()
+        .map(|&id| (id, service.model_params(id)))
+        .collect::<Vec<_>>();
+    let answers = repo
+        .get_all_answers()
+        .await?
+        .into_iter()
+        .map(|a| Answer {
+            question_id: a.question_id,
+            time: a.time,
+            score: Score::new(a.score),
+        })
+        .collect::<Vec<_>>();
+
+    println!(
+        "{:<10} {:>8} {:>8} {:>8}",
+        "Model", "Answers", "Brier", "Log loss"
+    );
+    for kind in ModelKind::ALL {
+        let ids = params
+            .iter()
+            .map(|&(id, params)| (id, ModelParams { kind, ..params }))
+            .collect::<Vec<_>>();
+        let evaluation = ProbabilityComputer::evaluate(answers.clone(), &ids);
+        let used = params.iter().any(|(_, p)| p.kind == kind);
+        println!(
+            "{:<10} {:>8} {:>8.4} {:>8.4}{}",
+            kind.to_string(),
+            evaluation.answers,
+            evaluation.brier,
+            evaluation.log_loss,
+            if used { "  (in use)" } else { "" }
+        );
+    }
+    Ok(())
+}
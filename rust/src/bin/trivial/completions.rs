@@ -0,0 +1,28 @@
+use std::{fs, io, path::PathBuf};
+
+use anyhow::Result;
+use clap_complete::Shell;
+
+/// Prints the shell completions for trivial, or writes its man pages.
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    /// Shell to print the completions for, e.g. to source from its rc file
+    #[arg(required_unless_present = "man")]
+    shell: Option<Shell>,
+    /// Write a man page for trivial and each of its subcommands to this
+    /// directory instead
+    #[arg(long, value_name = "DIR", conflicts_with = "shell")]
+    man: Option<PathBuf>,
+}
+
+pub fn run(mut cmd: clap::Command, args: Args) -> Result<()> {
+    if let Some(dir) = args.man {
+        fs::create_dir_all(&dir)?;
+        clap_mangen::generate_to(cmd, &dir)?;
+        println!("Wrote man pages to {}", dir.display());
+        return Ok(());
+    }
+    let name = cmd.get_name().to_string();
+    clap_complete::generate(args.shell.unwrap(), &mut cmd, name, &mut io::stdout());
+    Ok(())
+}
@@ -0,0 +1,70 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::{bail, Result};
+use rust::{
+    confusion::{drill_yaml, find_confusions},
+    db,
+    functionality::{load_models_dir, Service},
+    output,
+};
+
+/// Finds vocab words answered with the translation of another word, and
+/// optionally writes a set drilling the confused words together.
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    /// Only show words confused at least this many times
+    #[arg(short, long, default_value_t = 1)]
+    min: usize,
+    /// Write a set with the confused words under this name
+    #[arg(long)]
+    drill: Option<String>,
+    /// Directory to write the drill set to. Defaults to the one in the config
+    /// file
+    #[arg(short, long)]
+    pub path: Option<PathBuf>,
+    /// Load the question sets into the database after writing the drill set
+    #[arg(long)]
+    load: bool,
+}
+
+pub async fn run(repo: &db::Repository, args: Args) -> Result<()> {
+    let confusions = find_confusions(repo, args.min).await?;
+    if confusions.is_empty() {
+        println!("No confused words found");
+        return Ok(());
+    }
+    for c in &confusions {
+        println!(
+            "{} answered with {} ({}) {} times",
+            output::label(&format!("{}/{}", c.word.factory, c.word.name)),
+            output::wrong(&c.answer),
+            c.confused_with.name,
+            c.count
+        );
+    }
+
+    let Some(name) = &args.drill else {
+        println!("\nWrite a set to drill them with --drill <name>");
+        return Ok(());
+    };
+    let Some(dir) = &args.path else {
+        bail!("no directory given, pass it with --path");
+    };
+    let file = dir.join(format!("{}.yaml", name));
+    if file.exists() {
+        bail!("{} already exists", file.display());
+    }
+    fs::write(&file, drill_yaml(name, &confusions)?)?;
+    println!("Wrote {}", file.display());
+
+    if args.load {
+        let mut service = Service::new(repo).await?;
+        let report = service.import(&load_models_dir(dir)?, false).await?;
+        println!(
+            "Added {} and updated {} questions",
+            report.diff.added.len(),
+            report.diff.updated.len()
+        );
+    }
+    Ok(())
+}
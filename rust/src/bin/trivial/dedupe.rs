@@ -0,0 +1,48 @@
+use anyhow::Result;
+use rust::{db, dedupe::find_duplicates, functionality::Service, storage::Storage};
+
+/// Finds questions that ask the same thing, possibly in different sets.
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    /// How similar two questions with the same answer must be, from 0 to 1
+    #[arg(short, long, default_value_t = 0.9)]
+    threshold: f32,
+    /// Move the answers of each duplicate to the first question of its group
+    /// and delete the duplicate
+    #[arg(long)]
+    merge: bool,
+}
+
+pub async fn run(repo: &db::Repository, args: Args) -> Result<()> {
+    let service = Service::new(repo).await?;
+
+    let groups = find_duplicates(&service, args.threshold);
+    for group in &groups {
+        for (i, q) in group.questions.iter().enumerate() {
+            println!(
+                "{} {}/{}: {:?} ({} answers)",
+                if i == 0 { "*" } else { " " },
+                q.factory,
+                q.name,
+                q.runner.question_text(),
+                q.num_correct + q.num_incorrect
+            );
+        }
+        println!();
+    }
+    println!("{} groups of duplicates", groups.len());
+
+    if args.merge && !groups.is_empty() {
+        for group in &groups {
+            let duplicates = group.questions[1..]
+                .iter()
+                .map(|q| q.id)
+                .collect::<Vec<i64>>();
+            repo.merge_questions(group.questions[0].id, &duplicates)
+                .await?;
+        }
+        println!("Merged the duplicates into the questions marked with *.");
+        println!("Remove them from the question files too, or `trivial load` will add them back.");
+    }
+    Ok(())
+}
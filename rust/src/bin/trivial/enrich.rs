@@ -0,0 +1,47 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::Result;
+use rust::enrich::{lookup, to_yaml};
+
+/// Looks up definitions, examples and translations of words and writes them
+/// as a vocab question set.
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    /// File with one word per line
+    #[arg(short, long)]
+    words: PathBuf,
+    /// Name of the question set
+    #[arg(short, long, default_value = "vocabulary")]
+    name: String,
+    /// Wiktionary code of the language to translate to
+    #[arg(short, long, default_value = "da")]
+    lang: String,
+    /// Where to write the question set. Defaults to stdout
+    #[arg(short, long)]
+    out: Option<PathBuf>,
+}
+
+pub fn run(args: Args) -> Result<()> {
+    let mut entries = Vec::new();
+    for word in fs::read_to_string(&args.words)?.lines() {
+        let word = word.trim();
+        if word.is_empty() {
+            continue;
+        }
+        let entry = lookup(word, &args.lang)?;
+        if entry.definition.is_empty() {
+            eprintln!("No definition found for {:?}", word);
+        }
+        if entry.translations.is_empty() {
+            eprintln!("No translation found for {:?}", word);
+        }
+        entries.push(entry);
+    }
+
+    let yaml = to_yaml(&args.name, &entries)?;
+    match args.out {
+        Some(out) => fs::write(out, yaml)?,
+        None => print!("{}", yaml),
+    }
+    Ok(())
+}
@@ -0,0 +1,204 @@
+use std::{collections::HashMap, fs::File, io, io::Write, path::PathBuf};
+
+use anyhow::{bail, Result};
+use clap::ValueEnum;
+use rust::functionality::{Method, Selection, Service};
+use rust::worksheet::Worksheet;
+use rust::{db, storage::Storage};
+use serde::Serialize;
+
+/// Writes one record per question or per answer, for analysis elsewhere, or
+/// a printable worksheet with questions from a set and an answer key.
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    #[arg(short, long, value_enum, default_value_t = Format::Csv)]
+    format: Format,
+    #[arg(short, long, value_enum, default_value_t = Records::Answers)]
+    records: Records,
+    /// Where to write the records. Defaults to stdout
+    #[arg(short, long)]
+    out: Option<PathBuf>,
+    /// The set to put on a worksheet, for the markdown and pdf formats
+    #[arg(short, long)]
+    set: Option<String>,
+    /// Number of questions on a worksheet. Defaults to the whole set
+    #[arg(short, long)]
+    num: Option<usize>,
+    /// Which questions of the set go on a worksheet
+    #[arg(long, value_enum, default_value_t = Pick::Weakest)]
+    pick: Pick,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum Format {
+    Csv,
+    Json,
+    /// A worksheet in Markdown
+    Markdown,
+    /// A worksheet in PDF
+    Pdf,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum Pick {
+    /// The questions least likely to be answered correctly
+    Weakest,
+    Random,
+    /// The questions answered longest ago
+    Oldest,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum Records {
+    Questions,
+    Answers,
+}
+
+#[derive(Serialize)]
+struct QuestionRecord {
+    id: i64,
+    factory: String,
+    name: String,
+    /// Semicolon separated, so it fits in a CSV column.
+    sets: String,
+    probability: f64,
+    num_correct: u32,
+    num_incorrect: u32,
+    num_answers: usize,
+    created_at: String,
+    last_answered_at: Option<String>,
+}
+
+#[derive(Serialize)]
+struct AnswerRecord {
+    id: i64,
+    question_id: i64,
+    factory: String,
+    name: String,
+    time: String,
+    correct: bool,
+    score: f64,
+}
+
+pub async fn run(repo: &db::Repository, args: Args) -> Result<()> {
+    let mut out: Box<dyn Write> = match &args.out {
+        Some(path) => Box::new(File::create(path)?),
+        None => Box::new(io::stdout()),
+    };
+    if let Format::Markdown | Format::Pdf = args.format {
+        let sheet = worksheet(repo, &args).await?;
+        match args.format {
+            Format::Pdf => out.write_all(&sheet.to_pdf())?,
+            _ => out.write_all(sheet.to_markdown().as_bytes())?,
+        }
+        return Ok(());
+    }
+    match args.records {
+        Records::Questions => write(args.format, out, &question_records(repo).await?),
+        Records::Answers => write(args.format, out, &answer_records(repo).await?),
+    }
+}
+
+async fn worksheet(repo: &db::Repository, args: &Args) -> Result<Worksheet> {
+    let Some(set) = &args.set else {
+        bail!("pick the set to put on the worksheet with --set");
+    };
+    let mut service = Service::new(repo).await?;
+    if !service.has_set(set) {
+        bail!("no set {:?}", set);
+    }
+    let size = service.get_set_size(set, Selection::All);
+    let method = match args.pick {
+        Pick::Weakest => Method::Bottom,
+        Pick::Random => Method::UniformRandom,
+        Pick::Oldest => Method::OldestAnswer,
+    };
+    let ids = service.select(set, method, args.num.unwrap_or(size), Selection::All);
+    Ok(Worksheet {
+        title: set.clone(),
+        items: ids
+            .into_iter()
+            .map(|id| {
+                let runner = &service.get(id).runner;
+                (runner.question_text(), runner.answer_text())
+            })
+            .collect(),
+    })
+}
+
+async fn question_records(repo: &db::Repository) -> Result<Vec<QuestionRecord>> {
+    let mut sets = HashMap::<i64, Vec<String>>::new();
+    for s in repo.get_all_question_sets().await? {
+        sets.entry(s.question_id).or_default().push(s.name);
+    }
+    let mut num_answers = HashMap::<i64, usize>::new();
+    for a in repo.get_all_answers().await? {
+        *num_answers.entry(a.question_id).or_default() += 1;
+    }
+
+    let mut questions = repo.get_all_questions().await?;
+    questions.sort_by_key(|q| q.id);
+    Ok(questions
+        .into_iter()
+        .map(|q| {
+            let mut set_names = sets.remove(&q.id).unwrap_or_default();
+            set_names.sort();
+            QuestionRecord {
+                id: q.id,
+                sets: set_names.join(";"),
+                probability: q.probability,
+                num_correct: q.num_correct,
+                num_incorrect: q.num_incorrect,
+                num_answers: num_answers.get(&q.id).copied().unwrap_or(0),
+                created_at: q.created_at.to_rfc3339(),
+                last_answered_at: q.last_answered_at.map(|t| t.to_rfc3339()),
+                factory: q.factory,
+                name: q.name,
+            }
+        })
+        .collect())
+}
+
+async fn answer_records(repo: &db::Repository) -> Result<Vec<AnswerRecord>> {
+    let questions = repo
+        .get_all_questions()
+        .await?
+        .into_iter()
+        .map(|q| (q.id, q))
+        .collect::<HashMap<i64, db::Question>>();
+    let mut answers = repo.get_all_answers().await?;
+    answers.sort_by_key(|a| (a.time, a.id));
+    Ok(answers
+        .into_iter()
+        .filter_map(|a| {
+            let q = questions.get(&a.question_id)?;
+            Some(AnswerRecord {
+                id: a.id,
+                question_id: a.question_id,
+                factory: q.factory.clone(),
+                name: q.name.clone(),
+                time: a.time.to_rfc3339(),
+                correct: a.correct,
+                score: a.score,
+            })
+        })
+        .collect())
+}
+
+fn write<T: Serialize>(format: Format, mut out: Box<dyn Write>, records: &[T]) -> Result<()> {
+    match format {
+        Format::Csv => {
+            let mut w = csv::Writer::from_writer(out);
+            for r in records {
+                w.serialize(r)?;
+            }
+            w.flush()?;
+        }
+        Format::Json => {
+            out.write_all(serde_json::to_string(records)?.as_bytes())?;
+            out.write_all(b"\n")?;
+        }
+        Format::Markdown | Format::Pdf => bail!("records are exported as csv or json"),
+    }
+    Ok(())
+}
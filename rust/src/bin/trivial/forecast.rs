@@ -0,0 +1,107 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use chrono::{Datelike, Days, Local, NaiveDate, Utc};
+use rust::ics::{calendar, DueReviews};
+use rust::{db, functionality::Service, output};
+
+/// Shows how many questions will be due for review each day, per set.
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    /// Only show this set
+    #[arg(short, long)]
+    set: Option<String>,
+    /// Number of weeks to look ahead
+    #[arg(short, long, default_value_t = 4)]
+    weeks: u64,
+    /// Write the days with reviews due to an iCalendar file instead, to be
+    /// reminded of them by a calendar app
+    #[arg(long)]
+    ics: Option<PathBuf>,
+    /// Only put days with at least this many reviews of a set in the
+    /// calendar
+    #[arg(long, default_value_t = 1)]
+    min_due: usize,
+}
+
+pub async fn run(repo: &db::Repository, args: Args) -> Result<()> {
+    let service = Service::new(repo).await?;
+
+    let mut sets = match &args.set {
+        Some(set) => vec![set.clone()],
+        None => service.get_sets().into_iter().cloned().collect(),
+    };
+    sets.sort();
+
+    let today = Local::now().date_naive();
+    if let Some(path) = &args.ics {
+        let end = today + Days::new(args.weeks * 7);
+        let mut reviews = Vec::new();
+        for set in sets {
+            for (day, count) in due_per_day(&service, &set, today).range(..end) {
+                if *count >= args.min_due {
+                    reviews.push(DueReviews {
+                        set: set.clone(),
+                        day: *day,
+                        count: *count,
+                    });
+                }
+            }
+        }
+        fs::write(path, calendar(&reviews, Utc::now()))?;
+        println!(
+            "Wrote {} days with reviews to {}",
+            reviews.len(),
+            path.display()
+        );
+        return Ok(());
+    }
+
+    let monday = today - Days::new(today.weekday().num_days_from_monday() as u64);
+    for set in sets {
+        let per_day = due_per_day(&service, &set, today);
+
+        println!("{}", output::header(&set));
+        println!(
+            "{:>12} {}",
+            "",
+            output::muted(" Mon Tue Wed Thu Fri Sat Sun")
+        );
+        for week in 0..args.weeks {
+            let start = monday + Days::new(week * 7);
+            let mut line = format!("{:>12} ", start.format("%Y-%m-%d"));
+            for d in 0..7 {
+                let day = start + Days::new(d);
+                let cell = match per_day.get(&day) {
+                    _ if day < today => "    ".to_string(),
+                    Some(n) => format!("{:>4}", n),
+                    None => output::muted(&format!("{:>4}", ".")).to_string(),
+                };
+                line.push_str(&cell);
+            }
+            println!("{}", line);
+        }
+        let end = monday + Days::new(args.weeks * 7);
+        let later = per_day.range(end..).map(|(_, n)| n).sum::<usize>();
+        if later > 0 {
+            println!("{:>12} {} more later", "", later);
+        }
+        println!();
+    }
+    Ok(())
+}
+
+/// How many questions of the set are due each day, counting overdue ones as
+/// due today.
+fn due_per_day(service: &Service, set: &str, today: NaiveDate) -> BTreeMap<NaiveDate, usize> {
+    let mut per_day = BTreeMap::new();
+    for &id in service.get_set(set) {
+        if let Some(due) = service.due_date(id) {
+            let day = due.with_timezone(&Local).date_naive().max(today);
+            *per_day.entry(day).or_default() += 1;
+        }
+    }
+    per_day
+}
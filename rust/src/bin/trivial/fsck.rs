@@ -0,0 +1,85 @@
+use std::path::PathBuf;
+
+use anyhow::{bail, Result};
+use rust::{assets, db, storage::Storage};
+
+/// Checks the database for rows referring to rows that don't exist, e.g.
+/// answers left behind by deleting a question by hand, and for corruption.
+/// Also checks that the assets haven't changed since they were loaded.
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    /// Delete the rows referring to rows that don't exist, and the questions
+    /// without a factory along with their answers
+    #[arg(long)]
+    repair: bool,
+    /// Like --repair, but keep a copy of the rows in the quarantine table
+    #[arg(long, conflicts_with = "repair")]
+    quarantine: bool,
+    /// Directory with the question sets and their assets. Defaults to the one
+    /// in the config file
+    #[arg(short, long)]
+    pub path: Option<PathBuf>,
+}
+
+pub async fn run(repo: &db::Repository, args: Args) -> Result<()> {
+    let problems = repo.integrity_check().await?;
+    for problem in &problems {
+        println!("{}", problem);
+    }
+    let asset_problems = match &args.path {
+        Some(dir) => assets::verify(dir, &repo.get_assets().await?),
+        None => Vec::new(),
+    };
+    for problem in &asset_problems {
+        println!("{}", problem);
+    }
+
+    let (orphans, verb) = if args.quarantine {
+        (repo.quarantine_orphans().await?, "Quarantined")
+    } else if args.repair {
+        (repo.delete_orphans().await?, "Deleted")
+    } else {
+        (repo.find_orphans().await?, "Found")
+    };
+    let fixed = args.repair || args.quarantine;
+    if orphans.answers > 0 {
+        println!("{} {} answers to missing questions", verb, orphans.answers);
+    }
+    if orphans.memberships > 0 {
+        println!(
+            "{} {} set memberships of missing questions",
+            verb, orphans.memberships
+        );
+    }
+    if !orphans.questions.is_empty() {
+        println!(
+            "{} {} questions without a factory:",
+            verb,
+            orphans.questions.len()
+        );
+        for (factory, name) in &orphans.questions {
+            println!("  {}/{}", factory, name);
+        }
+        if !fixed {
+            println!("Load their sets to add the factories back and keep the answers.");
+        }
+    }
+    if !orphans.is_empty() && !fixed {
+        println!("Run with --repair to delete them, or --quarantine to move them aside.");
+    }
+    let quarantined = repo.quarantined().await?;
+    if quarantined > 0 {
+        println!("{} rows are in the quarantine table", quarantined);
+    }
+
+    if !problems.is_empty() {
+        bail!("the database is corrupt, restore it from a backup");
+    }
+    if !asset_problems.is_empty() {
+        bail!("the assets changed, load the sets again to record them");
+    }
+    if orphans.is_empty() {
+        println!("No problems found");
+    }
+    Ok(())
+}
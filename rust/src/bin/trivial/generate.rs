@@ -0,0 +1,61 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::{bail, Result};
+use rust::{
+    functionality::load_models,
+    generate::{generate, to_yaml},
+    llm::{Backend, Endpoint},
+};
+
+/// Drafts questions about a topic with a language model. The drafts are
+/// written to a review file, to be edited and moved next to the other
+/// question sets before loading them with `trivial load`.
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    /// What the questions should be about
+    #[arg(short, long)]
+    topic: String,
+    /// Name of the question set
+    #[arg(short, long)]
+    name: String,
+    /// Number of questions to ask for
+    #[arg(short, long, default_value_t = 20)]
+    count: usize,
+    #[arg(long, value_enum, default_value_t = Backend::Ollama)]
+    backend: Backend,
+    #[arg(long)]
+    model: String,
+    /// Base URL of the API, if not the backend's default
+    #[arg(long)]
+    url: Option<String>,
+    /// Where to write the drafts. Defaults to <name>.review.yaml
+    #[arg(short, long)]
+    out: Option<PathBuf>,
+}
+
+pub fn run(args: Args) -> Result<()> {
+    let out = args
+        .out
+        .unwrap_or_else(|| PathBuf::from(format!("{}.review.yaml", args.name)));
+    if out.exists() {
+        bail!("{} already exists", out.display());
+    }
+
+    let endpoint = Endpoint {
+        backend: args.backend,
+        model: args.model,
+        url: args.url,
+    };
+    let drafts = generate(&endpoint, &args.topic, args.count)?;
+    let yaml = format!(
+        "# Drafted from {:?}. Review the questions and answers, then move this\n\
+         # file to the question sets to load it.\n{}",
+        args.topic,
+        to_yaml(&args.name, &drafts)?
+    );
+    fs::write(&out, yaml)?;
+    // Make sure the drafts load before anyone spends time reviewing them.
+    load_models(std::slice::from_ref(&out))?;
+    println!("Wrote {} questions to {}", drafts.len(), out.display());
+    Ok(())
+}
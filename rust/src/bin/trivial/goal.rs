@@ -0,0 +1,61 @@
+use anyhow::{bail, Result};
+use chrono::NaiveDate;
+use clap::Subcommand;
+use rust::{db, storage::Storage};
+
+/// Sets a target probability for every question in a set to reach by a date.
+/// Progress is shown when picking a set and by `trivial stats goals`.
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Set or replace a set's goal, e.g. `goal set spanish_a2 --target 95% --by 2027-06-01`
+    Set {
+        set: String,
+        /// Probability to reach, as a fraction or a percentage
+        #[arg(short, long, value_parser = parse_target)]
+        target: f64,
+        /// Deadline, as YYYY-MM-DD
+        #[arg(short, long)]
+        by: NaiveDate,
+    },
+    /// Remove a set's goal
+    Clear { set: String },
+}
+
+fn parse_target(s: &str) -> Result<f64> {
+    let target = match s.strip_suffix('%') {
+        Some(percent) => percent.trim().parse::<f64>()? / 100.,
+        None => s.parse::<f64>()?,
+    };
+    if !(0. ..=1.).contains(&target) {
+        bail!("the target must be between 0 and 1, or 0% and 100%");
+    }
+    Ok(target)
+}
+
+pub async fn run(repo: &db::Repository, args: Args) -> Result<()> {
+    match args.command {
+        Command::Set { set, target, by } => {
+            if repo.get_questions_in_set(&set).await?.is_empty() {
+                bail!("no set {:?}", set);
+            }
+            repo.set_goal(&db::Goal {
+                set_name: set,
+                target,
+                deadline: by,
+            })
+            .await?;
+        }
+        Command::Clear { set } => {
+            if !repo.delete_goal(&set).await? {
+                bail!("{:?} has no goal", set);
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,100 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use chrono::{Datelike, Days, Local, NaiveDate};
+use rust::{db, output, storage::Storage};
+
+const SHADES: [&str; 5] = ["·", "░", "▒", "▓", "█"];
+
+/// Prints a calendar of how many questions were answered each day.
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    /// Only count answers to questions in this set
+    #[arg(short, long)]
+    set: Option<String>,
+    /// Number of weeks to show
+    #[arg(short, long, default_value_t = 52)]
+    weeks: u64,
+}
+
+pub async fn run(repo: &db::Repository, args: Args) -> Result<()> {
+    let answers = match &args.set {
+        Some(set) => repo.get_answers_in_set(set).await?,
+        None => repo.get_all_answers().await?,
+    };
+
+    let mut per_day = HashMap::<NaiveDate, u32>::new();
+    for a in &answers {
+        *per_day
+            .entry(a.time.with_timezone(&Local).date_naive())
+            .or_default() += 1;
+    }
+
+    // Columns are weeks starting on Monday, ending with the current one.
+    let today = Local::now().date_naive();
+    let monday = today - Days::new(today.weekday().num_days_from_monday() as u64);
+    let start = monday - Days::new(7 * (args.weeks.max(1) - 1));
+    let max = (0..args.weeks * 7)
+        .filter_map(|d| per_day.get(&(start + Days::new(d))))
+        .copied()
+        .max()
+        .unwrap_or(0);
+
+    let mut months = String::from("    ");
+    for week in 0..args.weeks {
+        let date = start + Days::new(week * 7);
+        let column = 4 + week as usize;
+        let new_month = week == 0 || (date - Days::new(7)).month() != date.month();
+        // Labels that would run into the previous one are skipped.
+        if new_month && (week == 0 || months.len() < column) {
+            months.push_str(&" ".repeat(column - months.len()));
+            months.push_str(&date.format("%b").to_string());
+        }
+    }
+    println!("{}", months);
+
+    for (row, label) in ["Mon", "", "Wed", "", "Fri", "", "Sun"].iter().enumerate() {
+        let mut line = format!("{:<4}", label);
+        for week in 0..args.weeks {
+            let date = start + Days::new(week * 7 + row as u64);
+            if date > today {
+                line.push(' ');
+                continue;
+            }
+            let count = per_day.get(&date).copied().unwrap_or(0);
+            let cell = SHADES[shade(count, max)];
+            line.push_str(&if count == 0 {
+                output::muted(cell).to_string()
+            } else {
+                output::correct(cell).to_string()
+            });
+        }
+        println!("{}", line);
+    }
+
+    let total = (0..args.weeks * 7)
+        .filter_map(|d| per_day.get(&(start + Days::new(d))))
+        .sum::<u32>();
+    let active = (0..args.weeks * 7)
+        .filter(|&d| per_day.contains_key(&(start + Days::new(d))))
+        .count();
+    println!();
+    println!(
+        "{} answers on {} days, at most {} a day. Less {} More",
+        total,
+        active,
+        max,
+        SHADES.join("")
+    );
+    Ok(())
+}
+
+/// Shades on a log scale, a single busy day would otherwise make every other
+/// day look empty.
+fn shade(count: u32, max: u32) -> usize {
+    if count == 0 {
+        return 0;
+    }
+    let scale = (max as f64).ln().max(f64::EPSILON);
+    (1 + ((count as f64).ln() / scale * 3.).round() as usize).min(4)
+}
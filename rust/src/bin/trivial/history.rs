@@ -0,0 +1,88 @@
+use anyhow::Result;
+use chrono::Local;
+use rust::db::{self, Load};
+use rust::{output, storage::Storage};
+
+/// Shows what every `trivial load` changed in the database, newest first.
+/// With a question, shows only the loads that touched it.
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    /// A question as `factory/name`
+    question: Option<String>,
+    /// Number of loads to show
+    #[arg(short, long, default_value_t = 20)]
+    num: usize,
+    /// List the questions changed by each load
+    #[arg(short, long)]
+    list: bool,
+}
+
+pub async fn run(repo: &db::Repository, args: Args) -> Result<()> {
+    let loads = repo.get_loads().await?;
+    if loads.is_empty() {
+        println!("No loads recorded");
+        return Ok(());
+    }
+    let mut shown = 0;
+    for load in loads.iter().rev() {
+        if shown == args.num {
+            break;
+        }
+        let time = load.time.with_timezone(&Local).format("%Y-%m-%d %H:%M");
+        if let Some(question) = &args.question {
+            let changes = changes(load)
+                .into_iter()
+                .filter(|(_, entry)| touches(entry, question))
+                .collect::<Vec<_>>();
+            for (change, entry) in &changes {
+                println!("{} {} {}", output::muted(&time.to_string()), change, entry);
+            }
+            shown += !changes.is_empty() as usize;
+            continue;
+        }
+        shown += 1;
+        let count = |list: &str| Load::entries(list).count();
+        println!(
+            "{} {} files: {} added, {} updated, {} renamed, {} pruned",
+            output::label(&time.to_string()),
+            count(&load.files),
+            count(&load.added),
+            count(&load.updated),
+            count(&load.renamed),
+            count(&load.pruned)
+        );
+        if args.list {
+            for (change, entry) in changes(load) {
+                println!("  {} {}", change, entry);
+            }
+        }
+    }
+    if shown == 0 {
+        println!("No load touched {}", args.question.unwrap_or_default());
+    }
+    Ok(())
+}
+
+/// The changes of the load, marked like `load --dry-run` marks them.
+fn changes(load: &Load) -> Vec<(char, &str)> {
+    [
+        ('+', &load.added),
+        ('~', &load.updated),
+        ('>', &load.renamed),
+        ('-', &load.pruned),
+    ]
+    .into_iter()
+    .flat_map(|(change, list)| Load::entries(list).map(move |e| (change, e)))
+    .collect()
+}
+
+/// Whether the entry is about the question, including renames to or from it.
+fn touches(entry: &str, question: &str) -> bool {
+    match entry.split_once(" -> ") {
+        Some((from, to)) => {
+            let factory = from.split('/').next().unwrap_or_default();
+            from == question || format!("{}/{}", factory, to) == question
+        }
+        None => entry == question,
+    }
+}
@@ -0,0 +1,75 @@
+use std::io::{stdout, Write};
+use std::time::Duration;
+
+use anyhow::Result;
+use rust::functionality::pause_with_message;
+use rust::output;
+use rust::room::{Client, CreateRoom, View};
+
+/// Opens a quiz room on a `trivial serve` and asks the questions of a set to
+/// everyone who joined with `trivial join`, faster correct answers getting
+/// more points.
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    /// The set to ask questions from
+    set: String,
+    /// Number of questions
+    #[arg(short, long, default_value_t = 10)]
+    num: usize,
+    /// Seconds to answer each question
+    #[arg(long, default_value_t = 20)]
+    seconds: u64,
+    /// Address of the server
+    #[arg(long, default_value = "http://127.0.0.1:9898")]
+    server: String,
+}
+
+pub fn print_scores(view: &View) {
+    for (i, (name, points)) in view.scores.iter().enumerate() {
+        println!("{}. {:<20} {:>6}", i + 1, name, points);
+    }
+}
+
+pub async fn run(args: Args) -> Result<()> {
+    let client = Client::new(&args.server);
+    let room = client.create(&CreateRoom {
+        set: args.set.clone(),
+        num: args.num,
+        seconds: args.seconds,
+    })?;
+    println!(
+        "Room {}, join with `trivial join {} <name> --server {}`",
+        output::header(&room.code),
+        room.code,
+        args.server
+    );
+    loop {
+        pause_with_message("Press enter to ask the next question...")?;
+        let mut view = client.next(&room.code, &room.host)?;
+        println!(
+            "\n{}",
+            output::header(&format!("Question {}/{}", view.number, view.questions))
+        );
+        println!("{}", view.question.as_deref().unwrap_or_default());
+        while view.question.is_some() {
+            tokio::time::sleep(Duration::from_millis(500)).await;
+            view = client.view(&room.code)?;
+            print!(
+                "\r{}/{} answered, {}s left  ",
+                view.answered,
+                view.scores.len(),
+                view.seconds_left
+            );
+            stdout().flush()?;
+        }
+        println!();
+        if let Some(previous) = &view.previous {
+            println!("{}", output::aside(&format!("answer: {}", previous.answer)));
+        }
+        print_scores(&view);
+        if view.finished {
+            break;
+        }
+    }
+    Ok(())
+}
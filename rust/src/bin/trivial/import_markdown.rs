@@ -0,0 +1,87 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Result};
+use rust::{
+    functionality::{load_models, load_models_dir, Service},
+    generate::to_yaml,
+    markdown::parse,
+};
+
+use crate::repository;
+
+/// Turns Markdown notes into question sets, one per heading. Questions are
+/// written as a `Q:` line followed by `A:` lines with the accepted answers,
+/// or as table rows with the question in the first column and the answers
+/// in the others.
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    /// The Markdown file
+    file: PathBuf,
+    /// Directory to write the sets to. Defaults to the one in the config file
+    #[arg(short, long)]
+    pub path: Option<PathBuf>,
+    /// Overwrite sets written by an earlier import, e.g. after editing the
+    /// notes
+    #[arg(short, long)]
+    force: bool,
+    /// Load the question sets into the database after writing them
+    #[arg(long)]
+    load: bool,
+}
+
+pub async fn run(db: Option<&Path>, args: Args) -> Result<()> {
+    let Some(dir) = &args.path else {
+        bail!("no directory given, pass it with --path");
+    };
+    let text = fs::read_to_string(&args.file)?;
+    let stem = args
+        .file
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let decks = parse(&text, &stem)?;
+    if decks.is_empty() {
+        bail!("no questions found in {}", args.file.display());
+    }
+
+    let files = decks
+        .iter()
+        .map(|d| dir.join(format!("{}.yaml", d.name)))
+        .collect::<Vec<_>>();
+    if !args.force {
+        if let Some(file) = files.iter().find(|f| f.exists()) {
+            bail!(
+                "{} already exists, pass --force to overwrite it",
+                file.display()
+            );
+        }
+    }
+    for (deck, file) in decks.iter().zip(&files) {
+        let yaml = format!(
+            "# Imported from {}, edit the notes and import them again rather\n\
+             # than editing this file.\n{}",
+            args.file.display(),
+            to_yaml(&deck.name, &deck.questions)?
+        );
+        fs::write(file, yaml)?;
+        println!(
+            "Wrote {} questions to {}",
+            deck.questions.len(),
+            file.display()
+        );
+    }
+    load_models(&files)?;
+
+    if args.load {
+        let repo = repository(db).await?;
+        let mut service = Service::new(&repo).await?;
+        let report = service.import(&load_models_dir(dir)?, false).await?;
+        println!(
+            "Added {} and updated {} questions",
+            report.diff.added.len(),
+            report.diff.updated.len()
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,126 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use inquire::{Confirm, Text};
+use rust::{
+    config::Config,
+    db::Repository,
+    functionality::{load_models_dir, Service},
+};
+
+use crate::run;
+
+const EXAMPLE_NAME: &str = "europe_capitals.yaml";
+const EXAMPLE: &str = include_str!("../../../../data/questions/europe_capitals.yaml");
+
+/// Sets up trivial for the first time: writes the config file and creates the
+/// database with an example question set, then offers a demo session.
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    /// Accept the defaults instead of asking, and skip the demo session
+    #[arg(short, long)]
+    yes: bool,
+    /// The database given with --db, offered instead of the default
+    #[arg(skip)]
+    pub db: Option<PathBuf>,
+}
+
+pub async fn run(config_path: &Path, args: Args) -> Result<()> {
+    let config = Config::load(config_path)?;
+    if config_path.exists()
+        && !args.yes
+        && !Confirm::new(&format!(
+            "{} already exists. Overwrite it?",
+            config_path.display()
+        ))
+        .with_default(false)
+        .prompt()?
+    {
+        return Ok(());
+    }
+
+    let data_dir = dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("trivial");
+    let db = ask(
+        &args,
+        "Where should the database be created?",
+        args.db
+            .clone()
+            .or(config.db)
+            .unwrap_or_else(|| data_dir.join("trivial.db")),
+    )?;
+    let questions = ask(
+        &args,
+        "Where should question sets be kept?",
+        config
+            .questions
+            .unwrap_or_else(|| data_dir.join("questions")),
+    )?;
+
+    // The config is read from any directory.
+    let db = std::path::absolute(db)?;
+    let questions = std::path::absolute(questions)?;
+    fs::create_dir_all(&questions)?;
+    let example = questions.join(EXAMPLE_NAME);
+    if !example.exists() {
+        fs::write(&example, EXAMPLE)?;
+        println!("Wrote an example question set to {}", example.display());
+    }
+
+    if let Some(dir) = db.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let repo = Repository::new(&format!("sqlite://{}?mode=rwc", db.display())).await?;
+    let mut service = Service::new(&repo).await?;
+    let models = load_models_dir(&questions)?;
+    service.import(&models, false).await?;
+    println!(
+        "Loaded {} questions into {}",
+        models.questions.len(),
+        db.display()
+    );
+
+    let config = Config {
+        db: Some(db),
+        questions: Some(questions.clone()),
+        ..config
+    };
+    config.save(config_path)?;
+    println!("Wrote {}", config_path.display());
+
+    if !args.yes
+        && Confirm::new("Start a demo session? Its answers won't be recorded")
+            .with_default(true)
+            .prompt()?
+    {
+        let args = run::Args {
+            path: Some(questions),
+            no_record: true,
+            exam: false,
+            sibling_distance: config.sibling_distance,
+            prerequisite_probability: config.prerequisite_probability,
+            profile: None,
+            ranking_methods: config.ranking_methods,
+            profiles: config.profiles,
+            header: config.header,
+            hooks: config.hooks,
+            journal: None,
+        };
+        run::run(&repo, args).await?;
+    }
+    Ok(())
+}
+
+fn ask(args: &Args, message: &str, default: PathBuf) -> Result<PathBuf> {
+    let default = default.display().to_string();
+    if args.yes {
+        return Ok(PathBuf::from(default));
+    }
+    Ok(PathBuf::from(
+        Text::new(message).with_default(&default).prompt()?,
+    ))
+}
@@ -0,0 +1,67 @@
+use std::path::PathBuf;
+
+use anyhow::{bail, Result};
+use rust::{
+    db::Repository,
+    functionality::{load_models_dir, Service},
+    remote::{fetch_pack, is_url, Index},
+};
+
+/// Downloads a pack by URL or by its name in the index, writes its sets to
+/// the question directory and loads them into the database.
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    /// URL of the pack, or its name in the index
+    pack: String,
+    /// Expected SHA-256 of the pack, required when installing from a URL
+    #[arg(long)]
+    sha256: Option<String>,
+    /// URL of the index. Defaults to the one in the config file
+    #[arg(long)]
+    pub index: Option<String>,
+    /// Directory to write the sets to. Defaults to the one in the config file
+    #[arg(short, long)]
+    pub path: Option<PathBuf>,
+    /// Replace files that already exist with other contents
+    #[arg(long)]
+    force: bool,
+}
+
+pub async fn run(repo: &Repository, args: Args) -> Result<()> {
+    let Some(dir) = &args.path else {
+        bail!("no directory given, pass it with --path");
+    };
+    let (url, sha256) = if is_url(&args.pack) {
+        let Some(sha256) = args.sha256 else {
+            bail!("pass the expected checksum of the pack with --sha256");
+        };
+        (args.pack, sha256)
+    } else {
+        let Some(index) = &args.index else {
+            bail!("no index given, pass it with --index or set `index` in the config file");
+        };
+        let index = Index::fetch(index)?;
+        let Some(entry) = index.get(&args.pack) else {
+            bail!("there is no pack named {:?} in the index", args.pack);
+        };
+        (
+            entry.url.clone(),
+            args.sha256.unwrap_or(entry.sha256.clone()),
+        )
+    };
+
+    let pack = fetch_pack(&url, &sha256)?;
+    pack.validate()?;
+    println!("Installing {}", pack.manifest.name);
+    for path in pack.unpack(dir, args.force)? {
+        println!("Wrote {}", path.display());
+    }
+    let mut service = Service::new(repo).await?;
+    let report = service.import(&load_models_dir(dir)?, false).await?;
+    println!(
+        "Added {} and updated {} questions",
+        report.diff.added.len(),
+        report.diff.updated.len()
+    );
+    Ok(())
+}
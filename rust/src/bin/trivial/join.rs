@@ -0,0 +1,65 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use rust::output;
+use rust::room::Client;
+
+use crate::host::print_scores;
+
+/// Joins a quiz room opened with `trivial host` and answers its questions
+/// as they're asked.
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    /// Code of the room
+    code: String,
+    /// Name shown to the others
+    name: String,
+    /// Address of the server
+    #[arg(long, default_value = "http://127.0.0.1:9898")]
+    server: String,
+}
+
+pub async fn run(args: Args) -> Result<()> {
+    let client = Client::new(&args.server);
+    let player = client.join(&args.code, &args.name)?.player;
+    println!(
+        "Joined room {}, waiting for the host",
+        args.code.to_uppercase()
+    );
+    // The last question answered, and the last one whose answer was shown.
+    let mut answered = 0;
+    let mut revealed = 0;
+    loop {
+        let view = client.view(&args.code)?;
+        match &view.question {
+            Some(question) if view.number > answered => {
+                answered = view.number;
+                let prompt = format!(
+                    "{}/{} ({}s) {}",
+                    view.number, view.questions, view.seconds_left, question
+                );
+                let answer = inquire::Text::new(&prompt).prompt()?;
+                match client.answer(&args.code, &player, &answer) {
+                    Ok(scored) if scored.correct => {
+                        println!("{}", output::correct(&format!("+{}", scored.points)))
+                    }
+                    Ok(_) => println!("{}", output::wrong("Wrong")),
+                    Err(err) => println!("{}", output::wrong(&format!("{:#}", err))),
+                }
+                continue;
+            }
+            None if view.number > revealed => {
+                revealed = view.number;
+                if let Some(previous) = &view.previous {
+                    println!("{}", output::aside(&format!("answer: {}", previous.answer)));
+                }
+                print_scores(&view);
+                if view.finished {
+                    return Ok(());
+                }
+            }
+            _ => {}
+        }
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+}
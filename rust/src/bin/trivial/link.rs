@@ -0,0 +1,71 @@
+use anyhow::{anyhow, bail, Result};
+use clap::Subcommand;
+use rust::{db, output, storage::Storage};
+
+/// Links questions that ask the same thing in different sets, e.g. a word in
+/// a vocabulary set and in its reverse, so answers to either count for both.
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Link two questions, given as <factory>/<name>
+    Add { a: String, b: String },
+    /// Remove the link between two questions
+    Remove { a: String, b: String },
+    /// List the linked questions
+    List,
+}
+
+pub async fn run(repo: &db::Repository, args: Args) -> Result<()> {
+    match args.command {
+        Command::Add { a, b } => {
+            let (a, b) = (question(repo, &a).await?, question(repo, &b).await?);
+            if a == b {
+                bail!("a question can't be linked to itself");
+            }
+            if !repo.link_questions(a, b).await? {
+                bail!("the questions are already linked");
+            }
+        }
+        Command::Remove { a, b } => {
+            let (a, b) = (question(repo, &a).await?, question(repo, &b).await?);
+            if !repo.unlink_questions(a, b).await? {
+                bail!("the questions aren't linked");
+            }
+        }
+        Command::List => {
+            let links = repo.get_links().await?;
+            if links.is_empty() {
+                println!("No linked questions");
+            }
+            for (a, b) in links {
+                let (a, b) = (
+                    repo.get_question_by_id(a).await?,
+                    repo.get_question_by_id(b).await?,
+                );
+                println!(
+                    "{} {} {}",
+                    output::label(&format!("{}/{}", a.factory, a.name)),
+                    output::muted("<->"),
+                    output::label(&format!("{}/{}", b.factory, b.name))
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn question(repo: &db::Repository, key: &str) -> Result<i64> {
+    let Some((factory, name)) = key.split_once('/') else {
+        bail!("expected <factory>/<name>, got {:?}", key);
+    };
+    let q = repo
+        .get_question_by_name(factory, name)
+        .await
+        .map_err(|_| anyhow!("no question {:?}", key))?;
+    Ok(q.id)
+}
@@ -0,0 +1,27 @@
+use anyhow::{bail, Result};
+use rust::{output, remote::Index};
+
+/// Lists the packs in the index that can be installed by name.
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    /// URL of the index. Defaults to the one in the config file
+    #[arg(long)]
+    pub index: Option<String>,
+}
+
+pub fn run(args: Args) -> Result<()> {
+    let Some(url) = &args.index else {
+        bail!("no index given, pass it with --index or set `index` in the config file");
+    };
+    let index = Index::fetch(url)?;
+    if index.packs.is_empty() {
+        println!("The index is empty");
+    }
+    for pack in &index.packs {
+        match &pack.description {
+            Some(description) => println!("{}: {}", output::label(&pack.name), description),
+            None => println!("{}", output::label(&pack.name)),
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,178 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::{bail, Result};
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use rust::{
+    assets, db,
+    functionality::{diff_models, load_models_dir, ModelDiff, QuestionKey, Service},
+    storage::Storage,
+};
+use tokio::{sync::mpsc, time::sleep};
+
+/// Adds and updates the questions and sets of the question set files in the
+/// database.
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    /// Path to the question set. Defaults to the one in the config file
+    #[arg(short, long)]
+    pub path: Option<PathBuf>,
+    /// Print how the question set differs from the database without writing anything
+    #[arg(long)]
+    dry_run: bool,
+    /// Delete questions that are in the database but missing from the question set
+    #[arg(long)]
+    prune: bool,
+    /// Keep running and sync the question set whenever its files change.
+    /// Can't be combined with --prune, as a file that's briefly missing while
+    /// it's edited would take its questions' answers with it
+    #[arg(long, conflicts_with = "prune")]
+    watch: bool,
+}
+
+pub async fn run(repo: &db::Repository, args: Args) -> Result<()> {
+    let Some(path) = &args.path else {
+        bail!("no question set given, pass it with --path");
+    };
+    sync(repo, path, &args).await?;
+    if args.watch && !args.dry_run {
+        watch(repo, path, &args).await?;
+    }
+
+    Ok(())
+}
+
+async fn sync(repo: &db::Repository, path: &Path, args: &Args) -> Result<()> {
+    let models = load_models_dir(path)?;
+    if args.dry_run {
+        let diff = diff_models(repo, &models).await?;
+        print_diff(&diff, args.prune);
+        return Ok(());
+    }
+
+    let assets = assets::scan(path, &assets::references(&models)?)?;
+    let mut service = Service::new(repo).await?;
+    let report = service.import(&models, args.prune).await?;
+    repo.set_assets(&assets).await?;
+    let mut files = fs::read_dir(path)?
+        .map(|e| Ok(e?.path()))
+        .collect::<Result<Vec<_>>>()?;
+    files.sort();
+    repo.record_load(&report.to_load(&files)).await?;
+    println!(
+        "Added {}, updated {}, renamed {} questions and wrote {} factories",
+        report.diff.added.len(),
+        report.diff.updated.len(),
+        report.diff.renamed.len(),
+        report.factories
+    );
+    for (set_name, keys) in &report.diff.joined {
+        println!("Inserted {} questions into {:?}", keys.len(), set_name);
+    }
+    for (set_name, keys) in &report.diff.left {
+        println!("Removed {} questions from {:?}", keys.len(), set_name);
+    }
+    if !assets.is_empty() {
+        println!("Recorded {} assets", assets.len());
+    }
+
+    if args.prune {
+        println!("Pruned {} orphaned questions", report.pruned);
+    } else if !report.diff.orphaned.is_empty() {
+        println!(
+            "{} orphaned questions are not in the question set, use --prune to delete them",
+            report.diff.orphaned.len()
+        );
+    }
+
+    Ok(())
+}
+
+async fn watch(repo: &db::Repository, path: &Path, args: &Args) -> Result<()> {
+    let (tx, mut rx) = mpsc::channel(16);
+    let mut watcher = notify::recommended_watcher(move |res: notify::Result<Event>| {
+        if let Ok(event) = res {
+            let _ = tx.blocking_send(event);
+        }
+    })?;
+    watcher.watch(path, RecursiveMode::NonRecursive)?;
+    println!("Watching {:?} for changes", path);
+
+    while let Some(event) = rx.recv().await {
+        if !is_relevant(&event) {
+            continue;
+        }
+        // Editors tend to write a file in several steps, so wait for things to
+        // settle before syncing.
+        sleep(Duration::from_millis(200)).await;
+        while rx.try_recv().is_ok() {}
+
+        // Half-written files are expected while authoring, so report errors
+        // and keep watching.
+        if let Err(err) = sync(repo, path, args).await {
+            println!("Sync failed: {:#}", err);
+        }
+    }
+
+    Ok(())
+}
+
+fn is_relevant(event: &Event) -> bool {
+    let modifies = matches!(
+        event.kind,
+        EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+    );
+    modifies
+        && event.paths.iter().any(|p| {
+            p.extension()
+                .is_some_and(|ext| ext == "yaml" || ext == "yml")
+        })
+}
+
+fn print_diff(diff: &ModelDiff, prune: bool) {
+    if diff.is_empty() {
+        println!("Database is up to date");
+        return;
+    }
+    for k in &diff.added {
+        println!("+ {}/{}", k.factory, k.name);
+    }
+    for k in &diff.updated {
+        println!("~ {}/{}", k.factory, k.name);
+    }
+    for r in &diff.renamed {
+        println!("> {}/{} -> {}", r.from.factory, r.from.name, r.to.name);
+    }
+    for q in &diff.orphaned {
+        println!("- {}/{}", q.factory, q.name);
+    }
+    for (set, keys) in &diff.joined {
+        for k in keys {
+            println!("+ {}/{} in {}", k.factory, k.name, set);
+        }
+    }
+    for (set, keys) in &diff.left {
+        for k in keys {
+            println!("- {}/{} in {}", k.factory, k.name, set);
+        }
+    }
+    let memberships = |sets: &[(String, Vec<QuestionKey>)]| {
+        sets.iter().map(|(_, keys)| keys.len()).sum::<usize>()
+    };
+    println!(
+        "{} to add, {} to update, {} to rename, {} orphaned{}",
+        diff.added.len(),
+        diff.updated.len(),
+        diff.renamed.len(),
+        diff.orphaned.len(),
+        if prune { " (would be pruned)" } else { "" }
+    );
+    println!(
+        "{} to add to sets, {} to take out of sets",
+        memberships(&diff.joined),
+        memberships(&diff.left)
+    );
+}
@@ -0,0 +1,277 @@
+use anyhow::{bail, Context, Result};
+use clap::{CommandFactory, Parser, Subcommand};
+use rust::autosave;
+use rust::config::Config;
+use rust::db::Repository;
+use rust::logging::LogArgs;
+use rust::model;
+use rust::output;
+use rust::plugins;
+use rust::remote;
+use std::path::{Path, PathBuf};
+
+mod aging;
+mod archive;
+mod compare_models;
+mod completions;
+mod confusions;
+mod dedupe;
+mod enrich;
+mod export;
+mod forecast;
+mod fsck;
+mod generate;
+mod goal;
+mod heatmap;
+mod history;
+mod host;
+mod import_markdown;
+mod init;
+mod install;
+mod join;
+mod link;
+mod list_remote;
+mod load;
+mod manage;
+mod merge;
+mod migrate_compress;
+mod migrate_json;
+mod new_set;
+mod notify;
+mod pack;
+mod relink;
+mod rename_question;
+mod rename_set;
+mod run;
+mod serve;
+mod show;
+mod simulate;
+mod stats;
+mod sync;
+mod unarchive;
+mod unpack;
+mod validate;
+mod versus;
+
+/// Spaced repetition trivia questions in the terminal.
+#[derive(Parser, Debug)]
+#[command(name = "trivial", author, version, about, long_about = None)]
+struct Cli {
+    /// Path to the database. Defaults to the one in the config file
+    #[arg(short, long, global = true)]
+    db: Option<PathBuf>,
+    /// Path to the config file
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+    /// Print without colors, also the default when stdout isn't a terminal or
+    /// NO_COLOR is set
+    #[arg(long, global = true)]
+    no_color: bool,
+    #[command(flatten)]
+    log: LogArgs,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    Init(init::Args),
+    Run(run::Args),
+    Load(load::Args),
+    Validate(validate::Args),
+    NewSet(new_set::Args),
+    Pack(pack::Args),
+    Unpack(unpack::Args),
+    Install(install::Args),
+    ListRemote(list_remote::Args),
+    Show(show::Args),
+    Stats(stats::Args),
+    Export(export::Args),
+    Heatmap(heatmap::Args),
+    Aging(aging::Args),
+    History(history::Args),
+    Forecast(forecast::Args),
+    Notify(notify::Args),
+    CompareModels(compare_models::Args),
+    Goal(goal::Args),
+    Archive(archive::Args),
+    Unarchive(unarchive::Args),
+    Dedupe(dedupe::Args),
+    Confusions(confusions::Args),
+    Merge(merge::Args),
+    Link(link::Args),
+    Manage(manage::Args),
+    Fsck(fsck::Args),
+    MigrateCompress(migrate_compress::Args),
+    MigrateJson(migrate_json::Args),
+    Sync(sync::Args),
+    Relink(relink::Args),
+    RenameSet(rename_set::Args),
+    RenameQuestion(rename_question::Args),
+    Versus(versus::Args),
+    Simulate(simulate::Args),
+    Generate(generate::Args),
+    ImportMarkdown(import_markdown::Args),
+    Enrich(enrich::Args),
+    Serve(serve::Args),
+    Host(host::Args),
+    Join(join::Args),
+    Completions(completions::Args),
+}
+
+async fn repository(db: Option<&Path>) -> Result<Repository> {
+    let Some(db) = db else {
+        bail!("no database given, pass it with --db or run `trivial init` to create one");
+    };
+    if !db.exists() {
+        bail!(
+            "{} doesn't exist, run `trivial init` to create it",
+            db.display()
+        );
+    }
+    Repository::new(&format!("sqlite://{}", db.display())).await
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let cli = Cli::parse();
+    cli.log.init();
+    let config_path = match cli.config {
+        Some(path) => path,
+        None => Config::default_path().unwrap_or_else(|| PathBuf::from("trivial.yaml")),
+    };
+    let config = Config::load(&config_path)?;
+    output::init(&config.theme.clone().unwrap_or_default(), !cli.no_color)?;
+    model::init(&config.model.unwrap_or_default())
+        .with_context(|| format!("model in {}", config_path.display()))?;
+    if let Some(cache) = config.cache {
+        remote::init_cache(cache);
+    }
+    if let Some(dir) = config.plugins.or_else(plugins::default_dir) {
+        plugins::load_dir(&dir).with_context(|| format!("plugins in {}", dir.display()))?;
+    }
+    let db = cli.db.or(config.db);
+    let db = db.as_deref();
+    match cli.command {
+        Command::Init(mut args) => {
+            args.db = db.map(Path::to_path_buf);
+            init::run(&config_path, args).await
+        }
+        Command::Run(mut args) => {
+            args.path = args.path.or(config.questions);
+            args.sibling_distance = args.sibling_distance.or(config.sibling_distance);
+            args.prerequisite_probability = args
+                .prerequisite_probability
+                .or(config.prerequisite_probability);
+            args.ranking_methods = config.ranking_methods;
+            args.profiles = config.profiles;
+            args.header = config.header;
+            args.hooks = config.hooks;
+            args.journal = db.map(autosave::journal_path);
+            run::run(&repository(db).await?, args).await
+        }
+        Command::Load(mut args) => {
+            args.path = args.path.or(config.questions);
+            load::run(&repository(db).await?, args).await
+        }
+        Command::Validate(args) => validate::run(args),
+        Command::NewSet(mut args) => {
+            args.path = args.path.or(config.questions);
+            let repo = match args.load {
+                true => Some(repository(db).await?),
+                false => None,
+            };
+            new_set::run(repo.as_ref(), args).await
+        }
+        Command::Pack(mut args) => {
+            args.path = args.path.or(config.questions);
+            pack::run(args)
+        }
+        Command::Unpack(mut args) => {
+            args.path = args.path.or(config.questions);
+            let repo = match args.load {
+                true => Some(repository(db).await?),
+                false => None,
+            };
+            unpack::run(repo.as_ref(), args).await
+        }
+        Command::Install(mut args) => {
+            args.path = args.path.or(config.questions);
+            args.index = args.index.or(config.index);
+            install::run(&repository(db).await?, args).await
+        }
+        Command::ListRemote(mut args) => {
+            args.index = args.index.or(config.index);
+            list_remote::run(args)
+        }
+        Command::Show(args) => show::run(&repository(db).await?, args).await,
+        Command::Stats(args) => stats::run(&repository(db).await?, args).await,
+        Command::Export(args) => export::run(&repository(db).await?, args).await,
+        Command::Aging(mut args) => {
+            args.path = args.path.or(config.questions);
+            aging::run(&repository(db).await?, args).await
+        }
+        Command::Heatmap(args) => heatmap::run(&repository(db).await?, args).await,
+        Command::History(args) => history::run(&repository(db).await?, args).await,
+        Command::Forecast(args) => forecast::run(&repository(db).await?, args).await,
+        Command::Notify(mut args) => {
+            args.db = db.map(Path::to_path_buf);
+            notify::run(&repository(db).await?, args).await
+        }
+        Command::CompareModels(args) => compare_models::run(&repository(db).await?, args).await,
+        Command::Goal(args) => goal::run(&repository(db).await?, args).await,
+        Command::Archive(args) => archive::run(&repository(db).await?, args).await,
+        Command::Unarchive(args) => unarchive::run(&repository(db).await?, args).await,
+        Command::Dedupe(args) => dedupe::run(&repository(db).await?, args).await,
+        Command::Confusions(mut args) => {
+            args.path = args.path.or(config.questions);
+            confusions::run(&repository(db).await?, args).await
+        }
+        Command::Merge(args) => merge::run(&repository(db).await?, args).await,
+        Command::Fsck(mut args) => {
+            args.path = args.path.or(config.questions);
+            fsck::run(&repository(db).await?, args).await
+        }
+        Command::MigrateCompress(args) => migrate_compress::run(&repository(db).await?, args).await,
+        Command::MigrateJson(args) => migrate_json::run(&repository(db).await?, args).await,
+        Command::Sync(mut args) => {
+            args.log = args.log.or(config.sync_log);
+            args.device = args.device.or(config.device);
+            sync::run(&repository(db).await?, args).await
+        }
+        Command::Link(args) => link::run(&repository(db).await?, args).await,
+        Command::Manage(args) => manage::run(&repository(db).await?, args).await,
+        Command::Relink(args) => relink::run(&repository(db).await?, args).await,
+        Command::RenameSet(mut args) => {
+            args.path = args.path.or(config.questions);
+            rename_set::run(&repository(db).await?, args).await
+        }
+        Command::RenameQuestion(mut args) => {
+            args.path = args.path.or(config.questions);
+            rename_question::run(&repository(db).await?, args).await
+        }
+        Command::Versus(args) => versus::run(&repository(db).await?, args).await,
+        Command::Simulate(args) => simulate::run(db, args).await,
+        Command::Generate(args) => generate::run(args),
+        Command::ImportMarkdown(mut args) => {
+            args.path = args.path.or(config.questions);
+            import_markdown::run(db, args).await
+        }
+        Command::Enrich(args) => enrich::run(args),
+        Command::Serve(args) => serve::run(&repository(db).await?, args).await,
+        Command::Host(args) => host::run(args).await,
+        Command::Join(args) => join::run(args).await,
+        Command::Completions(args) => completions::run(Cli::command(), args),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Cli;
+    use clap::CommandFactory;
+
+    #[test]
+    fn arguments_are_consistent() {
+        Cli::command().debug_assert();
+    }
+}
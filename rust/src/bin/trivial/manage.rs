@@ -0,0 +1,176 @@
+use std::collections::BTreeSet;
+use std::fmt;
+
+use anyhow::{bail, Result};
+use inquire::{Confirm, MultiSelect, Select, Text};
+use rust::bulk::{bulk_edit, BulkEdit};
+use rust::functionality::question_tags;
+use rust::{db, storage::Storage};
+
+/// Picks questions and adds tags or set memberships to all of them at once,
+/// or takes them away. Tags are kept in the database until the set file is
+/// loaded again, which sets them back to the file's.
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    /// Only offer the questions in this set
+    #[arg(short, long)]
+    set: Option<String>,
+}
+
+struct Item {
+    id: i64,
+    label: String,
+    tags: Vec<String>,
+}
+
+impl fmt::Display for Item {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.tags.is_empty() {
+            true => write!(f, "{}", self.label),
+            false => write!(f, "{} [{}]", self.label, self.tags.join(", ")),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Action {
+    AddTags,
+    RemoveTags,
+    AddToSets,
+    RemoveFromSets,
+    Save,
+    Cancel,
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Action::AddTags => write!(f, "Add tags"),
+            Action::RemoveTags => write!(f, "Remove tags"),
+            Action::AddToSets => write!(f, "Add to sets"),
+            Action::RemoveFromSets => write!(f, "Remove from sets"),
+            Action::Save => write!(f, "Save"),
+            Action::Cancel => write!(f, "Cancel"),
+        }
+    }
+}
+
+/// Whether the characters of the filter appear in the text in order,
+/// ignoring case, so `frpar` finds `capitals/France: Paris`.
+fn fuzzy(filter: &str, text: &str) -> bool {
+    let mut text = text.chars().flat_map(char::to_lowercase);
+    filter
+        .chars()
+        .flat_map(char::to_lowercase)
+        .filter(|c| !c.is_whitespace())
+        .all(|c| text.any(|t| t == c))
+}
+
+pub async fn run(repo: &db::Repository, args: Args) -> Result<()> {
+    let questions = match &args.set {
+        Some(set) => repo.get_questions_in_set(set).await?,
+        None => repo.get_all_questions().await?,
+    };
+    if questions.is_empty() {
+        bail!("there are no questions to pick from");
+    }
+    let items = questions
+        .iter()
+        .map(|q| Item {
+            id: q.id,
+            label: format!("{}/{}", q.factory, q.name),
+            tags: question_tags(&q.data),
+        })
+        .collect::<Vec<_>>();
+    let selected = MultiSelect::new("Questions", items)
+        .with_filter(&|filter, _, text, _| fuzzy(filter, text))
+        .with_page_size(15)
+        .with_help_message("type to filter, space picks, → picks all shown, enter when done")
+        .prompt()?;
+    if selected.is_empty() {
+        return Ok(());
+    }
+
+    let mut sets = BTreeSet::new();
+    for item in &selected {
+        sets.extend(repo.get_sets_of_question(item.id).await?);
+    }
+    let all_sets = repo
+        .get_all_question_sets()
+        .await?
+        .into_iter()
+        .map(|s| s.name)
+        .collect::<BTreeSet<_>>();
+    let tags = selected
+        .iter()
+        .flat_map(|i| i.tags.iter().cloned())
+        .collect::<BTreeSet<_>>();
+
+    let mut edit = BulkEdit::default();
+    loop {
+        let actions = vec![
+            Action::AddTags,
+            Action::RemoveTags,
+            Action::AddToSets,
+            Action::RemoveFromSets,
+            Action::Save,
+            Action::Cancel,
+        ];
+        let message = format!("{} questions picked", selected.len());
+        match Select::new(&message, actions).prompt()? {
+            Action::AddTags => {
+                let text = Text::new("Tags to add")
+                    .with_help_message("separated by commas")
+                    .prompt()?;
+                edit.add_tags.extend(
+                    text.split(',')
+                        .map(str::trim)
+                        .filter(|t| !t.is_empty())
+                        .map(String::from),
+                );
+            }
+            Action::RemoveTags => {
+                let options = tags.iter().cloned().collect();
+                edit.remove_tags
+                    .extend(MultiSelect::new("Tags to remove", options).prompt()?);
+            }
+            Action::AddToSets => {
+                let options = all_sets.iter().cloned().collect();
+                edit.add_sets
+                    .extend(MultiSelect::new("Sets to add to", options).prompt()?);
+            }
+            Action::RemoveFromSets => {
+                let options = sets.iter().cloned().collect();
+                edit.remove_sets
+                    .extend(MultiSelect::new("Sets to remove from", options).prompt()?);
+            }
+            Action::Save => break,
+            Action::Cancel => return Ok(()),
+        }
+    }
+    if edit.is_empty() {
+        return Ok(());
+    }
+
+    for (what, names) in [
+        ("Add tags", &edit.add_tags),
+        ("Remove tags", &edit.remove_tags),
+        ("Add to sets", &edit.add_sets),
+        ("Remove from sets", &edit.remove_sets),
+    ] {
+        if !names.is_empty() {
+            println!("{}: {}", what, names.join(", "));
+        }
+    }
+    let prompt = format!("Apply to {} questions?", selected.len());
+    if !Confirm::new(&prompt).with_default(true).prompt()? {
+        return Ok(());
+    }
+    let ids = selected.iter().map(|i| i.id).collect::<Vec<_>>();
+    let counts = bulk_edit(repo, &ids, &edit).await?;
+    println!(
+        "Retagged {} questions, added {} and removed {} set memberships",
+        counts.retagged, counts.added, counts.removed
+    );
+    Ok(())
+}
@@ -0,0 +1,54 @@
+use anyhow::Result;
+use inquire::Confirm;
+use rust::db;
+use rust::storage::Storage;
+
+/// Merges another database into this one, e.g. one used on a different
+/// machine. Questions are matched by their set and name.
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    /// Path to the database to merge from
+    #[arg(short, long)]
+    from: String,
+    /// Take the question data from the other database when both have a question
+    #[arg(long)]
+    prefer_other: bool,
+    /// Keep set memberships only one of the databases had without asking
+    #[arg(long)]
+    keep_memberships: bool,
+}
+
+pub async fn run(repo: &db::Repository, args: Args) -> Result<()> {
+    let diff = repo.membership_diff(&args.from).await?;
+    let counts = repo.merge_from(&args.from, args.prefer_other).await?;
+    println!(
+        "Merged {} factories, {} questions, {} answers and {} set memberships",
+        counts.factories, counts.questions, counts.answers, counts.memberships
+    );
+
+    // Both sides' memberships are kept, but one side may have removed them.
+    let mut removed = Vec::new();
+    for (memberships, missing_from) in [
+        (&diff.only_here, args.from.as_str()),
+        (&diff.only_there, "this database"),
+    ] {
+        if memberships.is_empty() || args.keep_memberships {
+            continue;
+        }
+        println!("Missing from {}:", missing_from);
+        for m in memberships {
+            println!("  {}: {}/{}", m.set, m.factory, m.name);
+        }
+        if Confirm::new(&format!("Remove these {} memberships?", memberships.len()))
+            .with_default(false)
+            .prompt()?
+        {
+            removed.extend(memberships.iter().cloned());
+        }
+    }
+    if !removed.is_empty() {
+        let n = repo.remove_memberships(&removed).await?;
+        println!("Removed {} set memberships", n);
+    }
+    Ok(())
+}
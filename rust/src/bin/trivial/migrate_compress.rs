@@ -0,0 +1,20 @@
+use anyhow::Result;
+use rust::db;
+
+/// Compresses the question data stored before `trivial` compressed it, which
+/// shrinks databases with large questions such as vocabulary lists.
+#[derive(clap::Args, Debug)]
+pub struct Args {}
+
+pub async fn run(repo: &db::Repository, _args: Args) -> Result<()> {
+    let counts = repo.compress_data().await?;
+    if counts.questions == 0 {
+        println!("Nothing to compress");
+        return Ok(());
+    }
+    println!(
+        "Compressed {} questions from {} to {} bytes",
+        counts.questions, counts.before, counts.after
+    );
+    Ok(())
+}
@@ -0,0 +1,20 @@
+use anyhow::Result;
+use rust::{db, encoding};
+
+/// Rewrites question data stored as YAML by older versions of `trivial` as
+/// versioned JSON, see `encoding`.
+#[derive(clap::Args, Debug)]
+pub struct Args {}
+
+pub async fn run(repo: &db::Repository, _args: Args) -> Result<()> {
+    let counts = encoding::migrate(repo).await?;
+    if counts == encoding::MigrateCounts::default() {
+        println!("All data is up to date");
+        return Ok(());
+    }
+    println!(
+        "Rewrote the data of {} questions and {} factories",
+        counts.questions, counts.factories
+    );
+    Ok(())
+}
@@ -0,0 +1,50 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::{bail, Result};
+use clap::builder::PossibleValuesParser;
+use rust::{
+    db::Repository,
+    functionality::{load_models_dir, Service},
+    scaffold::{scaffold, types},
+};
+
+/// Writes a new question set file with an example question and comments
+/// explaining its fields.
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    /// Name of the set, also used for the file name
+    name: String,
+    /// Type of the questions in the set
+    #[arg(short, long = "type", default_value = "default", value_parser = PossibleValuesParser::new(types()))]
+    type_: String,
+    /// Directory to write the set to. Defaults to the one in the config file
+    #[arg(short, long)]
+    pub path: Option<PathBuf>,
+    /// Load the question sets into the database afterwards
+    #[arg(long)]
+    pub load: bool,
+}
+
+pub async fn run(repo: Option<&Repository>, args: Args) -> Result<()> {
+    let Some(dir) = &args.path else {
+        bail!("no directory given, pass it with --path");
+    };
+    let file = dir.join(format!("{}.yaml", args.name));
+    if file.exists() {
+        bail!("{} already exists", file.display());
+    }
+    fs::create_dir_all(dir)?;
+    fs::write(&file, scaffold(&args.type_, &args.name)?)?;
+    println!("Wrote {}", file.display());
+
+    if let Some(repo) = repo {
+        let mut service = Service::new(repo).await?;
+        let report = service.import(&load_models_dir(dir)?, false).await?;
+        println!(
+            "Added {} and updated {} questions",
+            report.diff.added.len(),
+            report.diff.updated.len()
+        );
+    }
+    Ok(())
+}
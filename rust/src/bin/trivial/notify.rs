@@ -0,0 +1,45 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use chrono::Utc;
+use rust::functionality::Service;
+use rust::notify::{self, Due};
+use rust::{db, output};
+
+/// Sends a desktop notification with the number of questions due for
+/// review, meant to be run by cron or a systemd timer. Does nothing when
+/// too few are due.
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    /// Only notify when at least this many questions are due
+    #[arg(short, long, default_value_t = 1)]
+    min: usize,
+    /// Ring the terminal bell and print the message instead
+    #[arg(long)]
+    bell: bool,
+    /// The database, put in the command to run
+    #[arg(skip)]
+    pub db: Option<PathBuf>,
+}
+
+/// Sets named in the notification, the rest are counted.
+const MAX_SETS: usize = 3;
+
+pub async fn run(repo: &db::Repository, args: Args) -> Result<()> {
+    let service = Service::new(repo).await?;
+    let due = Due::new(&service, Utc::now());
+    if due.total == 0 || due.total < args.min {
+        return Ok(());
+    }
+    let command = match &args.db {
+        Some(db) => format!("trivial --db {} run", db.display()),
+        None => "trivial run".to_string(),
+    };
+    let (title, body) = due.message(&command, MAX_SETS);
+    if args.bell {
+        println!("\x07{}", output::header(&title));
+        println!("{}", body);
+        return Ok(());
+    }
+    notify::send(&title, &body)
+}
@@ -0,0 +1,66 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use anyhow::{bail, Result};
+use chrono::Utc;
+use rust::pack::{set_files, Manifest, Pack, EXTENSION, FORMAT};
+
+/// Bundles question sets into a pack file that can be shared without the
+/// database. Sets included by a union are packed along with it, and so are
+/// the assets the sets refer to.
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    /// Names of the sets to pack
+    #[arg(required = true)]
+    sets: Vec<String>,
+    /// Directory with the question sets. Defaults to the one in the config file
+    #[arg(short, long)]
+    pub path: Option<PathBuf>,
+    /// File to write the pack to. Defaults to the name of the pack
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+    /// Name of the pack. Defaults to the name of the first set
+    #[arg(long)]
+    name: Option<String>,
+    #[arg(long)]
+    description: Option<String>,
+    #[arg(long)]
+    author: Option<String>,
+    /// Directory with images or sounds the sets refer to
+    #[arg(long)]
+    media: Option<PathBuf>,
+}
+
+pub fn run(args: Args) -> Result<()> {
+    let Some(dir) = &args.path else {
+        bail!("no directory given, pass it with --path");
+    };
+    let name = args.name.unwrap_or_else(|| args.sets[0].clone());
+    let manifest = Manifest {
+        format: FORMAT,
+        name,
+        description: args.description,
+        author: args.author,
+        created: Utc::now(),
+        sets: Vec::new(),
+        media: Vec::new(),
+        assets: BTreeMap::new(),
+    };
+    let pack = Pack::create(
+        manifest,
+        &set_files(dir, &args.sets)?,
+        args.media.as_deref(),
+    )?;
+    let output = args
+        .output
+        .unwrap_or_else(|| PathBuf::from(format!("{}.{}", pack.manifest.name, EXTENSION)));
+    pack.save(&output)?;
+    println!(
+        "Packed {} sets, {} assets and {} media files into {}",
+        pack.sets.len(),
+        pack.assets.len(),
+        pack.media.len(),
+        output.display()
+    );
+    Ok(())
+}
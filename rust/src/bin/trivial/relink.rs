@@ -0,0 +1,48 @@
+use std::path::Path;
+
+use anyhow::Result;
+use rust::{
+    db,
+    functionality::{find_relinks, load_models_dir},
+    storage::Storage,
+};
+
+/// Moves the answers of orphaned questions to the question in the question set
+/// with the same content, e.g. after renaming a question's id.
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    /// Path to the question set
+    #[arg(short, long)]
+    path: String,
+    /// Print what would be relinked without writing anything
+    #[arg(long)]
+    dry_run: bool,
+}
+
+pub async fn run(repo: &db::Repository, args: Args) -> Result<()> {
+    let models = load_models_dir(Path::new(&args.path))?;
+
+    let relinks = find_relinks(repo, &models).await?;
+    for r in &relinks {
+        println!(
+            "{}/{} -> {} ({} answers)",
+            r.from.factory,
+            r.from.name,
+            r.to.name,
+            r.from.num_correct + r.from.num_incorrect
+        );
+        if !args.dry_run {
+            repo.merge_questions(r.to.id, &[r.from.id]).await?;
+        }
+    }
+    println!(
+        "{} {} questions",
+        if args.dry_run {
+            "Would relink"
+        } else {
+            "Relinked"
+        },
+        relinks.len()
+    );
+    Ok(())
+}
@@ -0,0 +1,33 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use rust::{db, rename, storage::Storage};
+
+/// Changes a question's id in the database, keeping its answers, and in the
+/// file defining its set.
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    /// The set the question is defined in
+    set: String,
+    old: String,
+    new: String,
+    /// Directory with the question sets. Defaults to the one in the config
+    /// file. The files are left alone if there is none
+    #[arg(short, long)]
+    pub path: Option<PathBuf>,
+}
+
+pub async fn run(repo: &db::Repository, args: Args) -> Result<()> {
+    let edits = match &args.path {
+        Some(dir) => rename::rename_question(dir, &args.set, &args.old, &args.new)?,
+        None => Vec::new(),
+    };
+    repo.rename_question(&args.set, &args.old, &args.new)
+        .await?;
+    rename::apply(&edits)?;
+    println!("Renamed {}/{} to {}", args.set, args.old, args.new);
+    for edit in edits {
+        println!("Updated {}", edit.path.display());
+    }
+    Ok(())
+}
@@ -0,0 +1,30 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use rust::{db, rename, storage::Storage};
+
+/// Renames a set in the database, keeping its answers and goal, and in the
+/// question set files.
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    old: String,
+    new: String,
+    /// Directory with the question sets. Defaults to the one in the config
+    /// file. The files are left alone if there is none
+    #[arg(short, long)]
+    pub path: Option<PathBuf>,
+}
+
+pub async fn run(repo: &db::Repository, args: Args) -> Result<()> {
+    let edits = match &args.path {
+        Some(dir) => rename::rename_set(dir, &args.old, &args.new)?,
+        None => Vec::new(),
+    };
+    repo.rename_set(&args.old, &args.new).await?;
+    rename::apply(&edits)?;
+    println!("Renamed {} to {}", args.old, args.new);
+    for edit in edits {
+        println!("Updated {}", edit.path.display());
+    }
+    Ok(())
+}
@@ -0,0 +1,586 @@
+use anyhow::{bail, Error, Result};
+use chrono::{Local, Utc};
+use core::fmt;
+use rust::assets;
+use rust::autosave::Autosave;
+use rust::db::Repository;
+use rust::functionality::{self, load_models_dir, pause, ImportReport, Method, Selection, Service};
+use rust::header;
+use rust::hooks::{self, Hook, Payload, STREAK_MILESTONES};
+use rust::input::{self, interrupted, Shortcut};
+use rust::output;
+use rust::pack::set_files;
+use rust::profile::{self, Profile};
+use rust::progress::Progress;
+use rust::ranking::CustomMethod;
+use std::collections::HashMap;
+use std::env;
+use std::fmt::Debug;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::Arc;
+
+/// Practices the questions of a set interactively.
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    /// Path to the question set, enables reloading it from the menu. Defaults
+    /// to the one in the config file
+    #[arg(short, long)]
+    pub path: Option<PathBuf>,
+    /// Practice without recording answers, e.g. for a demo. Can also be
+    /// toggled from the menu
+    #[arg(long)]
+    pub no_record: bool,
+    /// Minimum distance between sibling questions, ones sharing a tag or
+    /// asking for each other's answer. 2 never asks them right after each
+    /// other, 1 turns spacing off. Defaults to the one in the config file, or 2
+    #[arg(long)]
+    pub sibling_distance: Option<usize>,
+    /// How likely a question's prerequisites have to be answered correctly
+    /// before it's introduced. Defaults to the one in the config file, or 0.8
+    #[arg(long)]
+    pub prerequisite_probability: Option<f64>,
+    /// Withhold feedback until the end of the session, where all results
+    /// are revealed at once, like in a test. Wrong answers aren't asked again
+    #[arg(long)]
+    pub exam: bool,
+    /// Start sessions from the profile with this name in the config file
+    /// instead of the one for the time of day
+    #[arg(long)]
+    pub profile: Option<String>,
+    /// Ranking methods from the config file, offered along with the built-in
+    /// ones
+    #[arg(skip)]
+    pub ranking_methods: Vec<CustomMethod>,
+    /// Profiles from the config file, see `profile::Profile`
+    #[arg(skip)]
+    pub profiles: Vec<Profile>,
+    /// Template of the header shown above every question, see `header`
+    #[arg(skip)]
+    pub header: Option<String>,
+    /// Hooks from the config file, told about finished sessions and streaks
+    #[arg(skip)]
+    pub hooks: Vec<Hook>,
+    /// Where answers are journaled until they're in the database, see
+    /// `autosave`. Without one they're written before the next question
+    #[arg(skip)]
+    pub journal: Option<PathBuf>,
+}
+
+/// Leitner boxes shown in the header, a question moves up one with every
+/// correct answer in a row.
+const BOXES: usize = 5;
+
+fn header_fields<'a>(service: &Service, set: &str, id: i64) -> Result<HashMap<&'a str, String>> {
+    let question = service.get(id);
+    let last_answered = match service.last_answer(id) {
+        Some(answer) => {
+            let since = Utc::now().signed_duration_since(answer.time);
+            format!("{:?}", since.to_std()?)
+        }
+        None => String::from("-"),
+    };
+    let due = match service.due_date(id) {
+        Some(due) => due.with_timezone(&Local).format("%Y-%m-%d").to_string(),
+        None => String::from("-"),
+    };
+    let streak = service.streak(id);
+    Ok(HashMap::from([
+        ("name", question.name.clone()),
+        ("set", set.to_string()),
+        ("probability", format!("{:.3}", question.probability)),
+        ("last_answered", last_answered),
+        ("streak", streak.to_string()),
+        ("box", format!("{}/{}", streak.min(BOXES - 1) + 1, BOXES)),
+        ("due", due),
+        (
+            "tags",
+            if question.tags.is_empty() {
+                String::from("-")
+            } else {
+                question.tags.join(", ")
+            },
+        ),
+        ("correct", question.num_correct.to_string()),
+        ("incorrect", question.num_incorrect.to_string()),
+    ]))
+}
+
+const DEFAULT_SIBLING_DISTANCE: usize = 2;
+
+#[derive(Clone, PartialEq, Eq)]
+enum Choice {
+    /// A set, with its goal progress if it has a goal.
+    Value(String, Option<String>),
+    Reload,
+    /// Switch between recording answers and practice only, holding whether
+    /// answers are currently recorded.
+    ToggleRecording(bool),
+    Exit,
+}
+
+impl fmt::Display for Choice {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Choice::Value(s, None) => {
+                write!(f, "{}", s)
+            }
+            Choice::Value(s, Some(progress)) => {
+                write!(f, "{} [goal: {}]", s, progress)
+            }
+            Choice::Reload => {
+                write!(f, "Reload sets")
+            }
+            Choice::ToggleRecording(true) => {
+                write!(f, "Stop recording answers")
+            }
+            Choice::ToggleRecording(false) => {
+                write!(f, "Start recording answers")
+            }
+            Choice::Exit => {
+                write!(f, "Exit")
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+enum Ranking {
+    Builtin(Method),
+    Custom(CustomMethod),
+}
+
+impl fmt::Display for Ranking {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Ranking::Builtin(method) => write!(f, "{}", method),
+            Ranking::Custom(method) => write!(f, "{}", method),
+        }
+    }
+}
+
+#[derive(Clone)]
+struct Choice2 {
+    choice: Choice,
+    ranking: Ranking,
+    selection: Selection,
+    num: usize,
+}
+
+fn rankings(custom: &[CustomMethod]) -> Vec<Ranking> {
+    let mut rankings = Method::ALL.map(Ranking::Builtin).to_vec();
+    rankings.extend(custom.iter().cloned().map(Ranking::Custom));
+    rankings
+}
+
+const SELECTIONS: [Selection; 2] = [Selection::All, Selection::Practiced];
+
+/// Where a prompt starts, at the option named like the profile's setting.
+fn cursor<T: fmt::Display>(options: &[T], name: Option<&str>) -> Option<usize> {
+    let name = name?;
+    options
+        .iter()
+        .position(|o| o.to_string().eq_ignore_ascii_case(name))
+}
+
+fn check_profile(service: &Service, profile: &Profile, custom: &[CustomMethod]) -> Result<()> {
+    if let Some(set) = &profile.set {
+        if !service.has_set(set) {
+            bail!("profile {}: there is no set named {}", profile.name, set);
+        }
+    }
+    if let Some(ranking) = &profile.ranking {
+        if cursor(&rankings(custom), Some(ranking)).is_none() {
+            bail!(
+                "profile {}: there is no ranking method named {}",
+                profile.name,
+                ranking
+            );
+        }
+    }
+    if let Some(selection) = &profile.selection {
+        if cursor(&SELECTIONS, Some(selection)).is_none() {
+            bail!(
+                "profile {}: selection must be All or Practiced, not {}",
+                profile.name,
+                selection
+            );
+        }
+    }
+    Ok(())
+}
+
+fn get_choice(
+    service: &Service,
+    last_choice: &Option<Choice2>,
+    can_reload: bool,
+    recording: bool,
+    custom: &[CustomMethod],
+    profile: Option<&Profile>,
+) -> Result<Choice2> {
+    if let Some(choice) = last_choice {
+        if let Choice::Value(set, _) = &choice.choice {
+            if let Some(next) = service.next_set(set) {
+                let prompt = format!("Continue with {}?", next);
+                if inquire::Confirm::new(&prompt).with_default(true).prompt()? {
+                    return Ok(Choice2 {
+                        choice: Choice::Value(next.to_string(), None),
+                        ..choice.clone()
+                    });
+                }
+            }
+        }
+        if inquire::Confirm::new("Start again with same choice?").prompt()? {
+            return Ok(choice.clone());
+        }
+    }
+
+    let mut options = vec![Choice::Exit];
+    if can_reload {
+        options.push(Choice::Reload);
+    }
+    options.push(Choice::ToggleRecording(recording));
+    for s in service.get_sets() {
+        let progress = service.goal_progress(s).map(|p| p.to_string());
+        options.push(Choice::Value(s.clone(), progress));
+    }
+    let set = profile.and_then(|p| p.set.as_deref());
+    let start = options
+        .iter()
+        .position(|o| matches!(o, Choice::Value(s, _) if Some(s.as_str()) == set));
+    let select = inquire::Select::new("Pick a question set", options)
+        .with_starting_cursor(start.unwrap_or(0));
+    let choice = match select.prompt()? {
+        Choice::Value(s, _) => s,
+        choice @ (Choice::Exit | Choice::Reload | Choice::ToggleRecording(_)) => {
+            return Ok(Choice2 {
+                choice,
+                ranking: Ranking::Builtin(Method::Bottom),
+                selection: Selection::All,
+                num: 0,
+            })
+        }
+    };
+    let start = cursor(&SELECTIONS, profile.and_then(|p| p.selection.as_deref()));
+    let selection = inquire::Select::new("Selection method", SELECTIONS.to_vec())
+        .with_starting_cursor(start.unwrap_or(0))
+        .prompt()?;
+    let size = service.get_set_size(&choice, selection);
+    let num = profile.and_then(|p| p.num).unwrap_or(size).min(size);
+    let num = inquire::Text::new(&format!("Number of questions (out of {})", size))
+        .with_initial_value(&format!("{}", num))
+        .prompt()?
+        .parse::<usize>()?;
+    let rankings = rankings(custom);
+    let start = cursor(&rankings, profile.and_then(|p| p.ranking.as_deref()));
+    let ranking = inquire::Select::new("Ranking method", rankings)
+        .with_starting_cursor(start.unwrap_or(0))
+        .prompt()?;
+
+    Ok(Choice2 {
+        choice: Choice::Value(choice, None),
+        ranking,
+        selection,
+        num,
+    })
+}
+
+async fn reload(service: &mut Service<'_>, path: &Path) -> Result<ImportReport> {
+    let models = load_models_dir(path)?;
+    service.import(&models, false).await
+}
+
+/// Opens the file defining the question in `$VISUAL` or `$EDITOR` and
+/// reloads the sets once the editor exits.
+async fn edit(service: &mut Service<'_>, dir: Option<&Path>, id: i64) -> Result<ImportReport> {
+    let Some(dir) = dir else {
+        bail!("the question directory isn't known, pass it with --path");
+    };
+    let factory = service.get(id).factory.clone();
+    let Some(file) = set_files(dir, &[factory])?.into_iter().next() else {
+        bail!("no file defines the question");
+    };
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    let mut words = editor.split_whitespace();
+    let Some(program) = words.next() else {
+        bail!("no editor set, set $EDITOR");
+    };
+    let status = process::Command::new(program)
+        .args(words)
+        .arg(&file)
+        .status()?;
+    if !status.success() {
+        bail!("{} exited with {}", editor, status);
+    }
+    reload(service, dir).await
+}
+
+/// Reveals the results of an exam, with the expected answer of every
+/// question answered wrong.
+fn print_results(service: &Service, results: &[(i64, bool)]) {
+    let num_correct = results.iter().filter(|(_, correct)| *correct).count();
+    println!("\n{}/{} correct", num_correct, results.len());
+    for &(id, correct) in results {
+        let runner = &service.get(id).runner;
+        if correct {
+            println!("{} {}", output::correct("✓"), runner.question_text());
+        } else {
+            println!(
+                "{} {} {}",
+                output::wrong("✗"),
+                runner.question_text(),
+                output::aside(&format!("answer: {}", runner.answer_text()))
+            );
+        }
+    }
+}
+
+pub async fn run(db: &Repository, args: Args) -> Result<(), Error> {
+    for method in &args.ranking_methods {
+        method.validate()?;
+    }
+    for profile in &args.profiles {
+        profile.validate()?;
+    }
+    let template = args.header.as_deref().unwrap_or(header::DEFAULT_TEMPLATE);
+    header::validate(template)?;
+    if let Some(path) = &args.path {
+        assets::set_root(path);
+    }
+    for hook in &args.hooks {
+        hook.validate()?;
+    }
+    let autosave = match &args.journal {
+        Some(journal) => {
+            let (autosave, recovered) = Autosave::start(Arc::new(db.clone()), journal).await?;
+            if recovered > 0 {
+                println!(
+                    "Saved {} answers left over from the last session",
+                    recovered
+                );
+            }
+            Some(autosave)
+        }
+        None => None,
+    };
+    let mut service = functionality::Service::new(db).await?;
+    let profile = profile::pick(&args.profiles, args.profile.as_deref(), Local::now().time())?;
+    if let Some(profile) = profile {
+        check_profile(&service, profile, &args.ranking_methods)?;
+        println!("Using profile {}", output::header(&profile.name));
+    }
+    if let Some(probability) = args.prerequisite_probability {
+        service.set_prerequisite_probability(probability);
+    }
+    if let Some(autosave) = &autosave {
+        service.set_autosave(autosave);
+    }
+    let res = sessions(&mut service, &args, template, profile).await;
+    drop(service);
+    if let Some(autosave) = autosave {
+        autosave.close().await?;
+    }
+    res
+}
+
+async fn sessions(
+    service: &mut Service<'_>,
+    args: &Args,
+    template: &str,
+    profile: Option<&Profile>,
+) -> Result<(), Error> {
+    let mut last_choice: Option<Choice2> = None;
+    let mut recording = !args.no_record;
+    loop {
+        if !recording {
+            println!("Practice only, answers are not recorded.");
+        }
+        service.refresh_thresholds();
+        let choice = get_choice(
+            service,
+            &last_choice,
+            args.path.is_some(),
+            recording,
+            &args.ranking_methods,
+            profile,
+        )?;
+        let set = match &choice.choice {
+            Choice::Value(set, _) => set,
+            Choice::Reload => {
+                match reload(service, args.path.as_ref().unwrap()).await {
+                    Ok(report) => println!(
+                        "Added {} and updated {} questions",
+                        report.diff.added.len(),
+                        report.diff.updated.len()
+                    ),
+                    // Keep the session going so the set files can be fixed and reloaded.
+                    Err(err) => println!("Reload failed: {:#}", err),
+                }
+                continue;
+            }
+            Choice::ToggleRecording(_) => {
+                recording = !recording;
+                continue;
+            }
+            Choice::Exit => return Ok(()),
+        };
+
+        let mut question_ids = match &choice.ranking {
+            Ranking::Builtin(method) => service.select(set, *method, choice.num, choice.selection),
+            Ranking::Custom(method) => {
+                service.get_custom_selection(set, method, choice.num, choice.selection)?
+            }
+        };
+        clearscreen::clear()?;
+        let streak = service.day_streak(Local::now().date_naive());
+        let mut progress = Progress::new(question_ids.len());
+        if args.exam {
+            progress.hide_score();
+        }
+        let silenced = args.exam.then(output::silence);
+        let mut wrong = Vec::new();
+        // Every answer of the session, revealed at the end of an exam.
+        let mut results = Vec::new();
+        'session: loop {
+            service.shuffle(
+                &mut question_ids,
+                args.sibling_distance.unwrap_or(DEFAULT_SIBLING_DISTANCE),
+            );
+            // Whether each question so far this round was answered
+            // correctly, or None if it was skipped, so answers can be undone.
+            let mut history: Vec<Option<bool>> = Vec::new();
+            let mut i = 0;
+            while i < question_ids.len() {
+                let id = question_ids[i];
+                println!("{}", output::header(&progress.to_string()));
+                let header = header::render(template, &header_fields(service, set, id)?)?;
+                if !header.is_empty() {
+                    println!("{}", header);
+                }
+                let question = service.get(id);
+                let result = question.runner.run();
+                let details = input::take_details();
+                let score = match result {
+                    Ok(score) => score,
+                    Err(err) => match interrupted(&err) {
+                        Some(Shortcut::Quit) => break 'session,
+                        Some(Shortcut::Skip) => {
+                            progress.skip();
+                            history.push(None);
+                            i += 1;
+                            continue;
+                        }
+                        Some(Shortcut::Undo) => {
+                            let Some(answer) = history.pop() else {
+                                println!("Nothing to undo");
+                                continue;
+                            };
+                            i -= 1;
+                            if answer == Some(false) {
+                                wrong.pop();
+                            }
+                            if answer.is_some() {
+                                results.pop();
+                            }
+                            if answer.is_some() && recording {
+                                service.undo_answer(question_ids[i]).await?;
+                            }
+                            progress.undo(answer);
+                            continue;
+                        }
+                        Some(Shortcut::Edit) => {
+                            match edit(service, args.path.as_deref(), id).await {
+                                Ok(report) => println!(
+                                    "Added {} and updated {} questions",
+                                    report.diff.added.len(),
+                                    report.diff.updated.len()
+                                ),
+                                Err(err) => println!("Editing failed: {:#}", err),
+                            }
+                            if !service.get_questions().iter().any(|q| q.id == id) {
+                                question_ids.remove(i);
+                                progress.remove();
+                            }
+                            continue;
+                        }
+                        _ => return Err(err),
+                    },
+                };
+                progress.record(score.is_correct());
+                history.push(Some(score.is_correct()));
+                if !score.is_correct() {
+                    wrong.push(id);
+                }
+                results.push((id, score.is_correct()));
+                if recording {
+                    service.add_answer_with(id, score, &details).await?;
+                    let average = if args.exam {
+                        None
+                    } else {
+                        service.average_error(id).await?
+                    };
+                    if let Some((error, count)) = average {
+                        println!(
+                            "{}",
+                            output::aside(&format!(
+                                "Average error {:.1}% over {} guesses",
+                                error * 100.,
+                                count
+                            ))
+                        );
+                    }
+                }
+                i += 1;
+            }
+
+            println!("{}", output::header(&progress.to_string()));
+            if args.exam || wrong.is_empty() {
+                break;
+            }
+
+            let num_correct = history.iter().filter(|a| **a == Some(true)).count();
+            println!(
+                "\n{}/{} correct. Continuing with the remaining {} wrong answers.",
+                num_correct,
+                question_ids.len(),
+                wrong.len()
+            );
+
+            std::mem::swap(&mut wrong, &mut question_ids);
+            wrong.clear();
+            progress.next_round();
+
+            pause()?;
+            clearscreen::clear()?;
+        }
+        drop(silenced);
+        if args.exam {
+            print_results(service, &results);
+        }
+        if recording && !results.is_empty() {
+            let time = Utc::now();
+            let mut payloads = vec![Payload::SessionCompleted {
+                set: set.clone(),
+                answers: results.len(),
+                correct: results.iter().filter(|(_, correct)| *correct).count(),
+                seconds: progress.elapsed().as_secs(),
+                time,
+            }];
+            let days = service.day_streak(Local::now().date_naive());
+            if days > streak && STREAK_MILESTONES.contains(&days) {
+                println!("{}", output::correct(&format!("{} days in a row!", days)));
+                payloads.push(Payload::StreakMilestone { days, time });
+            }
+            for payload in &payloads {
+                for err in hooks::fire(&args.hooks, payload) {
+                    println!("{}", output::wrong(&format!("Hook failed: {:#}", err)));
+                }
+            }
+        }
+        pause()?;
+        clearscreen::clear()?;
+        last_choice = Some(choice);
+    }
+}
@@ -0,0 +1,235 @@
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Result};
+use rust::functionality::{Method, Selection, Service};
+use rust::room::{self, RoomQuestion, Rooms};
+use rust::{db, metrics};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use tokio::time::timeout;
+use tracing::warn;
+
+/// Serves study metrics on /metrics for Prometheus to scrape, and quiz rooms
+/// for `trivial host` and `trivial join`.
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    /// Address to listen on
+    #[arg(short, long, default_value = "127.0.0.1:9898")]
+    addr: String,
+}
+
+/// Largest request read, room requests are small.
+const MAX_REQUEST: usize = 64 * 1024;
+
+/// How long a client gets to send its request and read the response, so one
+/// that stalls can't hold up the others.
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(10);
+
+struct Server<'a> {
+    repo: &'a db::Repository,
+    /// Loaded when the first room is opened, scores the answers in rooms.
+    service: Option<Service<'a>>,
+    rooms: Rooms,
+}
+
+pub async fn run(repo: &db::Repository, args: Args) -> Result<()> {
+    let listener = TcpListener::bind(&args.addr).await?;
+    let addr = listener.local_addr()?;
+    println!("Serving metrics on http://{}/metrics", addr);
+    println!(
+        "Open quiz rooms with `trivial host --server http://{}`",
+        addr
+    );
+    let mut server = Server {
+        repo,
+        service: None,
+        rooms: Rooms::new(),
+    };
+    // Requests are read concurrently, as clients can be slow to send them,
+    // and answered one at a time, as answering them is quick.
+    let (tx, mut rx) = mpsc::channel(64);
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => {
+                let (mut stream, peer) = accepted?;
+                let tx = tx.clone();
+                tokio::spawn(async move {
+                    match timeout(CLIENT_TIMEOUT, read_request(&mut stream)).await {
+                        Ok(Ok(request)) => {
+                            let _ = tx.send((stream, peer, request)).await;
+                        }
+                        Ok(Err(e)) => warn!("reading from {}: {:#}", peer, e),
+                        Err(_) => warn!("{} didn't send a request in time", peer),
+                    }
+                });
+            }
+            Some((mut stream, peer, request)) = rx.recv() => {
+                let response = server.respond(request).await;
+                tokio::spawn(async move {
+                    let write = async {
+                        stream.write_all(response.as_bytes()).await?;
+                        stream.shutdown().await
+                    };
+                    match timeout(CLIENT_TIMEOUT, write).await {
+                        Ok(Ok(())) => {}
+                        Ok(Err(e)) => warn!("responding to {}: {:#}", peer, e),
+                        Err(_) => warn!("{} didn't read the response in time", peer),
+                    }
+                });
+            }
+        }
+    }
+}
+
+struct Request {
+    method: String,
+    path: String,
+    body: Vec<u8>,
+}
+
+async fn read_request(stream: &mut TcpStream) -> Result<Request> {
+    let mut request = Vec::new();
+    let mut buf = [0; 1024];
+    let end = loop {
+        if let Some(i) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+            break i + 4;
+        }
+        if request.len() > MAX_REQUEST {
+            bail!("request too large");
+        }
+        let n = stream.read(&mut buf).await?;
+        if n == 0 {
+            break request.len();
+        }
+        request.extend_from_slice(&buf[..n]);
+    };
+    let head = String::from_utf8_lossy(&request[..end]).to_string();
+    let length = head
+        .lines()
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("content-length"))
+        .map(|(_, value)| value.trim().parse::<usize>())
+        .transpose()?
+        .unwrap_or(0);
+    if length > MAX_REQUEST {
+        bail!("request too large");
+    }
+    let mut body = request[end..].to_vec();
+    while body.len() < length {
+        let n = stream.read(&mut buf).await?;
+        if n == 0 {
+            break;
+        }
+        body.extend_from_slice(&buf[..n]);
+    }
+    let mut parts = head.split_whitespace();
+    Ok(Request {
+        method: parts.next().unwrap_or_default().to_string(),
+        path: parts.next().unwrap_or_default().to_string(),
+        body,
+    })
+}
+
+fn json<T: Serialize>(value: Result<T>) -> (&'static str, &'static str, String) {
+    match value {
+        Ok(value) => (
+            "200 OK",
+            "application/json",
+            serde_json::to_string(&value).unwrap(),
+        ),
+        Err(e) => (
+            "400 Bad Request",
+            "application/json",
+            serde_json::json!({ "error": format!("{:#}", e) }).to_string(),
+        ),
+    }
+}
+
+fn body<T: DeserializeOwned>(request: &Request) -> Result<T> {
+    Ok(serde_json::from_slice(&request.body)?)
+}
+
+impl<'a> Server<'a> {
+    /// The response to the request, which closes the connection.
+    async fn respond(&mut self, request: Request) -> String {
+        let path = request.path.trim_end_matches('/');
+        let route = path.split('/').skip(1).collect::<Vec<_>>();
+        let now = Instant::now();
+        let (status, content_type, body) = match (request.method.as_str(), route.as_slice()) {
+            ("GET", ["metrics"]) => match metrics::render(self.repo).await {
+                Ok(body) => ("200 OK", "text/plain; version=0.0.4", body),
+                Err(e) => (
+                    "500 Internal Server Error",
+                    "text/plain",
+                    format!("{:#}\n", e),
+                ),
+            },
+            ("POST", ["rooms"]) => json(self.create_room(&request).await),
+            ("GET", ["rooms", code]) => json(self.rooms.view(code, now)),
+            ("POST", ["rooms", code, "join"]) => json(
+                body::<room::Join>(&request)
+                    .and_then(|join| self.rooms.join(code, &join.name))
+                    .map(|player| room::Joined { player }),
+            ),
+            ("POST", ["rooms", code, "next"]) => json(
+                body::<room::Next>(&request)
+                    .and_then(|next| self.rooms.next(code, &next.host, now))
+                    .and_then(|()| self.rooms.view(code, now)),
+            ),
+            ("POST", ["rooms", code, "answer"]) => json(self.answer(code, &request, now)),
+            _ => ("404 Not Found", "text/plain", "Not found\n".to_string()),
+        };
+        format!(
+            "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            content_type,
+            body.len(),
+            body
+        )
+    }
+
+    async fn create_room(&mut self, request: &Request) -> Result<room::Created> {
+        let create = body::<room::CreateRoom>(request)?;
+        if self.service.is_none() {
+            self.service = Some(Service::new(self.repo).await?);
+        }
+        let service = self.service.as_mut().unwrap();
+        if !service.has_set(&create.set) {
+            bail!("there is no set named {}", create.set);
+        }
+        let questions = service
+            .select(
+                &create.set,
+                Method::UniformRandom,
+                create.num,
+                Selection::All,
+            )
+            .into_iter()
+            .map(|id| {
+                let runner = &service.get(id).runner;
+                RoomQuestion {
+                    id,
+                    text: runner.question_text(),
+                    answer: runner.answer_text(),
+                }
+            })
+            .collect();
+        let time_limit = Duration::from_secs(create.seconds.max(1));
+        let (code, host) = self.rooms.create(&create.set, questions, time_limit)?;
+        println!("Opened room {} on {}", code, create.set);
+        Ok(room::Created { code, host })
+    }
+
+    fn answer(&mut self, code: &str, request: &Request, now: Instant) -> Result<room::Scored> {
+        let answer = body::<room::Answer>(request)?;
+        let id = self.rooms.question(code, &answer.player, now)?;
+        let Some(service) = &self.service else {
+            bail!("no room is open");
+        };
+        let score = room::score_reply(service.get(id).runner.as_ref(), &answer.answer);
+        self.rooms.answer(code, &answer.player, score, now)
+    }
+}
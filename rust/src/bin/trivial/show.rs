@@ -0,0 +1,151 @@
+use anyhow::{anyhow, bail, Result};
+use chrono::Local;
+use clap::Subcommand;
+use rust::sequence::parse_steps;
+use rust::{db, encoding, output, storage::Storage};
+
+/// Prints what the database knows about a question or a set.
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Show a question's data, answers and sets, e.g. `show question vocabulary/inure`
+    Question { key: String },
+    /// Show the questions in a set, least known first
+    Set { name: String },
+}
+
+pub async fn run(repo: &db::Repository, args: Args) -> Result<()> {
+    match args.command {
+        Command::Question { key } => show_question(repo, &key).await,
+        Command::Set { name } => show_set(repo, &name).await,
+    }
+}
+
+async fn show_question(repo: &db::Repository, key: &str) -> Result<()> {
+    let Some((factory, name)) = key.split_once('/') else {
+        bail!("expected <factory>/<name>, got {:?}", key);
+    };
+    let q = repo
+        .get_question_by_name(factory, name)
+        .await
+        .map_err(|_| anyhow!("no question {:?}", key))?;
+
+    println!(
+        "{} {}/{} (id {})",
+        output::label("Question"),
+        q.factory,
+        q.name,
+        q.id
+    );
+    println!("{} {:.3}", output::label("Probability:"), q.probability);
+    for (a, b) in repo.get_links().await? {
+        let other = match (a == q.id, b == q.id) {
+            (true, _) => b,
+            (_, true) => a,
+            _ => continue,
+        };
+        let other = repo.get_question_by_id(other).await?;
+        println!(
+            "{} {}/{}",
+            output::label("Linked to:"),
+            other.factory,
+            other.name
+        );
+    }
+    println!(
+        "{} {} correct, {} incorrect",
+        output::label("Counts:"),
+        q.num_correct,
+        q.num_incorrect
+    );
+    println!(
+        "{} {}",
+        output::label("Created:"),
+        q.created_at.with_timezone(&Local).format("%Y-%m-%d %H:%M")
+    );
+    println!(
+        "{} {}",
+        output::label("Sets:"),
+        repo.get_sets_of_question(q.id).await?.join(", ")
+    );
+    println!("{}", output::label("Data:"));
+    for line in encoding::to_yaml(&q.data)?.lines() {
+        println!("  {}", line);
+    }
+
+    let answers = repo.get_answers(q.id).await?;
+    println!("{} {}", output::label("Answers:"), answers.len());
+    for a in answers {
+        let score = format!("{:.2}", a.score);
+        let error = match (a.error, a.tolerance) {
+            (Some(error), Some(tolerance)) => {
+                format!(" ({:+.1}%, within {:.0}%)", error * 100., tolerance * 100.)
+            }
+            (Some(error), None) => format!(" ({:+.1}%)", error * 100.),
+            (None, _) => String::new(),
+        };
+        let steps = match &a.steps {
+            Some(steps) => {
+                let steps = parse_steps(steps);
+                let correct = steps.iter().filter(|&&c| c).count();
+                format!(" ({}/{} steps)", correct, steps.len())
+            }
+            None => String::new(),
+        };
+        println!(
+            "  {} {}{}{}",
+            a.time.with_timezone(&Local).format("%Y-%m-%d %H:%M"),
+            if a.correct {
+                output::correct(&score)
+            } else {
+                output::wrong(&score)
+            },
+            output::aside(&error),
+            output::aside(&steps)
+        );
+    }
+    Ok(())
+}
+
+async fn show_set(repo: &db::Repository, name: &str) -> Result<()> {
+    let mut questions = repo.get_questions_in_set(name).await?;
+    if questions.is_empty() {
+        bail!("no set {:?}", name);
+    }
+    questions.sort_by(|a, b| a.probability.total_cmp(&b.probability));
+
+    println!("{} {}", output::label("Set"), name);
+    if let Some(f) = repo.get_question_factory(name).await? {
+        println!("{} {}", output::label("Type:"), f.factory_type);
+        let data = encoding::to_yaml(&f.data)?;
+        if data.trim() != "{}" {
+            println!("{}", output::label("Data:"));
+            for line in data.lines() {
+                println!("  {}", line);
+            }
+        }
+    }
+    let mean = questions.iter().map(|q| q.probability).sum::<f64>() / questions.len() as f64;
+    println!(
+        "{} {} questions, mean probability {:.3}",
+        output::label("Size:"),
+        questions.len(),
+        mean
+    );
+    for q in questions {
+        let last = match q.last_answered_at {
+            Some(t) => t.with_timezone(&Local).format("%Y-%m-%d").to_string(),
+            None => "never".to_string(),
+        };
+        println!(
+            "  {:.3}  {:>4}/{:<4} {:>10}  {}/{}",
+            q.probability, q.num_correct, q.num_incorrect, last, q.factory, q.name
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,223 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Result};
+use chrono::{Duration, Utc};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rust::{
+    db,
+    functionality::{load_models_dir, Method, Score, Selection, Service},
+    output,
+};
+
+/// Runs sessions headlessly with a simulated answerer, to compare how the
+/// selection methods spread the reviews over a set. Starts from the history in
+/// --db or from the question set in --path, working on copies so the database
+/// itself is left alone.
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    /// Question set to start from, with no history
+    #[arg(short, long)]
+    path: Option<PathBuf>,
+    #[arg(short, long)]
+    set: String,
+    /// Questions per session
+    #[arg(short, long, default_value_t = 20)]
+    num: usize,
+    #[arg(long, default_value_t = 30)]
+    sessions: usize,
+    /// Hours between sessions
+    #[arg(long, default_value_t = 24)]
+    hours: i64,
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+    /// Chance of answering a question correctly
+    #[arg(long, default_value_t = 0.7)]
+    accuracy: f64,
+    /// YAML map from <factory>/<name> to the chance of answering that question
+    /// correctly, overriding --accuracy
+    #[arg(long)]
+    accuracies: Option<PathBuf>,
+    /// Use each question's current probability as its chance of being answered
+    /// correctly, overriding --accuracy
+    #[arg(long)]
+    from_probability: bool,
+    /// Only simulate these methods
+    #[arg(short, long, value_parser = parse_method)]
+    method: Vec<Method>,
+}
+
+fn parse_method(s: &str) -> Result<Method> {
+    match Method::ALL
+        .iter()
+        .find(|m| m.to_string().to_lowercase().replace(' ', "_") == s.to_lowercase())
+    {
+        Some(&m) => Ok(m),
+        None => bail!(
+            "expected bottom, weighted_random, uniform_random, oldest_answer, recently_wrong or hardest_tags"
+        ),
+    }
+}
+
+struct Report {
+    method: Method,
+    reviews: HashMap<i64, usize>,
+    accuracy: HashMap<i64, f64>,
+}
+
+/// Simulates on a copy of `db`, if given.
+pub async fn run(db: Option<&Path>, args: Args) -> Result<()> {
+    if db.is_none() && args.path.is_none() {
+        bail!("pass --db or --path to simulate on");
+    }
+    let overrides = match &args.accuracies {
+        Some(path) => serde_yaml::from_slice::<HashMap<String, f64>>(&fs::read(path)?)?,
+        None => HashMap::new(),
+    };
+    let methods = if args.method.is_empty() {
+        Method::ALL.to_vec()
+    } else {
+        args.method.clone()
+    };
+
+    let mut reports = Vec::new();
+    for method in methods {
+        let copy = std::env::temp_dir().join(format!("trivial-sim-{}.db", std::process::id()));
+        let url = match db {
+            Some(db) => {
+                fs::copy(db, &copy)?;
+                format!("sqlite://{}", copy.display())
+            }
+            None => "sqlite::memory:".to_string(),
+        };
+        let repo = db::Repository::new(&url).await?;
+        let mut service = Service::new(&repo).await?;
+        if let Some(path) = &args.path {
+            service.import(&load_models_dir(path)?, false).await?;
+        }
+        let report = simulate(&mut service, &args, &overrides, method).await;
+        drop(service);
+        drop(repo);
+        let _ = fs::remove_file(&copy);
+        reports.push(report?);
+    }
+    print_reports(&reports);
+    Ok(())
+}
+
+async fn simulate(
+    service: &mut Service<'_>,
+    args: &Args,
+    overrides: &HashMap<String, f64>,
+    method: Method,
+) -> Result<Report> {
+    service.seed(args.seed);
+    let mut answerer = StdRng::seed_from_u64(args.seed.wrapping_add(1));
+    let accuracy = service
+        .get_set(&args.set)
+        .iter()
+        .map(|&id| {
+            let q = service.get(id);
+            let key = format!("{}/{}", q.factory, q.name);
+            let accuracy = match overrides.get(&key) {
+                Some(&a) => a,
+                None if args.from_probability => q.probability,
+                None => args.accuracy,
+            };
+            (id, accuracy)
+        })
+        .collect::<HashMap<i64, f64>>();
+
+    let mut reviews = accuracy
+        .keys()
+        .map(|&id| (id, 0))
+        .collect::<HashMap<_, _>>();
+    let mut now = Utc::now();
+    for _ in 0..args.sessions {
+        let num = args
+            .num
+            .min(service.get_set_size(&args.set, Selection::All));
+        let mut question_ids = service.select(&args.set, method, num, Selection::All);
+        // Like a real session, wrong answers are asked again until they're right.
+        while !question_ids.is_empty() {
+            let mut wrong = Vec::new();
+            for id in question_ids {
+                let correct = answerer.gen_bool(accuracy[&id].clamp(0., 1.));
+                if !correct {
+                    wrong.push(id);
+                }
+                *reviews.get_mut(&id).unwrap() += 1;
+                service.add_answer_at(id, Score::from(correct), now).await?;
+                now += Duration::seconds(10);
+            }
+            question_ids = wrong;
+        }
+        now += Duration::hours(args.hours);
+    }
+    Ok(Report {
+        method,
+        reviews,
+        accuracy,
+    })
+}
+
+fn print_reports(reports: &[Report]) {
+    println!(
+        "{:<16} {:>8} {:>8} {:>5} {:>6} {:>5} {:>6} {:>8}",
+        output::header("Method"),
+        output::header("Reviews"),
+        output::header("Covered"),
+        output::header("Min"),
+        output::header("Median"),
+        output::header("Max"),
+        output::header("Gini"),
+        output::header("Hardest")
+    );
+    for r in reports {
+        let mut counts = r.reviews.values().copied().collect::<Vec<usize>>();
+        counts.sort();
+        let total = counts.iter().sum::<usize>();
+        let covered = counts.iter().filter(|&&c| c > 0).count();
+
+        // Share of the reviews that went to the fifth of the questions that
+        // are hardest to answer.
+        let mut by_accuracy = r.accuracy.iter().collect::<Vec<(&i64, &f64)>>();
+        by_accuracy.sort_by(|a, b| a.1.total_cmp(b.1).then(a.0.cmp(b.0)));
+        let hardest = by_accuracy
+            .iter()
+            .take(by_accuracy.len().div_ceil(5))
+            .map(|(id, _)| r.reviews[id])
+            .sum::<usize>();
+
+        println!(
+            "{:<16} {:>8} {:>7.0}% {:>5} {:>6} {:>5} {:>6.3} {:>7.0}%",
+            r.method.to_string(),
+            total,
+            covered as f64 / counts.len().max(1) as f64 * 100.,
+            counts.first().unwrap_or(&0),
+            counts.get(counts.len() / 2).unwrap_or(&0),
+            counts.last().unwrap_or(&0),
+            gini(&counts),
+            hardest as f64 / total.max(1) as f64 * 100.
+        );
+    }
+}
+
+/// 0 when every question got the same number of reviews, approaching 1 as the
+/// reviews go to fewer questions. Expects the counts sorted.
+fn gini(sorted: &[usize]) -> f64 {
+    let n = sorted.len() as f64;
+    let total = sorted.iter().sum::<usize>() as f64;
+    if total == 0. {
+        return 0.;
+    }
+    let weighted = sorted
+        .iter()
+        .enumerate()
+        .map(|(i, &c)| (i as f64 + 1.) * c as f64)
+        .sum::<f64>();
+    2. * weighted / (n * total) - (n + 1.) / n
+}
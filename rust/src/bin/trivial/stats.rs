@@ -0,0 +1,245 @@
+use std::collections::{BTreeMap, HashMap};
+
+use anyhow::{bail, Result};
+use clap::Subcommand;
+use rust::{db, functionality::Service, output, retention::ForgettingCurve, storage::Storage};
+
+/// Statistics about the answers in the database.
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Fit a forgetting curve per set and estimate how much is remembered
+    /// after a day, a week and a month
+    Retention {
+        /// Only analyze this set
+        #[arg(short, long)]
+        set: Option<String>,
+    },
+    /// Show the progress towards each set's goal
+    Goals,
+    /// Show the most common wrong answers per question, e.g. to spot
+    /// ambiguous questions
+    WrongAnswers {
+        /// Only show questions in this set
+        #[arg(short, long)]
+        set: Option<String>,
+        /// Number of wrong answers to show per question
+        #[arg(short, long, default_value_t = 3)]
+        top: usize,
+    },
+    /// Show how hard each set is, by the average probability of its
+    /// questions and how often they were right the first time, hardest first
+    Difficulty {
+        /// Show each tag instead of each set
+        #[arg(short, long)]
+        tags: bool,
+        /// Only show the tags of the questions in this set
+        #[arg(short, long, requires = "tags")]
+        set: Option<String>,
+    },
+    /// Show the totals of the players of `trivial versus`, the most wins
+    /// first
+    Players,
+}
+
+pub async fn run(repo: &db::Repository, args: Args) -> Result<()> {
+    match args.command {
+        Command::Retention { set } => retention(repo, set.as_deref()).await,
+        Command::Goals => goals(repo).await,
+        Command::WrongAnswers { set, top } => wrong_answers(repo, set.as_deref(), top).await,
+        Command::Difficulty { tags, set } => difficulty(repo, tags, set.as_deref()).await,
+        Command::Players => players(repo).await,
+    }
+}
+
+async fn players(repo: &db::Repository) -> Result<()> {
+    let mut users = repo.get_users().await?;
+    if users.is_empty() {
+        println!("No games played, start one with `trivial versus`");
+        return Ok(());
+    }
+    users.sort_by(|a, b| b.wins.cmp(&a.wins).then_with(|| a.name.cmp(&b.name)));
+    println!(
+        "{:<20} {:>5} {:>5} {:>8} {:>8}",
+        output::header("Player"),
+        output::header("Games"),
+        output::header("Wins"),
+        output::header("Correct"),
+        output::header("Points")
+    );
+    for u in users {
+        println!(
+            "{:<20} {:>5} {:>5} {:>7.0}% {:>8}",
+            u.name,
+            u.games,
+            u.wins,
+            u.correct as f64 / u.answers.max(1) as f64 * 100.,
+            u.points
+        );
+    }
+    Ok(())
+}
+
+async fn goals(repo: &db::Repository) -> Result<()> {
+    let service = Service::new(repo).await?;
+    let goals = repo.get_all_goals().await?;
+    if goals.is_empty() {
+        println!("No goals, set one with `trivial goal set`");
+    }
+    for goal in goals {
+        match service.goal_progress(&goal.set_name) {
+            Some(progress) => println!("{}: {}", output::label(&goal.set_name), progress),
+            None => println!("{}: the set doesn't exist", output::label(&goal.set_name)),
+        }
+    }
+    Ok(())
+}
+
+async fn difficulty(repo: &db::Repository, tags: bool, set: Option<&str>) -> Result<()> {
+    let service = Service::new(repo).await?;
+    let difficulties = if tags {
+        let ids = match set {
+            Some(set) if !service.has_set(set) => bail!("there is no set named {}", set),
+            Some(set) => service.get_set(set).to_vec(),
+            None => service.get_questions().iter().map(|q| q.id).collect(),
+        };
+        service.difficulty_by_tag(&ids)
+    } else {
+        service.difficulty_by_set()
+    };
+    if difficulties.is_empty() {
+        println!("No {}", if tags { "tagged questions" } else { "sets" });
+        return Ok(());
+    }
+
+    println!(
+        "{:<30} {:>9} {:>11} {:>9}",
+        output::header(if tags { "Tag" } else { "Set" }),
+        output::header("Questions"),
+        output::header("Probability"),
+        output::header("First try")
+    );
+    for d in difficulties {
+        let first_try = match d.first_try_accuracy {
+            Some(accuracy) => format!("{:.0}%", accuracy * 100.),
+            None => "-".to_string(),
+        };
+        println!(
+            "{:<30} {:>9} {:>10.0}% {:>9}",
+            d.name,
+            d.questions,
+            d.mean_probability * 100.,
+            first_try
+        );
+    }
+    Ok(())
+}
+
+/// Counts the typed wrong answers per question, ignoring case and
+/// surrounding whitespace, and lists the questions with the most first.
+async fn wrong_answers(repo: &db::Repository, set: Option<&str>, top: usize) -> Result<()> {
+    let answers = match set {
+        Some(set) => repo.get_answers_in_set(set).await?,
+        None => repo.get_all_answers().await?,
+    };
+    let mut by_question = HashMap::<i64, HashMap<String, usize>>::new();
+    for a in answers {
+        if let (false, Some(text)) = (a.correct, a.text) {
+            *by_question
+                .entry(a.question_id)
+                .or_default()
+                .entry(text.trim().to_lowercase())
+                .or_default() += 1;
+        }
+    }
+    if by_question.is_empty() {
+        println!("No wrong answers with their text recorded");
+        return Ok(());
+    }
+
+    let mut questions = Vec::new();
+    for (id, counts) in by_question {
+        let mut counts = counts.into_iter().collect::<Vec<_>>();
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        let total = counts.iter().map(|(_, n)| n).sum::<usize>();
+        questions.push((repo.get_question_by_id(id).await?, total, counts));
+    }
+    questions.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.name.cmp(&b.0.name)));
+    for (q, total, counts) in questions {
+        println!(
+            "{} {}",
+            output::label(&format!("{}/{}", q.factory, q.name)),
+            output::muted(&format!("({} wrong)", total))
+        );
+        for (text, n) in counts.into_iter().take(top) {
+            println!("  {:>4}  {}", n, output::wrong(&text));
+        }
+    }
+    Ok(())
+}
+
+/// Pairs every answer that follows a correct one with the days in between
+/// and whether it was correct again, per set and overall.
+async fn retention(repo: &db::Repository, only: Option<&str>) -> Result<()> {
+    let mut by_question = HashMap::<i64, Vec<db::Answer>>::new();
+    for a in repo.get_all_answers().await? {
+        by_question.entry(a.question_id).or_default().push(a);
+    }
+    let mut samples = HashMap::<i64, Vec<(f64, bool)>>::new();
+    for (id, mut answers) in by_question {
+        answers.sort_by_key(|a| a.time);
+        let s = samples.entry(id).or_default();
+        for pair in answers.windows(2) {
+            if pair[0].correct {
+                let days = (pair[1].time - pair[0].time).num_seconds() as f64 / 86400.;
+                s.push((days, pair[1].correct));
+            }
+        }
+    }
+
+    let mut sets = BTreeMap::<String, Vec<(f64, bool)>>::new();
+    for s in repo.get_all_question_sets().await? {
+        if only.is_some_and(|only| only != s.name) {
+            continue;
+        }
+        if let Some(samples) = samples.get(&s.question_id) {
+            sets.entry(s.name).or_default().extend(samples);
+        }
+    }
+    if only.is_none() {
+        sets.insert(
+            "(all questions)".to_string(),
+            samples.into_values().flatten().collect(),
+        );
+    }
+
+    println!(
+        "{:<30} {:>8} {:>10} {:>7} {:>7} {:>7}",
+        output::header("Set"),
+        output::header("Reviews"),
+        output::header("Stability"),
+        output::header("1 day"),
+        output::header("1 week"),
+        output::header("1 month")
+    );
+    for (name, samples) in sets {
+        let Some(curve) = ForgettingCurve::fit(&samples) else {
+            continue;
+        };
+        println!(
+            "{:<30} {:>8} {:>9.1}d {:>6.0}% {:>6.0}% {:>6.0}%",
+            name,
+            samples.len(),
+            curve.stability,
+            curve.retention(1.) * 100.,
+            curve.retention(7.) * 100.,
+            curve.retention(30.) * 100.
+        );
+    }
+    Ok(())
+}
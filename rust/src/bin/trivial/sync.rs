@@ -0,0 +1,48 @@
+use anyhow::{Context, Result};
+use rust::{db, sync};
+use std::path::PathBuf;
+
+/// Exchanges answers with other devices through a shared log file, e.g. one
+/// in a Dropbox folder or a git repository. Answers in the log are added to
+/// the database and answers given here are appended to the log.
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    /// The log file, created if it doesn't exist. Defaults to `sync_log` in
+    /// the config
+    #[arg(short, long)]
+    pub log: Option<PathBuf>,
+    /// Name of this device in the log. Defaults to `device` in the config or
+    /// the hostname
+    #[arg(long)]
+    pub device: Option<String>,
+}
+
+pub async fn run(repo: &db::Repository, args: Args) -> Result<()> {
+    let log = args
+        .log
+        .context("no log given, pass it with --log or set sync_log in the config")?;
+    let device = match args.device {
+        Some(device) => device,
+        None => whoami::fallible::hostname().context("finding the hostname, pass --device")?,
+    };
+    let counts = sync::sync(repo, &log, &device).await?;
+    println!(
+        "Imported {} answers and exported {} to {}",
+        counts.imported,
+        counts.exported,
+        log.display()
+    );
+    if counts.unknown > 0 {
+        println!(
+            "Skipped {} answers to questions that aren't loaded here",
+            counts.unknown
+        );
+    }
+    if counts.orphaned > 0 {
+        println!(
+            "Skipped {} answers to questions missing from the database, see trivial fsck",
+            counts.orphaned
+        );
+    }
+    Ok(())
+}
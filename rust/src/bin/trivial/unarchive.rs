@@ -0,0 +1,16 @@
+use anyhow::Result;
+use rust::{db, storage::Storage};
+
+/// Shows an archived set in the set picker and the forecast again.
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    set: String,
+}
+
+pub async fn run(repo: &db::Repository, args: Args) -> Result<()> {
+    match repo.set_archived(&args.set, false).await? {
+        true => println!("Unarchived {}", args.set),
+        false => println!("{} isn't archived", args.set),
+    }
+    Ok(())
+}
@@ -0,0 +1,59 @@
+use std::path::PathBuf;
+
+use anyhow::{bail, Result};
+use rust::{
+    assets,
+    db::Repository,
+    functionality::{load_models_dir, Service},
+    pack::Pack,
+    storage::Storage,
+};
+
+/// Writes the question sets in a pack file to the question directory.
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    /// The pack file
+    file: PathBuf,
+    /// Directory to write the sets to. Defaults to the one in the config file
+    #[arg(short, long)]
+    pub path: Option<PathBuf>,
+    /// Replace files that already exist with other contents
+    #[arg(long)]
+    force: bool,
+    /// Load the question sets into the database afterwards
+    #[arg(long)]
+    pub load: bool,
+}
+
+pub async fn run(repo: Option<&Repository>, args: Args) -> Result<()> {
+    let Some(dir) = &args.path else {
+        bail!("no directory given, pass it with --path");
+    };
+    let pack = Pack::open(&args.file)?;
+    pack.validate()?;
+    let m = &pack.manifest;
+    println!("{}", m.name);
+    if let Some(description) = &m.description {
+        println!("{}", description);
+    }
+    if let Some(author) = &m.author {
+        println!("by {}", author);
+    }
+    for path in pack.unpack(dir, args.force)? {
+        println!("Wrote {}", path.display());
+    }
+
+    if let Some(repo) = repo {
+        let models = load_models_dir(dir)?;
+        let assets = assets::scan(dir, &assets::references(&models)?)?;
+        let mut service = Service::new(repo).await?;
+        let report = service.import(&models, false).await?;
+        repo.set_assets(&assets).await?;
+        println!(
+            "Added {} and updated {} questions",
+            report.diff.added.len(),
+            report.diff.updated.len()
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,30 @@
+use std::path::PathBuf;
+
+use anyhow::{bail, Result};
+use rust::functionality::{load_models_dir, validate_models};
+
+/// Checks that the question set files parse and every question in them can be
+/// built, without touching the database.
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    /// Path to the question set
+    #[arg(short, long)]
+    path: PathBuf,
+}
+
+pub fn run(args: Args) -> Result<()> {
+    let models = load_models_dir(&args.path)?;
+    let problems = validate_models(&models);
+    for p in &problems {
+        println!("{}", p);
+    }
+    if !problems.is_empty() {
+        bail!("{} problems found", problems.len());
+    }
+    println!(
+        "{} sets with {} questions are valid",
+        models.sets.len(),
+        models.questions.len()
+    );
+    Ok(())
+}
@@ -0,0 +1,106 @@
+use anyhow::{bail, Result};
+use clap::ValueEnum;
+use rust::db;
+use rust::functionality::{pause, Method, Selection, Service};
+use rust::input::{interrupted, Shortcut};
+use rust::output;
+use rust::versus::Game;
+
+/// Two players take turns answering questions from the same set, with a
+/// scoreboard at the end. Answers aren't recorded as practice, each
+/// player's totals are kept and shown by `trivial stats players`.
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    /// Names of the two players, the first one starts
+    #[arg(num_args = 2, required = true, value_names = ["FIRST", "SECOND"])]
+    players: Vec<String>,
+    /// The set to play, picked from a list when not given
+    #[arg(short, long)]
+    set: Option<String>,
+    /// Number of questions each player answers
+    #[arg(short, long, default_value_t = 10)]
+    num: usize,
+    /// How the questions are picked
+    #[arg(long, value_enum, default_value_t = Pick::Random)]
+    pick: Pick,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum Pick {
+    /// Any question of the set
+    Random,
+    /// The questions known the least
+    Hardest,
+}
+
+pub async fn run(repo: &db::Repository, args: Args) -> Result<()> {
+    let mut game = Game::new(&args.players)?;
+    let mut service = Service::new(repo).await?;
+    let set = match args.set {
+        Some(set) => set,
+        None => {
+            let sets = service.get_sets().into_iter().cloned().collect();
+            inquire::Select::new("Pick a question set", sets).prompt()?
+        }
+    };
+    if !service.has_set(&set) {
+        bail!("there is no set named {}", set);
+    }
+    let method = match args.pick {
+        Pick::Random => Method::UniformRandom,
+        Pick::Hardest => Method::Bottom,
+    };
+    let num = args.num * args.players.len();
+    let question_ids = service.select(&set, method, num, Selection::All);
+
+    clearscreen::clear()?;
+    for id in question_ids {
+        let scores = game
+            .scoreboard()
+            .iter()
+            .map(|p| format!("{} {}", p.name, p.points))
+            .collect::<Vec<_>>()
+            .join(" · ");
+        println!("{}", output::header(&scores));
+        println!("{}'s turn", output::header(&game.current().name));
+        match service.get(id).runner.run() {
+            Ok(score) => game.record(score),
+            Err(err) => match interrupted(&err) {
+                Some(Shortcut::Quit) => break,
+                Some(Shortcut::Skip) => game.pass(),
+                Some(_) => println!("Only :s and :q work in a game"),
+                None => return Err(err),
+            },
+        }
+        println!();
+    }
+
+    if !game.started() {
+        return Ok(());
+    }
+    println!("{}", output::header("Final score"));
+    for (i, player) in game.scoreboard().iter().enumerate() {
+        println!(
+            "{}. {:<20} {:>5} points, {}/{} correct",
+            i + 1,
+            player.name,
+            player.points,
+            player.correct,
+            player.answers
+        );
+    }
+    let winners = game.winners();
+    if winners.len() == args.players.len() {
+        println!("{}", output::correct("It's a draw!"));
+    } else {
+        let names = winners
+            .iter()
+            .map(|p| p.name.as_str())
+            .collect::<Vec<_>>()
+            .join(" and ");
+        println!("{}", output::correct(&format!("{} wins!", names)));
+    }
+    game.save(repo).await?;
+    pause()?;
+    Ok(())
+}
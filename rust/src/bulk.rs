@@ -0,0 +1,112 @@
+use crate::db::SetMembership;
+use crate::encoding;
+use crate::functionality::content_hash;
+use crate::storage::Storage;
+use anyhow::{bail, Context, Result};
+use serde_json::Value;
+
+/// Tags and sets to add to or take out of many questions at once.
+///
+/// Tags are stored in the data of the questions, so loading a set file
+/// again sets the tags of its questions back to the ones in the file.
+/// Set memberships stay unless the set excludes the question.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct BulkEdit {
+    pub add_tags: Vec<String>,
+    pub remove_tags: Vec<String>,
+    pub add_sets: Vec<String>,
+    pub remove_sets: Vec<String>,
+}
+
+impl BulkEdit {
+    pub fn is_empty(&self) -> bool {
+        self.add_tags.is_empty()
+            && self.remove_tags.is_empty()
+            && self.add_sets.is_empty()
+            && self.remove_sets.is_empty()
+    }
+}
+
+/// What `bulk_edit` changed.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct BulkCounts {
+    /// Questions whose tags changed.
+    pub retagged: usize,
+    /// Questions put in a set they weren't in.
+    pub added: usize,
+    /// Questions taken out of a set they were in.
+    pub removed: usize,
+}
+
+/// Applies the edit to the questions in a single transaction, so either
+/// every question changes or, on error, none does.
+pub async fn bulk_edit(repo: &dyn Storage, ids: &[i64], edit: &BulkEdit) -> Result<BulkCounts> {
+    let mut retagged = Vec::new();
+    let mut added = Vec::new();
+    let mut removed = Vec::new();
+    for &id in ids {
+        let mut q = repo.get_question_by_id(id).await?;
+        let membership = |set: &String| SetMembership {
+            set: set.clone(),
+            factory: q.factory.clone(),
+            name: q.name.clone(),
+        };
+        let sets = repo.get_sets_of_question(id).await?;
+        added.extend(
+            edit.add_sets
+                .iter()
+                .filter(|s| !sets.contains(s))
+                .map(membership),
+        );
+        removed.extend(
+            edit.remove_sets
+                .iter()
+                .filter(|s| sets.contains(s))
+                .map(membership),
+        );
+
+        let data = retag(&q.data, edit).with_context(|| format!("{}/{}", q.factory, q.name))?;
+        if let Some(data) = data {
+            q.content_hash = Some(content_hash(&data));
+            q.data = data;
+            retagged.push(q);
+        }
+    }
+    repo.edit_questions(&retagged, &added, &removed).await?;
+    Ok(BulkCounts {
+        retagged: retagged.len(),
+        added: added.len(),
+        removed: removed.len(),
+    })
+}
+
+/// The data with the tags of the edit added and removed, or None if its
+/// tags stay the same.
+fn retag(data: &[u8], edit: &BulkEdit) -> Result<Option<Vec<u8>>> {
+    if edit.add_tags.is_empty() && edit.remove_tags.is_empty() {
+        return Ok(None);
+    }
+    let mut value = encoding::decode::<Value>(data)?;
+    let Value::Object(map) = &mut value else {
+        bail!("the question's data has no place for tags");
+    };
+    let old = match map.get("tags") {
+        Some(tags) => serde_json::from_value::<Vec<String>>(tags.clone())?,
+        None => Vec::new(),
+    };
+    let mut tags = old.clone();
+    tags.retain(|t| !edit.remove_tags.contains(t));
+    for tag in &edit.add_tags {
+        if !tags.contains(tag) {
+            tags.push(tag.clone());
+        }
+    }
+    if tags == old {
+        return Ok(None);
+    }
+    match tags.is_empty() {
+        true => map.shift_remove("tags"),
+        false => map.insert("tags".to_string(), tags.into()),
+    };
+    Ok(Some(encoding::encode(&value)?))
+}
@@ -0,0 +1,208 @@
+use crate::encoding;
+use crate::functionality::{
+    FactoryOptions, QuestionFactory, QuestionKey, QuestionRunner, QuestionSetFactory, Score,
+    SetIndex,
+};
+use crate::hints::Hints;
+use crate::output;
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{Read, Write};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+const ANSWER_PLACEHOLDER: &str = "{answer}";
+
+/// How long the output may take to arrive once the command has exited.
+const OUTPUT_GRACE: Duration = Duration::from_secs(1);
+
+fn default_timeout() -> u64 {
+    5
+}
+
+/// Questions answered with a snippet (a regex, a query, a shell one-liner)
+/// that is checked by running `command` on the question's input. Any
+/// `{answer}` in the command's arguments is replaced by the snippet.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct CodeData {
+    command: Vec<String>,
+    /// Seconds the command may run before it's killed.
+    #[serde(default = "default_timeout")]
+    timeout: u64,
+    #[serde(flatten)]
+    options: FactoryOptions,
+    #[serde(skip)]
+    depends: Vec<String>,
+}
+
+impl QuestionFactory for CodeData {
+    fn build(&self, data: &[u8]) -> Result<Box<dyn QuestionRunner>> {
+        let mut question = encoding::decode::<CodeQuestion>(data)?;
+        question.command = self.command.clone();
+        question.timeout = self.timeout;
+        Ok(Box::new(question) as Box<dyn QuestionRunner>)
+    }
+}
+
+impl QuestionSetFactory for CodeData {
+    fn build_set(&self, s: &SetIndex, set_name: &str) -> Vec<QuestionKey> {
+        s.get_factory(set_name).to_vec()
+    }
+
+    fn depends_on(&self) -> &Vec<String> {
+        &self.depends
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct CodeQuestion {
+    id: String,
+    question: String,
+    /// Passed to the command on stdin.
+    #[serde(default)]
+    input: String,
+    /// The output the command has to produce, ignoring surrounding whitespace.
+    output: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    hints: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    /// Questions to know before this one is introduced, see
+    /// `Question::requires`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    requires: Vec<String>,
+    #[serde(skip)]
+    command: Vec<String>,
+    #[serde(skip)]
+    timeout: u64,
+}
+
+impl QuestionRunner for CodeQuestion {
+    fn run(&self) -> Result<Score> {
+        let mut hints = Hints::new(&self.hints);
+        let answer = hints.prompt(&self.question, None)?;
+        let correct = match self.evaluate(&answer) {
+            Ok(output) if output.trim() == self.output.trim() => {
+                output::feedback(output::correct("Correct!"));
+                true
+            }
+            Ok(output) => {
+                output::feedback(output::wrong("Wrong. The output was:"));
+                output::feedback(output::wrong(output.trim_end()));
+                output::feedback("Expected:");
+                output::feedback(output::correct(self.output.trim_end()));
+                false
+            }
+            Err(err) => {
+                output::feedback(output::wrong(&format!("Wrong. {:#}", err)));
+                false
+            }
+        };
+        println!();
+        Ok(hints.score(correct.into()))
+    }
+
+    fn name(&self) -> String {
+        self.id.clone()
+    }
+
+    fn question_text(&self) -> String {
+        self.question.clone()
+    }
+
+    fn answer_text(&self) -> String {
+        self.output.clone()
+    }
+}
+
+impl CodeQuestion {
+    /// Runs the command with the answer filled in and returns its stdout.
+    fn evaluate(&self, answer: &str) -> Result<String> {
+        let command = self
+            .command
+            .iter()
+            .map(|a| a.replace(ANSWER_PLACEHOLDER, answer))
+            .collect::<Vec<_>>();
+        run_command(&command, &self.input, self.timeout)
+    }
+}
+
+/// Runs the command with the input on stdin and returns its stdout. The
+/// command runs with an empty environment apart from PATH, inside an empty
+/// scratch directory, and is killed along with anything it started once
+/// `timeout` seconds have passed. This is no sandbox: the command can do
+/// whatever the user running it can, so only use sets you trust.
+pub fn run_command(command: &[String], input: &str, timeout: u64) -> Result<String> {
+    let Some((program, args)) = command.split_first() else {
+        bail!("the question set doesn't configure a command");
+    };
+    let dir = std::env::temp_dir().join(format!("trivial-{}", std::process::id()));
+    fs::create_dir_all(&dir)?;
+
+    let mut command = Command::new(program);
+    command
+        .args(args)
+        .env_clear()
+        .env("PATH", std::env::var_os("PATH").unwrap_or_default())
+        .current_dir(&dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null());
+    // A group of its own, so whatever it starts can be killed with it.
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut command, 0);
+    let mut child = command.spawn()?;
+
+    // Feed stdin and drain stdout on their own threads so a chatty
+    // command can't block on a full pipe.
+    let mut stdin = child.stdin.take().unwrap();
+    let input = input.to_string();
+    thread::spawn(move || stdin.write_all(input.as_bytes()));
+    let mut stdout = child.stdout.take().unwrap();
+    let (sender, output) = mpsc::channel();
+    thread::spawn(move || {
+        let mut output = String::new();
+        let _ = sender.send(stdout.read_to_string(&mut output).map(|_| output));
+    });
+
+    let deadline = Instant::now() + Duration::from_secs(timeout);
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            kill_group(&child);
+            child.kill()?;
+            child.wait()?;
+            let _ = fs::remove_dir_all(&dir);
+            bail!("the command timed out after {}s", timeout);
+        }
+        thread::sleep(Duration::from_millis(10));
+    };
+    // Anything left running could keep stdout open.
+    kill_group(&child);
+    let _ = fs::remove_dir_all(&dir);
+
+    let output = match output.recv_timeout(OUTPUT_GRACE) {
+        Ok(output) => output?,
+        Err(_) => bail!("the output of the command couldn't be read"),
+    };
+    if !status.success() {
+        bail!("the command failed with {}", status);
+    }
+    Ok(output)
+}
+
+/// Kills the process group the command leads, see `run_command`.
+fn kill_group(child: &Child) {
+    #[cfg(unix)]
+    unsafe {
+        // Fails harmlessly if the group is gone already.
+        libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL);
+    }
+    #[cfg(not(unix))]
+    let _ = child;
+}
@@ -0,0 +1,41 @@
+use anyhow::{bail, Context, Result};
+
+/// First byte of question data compressed with zstd, the format version.
+/// Uncompressed data is YAML or JSON text, which never starts with it.
+pub const ZSTD_V1: u8 = 1;
+
+/// Data shorter than this is stored as is, compressing it gains little.
+pub const MIN_SIZE: usize = 256;
+
+const LEVEL: i32 = 3;
+
+/// Compresses the data if it's large enough and gets smaller for it.
+pub fn compress(data: &[u8]) -> Vec<u8> {
+    if data.len() < MIN_SIZE || is_compressed(data) {
+        return data.to_vec();
+    }
+    match zstd::bulk::compress(data, LEVEL) {
+        Ok(compressed) if compressed.len() + 1 < data.len() => {
+            let mut out = Vec::with_capacity(compressed.len() + 1);
+            out.push(ZSTD_V1);
+            out.extend(compressed);
+            out
+        }
+        _ => data.to_vec(),
+    }
+}
+
+/// Undoes `compress`, passing uncompressed data through.
+pub fn decompress(data: &[u8]) -> Result<Vec<u8>> {
+    match data.first() {
+        Some(&ZSTD_V1) => {
+            zstd::stream::decode_all(&data[1..]).context("decompressing question data")
+        }
+        Some(&b) if b < 0x09 => bail!("question data in an unknown format {}", b),
+        _ => Ok(data.to_vec()),
+    }
+}
+
+pub fn is_compressed(data: &[u8]) -> bool {
+    data.first() == Some(&ZSTD_V1)
+}
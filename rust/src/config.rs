@@ -0,0 +1,96 @@
+use crate::hooks::Hook;
+use crate::model::ModelOverrides;
+use crate::output::Theme;
+use crate::profile::Profile;
+use crate::ranking::CustomMethod;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Settings read from the config file, so they don't have to be passed to
+/// every command. Written by `trivial init`.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Config {
+    /// Path to the database, used when --db isn't given.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub db: Option<PathBuf>,
+    /// Directory with the question set files, used when --path isn't given.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub questions: Option<PathBuf>,
+    /// URL of the index of packs used by `install` and `list-remote`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub index: Option<String>,
+    /// Colors and attributes of the output, see `output::Theme`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub theme: Option<Theme>,
+    /// Minimum distance between sibling questions in a session, used when
+    /// --sibling-distance isn't given.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sibling_distance: Option<usize>,
+    /// How well a prerequisite has to be known before the questions
+    /// requiring it are introduced, used when --prerequisite-probability
+    /// isn't given.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prerequisite_probability: Option<f64>,
+    /// Ranking methods offered by `run` along with the built-in ones, see
+    /// `ranking::CustomMethod`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ranking_methods: Vec<CustomMethod>,
+    /// Settings sessions start from, picked by the time of day or with
+    /// --profile, see `profile::Profile`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub profiles: Vec<Profile>,
+    /// Header shown above every question, with fields such as
+    /// `{probability}` or `{due}`, see `header::FIELDS`. Empty hides it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub header: Option<String>,
+    /// Commands or URLs told about finished sessions and practice streaks,
+    /// see `hooks::Hook`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hooks: Vec<Hook>,
+    /// Log of answers shared with other devices, used by `sync` when --log
+    /// isn't given.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sync_log: Option<PathBuf>,
+    /// Name of this device in the sync log, used when --device isn't given.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device: Option<String>,
+    /// Parameters of the probability model, for sets that don't give their
+    /// own, see `model::ModelParams`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<ModelOverrides>,
+    /// Where sets of type `remote` are kept between fetches, see
+    /// `remote::RemoteSet`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache: Option<PathBuf>,
+    /// Where plugins providing question types are loaded from, see
+    /// `plugins::Plugin`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub plugins: Option<PathBuf>,
+}
+
+impl Config {
+    /// Where the config file is kept by default, e.g.
+    /// `~/.config/trivial/config.yaml` on Linux.
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("trivial").join("config.yaml"))
+    }
+
+    /// Reads the config file, or returns the defaults if there is none.
+    pub fn load(path: &Path) -> Result<Config> {
+        if !path.exists() {
+            return Ok(Config::default());
+        }
+        let data = fs::read(path).with_context(|| format!("reading {}", path.display()))?;
+        serde_yaml::from_slice(&data).with_context(|| format!("parsing {}", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_yaml::to_string(self)?)?;
+        Ok(())
+    }
+}
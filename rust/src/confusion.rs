@@ -0,0 +1,136 @@
+use crate::encoding;
+use crate::functionality::QuestionKey;
+use crate::normalize::Normalize;
+use crate::storage::Storage;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// A word answered with the translation of another word.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Confusion {
+    pub word: QuestionKey,
+    pub confused_with: QuestionKey,
+    /// The translation of `confused_with` that was given, as typed the last
+    /// time.
+    pub answer: String,
+    pub count: usize,
+}
+
+#[derive(Deserialize)]
+struct Translations {
+    translations: Vec<String>,
+}
+
+#[derive(Deserialize, Default)]
+struct VocabNormalize {
+    #[serde(default)]
+    normalize: Normalize,
+}
+
+/// Finds the vocab words whose recorded wrong answers are translations of
+/// other words, at least `min` times, the most frequent first.
+pub async fn find_confusions(repo: &dyn Storage, min: usize) -> Result<Vec<Confusion>> {
+    let mut normalize = HashMap::new();
+    for f in repo.get_all_question_factories().await? {
+        if f.factory_type == "vocab" {
+            let n = encoding::decode::<VocabNormalize>(&f.data).unwrap_or_default();
+            normalize.insert(f.name, n.normalize);
+        }
+    }
+
+    let mut words = HashMap::new();
+    for q in repo.get_all_questions().await? {
+        if !normalize.contains_key(&q.factory) {
+            continue;
+        }
+        if let Ok(t) = encoding::decode::<Translations>(&q.data) {
+            let key = QuestionKey {
+                factory: q.factory,
+                name: q.name,
+            };
+            words.insert(q.id, (key, t.translations));
+        }
+    }
+
+    // The words by their translations, normalized the way each set compares
+    // its answers.
+    let mut vocabulary = HashMap::<&str, HashMap<String, Vec<i64>>>::new();
+    for (factory, n) in &normalize {
+        let by_translation = vocabulary.entry(factory).or_default();
+        for (&id, (_, translations)) in &words {
+            for t in translations {
+                let ids = by_translation.entry(n.apply(t)).or_default();
+                if !ids.contains(&id) {
+                    ids.push(id);
+                }
+            }
+        }
+    }
+
+    let mut counts = HashMap::<(i64, i64), (String, usize)>::new();
+    for a in repo.get_all_answers().await? {
+        let (Some((key, _)), false, Some(text)) = (words.get(&a.question_id), a.correct, a.text)
+        else {
+            continue;
+        };
+        let normalized = normalize[&key.factory].apply(&text);
+        let Some(others) = vocabulary[key.factory.as_str()].get(&normalized) else {
+            continue;
+        };
+        for &other in others.iter().filter(|&&other| other != a.question_id) {
+            let entry = counts.entry((a.question_id, other)).or_default();
+            entry.0 = text.clone();
+            entry.1 += 1;
+        }
+    }
+
+    let mut confusions = counts
+        .into_iter()
+        .filter(|(_, (_, count))| *count >= min)
+        .map(|((word, other), (answer, count))| Confusion {
+            word: words[&word].0.clone(),
+            confused_with: words[&other].0.clone(),
+            answer,
+            count,
+        })
+        .collect::<Vec<_>>();
+    confusions.sort_by(|a, b| {
+        b.count
+            .cmp(&a.count)
+            .then_with(|| a.word.name.cmp(&b.word.name))
+            .then_with(|| a.confused_with.name.cmp(&b.confused_with.name))
+    });
+    Ok(confusions)
+}
+
+#[derive(Serialize)]
+struct DrillData {
+    questions: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct DrillSet<'a> {
+    name: &'a str,
+    type_: &'a str,
+    data: DrillData,
+}
+
+/// Formats a `questions` set with both words of every confusion, to be
+/// practiced together.
+pub fn drill_yaml(name: &str, confusions: &[Confusion]) -> Result<String> {
+    let mut questions = Vec::new();
+    for c in confusions {
+        for key in [&c.word, &c.confused_with] {
+            let key = format!("{}/{}", key.factory, key.name);
+            if !questions.contains(&key) {
+                questions.push(key);
+            }
+        }
+    }
+    Ok(serde_yaml::to_string(&DrillSet {
+        name,
+        type_: "questions",
+        data: DrillData { questions },
+    })?)
+}
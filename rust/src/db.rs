@@ -1,9 +1,33 @@
-use anyhow::Result;
+use crate::compress::{compress, decompress, is_compressed};
+use crate::functionality::{rename_set_references, ProbabilityComputer};
+use crate::storage::Storage;
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use log::LevelFilter;
 use sqlx::{
+    pool::PoolConnection,
     prelude::FromRow,
-    types::chrono::{DateTime, Utc},
-    Pool, Sqlite, SqlitePool,
+    sqlite::{
+        SqliteConnectOptions, SqliteConnection, SqliteJournalMode, SqlitePoolOptions,
+        SqliteSynchronous,
+    },
+    types::chrono::{DateTime, NaiveDate, Utc},
+    ConnectOptions, Connection, Pool, QueryBuilder, Sqlite, SqlitePool,
 };
+use std::future::Future;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+use tracing::{debug, warn};
+
+// Rows per multi-row INSERT. Kept well below SQLite's bound parameter limit.
+const BATCH_SIZE: usize = 500;
+/// Statements slower than this are logged as warnings.
+const SLOW_STATEMENT: Duration = Duration::from_millis(500);
+/// How long a statement waits for another connection's write lock.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(10);
+/// Times a write is retried when the database is still locked after waiting.
+const LOCKED_RETRIES: u32 = 5;
 
 #[derive(Clone, FromRow, Debug, Default)]
 pub struct Question {
@@ -16,6 +40,10 @@ pub struct Question {
     pub num_correct: u32,
     pub num_incorrect: u32,
     pub data: Vec<u8>,
+    /// Hash of the data without the question's name, see
+    /// `functionality::content_hash`. Missing for questions stored before
+    /// it was introduced, until they're loaded again.
+    pub content_hash: Option<String>,
 }
 
 #[derive(Clone, FromRow, Debug)]
@@ -24,6 +52,35 @@ pub struct Answer {
     pub question_id: i64,
     pub time: DateTime<Utc>,
     pub correct: bool,
+    pub score: f64,
+    /// Relative error of a numeric guess, e.g. 0.1 for 10% too high.
+    pub error: Option<f64>,
+    /// What was typed, for questions answered by typing.
+    pub text: Option<String>,
+    /// Which steps of a sequence were answered correctly, see
+    /// `sequence::format_steps`.
+    pub steps: Option<String>,
+    /// The model parameters in effect when it was given, as JSON, see
+    /// `model::ModelParams`.
+    pub params: Option<String>,
+    /// How far off a numeric guess was allowed to be, e.g. 0.1 for 10%.
+    pub tolerance: Option<f64>,
+}
+
+/// What is known about an answer besides its score, recorded by the
+/// question while it was answered, see `input::take_details`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AnswerDetails {
+    /// Relative error of a numeric guess.
+    pub error: Option<f64>,
+    /// What was typed.
+    pub text: Option<String>,
+    /// Which steps of a sequence were answered correctly.
+    pub steps: Option<String>,
+    /// The model parameters in effect, filled in by the service.
+    pub params: Option<String>,
+    /// How far off a numeric guess was allowed to be.
+    pub tolerance: Option<f64>,
 }
 
 #[derive(Clone, FromRow, Debug)]
@@ -41,24 +98,419 @@ pub struct QuestionFactory {
     pub data: Vec<u8>,
 }
 
+/// A target probability every question in a set should reach by a deadline.
+#[derive(Clone, FromRow, Debug)]
+pub struct Goal {
+    pub set_name: String,
+    pub target: f64,
+    pub deadline: NaiveDate,
+}
+
+/// A player of versus games, see `versus::Game`, with their totals.
+#[derive(Clone, FromRow, Debug, Default, PartialEq)]
+pub struct User {
+    pub name: String,
+    pub games: u32,
+    pub wins: u32,
+    pub answers: u32,
+    pub correct: u32,
+    /// Sum of the scores of the answers, counting partial credit.
+    pub points: f64,
+}
+
+/// A file in the assets directory questions refer to, as of the last load,
+/// see `assets::DIR`.
+#[derive(Clone, FromRow, Debug, Default, PartialEq)]
+pub struct Asset {
+    /// Relative to the directory of the sets, e.g. `assets/flags/fr.png`.
+    pub path: String,
+    pub sha256: String,
+    pub size: i64,
+}
+
+/// A run of `trivial load` and what it changed, shown by `trivial history`.
+/// The lists have an entry per line.
+#[derive(Clone, FromRow, Debug, Default, PartialEq)]
+pub struct Load {
+    pub id: i64,
+    pub time: DateTime<Utc>,
+    /// The question set files that were read.
+    pub files: String,
+    /// `factory/name` of each question added.
+    pub added: String,
+    /// `factory/name` of each question whose data changed.
+    pub updated: String,
+    /// `factory/old -> new` for each question renamed.
+    pub renamed: String,
+    /// `factory/name` of each question deleted by --prune.
+    pub pruned: String,
+}
+
+impl Load {
+    /// The entries of one of the lists.
+    pub fn entries(list: &str) -> impl Iterator<Item = &str> {
+        list.lines().filter(|l| !l.is_empty())
+    }
+}
+
+/// A question's membership in a set, keyed by the question's factory and name
+/// since imported questions don't have an id until they are inserted.
+#[derive(Clone, Debug)]
+pub struct SetMembership {
+    pub set: String,
+    pub factory: String,
+    pub name: String,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ImportCounts {
+    pub questions: u64,
+    pub factories: u64,
+    pub memberships: u64,
+}
+
+/// Set memberships only one of two databases has, for questions and sets
+/// both have. Merging keeps them all, since without a history it can't be
+/// told whether one side added them or the other removed them.
+#[derive(Clone, Debug, Default)]
+pub struct MembershipDiff {
+    pub only_here: Vec<SetMembership>,
+    pub only_there: Vec<SetMembership>,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CompressCounts {
+    pub questions: u64,
+    /// Bytes of data before compressing.
+    pub before: u64,
+    pub after: u64,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MergeCounts {
+    pub factories: u64,
+    pub questions: u64,
+    pub answers: u64,
+    pub memberships: u64,
+}
+
+/// Rows referring to rows that don't exist, left by databases from before
+/// foreign keys or by deleting rows by hand without them.
+#[derive(Clone, Debug, Default)]
+pub struct Orphans {
+    /// Answers to questions that don't exist.
+    pub answers: u64,
+    /// Set memberships of questions that don't exist.
+    pub memberships: u64,
+    /// Questions whose factory doesn't exist, as factory and name. Loading
+    /// their set adds the factory back.
+    pub questions: Vec<(String, String)>,
+}
+
+impl Orphans {
+    pub fn is_empty(&self) -> bool {
+        self.answers == 0 && self.memberships == 0 && self.questions.is_empty()
+    }
+}
+
+#[derive(Clone)]
 pub struct Repository {
     db: Pool<Sqlite>,
 }
 
 impl Repository {
     pub async fn new(db_url: &str) -> Result<Repository> {
-        let db = SqlitePool::connect(db_url).await?;
+        // Statements are logged with their duration, see `logging`.
+        let options = SqliteConnectOptions::from_str(db_url)?
+            .log_statements(LevelFilter::Debug)
+            .log_slow_statements(LevelFilter::Warn, SLOW_STATEMENT)
+            .busy_timeout(BUSY_TIMEOUT);
+        let db = if db_url.contains(":memory:") {
+            // Every connection to an in-memory database gets its own database,
+            // so there must only ever be one.
+            SqlitePoolOptions::new()
+                .max_connections(1)
+                .idle_timeout(None)
+                .max_lifetime(None)
+                .connect_with(options)
+                .await?
+        } else {
+            // With write-ahead logging readers don't block the writer, so a
+            // session can run while another process uses the same database.
+            let options = options
+                .journal_mode(SqliteJournalMode::Wal)
+                .synchronous(SqliteSynchronous::Normal);
+            // Migrations rebuild tables, which foreign keys get in the way
+            // of. Inside their transaction they can't be turned off, so the
+            // migrations get a connection of their own.
+            let mut conn = options.clone().foreign_keys(false).connect().await?;
+            sqlx::migrate!("../sql/migrations").run(&mut conn).await?;
+            conn.close().await?;
+            SqlitePool::connect_with(options).await?
+        };
+        debug!(db_url, "connected");
+        // A new in-memory database has nothing for the foreign keys to trip
+        // over, and a file database is up to date by now.
+        sqlx::migrate!("../sql/migrations").run(&db).await?;
         Ok(Repository { db })
     }
 
-    pub async fn get_all_questions(&self) -> Result<Vec<Question>> {
+    async fn import_once(
+        &self,
+        questions: &[Question],
+        factories: &[QuestionFactory],
+        memberships: &[SetMembership],
+        renames: &[(i64, String)],
+        removed: &[SetMembership],
+        pruned: &[i64],
+    ) -> Result<ImportCounts> {
+        let mut counts = ImportCounts::default();
+        let created_at = chrono::offset::Utc::now();
+        let mut tx = self.db.begin().await?;
+
+        // Renames first, so the questions under their new names are updated
+        // rather than inserted again.
+        for (id, name) in renames {
+            sqlx::query("UPDATE questions SET name = $1 WHERE id = $2;")
+                .bind(name)
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        // Factories first, questions refer to them.
+        for chunk in factories.chunks(BATCH_SIZE) {
+            let mut qb = QueryBuilder::<Sqlite>::new(
+                "INSERT INTO question_factories(name, factory_type, data) ",
+            );
+            qb.push_values(chunk, |mut b, f| {
+                b.push_bind(&f.name)
+                    .push_bind(&f.factory_type)
+                    .push_bind(&f.data);
+            });
+            qb.push(
+                " ON CONFLICT(name) DO UPDATE SET factory_type = excluded.factory_type, data = excluded.data \
+                WHERE factory_type != excluded.factory_type OR data != excluded.data",
+            );
+            counts.factories += qb.build().execute(&mut *tx).await?.rows_affected();
+        }
+
+        for chunk in questions.chunks(BATCH_SIZE) {
+            let mut qb = QueryBuilder::<Sqlite>::new(
+                "INSERT INTO questions(factory, name, created_at, probability, num_correct, num_incorrect, data, content_hash) ",
+            );
+            qb.push_values(chunk, |mut b, q| {
+                b.push_bind(&q.factory)
+                    .push_bind(&q.name)
+                    .push_bind(created_at)
+                    .push_bind(0.5)
+                    .push_bind(1)
+                    .push_bind(1)
+                    .push_bind(compress(&q.data))
+                    .push_bind(&q.content_hash);
+            });
+            qb.push(
+                " ON CONFLICT(factory, name) DO UPDATE SET data = excluded.data, content_hash = excluded.content_hash \
+                WHERE data != excluded.data OR content_hash IS NOT excluded.content_hash",
+            );
+            counts.questions += qb.build().execute(&mut *tx).await?.rows_affected();
+        }
+
+        counts.memberships += insert_memberships(&mut tx, memberships).await?;
+        delete_memberships(&mut tx, removed).await?;
+        delete_questions(&mut tx, pruned).await?;
+
+        tx.commit().await?;
+        Ok(counts)
+    }
+
+    /// Compresses the data of the questions stored before it was compressed,
+    /// then rebuilds the file so it shrinks. Returns the number of questions
+    /// compressed and the total size of their data before and after.
+    pub async fn compress_data(&self) -> Result<CompressCounts> {
+        let rows: Vec<(i64, Vec<u8>)> = sqlx::query_as("SELECT id, data FROM questions;")
+            .fetch_all(&self.db)
+            .await?;
+        let mut counts = CompressCounts::default();
+        let mut tx = self.db.begin().await?;
+        for (id, data) in rows {
+            if is_compressed(&data) {
+                continue;
+            }
+            let compressed = compress(&data);
+            if compressed.len() == data.len() {
+                continue;
+            }
+            counts.questions += 1;
+            counts.before += data.len() as u64;
+            counts.after += compressed.len() as u64;
+            sqlx::query("UPDATE questions SET data = $1 WHERE id = $2;")
+                .bind(compressed)
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        if counts.questions > 0 {
+            sqlx::query("VACUUM;").execute(&self.db).await?;
+        }
+        Ok(counts)
+    }
+
+    /// Compares the set memberships with those of the database at `path`,
+    /// matching questions by factory and name. Call it before `merge_from`,
+    /// which adds the memberships of both.
+    pub async fn membership_diff(&self, path: &str) -> Result<MembershipDiff> {
+        let mut conn = self.attach(path).await?;
+        let diff = attached_membership_diff(&mut conn).await;
+        detach(conn).await?;
+        diff
+    }
+
+    /// Copies the factories, questions, answers and set memberships of the
+    /// database at `path` into this one, matching questions by factory and
+    /// name. On conflicts the data in this database is kept unless
+    /// `prefer_other` is set. Answers already present are skipped, so merging
+    /// the same database twice changes nothing.
+    pub async fn merge_from(&self, path: &str, prefer_other: bool) -> Result<MergeCounts> {
+        let mut conn = self.attach(path).await?;
+        let counts = merge_attached(&mut conn, prefer_other).await;
+        detach(conn).await?;
+        counts
+    }
+
+    /// Attaches the database at `path` as `other`, read-only, so nothing is
+    /// written to it. Its schema has to be that of this one: it isn't
+    /// migrated. Hand the connection to `detach` when done, also on errors.
+    async fn attach(&self, path: &str) -> Result<PoolConnection<Sqlite>> {
+        if !Path::new(path).exists() {
+            bail!("there is no database at {}", path);
+        }
+        let mut conn = self.db.acquire().await?;
+        sqlx::query("ATTACH DATABASE $1 AS other;")
+            .bind(read_only_uri(path))
+            .execute(&mut *conn)
+            .await?;
+        if let Err(err) = check_schema(&mut conn, path).await {
+            detach(conn).await?;
+            return Err(err);
+        }
+        Ok(conn)
+    }
+
+    /// Runs SQLite's own consistency check, returning the problems found.
+    pub async fn integrity_check(&self) -> Result<Vec<String>> {
+        let rows: Vec<String> = sqlx::query_scalar("PRAGMA integrity_check;")
+            .fetch_all(&self.db)
+            .await?;
+        Ok(rows.into_iter().filter(|r| r != "ok").collect())
+    }
+
+    pub async fn find_orphans(&self) -> Result<Orphans> {
+        let answers = sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(*) FROM answers WHERE question_id NOT IN (SELECT id FROM questions);",
+        )
+        .fetch_one(&self.db)
+        .await?;
+        let memberships = sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(*) FROM question_sets WHERE question_id NOT IN (SELECT id FROM questions);",
+        )
+        .fetch_one(&self.db)
+        .await?;
+        let questions = sqlx::query_as(
+            "SELECT factory, name FROM questions WHERE factory NOT IN (SELECT name FROM question_factories) ORDER BY factory, name;",
+        )
+        .fetch_all(&self.db)
+        .await?;
+        Ok(Orphans {
+            answers: answers as u64,
+            memberships: memberships as u64,
+            questions,
+        })
+    }
+
+    /// Deletes the orphans, questions without a factory along with their
+    /// answers and set memberships. Returns what was deleted.
+    pub async fn delete_orphans(&self) -> Result<Orphans> {
+        self.remove_orphans(false).await
+    }
+
+    /// Like `delete_orphans`, but first copies the rows to the `quarantine`
+    /// table, where they can still be looked at.
+    pub async fn quarantine_orphans(&self) -> Result<Orphans> {
+        self.remove_orphans(true).await
+    }
+
+    /// Rows in the `quarantine` table.
+    pub async fn quarantined(&self) -> Result<u64> {
+        let count = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM quarantine;")
+            .fetch_one(&self.db)
+            .await?;
+        Ok(count as u64)
+    }
+
+    async fn remove_orphans(&self, quarantine: bool) -> Result<Orphans> {
+        let orphans = self.find_orphans().await?;
+        let known =
+            "(SELECT id FROM questions WHERE factory IN (SELECT name FROM question_factories))";
+        let mut tx = self.db.begin().await?;
+        if quarantine {
+            let time = chrono::offset::Utc::now();
+            for (source, row, condition) in [
+                (
+                    "answers",
+                    "json_object('id', id, 'question_id', question_id, 'time', time, 'correct', correct, \
+                    'score', score, 'error', error, 'text', text, 'steps', steps, 'params', params, 'tolerance', tolerance)",
+                    format!("question_id NOT IN {}", known),
+                ),
+                (
+                    "question_sets",
+                    "json_object('id', id, 'name', name, 'question_id', question_id)",
+                    format!("question_id NOT IN {}", known),
+                ),
+                (
+                    "questions",
+                    "json_object('id', id, 'factory', factory, 'name', name, 'created_at', created_at, \
+                    'last_answered_at', last_answered_at, 'probability', probability, 'num_correct', num_correct, \
+                    'num_incorrect', num_incorrect, 'data', hex(data), 'content_hash', content_hash)",
+                    format!("id NOT IN {}", known),
+                ),
+            ] {
+                sqlx::query(&format!(
+                    "INSERT INTO quarantine(time, source, row) SELECT $1, '{}', {} FROM {} WHERE {};",
+                    source, row, source, condition
+                ))
+                .bind(time)
+                .execute(&mut *tx)
+                .await?;
+            }
+        }
+        for table in ["answers", "question_sets"] {
+            sqlx::query(&format!(
+                "DELETE FROM {} WHERE question_id NOT IN {};",
+                table, known
+            ))
+            .execute(&mut *tx)
+            .await?;
+        }
+        sqlx::query(&format!("DELETE FROM questions WHERE id NOT IN {};", known))
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(orphans)
+    }
+}
+
+#[async_trait]
+impl Storage for Repository {
+    async fn get_all_questions(&self) -> Result<Vec<Question>> {
         let res = sqlx::query_as::<_, Question>("SELECT * FROM questions;")
             .fetch_all(&self.db)
             .await?;
-        Ok(res)
+        res.into_iter().map(decompressed).collect()
     }
 
-    pub async fn has_question(&self, factory: &str, name: &str) -> Result<bool> {
+    async fn has_question(&self, factory: &str, name: &str) -> Result<bool> {
         let res = sqlx::query("SELECT * FROM questions WHERE factory = $1 AND name = $2 LIMIT 1")
             .bind(factory)
             .bind(name)
@@ -67,7 +519,7 @@ impl Repository {
         Ok(res.is_some())
     }
 
-    pub async fn get_question_by_name(&self, factory: &str, name: &str) -> Result<Question> {
+    async fn get_question_by_name(&self, factory: &str, name: &str) -> Result<Question> {
         let q = sqlx::query_as::<_, Question>(
             "
     SELECT * FROM questions WHERE  factory = $1 AND name = $2 LIMIT 1;
@@ -77,10 +529,10 @@ impl Repository {
         .bind(name)
         .fetch_one(&self.db)
         .await?;
-        Ok(q)
+        decompressed(q)
     }
 
-    pub async fn get_question_by_id(&self, id: i64) -> Result<Question> {
+    async fn get_question_by_id(&self, id: i64) -> Result<Question> {
         let q = sqlx::query_as::<_, Question>(
             "
     SELECT * FROM questions WHERE id = $1 LIMIT 1;
@@ -89,10 +541,10 @@ impl Repository {
         .bind(id)
         .fetch_one(&self.db)
         .await?;
-        Ok(q)
+        decompressed(q)
     }
 
-    pub async fn insert_question(&self, factory: &str, name: &str, data: &Vec<u8>) -> Result<()> {
+    async fn insert_question(&self, factory: &str, name: &str, data: &[u8]) -> Result<()> {
         let created_at = chrono::offset::Utc::now();
         sqlx::query("INSERT INTO questions(factory, name, created_at, probability, num_correct, num_incorrect, data) VALUES($1, $2, $3, $4, $5, $6, $7);")
             .bind(factory)
@@ -101,12 +553,12 @@ impl Repository {
             .bind(0.5)
             .bind(1)
             .bind(1)
-            .bind(data)
+            .bind(compress(data))
             .execute(&self.db).await?;
         Ok(())
     }
 
-    pub async fn set_probability(&self, question_id: i64, probability: f64) -> Result<()> {
+    async fn set_probability(&self, question_id: i64, probability: f64) -> Result<()> {
         sqlx::query(
             "
         UPDATE 
@@ -124,58 +576,170 @@ impl Repository {
         Ok(())
     }
 
-    pub async fn add_answer(
+    async fn add_answer(
         &self,
         question_id: i64,
         time: DateTime<Utc>,
         correct: bool,
+        score: f64,
+        details: &AnswerDetails,
         new_prob: f64,
     ) -> Result<()> {
         let (cor, inc) = if correct { (1, 0) } else { (0, 1) };
-        sqlx::query(
-            "
+        retry_on_locked(|| async move {
+            let mut tx = self.db.begin().await?;
+            sqlx::query(
+                "
         UPDATE 
             questions
         SET
             probability = $1, 
-            last_answered_at = $2,
+            last_answered_at = MAX(COALESCE(last_answered_at, $2), $2),
             num_correct = num_correct + $3,
             num_incorrect = num_incorrect + $4
         WHERE
             id = $5
         ;",
+            )
+            .bind(new_prob)
+            .bind(time)
+            .bind(cor)
+            .bind(inc)
+            .bind(question_id)
+            .execute(&mut *tx)
+            .await?;
+
+            sqlx::query(
+                "
+    INSERT INTO
+            answers(question_id, time, correct, score, error, text, steps, params, tolerance)
+            VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9);",
+            )
+            .bind(question_id)
+            .bind(time)
+            .bind(correct)
+            .bind(score)
+            .bind(details.error)
+            .bind(&details.text)
+            .bind(&details.steps)
+            .bind(&details.params)
+            .bind(details.tolerance)
+            .execute(&mut *tx)
+            .await?;
+            tx.commit().await?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn delete_last_answer(&self, question_id: i64, new_prob: f64) -> Result<bool> {
+        retry_on_locked(|| async move {
+            let mut tx = self.db.begin().await?;
+            let last = sqlx::query_as::<_, Answer>(
+                "SELECT * FROM answers WHERE question_id = $1 ORDER BY time DESC LIMIT 1;",
+            )
+            .bind(question_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+            let Some(last) = last else {
+                return Ok(false);
+            };
+            sqlx::query("DELETE FROM answers WHERE id = $1;")
+                .bind(last.id)
+                .execute(&mut *tx)
+                .await?;
+            let (cor, inc) = if last.correct { (1, 0) } else { (0, 1) };
+            sqlx::query(
+                "
+        UPDATE
+            questions
+        SET
+            probability = $1,
+            last_answered_at = (SELECT MAX(time) FROM answers WHERE question_id = $4),
+            num_correct = num_correct - $2,
+            num_incorrect = num_incorrect - $3
+        WHERE
+            id = $4
+        ;",
+            )
+            .bind(new_prob)
+            .bind(cor)
+            .bind(inc)
+            .bind(question_id)
+            .execute(&mut *tx)
+            .await?;
+            tx.commit().await?;
+            Ok(true)
+        })
+        .await
+    }
+
+    async fn get_all_answers(&self) -> Result<Vec<Answer>> {
+        let res = sqlx::query_as::<_, Answer>("SELECT * FROM answers;")
+            .fetch_all(&self.db)
+            .await?;
+        Ok(res)
+    }
+
+    async fn get_answers(&self, question_id: i64) -> Result<Vec<Answer>> {
+        let res = sqlx::query_as::<_, Answer>(
+            "SELECT * FROM answers WHERE question_id = $1 ORDER BY time;",
         )
-        .bind(new_prob)
-        .bind(time)
-        .bind(cor)
-        .bind(inc)
         .bind(question_id)
-        .execute(&self.db)
+        .fetch_all(&self.db)
         .await?;
+        Ok(res)
+    }
 
-        sqlx::query(
+    async fn get_answers_in_set(&self, name: &str) -> Result<Vec<Answer>> {
+        let res = sqlx::query_as::<_, Answer>(
             "
-    INSERT INTO
-            answers(question_id, time, correct)
-            VALUES($1, $2, $3);",
+        SELECT
+            a.*
+        FROM
+            answers a
+            JOIN question_sets s ON s.question_id = a.question_id
+        WHERE
+            s.name = $1
+        ;",
         )
-        .bind(question_id)
-        .bind(time)
-        .bind(correct)
-        .execute(&self.db)
+        .bind(name)
+        .fetch_all(&self.db)
         .await?;
-
-        Ok(())
+        Ok(res)
     }
 
-    pub async fn get_all_answers(&self) -> Result<Vec<Answer>> {
-        let res = sqlx::query_as::<_, Answer>("SELECT * FROM answers;")
-            .fetch_all(&self.db)
-            .await?;
+    async fn get_sets_of_question(&self, question_id: i64) -> Result<Vec<String>> {
+        let res = sqlx::query_scalar::<_, String>(
+            "SELECT name FROM question_sets WHERE question_id = $1 ORDER BY name;",
+        )
+        .bind(question_id)
+        .fetch_all(&self.db)
+        .await?;
         Ok(res)
     }
 
-    pub async fn has_question_in_set(&self, name: &str, question_id: i64) -> Result<bool> {
+    async fn get_questions_in_set(&self, name: &str) -> Result<Vec<Question>> {
+        let res = sqlx::query_as::<_, Question>(
+            "
+        SELECT
+            q.*
+        FROM
+            questions q
+            JOIN question_sets s ON s.question_id = q.id
+        WHERE
+            s.name = $1
+        ORDER BY
+            q.factory, q.name
+        ;",
+        )
+        .bind(name)
+        .fetch_all(&self.db)
+        .await?;
+        res.into_iter().map(decompressed).collect()
+    }
+
+    async fn has_question_in_set(&self, name: &str, question_id: i64) -> Result<bool> {
         let res = sqlx::query(
             "SELECT id FROM question_sets WHERE name = $1 AND question_id = $2 LIMIT 1",
         )
@@ -186,7 +750,7 @@ impl Repository {
         Ok(res.is_some())
     }
 
-    pub async fn insert_question_in_set(&self, name: &str, question_id: i64) -> Result<()> {
+    async fn insert_question_in_set(&self, name: &str, question_id: i64) -> Result<()> {
         sqlx::query("INSERT INTO question_sets(name, question_id) VALUES($1, $2);")
             .bind(name)
             .bind(question_id)
@@ -195,14 +759,14 @@ impl Repository {
         Ok(())
     }
 
-    pub async fn get_all_question_sets(&self) -> Result<Vec<QuestionSet>> {
+    async fn get_all_question_sets(&self) -> Result<Vec<QuestionSet>> {
         let res = sqlx::query_as::<_, QuestionSet>("SELECT * FROM question_sets;")
             .fetch_all(&self.db)
             .await?;
         Ok(res)
     }
 
-    pub async fn has_question_factory(&self, name: &str) -> Result<bool> {
+    async fn has_question_factory(&self, name: &str) -> Result<bool> {
         let res = sqlx::query("SELECT id FROM question_factories WHERE name = $1 LIMIT 1")
             .bind(name)
             .fetch_optional(&self.db)
@@ -210,11 +774,11 @@ impl Repository {
         Ok(res.is_some())
     }
 
-    pub async fn insert_question_factory(
+    async fn insert_question_factory(
         &self,
         name: &str,
         factory_type: &str,
-        data: &Vec<u8>,
+        data: &[u8],
     ) -> Result<()> {
         let q = sqlx::query(
             "INSERT INTO question_factories(name, factory_type, data) VALUES($1, $2, $3);",
@@ -226,10 +790,640 @@ impl Repository {
         Ok(())
     }
 
-    pub async fn get_all_question_factories(&self) -> Result<Vec<QuestionFactory>> {
+    async fn get_question_factory(&self, name: &str) -> Result<Option<QuestionFactory>> {
+        let res = sqlx::query_as::<_, QuestionFactory>(
+            "SELECT * FROM question_factories WHERE name = $1;",
+        )
+        .bind(name)
+        .fetch_optional(&self.db)
+        .await?;
+        Ok(res)
+    }
+
+    async fn get_all_question_factories(&self) -> Result<Vec<QuestionFactory>> {
         let res = sqlx::query_as::<_, QuestionFactory>("SELECT * FROM question_factories;")
             .fetch_all(&self.db)
             .await?;
         Ok(res)
     }
+
+    async fn get_all_goals(&self) -> Result<Vec<Goal>> {
+        let res = sqlx::query_as::<_, Goal>("SELECT * FROM goals ORDER BY set_name;")
+            .fetch_all(&self.db)
+            .await?;
+        Ok(res)
+    }
+
+    async fn set_goal(&self, goal: &Goal) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO goals(set_name, target, deadline) VALUES($1, $2, $3) \
+            ON CONFLICT(set_name) DO UPDATE SET target = excluded.target, deadline = excluded.deadline;",
+        )
+        .bind(&goal.set_name)
+        .bind(goal.target)
+        .bind(goal.deadline)
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+
+    async fn delete_goal(&self, set_name: &str) -> Result<bool> {
+        let res = sqlx::query("DELETE FROM goals WHERE set_name = $1;")
+            .bind(set_name)
+            .execute(&self.db)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    async fn record_load(&self, load: &Load) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO loads(time, files, added, updated, renamed, pruned) VALUES($1, $2, $3, $4, $5, $6);",
+        )
+        .bind(load.time)
+        .bind(&load.files)
+        .bind(&load.added)
+        .bind(&load.updated)
+        .bind(&load.renamed)
+        .bind(&load.pruned)
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+
+    async fn get_loads(&self) -> Result<Vec<Load>> {
+        let res = sqlx::query_as::<_, Load>("SELECT * FROM loads ORDER BY time, id;")
+            .fetch_all(&self.db)
+            .await?;
+        Ok(res)
+    }
+
+    async fn get_users(&self) -> Result<Vec<User>> {
+        let res = sqlx::query_as::<_, User>("SELECT * FROM users ORDER BY name;")
+            .fetch_all(&self.db)
+            .await?;
+        Ok(res)
+    }
+
+    async fn record_game(&self, players: &[User]) -> Result<()> {
+        let mut tx = self.db.begin().await?;
+        for p in players {
+            sqlx::query(
+                "INSERT INTO users(name, games, wins, answers, correct, points) VALUES($1, $2, $3, $4, $5, $6) \
+                ON CONFLICT(name) DO UPDATE SET games = games + excluded.games, wins = wins + excluded.wins, \
+                answers = answers + excluded.answers, correct = correct + excluded.correct, \
+                points = points + excluded.points;",
+            )
+            .bind(&p.name)
+            .bind(p.games)
+            .bind(p.wins)
+            .bind(p.answers)
+            .bind(p.correct)
+            .bind(p.points)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get_archived_sets(&self) -> Result<Vec<String>> {
+        let res = sqlx::query_scalar::<_, String>("SELECT name FROM archived_sets ORDER BY name;")
+            .fetch_all(&self.db)
+            .await?;
+        Ok(res)
+    }
+
+    async fn set_archived(&self, name: &str, archived: bool) -> Result<bool> {
+        let res = if archived {
+            sqlx::query("INSERT OR IGNORE INTO archived_sets(name, archived_at) VALUES($1, $2);")
+                .bind(name)
+                .bind(Utc::now())
+                .execute(&self.db)
+                .await?
+        } else {
+            sqlx::query("DELETE FROM archived_sets WHERE name = $1;")
+                .bind(name)
+                .execute(&self.db)
+                .await?
+        };
+        Ok(res.rows_affected() > 0)
+    }
+
+    async fn import(
+        &self,
+        questions: &[Question],
+        factories: &[QuestionFactory],
+        memberships: &[SetMembership],
+        renames: &[(i64, String)],
+        removed: &[SetMembership],
+        pruned: &[i64],
+    ) -> Result<ImportCounts> {
+        retry_on_locked(|| {
+            self.import_once(questions, factories, memberships, renames, removed, pruned)
+        })
+        .await
+    }
+
+    async fn remove_memberships(&self, memberships: &[SetMembership]) -> Result<u64> {
+        let mut tx = self.db.begin().await?;
+        let removed = delete_memberships(&mut tx, memberships).await?;
+        tx.commit().await?;
+        Ok(removed)
+    }
+
+    async fn edit_questions(
+        &self,
+        questions: &[Question],
+        added: &[SetMembership],
+        removed: &[SetMembership],
+    ) -> Result<()> {
+        let mut tx = self.db.begin().await?;
+        for q in questions {
+            sqlx::query("UPDATE questions SET data = $1, content_hash = $2 WHERE id = $3;")
+                .bind(compress(&q.data))
+                .bind(&q.content_hash)
+                .bind(q.id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        insert_memberships(&mut tx, added).await?;
+        delete_memberships(&mut tx, removed).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn rename_set(&self, old: &str, new: &str) -> Result<()> {
+        let mut tx = self.db.begin().await?;
+        let exists = "SELECT EXISTS(SELECT 1 FROM question_sets WHERE name = $1) \
+            OR EXISTS(SELECT 1 FROM question_factories WHERE name = $1);";
+        if sqlx::query_scalar::<_, bool>(exists)
+            .bind(new)
+            .fetch_one(&mut *tx)
+            .await?
+        {
+            bail!("a set named {} already exists", new);
+        }
+        if !sqlx::query_scalar::<_, bool>(exists)
+            .bind(old)
+            .fetch_one(&mut *tx)
+            .await?
+        {
+            bail!("there is no set named {}", old);
+        }
+        for update in [
+            "UPDATE question_sets SET name = $2 WHERE name = $1;",
+            "UPDATE question_factories SET name = $2 WHERE name = $1;",
+            "UPDATE questions SET factory = $2 WHERE factory = $1;",
+            "UPDATE goals SET set_name = $2 WHERE set_name = $1;",
+            "UPDATE archived_sets SET name = $2 WHERE name = $1;",
+        ] {
+            sqlx::query(update)
+                .bind(old)
+                .bind(new)
+                .execute(&mut *tx)
+                .await?;
+        }
+        let factories = sqlx::query_as::<_, QuestionFactory>("SELECT * FROM question_factories;")
+            .fetch_all(&mut *tx)
+            .await?;
+        for f in factories {
+            if let Some(data) = rename_set_references(&f, old, new)? {
+                sqlx::query("UPDATE question_factories SET data = $1 WHERE id = $2;")
+                    .bind(data)
+                    .bind(f.id)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn rename_question(&self, factory: &str, old: &str, new: &str) -> Result<()> {
+        let res = sqlx::query("UPDATE questions SET name = $3 WHERE factory = $1 AND name = $2;")
+            .bind(factory)
+            .bind(old)
+            .bind(new)
+            .execute(&self.db)
+            .await;
+        match res {
+            Ok(res) if res.rows_affected() == 0 => {
+                bail!("there is no question {}/{}", factory, old)
+            }
+            Ok(_) => Ok(()),
+            Err(sqlx::Error::Database(err)) if err.is_unique_violation() => {
+                bail!("{}/{} already exists", factory, new)
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    async fn delete_questions(&self, question_ids: &[i64]) -> Result<()> {
+        let mut tx = self.db.begin().await?;
+        delete_questions(&mut tx, question_ids).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn merge_questions(&self, canonical: i64, duplicates: &[i64]) -> Result<()> {
+        let mut tx = self.db.begin().await?;
+        for &id in duplicates {
+            sqlx::query("UPDATE answers SET question_id = $1 WHERE question_id = $2;")
+                .bind(canonical)
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+            sqlx::query(
+                "INSERT OR IGNORE INTO question_sets(name, question_id) SELECT name, $1 FROM question_sets WHERE question_id = $2;",
+            )
+            .bind(canonical)
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+            sqlx::query(
+                "
+        UPDATE
+            questions
+        SET
+            num_correct = questions.num_correct + d.num_correct - 1,
+            num_incorrect = questions.num_incorrect + d.num_incorrect - 1,
+            last_answered_at = COALESCE(
+                MAX(questions.last_answered_at, d.last_answered_at),
+                questions.last_answered_at,
+                d.last_answered_at
+            )
+        FROM
+            -- Every question starts at 1/1, which only counts once.
+            (SELECT * FROM questions WHERE id = $2) AS d
+        WHERE
+            questions.id = $1
+        ;",
+            )
+            .bind(canonical)
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+            sqlx::query("DELETE FROM question_sets WHERE question_id = $1;")
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+            sqlx::query("DELETE FROM questions WHERE id = $1;")
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        let answers = sqlx::query_as::<_, Answer>(
+            "SELECT * FROM answers WHERE question_id = $1 ORDER BY time;",
+        )
+        .bind(canonical)
+        .fetch_all(&mut *tx)
+        .await?;
+        sqlx::query("UPDATE questions SET probability = $1 WHERE id = $2;")
+            .bind(ProbabilityComputer::default_probability(&answers))
+            .bind(canonical)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get_assets(&self) -> Result<Vec<Asset>> {
+        let res = sqlx::query_as::<_, Asset>("SELECT * FROM assets ORDER BY path;")
+            .fetch_all(&self.db)
+            .await?;
+        Ok(res)
+    }
+
+    async fn set_assets(&self, assets: &[Asset]) -> Result<()> {
+        let mut tx = self.db.begin().await?;
+        sqlx::query("DELETE FROM assets;").execute(&mut *tx).await?;
+        for asset in assets {
+            sqlx::query("INSERT INTO assets(path, sha256, size) VALUES($1, $2, $3);")
+                .bind(&asset.path)
+                .bind(&asset.sha256)
+                .bind(asset.size)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get_links(&self) -> Result<Vec<(i64, i64)>> {
+        Ok(
+            sqlx::query_as("SELECT a, b FROM question_links ORDER BY a, b;")
+                .fetch_all(&self.db)
+                .await?,
+        )
+    }
+
+    async fn link_questions(&self, a: i64, b: i64) -> Result<bool> {
+        let res = sqlx::query("INSERT OR IGNORE INTO question_links(a, b) VALUES($1, $2);")
+            .bind(a.min(b))
+            .bind(a.max(b))
+            .execute(&self.db)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    async fn unlink_questions(&self, a: i64, b: i64) -> Result<bool> {
+        let res = sqlx::query("DELETE FROM question_links WHERE a = $1 AND b = $2;")
+            .bind(a.min(b))
+            .bind(a.max(b))
+            .execute(&self.db)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+}
+
+/// The memberships of sets both databases have that only one of them has,
+/// with the other database attached, see `Repository::attach`.
+async fn attached_membership_diff(conn: &mut SqliteConnection) -> Result<MembershipDiff> {
+    let mut diff = MembershipDiff::default();
+    for (a, b, list) in [
+        ("main", "other", &mut diff.only_here),
+        ("other", "main", &mut diff.only_there),
+    ] {
+        let rows: Vec<(String, String, String)> = sqlx::query_as(&format!(
+            "
+        SELECT
+            s.name, q.factory, q.name
+        FROM
+            {a}.question_sets s
+            JOIN {a}.questions q ON q.id = s.question_id
+            JOIN {b}.questions bq ON bq.factory = q.factory AND bq.name = q.name
+        WHERE
+            EXISTS (SELECT 1 FROM {b}.question_sets bs WHERE bs.name = s.name)
+            AND NOT EXISTS (SELECT 1 FROM {b}.question_sets bs WHERE bs.name = s.name AND bs.question_id = bq.id)
+        ORDER BY
+            s.name, q.factory, q.name
+        ;",
+            a = a,
+            b = b
+        ))
+        .fetch_all(&mut *conn)
+        .await?;
+        *list = rows
+            .into_iter()
+            .map(|(set, factory, name)| SetMembership { set, factory, name })
+            .collect();
+    }
+    Ok(diff)
+}
+
+/// Merges the attached database into this one, see `Repository::merge_from`.
+async fn merge_attached(conn: &mut SqliteConnection, prefer_other: bool) -> Result<MergeCounts> {
+    let mut counts = MergeCounts::default();
+    let (factory_conflict, question_conflict) = if prefer_other {
+        (
+            "DO UPDATE SET factory_type = excluded.factory_type, data = excluded.data WHERE data != excluded.data",
+            "DO UPDATE SET data = excluded.data, content_hash = excluded.content_hash WHERE data != excluded.data",
+        )
+    } else {
+        ("DO NOTHING", "DO NOTHING")
+    };
+
+    let mut tx = conn.begin().await?;
+    counts.factories = sqlx::query(&format!(
+        "INSERT INTO question_factories(name, factory_type, data) \
+            SELECT name, factory_type, data FROM other.question_factories WHERE true \
+            ON CONFLICT(name) {}",
+        factory_conflict
+    ))
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+
+    // Answers merged into questions that were already here have to be
+    // added to their counts, new questions bring their own.
+    sqlx::query("CREATE TEMP TABLE existing AS SELECT id FROM main.questions;")
+        .execute(&mut *tx)
+        .await?;
+    counts.questions = sqlx::query(&format!(
+        "INSERT INTO questions(factory, name, created_at, last_answered_at, probability, num_correct, num_incorrect, data, content_hash) \
+        SELECT factory, name, created_at, last_answered_at, probability, num_correct, num_incorrect, data, content_hash \
+        FROM other.questions WHERE true \
+        ON CONFLICT(factory, name) {}",
+        question_conflict
+    ))
+    .execute(&mut *tx)
+    .await?
+        .rows_affected();
+
+    sqlx::query(
+        "
+    CREATE TEMP TABLE merged AS
+    SELECT
+        q.id AS question_id, a.time, a.correct, a.score, a.error, a.text, a.steps, a.params, a.tolerance
+    FROM
+        other.answers a
+        JOIN other.questions oq ON oq.id = a.question_id
+        JOIN main.questions q ON q.factory = oq.factory AND q.name = oq.name
+    WHERE
+        NOT EXISTS (SELECT 1 FROM main.answers b WHERE b.question_id = q.id AND b.time = a.time)
+    ;",
+    )
+        .execute(&mut *tx)
+    .await?;
+    counts.answers = sqlx::query(
+        "INSERT INTO answers(question_id, time, correct, score, error, text, steps, params, tolerance) SELECT question_id, time, correct, score, error, text, steps, params, tolerance FROM merged;",
+    )
+    .execute(&mut *tx)
+    .await?
+        .rows_affected();
+    sqlx::query(
+        "
+    UPDATE
+        questions
+    SET
+        num_correct = questions.num_correct + m.num_correct,
+        num_incorrect = questions.num_incorrect + m.num_incorrect,
+        last_answered_at = COALESCE(
+            MAX(questions.last_answered_at, m.last_answered_at),
+            questions.last_answered_at,
+            m.last_answered_at
+        )
+    FROM
+        (SELECT question_id, SUM(correct) AS num_correct, SUM(NOT correct) AS num_incorrect, MAX(time) AS last_answered_at
+         FROM merged GROUP BY question_id) AS m
+    WHERE
+        questions.id = m.question_id AND questions.id IN (SELECT id FROM existing)
+    ;",
+    )
+    .execute(&mut *tx)
+        .await?;
+
+    counts.memberships = sqlx::query(
+        "
+        INSERT OR IGNORE INTO question_sets(name, question_id)
+        SELECT
+            s.name, q.id
+        FROM
+            other.question_sets s
+            JOIN other.questions oq ON oq.id = s.question_id
+            JOIN main.questions q ON q.factory = oq.factory AND q.name = oq.name
+        ;",
+    )
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+
+    sqlx::query("DROP TABLE temp.merged;")
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("DROP TABLE temp.existing;")
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    Ok(counts)
+}
+
+/// Fails unless the attached database at `path` has the schema of this one.
+async fn check_schema(conn: &mut SqliteConnection, path: &str) -> Result<()> {
+    let version = |schema: &str| format!("SELECT MAX(version) FROM {}._sqlx_migrations;", schema);
+    let here: Option<i64> = sqlx::query_scalar(&version("main"))
+        .fetch_one(&mut *conn)
+        .await?;
+    let there: Option<i64> = sqlx::query_scalar(&version("other"))
+        .fetch_one(&mut *conn)
+        .await
+        .with_context(|| format!("{} isn't a database of questions", path))?;
+    if there < here {
+        bail!(
+            "{} was last used by an older version, open it with this one to upgrade it",
+            path
+        );
+    }
+    if there > here {
+        bail!("{} was last used by a newer version", path);
+    }
+    Ok(())
+}
+
+/// Detaches the database `Repository::attach` attached. If that fails the
+/// connection is closed rather than going back to the pool with it attached.
+async fn detach(mut conn: PoolConnection<Sqlite>) -> Result<()> {
+    if let Err(err) = sqlx::query("DETACH DATABASE other;")
+        .execute(&mut *conn)
+        .await
+    {
+        let _ = conn.close().await;
+        return Err(err.into());
+    }
+    Ok(())
+}
+
+/// The URI SQLite opens the database at `path` with read-only.
+fn read_only_uri(path: &str) -> String {
+    let path = path
+        .replace('%', "%25")
+        .replace('?', "%3f")
+        .replace('#', "%23");
+    format!("file:{}?mode=ro", path)
+}
+
+/// Puts the questions in the sets, returning how many weren't in them.
+async fn insert_memberships(
+    conn: &mut SqliteConnection,
+    memberships: &[SetMembership],
+) -> Result<u64> {
+    let mut inserted = 0;
+    for chunk in memberships.chunks(BATCH_SIZE) {
+        let mut qb = QueryBuilder::<Sqlite>::new(
+            "INSERT OR IGNORE INTO question_sets(name, question_id) SELECT m.column1, q.id FROM (",
+        );
+        qb.push_values(chunk, |mut b, m| {
+            b.push_bind(&m.set).push_bind(&m.factory).push_bind(&m.name);
+        });
+        qb.push(") AS m JOIN questions q ON q.factory = m.column2 AND q.name = m.column3");
+        inserted += qb.build().execute(&mut *conn).await?.rows_affected();
+    }
+    Ok(inserted)
+}
+
+/// Takes the questions out of the sets, returning how many were in them.
+async fn delete_memberships(
+    conn: &mut SqliteConnection,
+    memberships: &[SetMembership],
+) -> Result<u64> {
+    let mut removed = 0;
+    for m in memberships {
+        removed += sqlx::query(
+            "DELETE FROM question_sets WHERE name = $1 AND question_id IN \
+            (SELECT id FROM questions WHERE factory = $2 AND name = $3);",
+        )
+        .bind(&m.set)
+        .bind(&m.factory)
+        .bind(&m.name)
+        .execute(&mut *conn)
+        .await?
+        .rows_affected();
+    }
+    Ok(removed)
+}
+
+/// Deletes the questions along with their answers and set memberships.
+async fn delete_questions(conn: &mut SqliteConnection, question_ids: &[i64]) -> Result<()> {
+    for chunk in question_ids.chunks(BATCH_SIZE) {
+        for table in ["answers", "question_sets"] {
+            let mut qb = QueryBuilder::<Sqlite>::new(format!(
+                "DELETE FROM {} WHERE question_id IN (",
+                table
+            ));
+            let mut ids = qb.separated(", ");
+            for id in chunk {
+                ids.push_bind(id);
+            }
+            qb.push(")");
+            qb.build().execute(&mut *conn).await?;
+        }
+
+        let mut qb = QueryBuilder::<Sqlite>::new("DELETE FROM questions WHERE id IN (");
+        let mut ids = qb.separated(", ");
+        for id in chunk {
+            ids.push_bind(id);
+        }
+        qb.push(")");
+        qb.build().execute(&mut *conn).await?;
+    }
+    Ok(())
+}
+
+/// Questions are stored with their data compressed, see `compress`.
+fn decompressed(q: Question) -> Result<Question> {
+    Ok(Question {
+        data: decompress(&q.data)?,
+        ..q
+    })
+}
+
+/// Runs the operation again, with a growing delay, while it fails because
+/// another connection holds a lock on the database for longer than the busy
+/// timeout, or because a snapshot went stale before a transaction could write.
+async fn retry_on_locked<T, F, Fut>(mut op: F) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let mut attempt = 0;
+    loop {
+        match op().await {
+            Err(err) if attempt < LOCKED_RETRIES && is_locked(&err) => {
+                attempt += 1;
+                warn!(attempt, "database is locked, retrying");
+                tokio::time::sleep(Duration::from_millis(50 << attempt)).await;
+            }
+            res => return res,
+        }
+    }
+}
+
+/// Whether the error is SQLITE_BUSY or SQLITE_LOCKED, in any of their
+/// extended forms.
+fn is_locked(err: &anyhow::Error) -> bool {
+    let Some(sqlx::Error::Database(err)) = err.downcast_ref::<sqlx::Error>() else {
+        return false;
+    };
+    let code = err.code().and_then(|c| c.parse::<i32>().ok()).unwrap_or(0);
+    matches!(code & 0xff, 5 | 6)
 }
@@ -0,0 +1,64 @@
+use crate::functionality::{Question, Service};
+use similar::TextDiff;
+use std::collections::HashMap;
+
+/// Questions that ask the same thing. The first one is the canonical
+/// question, the one that has been answered the most.
+pub struct Duplicates<'a> {
+    pub questions: Vec<&'a Question>,
+}
+
+/// Lowercases the text and drops punctuation and repeated whitespace, so
+/// "What is the capital of France?" and "what is the capital of france"
+/// compare equal.
+fn normalize(s: &str) -> String {
+    s.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+/// Finds questions with the same answer whose texts are at least `threshold`
+/// similar, where 1 only matches identical texts.
+pub fn find_duplicates<'a>(service: &'a Service, threshold: f32) -> Vec<Duplicates<'a>> {
+    let mut by_answer = HashMap::<String, Vec<(&Question, String)>>::new();
+    for q in service.get_questions() {
+        by_answer
+            .entry(normalize(&q.runner.answer_text()))
+            .or_default()
+            .push((q, normalize(&q.runner.question_text())));
+    }
+
+    let mut res = Vec::new();
+    for mut candidates in by_answer.into_values() {
+        candidates.sort_by(|(a, _), (b, _)| (&a.factory, &a.name).cmp(&(&b.factory, &b.name)));
+        let mut grouped = vec![false; candidates.len()];
+        for i in 0..candidates.len() {
+            if grouped[i] {
+                continue;
+            }
+            let mut group = vec![candidates[i].0];
+            for j in i + 1..candidates.len() {
+                if grouped[j] {
+                    continue;
+                }
+                let ratio = TextDiff::from_chars(&candidates[i].1, &candidates[j].1).ratio();
+                if ratio >= threshold {
+                    grouped[j] = true;
+                    group.push(candidates[j].0);
+                }
+            }
+            if group.len() > 1 {
+                // Stable, so ties keep the alphabetical order.
+                group.sort_by_key(|q| std::cmp::Reverse(q.num_correct + q.num_incorrect));
+                res.push(Duplicates { questions: group });
+            }
+        }
+    }
+    res.sort_by(|a, b| {
+        let (a, b) = (a.questions[0], b.questions[0]);
+        (&a.factory, &a.name).cmp(&(&b.factory, &b.name))
+    });
+    res
+}
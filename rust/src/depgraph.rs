@@ -0,0 +1,72 @@
+use anyhow::{bail, Result};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// Orders the nodes so every node comes after the nodes it depends on.
+/// Dependencies that aren't nodes of the graph are assumed to be satisfied
+/// already. Fails with the offending nodes if the dependencies form a cycle.
+pub fn topsort<'a>(deps: &HashMap<&'a str, &'a Vec<String>>) -> Result<Vec<&'a str>> {
+    // BTreeMaps keep the order deterministic between runs.
+    let mut remaining: BTreeMap<&str, usize> = BTreeMap::new();
+    let mut dependents: HashMap<&str, Vec<&str>> = HashMap::new();
+    for (&node, &ds) in deps {
+        let ds = ds
+            .iter()
+            .map(|d| d.as_str())
+            .filter(|d| deps.contains_key(d))
+            .collect::<BTreeSet<&str>>();
+        remaining.insert(node, ds.len());
+        for d in ds {
+            dependents.entry(d).or_default().push(node);
+        }
+    }
+
+    let mut ready = remaining
+        .iter()
+        .filter(|(_, &count)| count == 0)
+        .map(|(&node, _)| node)
+        .collect::<Vec<&str>>();
+    ready.reverse();
+
+    let mut res = Vec::new();
+    while let Some(node) = ready.pop() {
+        remaining.remove(node);
+        res.push(node);
+        for &d in dependents.get(node).into_iter().flatten() {
+            let count = remaining.get_mut(d).unwrap();
+            *count -= 1;
+            if *count == 0 {
+                ready.push(d);
+            }
+        }
+    }
+
+    if !remaining.is_empty() {
+        let cycle = find_cycle(deps, &remaining);
+        bail!("question sets depend on each other: {}", cycle.join(" -> "));
+    }
+
+    Ok(res)
+}
+
+// Every node left over after sorting has a dependency that is also left over,
+// so following those dependencies must eventually revisit a node.
+fn find_cycle<'a>(
+    deps: &HashMap<&'a str, &'a Vec<String>>,
+    remaining: &BTreeMap<&'a str, usize>,
+) -> Vec<&'a str> {
+    let mut path: Vec<&str> = Vec::new();
+    let mut node = *remaining.keys().next().unwrap();
+    while !path.contains(&node) {
+        path.push(node);
+        let ds: &'a Vec<String> = deps[node];
+        node = ds
+            .iter()
+            .map(|d| d.as_str())
+            .find(|d| remaining.contains_key(d))
+            .unwrap();
+    }
+    let start = path.iter().position(|&n| n == node).unwrap();
+    let mut cycle = path[start..].to_vec();
+    cycle.push(node);
+    cycle
+}
@@ -0,0 +1,114 @@
+use crate::storage::Storage;
+use anyhow::{bail, Context, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Version of the layout of question and factory data written by `encode`.
+/// Bump it when a question type changes in a way old data can't be read as,
+/// and teach `upgrade` to bring the old data up to date.
+pub const VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct Envelope<T> {
+    version: u32,
+    data: T,
+}
+
+/// Encodes the data of a question or factory for the database, as JSON
+/// tagged with `VERSION`.
+pub fn encode<T: Serialize>(value: &T) -> Result<Vec<u8>> {
+    Ok(serde_json::to_string(&Envelope {
+        version: VERSION,
+        data: value,
+    })?
+    .into_bytes())
+}
+
+/// Reads data written by `encode`, by any earlier version, or stored as YAML
+/// before data was versioned.
+pub fn decode<T: DeserializeOwned>(data: &[u8]) -> Result<T> {
+    let (version, value) = read(data)?;
+    let value = upgrade(version, value)?;
+    serde_json::from_value(value)
+        .with_context(|| format!("reading data stored in version {}", version))
+}
+
+/// Whether the data was stored as YAML, before data was versioned.
+pub fn is_legacy(data: &[u8]) -> bool {
+    !data.starts_with(b"{\"version\":")
+}
+
+/// Whether two encoded values hold the same data, however they're stored.
+pub fn same(a: &[u8], b: &[u8]) -> bool {
+    a == b
+        || matches!(
+            (decode::<Value>(a), decode::<Value>(b)),
+            (Ok(a), Ok(b)) if a == b
+        )
+}
+
+/// The data as YAML, for people to read.
+pub fn to_yaml(data: &[u8]) -> Result<String> {
+    Ok(serde_yaml::to_string(&decode::<serde_yaml::Value>(data)?)?)
+}
+
+/// Writes the data again in the current version, keeping the order of its
+/// fields so its `functionality::content_hash` stays the same.
+pub fn reencode(data: &[u8]) -> Result<Vec<u8>> {
+    encode(&decode::<serde_yaml::Value>(data)?)
+}
+
+/// The version of the data and the data itself, legacy YAML being version 0.
+fn read(data: &[u8]) -> Result<(u32, Value)> {
+    if is_legacy(data) {
+        let value = serde_yaml::from_slice(data).context("reading unversioned data")?;
+        return Ok((0, value));
+    }
+    let envelope = serde_json::from_slice::<Envelope<Value>>(data)?;
+    if envelope.version > VERSION {
+        bail!(
+            "data stored in version {} by a newer trivial, this one reads up to {}",
+            envelope.version,
+            VERSION
+        );
+    }
+    Ok((envelope.version, envelope.data))
+}
+
+/// Brings data of an older version up to `VERSION`, one version at a time.
+fn upgrade(version: u32, value: Value) -> Result<Value> {
+    match version {
+        // The YAML was written from the same types as version 1.
+        0 | 1 => Ok(value),
+        _ => bail!("no upgrade from data version {}", version),
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct MigrateCounts {
+    pub questions: usize,
+    pub factories: usize,
+}
+
+/// Writes the data stored as YAML, before data was versioned, in the
+/// current version. Loading the question sets does the same for the
+/// questions still in them, this also covers the rest.
+pub async fn migrate(repo: &dyn Storage) -> Result<MigrateCounts> {
+    let mut questions = repo.get_all_questions().await?;
+    questions.retain(|q| is_legacy(&q.data));
+    for q in &mut questions {
+        q.data = reencode(&q.data).with_context(|| format!("{}/{}", q.factory, q.name))?;
+    }
+    let mut factories = repo.get_all_question_factories().await?;
+    factories.retain(|f| is_legacy(&f.data));
+    for f in &mut factories {
+        f.data = reencode(&f.data).with_context(|| f.name.clone())?;
+    }
+    repo.import(&questions, &factories, &[], &[], &[], &[])
+        .await?;
+    Ok(MigrateCounts {
+        questions: questions.len(),
+        factories: factories.len(),
+    })
+}
@@ -0,0 +1,133 @@
+use anyhow::{bail, Result};
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use serde::{Deserialize, Serialize};
+
+const DICTIONARY_URL: &str = "https://api.dictionaryapi.dev/api/v2/entries/en";
+const WIKTIONARY_URL: &str = "https://en.wiktionary.org/w/index.php";
+
+/// A word in the schema of the `vocab` question type.
+#[derive(Serialize, Debug, Clone)]
+pub struct Entry {
+    pub word: String,
+    pub id: String,
+    pub definition: String,
+    pub example: String,
+    pub translations: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct VocabData {}
+
+#[derive(Serialize)]
+struct VocabSet<'a> {
+    name: &'a str,
+    type_: &'a str,
+    data: VocabData,
+    items: &'a [Entry],
+}
+
+#[derive(Deserialize)]
+struct DictionaryEntry {
+    meanings: Vec<Meaning>,
+}
+
+#[derive(Deserialize)]
+struct Meaning {
+    definitions: Vec<Definition>,
+}
+
+#[derive(Deserialize)]
+struct Definition {
+    definition: String,
+    #[serde(default)]
+    example: Option<String>,
+}
+
+/// Looks up the first definition and example of an English word, and its
+/// translations to `lang` (a Wiktionary language code such as `da`). Fields
+/// that couldn't be found are left empty.
+pub fn lookup(word: &str, lang: &str) -> Result<Entry> {
+    let (definition, example) = definition(word)?;
+    Ok(Entry {
+        word: word.to_string(),
+        id: word.to_lowercase().replace(' ', "_"),
+        definition,
+        example,
+        translations: translations(word, lang)?,
+    })
+}
+
+fn definition(word: &str) -> Result<(String, String)> {
+    // The word is a path segment, where e.g. `?` or `/` would change the URL.
+    let url = format!(
+        "{}/{}",
+        DICTIONARY_URL,
+        utf8_percent_encode(word, NON_ALPHANUMERIC)
+    );
+    let response = match ureq::get(&url).call() {
+        Ok(response) => response.into_string()?,
+        Err(ureq::Error::Status(404, _)) => return Ok(Default::default()),
+        Err(err) => return Err(err.into()),
+    };
+    let entries = serde_json::from_str::<Vec<DictionaryEntry>>(&response)?;
+    let definitions = entries
+        .iter()
+        .flat_map(|e| &e.meanings)
+        .flat_map(|m| &m.definitions)
+        .collect::<Vec<&Definition>>();
+    let Some(first) = definitions.first() else {
+        return Ok(Default::default());
+    };
+    let example = definitions
+        .iter()
+        .find_map(|d| d.example.clone())
+        .unwrap_or_default();
+    Ok((first.definition.clone(), example))
+}
+
+/// Reads the translations from the `{{t|da|...}}` templates in the word's
+/// Wiktionary page.
+fn translations(word: &str, lang: &str) -> Result<Vec<String>> {
+    let wikitext = match ureq::get(WIKTIONARY_URL)
+        .query("title", word)
+        .query("action", "raw")
+        .call()
+    {
+        Ok(response) => response.into_string()?,
+        Err(ureq::Error::Status(404, _)) => return Ok(Vec::new()),
+        Err(err) => return Err(err.into()),
+    };
+    Ok(parse_translations(&wikitext, lang))
+}
+
+fn parse_translations(wikitext: &str, lang: &str) -> Vec<String> {
+    let mut translations = Vec::<String>::new();
+    for template in wikitext.split("{{").skip(1) {
+        let Some(end) = template.find("}}") else {
+            continue;
+        };
+        let mut args = template[..end].split('|');
+        if !matches!(args.next(), Some("t" | "t+" | "tt" | "tt+")) || args.next() != Some(lang) {
+            continue;
+        }
+        if let Some(t) = args.next().map(str::trim).filter(|t| !t.is_empty()) {
+            if !translations.iter().any(|s| s == t) {
+                translations.push(t.to_string());
+            }
+        }
+    }
+    translations
+}
+
+/// Formats the entries as a question set file of the `vocab` type.
+pub fn to_yaml(name: &str, entries: &[Entry]) -> Result<String> {
+    if entries.is_empty() {
+        bail!("no words to write");
+    }
+    Ok(serde_yaml::to_string(&VocabSet {
+        name,
+        type_: "vocab",
+        data: VocabData {},
+        items: entries,
+    })?)
+}
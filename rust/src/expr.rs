@@ -0,0 +1,201 @@
+use crate::numeric;
+use anyhow::{anyhow, bail, Result};
+use core::fmt;
+
+/// Evaluates an arithmetic expression such as `2^10` or `(1 + sqrt(5)) / 2`.
+///
+/// Supports `+ - * / ^`, parentheses, unary minus, the constants `pi` and `e`
+/// and the functions `sqrt`, `abs`, `ln`, `log` (base 10), `sin`, `cos` and
+/// `tan`. `^` binds tighter than unary minus and is right associative, so
+/// `-2^2` is -4 and `2^3^2` is 512. A number written out in words, e.g.
+/// `seven`, is read as that number.
+pub fn eval(s: &str) -> Result<f64> {
+    if let Some(n) = numeric::parse_words(s) {
+        return Ok(n as f64);
+    }
+    eval_with(s, &|_| None)
+}
+
+/// Evaluates the expression like `eval`, looking up any other name in
+/// `vars`, e.g. `(1 - probability) * 2`.
+pub fn eval_with(s: &str, vars: &dyn Fn(&str) -> Option<f64>) -> Result<f64> {
+    let mut p = Parser {
+        tokens: tokenize(s)?,
+        pos: 0,
+        vars,
+    };
+    let v = p.expr()?;
+    if let Some(t) = p.peek() {
+        bail!("unexpected '{}'", t);
+    }
+    Ok(v)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Num(f64),
+    Ident(String),
+    Op(char),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Token::Num(n) => write!(f, "{}", n),
+            Token::Ident(s) => write!(f, "{}", s),
+            Token::Op(c) => write!(f, "{}", c),
+        }
+    }
+}
+
+fn tokenize(s: &str) -> Result<Vec<Token>> {
+    let chars = s.chars().collect::<Vec<char>>();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit() || c == '.' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            let num = chars[start..i].iter().collect::<String>();
+            tokens.push(Token::Num(
+                num.parse()
+                    .map_err(|_| anyhow!("invalid number {:?}", num))?,
+            ));
+        } else if c.is_alphabetic() {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push(Token::Ident(chars[start..i].iter().collect()));
+        } else if "+-*/^()".contains(c) {
+            tokens.push(Token::Op(c));
+            i += 1;
+        } else {
+            bail!("unexpected character {:?}", c);
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    tokens: Vec<Token>,
+    pos: usize,
+    vars: &'a dyn Fn(&str) -> Option<f64>,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let t = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        t
+    }
+
+    fn eat(&mut self, op: char) -> bool {
+        if self.peek() == Some(&Token::Op(op)) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    // expr := term (('+' | '-') term)*
+    fn expr(&mut self) -> Result<f64> {
+        let mut v = self.term()?;
+        loop {
+            if self.eat('+') {
+                v += self.term()?;
+            } else if self.eat('-') {
+                v -= self.term()?;
+            } else {
+                return Ok(v);
+            }
+        }
+    }
+
+    // term := unary (('*' | '/') unary)*
+    fn term(&mut self) -> Result<f64> {
+        let mut v = self.unary()?;
+        loop {
+            if self.eat('*') {
+                v *= self.unary()?;
+            } else if self.eat('/') {
+                v /= self.unary()?;
+            } else {
+                return Ok(v);
+            }
+        }
+    }
+
+    // unary := '-' unary | power
+    fn unary(&mut self) -> Result<f64> {
+        if self.eat('-') {
+            Ok(-self.unary()?)
+        } else {
+            self.power()
+        }
+    }
+
+    // power := atom ('^' unary)?
+    fn power(&mut self) -> Result<f64> {
+        let base = self.atom()?;
+        if self.eat('^') {
+            Ok(base.powf(self.unary()?))
+        } else {
+            Ok(base)
+        }
+    }
+
+    // atom := number | constant | variable | function '(' expr ')' | '(' expr ')'
+    fn atom(&mut self) -> Result<f64> {
+        match self.next() {
+            Some(Token::Num(n)) => Ok(n),
+            Some(Token::Op('(')) => {
+                let v = self.expr()?;
+                if !self.eat(')') {
+                    bail!("missing closing parenthesis");
+                }
+                Ok(v)
+            }
+            Some(Token::Ident(name)) => match name.as_str() {
+                "pi" => Ok(std::f64::consts::PI),
+                "e" => Ok(std::f64::consts::E),
+                _ if self.peek() != Some(&Token::Op('(')) => match (self.vars)(&name) {
+                    Some(v) => Ok(v),
+                    None => bail!("unknown name {:?}", name),
+                },
+                _ => {
+                    let f: fn(f64) -> f64 = match name.as_str() {
+                        "sqrt" => f64::sqrt,
+                        "abs" => f64::abs,
+                        "ln" => f64::ln,
+                        "log" => f64::log10,
+                        "sin" => f64::sin,
+                        "cos" => f64::cos,
+                        "tan" => f64::tan,
+                        _ => bail!("unknown name {:?}", name),
+                    };
+                    if !self.eat('(') {
+                        bail!("expected '(' after {}", name);
+                    }
+                    let v = self.expr()?;
+                    if !self.eat(')') {
+                        bail!("missing closing parenthesis");
+                    }
+                    Ok(f(v))
+                }
+            },
+            Some(t) => bail!("unexpected '{}'", t),
+            None => bail!("unexpected end of expression"),
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use crate::encoding;
+use crate::frontend;
+use crate::functionality::{
+    pause_with_message, FactoryOptions, Grade, QuestionFactory, QuestionKey, QuestionRunner,
+    QuestionSetFactory, Score, SetIndex,
+};
+use crate::output;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct FlashcardData {
+    #[serde(flatten)]
+    options: FactoryOptions,
+    #[serde(skip)]
+    depends: Vec<String>,
+}
+
+impl QuestionFactory for FlashcardData {
+    fn build(&self, data: &[u8]) -> Result<Box<dyn QuestionRunner>> {
+        let question = encoding::decode::<Flashcard>(data)?;
+        Ok(Box::new(question) as Box<dyn QuestionRunner>)
+    }
+}
+
+impl QuestionSetFactory for FlashcardData {
+    fn build_set(&self, s: &SetIndex, set_name: &str) -> Vec<QuestionKey> {
+        s.get_factory(set_name).to_vec()
+    }
+
+    fn depends_on(&self) -> &Vec<String> {
+        &self.depends
+    }
+}
+
+/// A card that is graded by the user after revealing the back, no typing
+/// involved.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Flashcard {
+    id: String,
+    front: String,
+    back: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    /// Questions to know before this one is introduced, see
+    /// `Question::requires`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    requires: Vec<String>,
+}
+
+impl QuestionRunner for Flashcard {
+    fn run(&self) -> Result<Score> {
+        println!("{}", output::label(&self.front));
+        pause_with_message("Press any key to reveal the answer.")?;
+        println!("{}", self.back);
+        let options = Grade::ALL.map(|g| g.to_string());
+        let grade = Grade::ALL[frontend::current().select("Did you know it?", &options)?];
+        println!();
+        Ok(grade.into())
+    }
+
+    fn name(&self) -> String {
+        self.id.clone()
+    }
+
+    fn question_text(&self) -> String {
+        self.front.clone()
+    }
+
+    fn answer_text(&self) -> String {
+        self.back.clone()
+    }
+}
@@ -0,0 +1,94 @@
+use anyhow::Result;
+use inquire::validator::{ErrorMessage, Validation};
+use inquire::{Confirm, Editor, Select, Text};
+use std::cell::RefCell;
+use std::io::{stdin, stdout, Read, Write};
+use std::rc::Rc;
+
+/// Checks a line of input before it is accepted, returning the message to
+/// show when it isn't.
+pub type Validator = fn(&str) -> Result<(), String>;
+
+/// Where questions get their answers from. The terminal, unless something
+/// else has been set for the thread, e.g. a script in tests.
+pub trait Frontend {
+    /// Asks for a line of text until the validator accepts it.
+    fn text(
+        &self,
+        message: &str,
+        help: Option<&str>,
+        validator: Option<Validator>,
+    ) -> Result<String>;
+    /// Asks for text that may span several lines.
+    fn editor(&self, message: &str, help: Option<&str>) -> Result<String>;
+    fn confirm(&self, message: &str) -> Result<bool>;
+    /// Returns the index of the picked option.
+    fn select(&self, message: &str, options: &[String]) -> Result<usize>;
+    /// Waits for any key.
+    fn pause(&self, message: &str) -> Result<()>;
+}
+
+pub struct Terminal;
+
+impl Frontend for Terminal {
+    fn text(
+        &self,
+        message: &str,
+        help: Option<&str>,
+        validator: Option<Validator>,
+    ) -> Result<String> {
+        let mut text = Text::new(message);
+        if let Some(help) = help {
+            text = text.with_help_message(help);
+        }
+        if let Some(validator) = validator {
+            text = text.with_validator(move |input: &str| {
+                Ok(match validator(input) {
+                    Ok(()) => Validation::Valid,
+                    Err(msg) => Validation::Invalid(ErrorMessage::Custom(msg)),
+                })
+            });
+        }
+        Ok(text.prompt()?)
+    }
+
+    fn editor(&self, message: &str, help: Option<&str>) -> Result<String> {
+        let mut editor = Editor::new(message);
+        if let Some(help) = help {
+            editor = editor.with_help_message(help);
+        }
+        Ok(editor.prompt()?)
+    }
+
+    fn confirm(&self, message: &str) -> Result<bool> {
+        Ok(Confirm::new(message).prompt()?)
+    }
+
+    fn select(&self, message: &str, options: &[String]) -> Result<usize> {
+        Ok(Select::new(message, options.to_vec()).raw_prompt()?.index)
+    }
+
+    fn pause(&self, message: &str) -> Result<()> {
+        let mut stdout = stdout();
+        stdout.write_all(message.as_bytes())?;
+        stdout.flush().unwrap();
+        // Reaching the end of the input, e.g. when it's piped, is as good
+        // as a key.
+        let _read = stdin().read(&mut [0])?;
+        Ok(())
+    }
+}
+
+thread_local! {
+    static CURRENT: RefCell<Rc<dyn Frontend>> = RefCell::new(Rc::new(Terminal));
+}
+
+/// The frontend questions on this thread use.
+pub fn current() -> Rc<dyn Frontend> {
+    CURRENT.with(|f| f.borrow().clone())
+}
+
+/// Makes questions on this thread use the frontend, returning the previous one.
+pub fn set(frontend: Rc<dyn Frontend>) -> Rc<dyn Frontend> {
+    CURRENT.with(|f| f.replace(frontend))
+}
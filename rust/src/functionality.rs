@@ -1,24 +1,129 @@
-use crate::db;
-use anyhow::{bail, Result};
-use chrono::{DateTime, Utc};
-use colored::Colorize;
+use crate::arithmetic::{Arithmetic, ArithmeticData};
+use crate::assets;
+use crate::autosave::{Autosave, PendingAnswer};
+use crate::codeeval::{CodeData, CodeQuestion};
+use crate::db::AnswerDetails;
+use crate::flashcard::{Flashcard, FlashcardData};
+use crate::frontend::{self, Validator};
+use crate::geo::{GeoData, GeoQuestion};
+use crate::grader::GraderConfig;
+use crate::hints::Hints;
+use crate::input::{self, is_shortcut};
+use crate::math::{MathData, MathQuestion};
+use crate::model::{self, Evaluation, ModelKind, ModelOverrides, ModelParams};
+use crate::normalize::{Normalize, NormalizeOverrides};
+use crate::numeric::{parse_with_unit, relative_error, si_parse};
+use crate::output;
+use crate::plugins::{self, PluginData};
+use crate::ranking::{Attributes, CustomMethod, NEVER_ANSWERED_DAYS};
+use crate::records::{RecordQuestion, RecordSet, RecordsData};
+use crate::remote::RemoteSet;
+use crate::script::{ScriptData, ScriptQuestion};
+use crate::sequence::{Sequence, SequenceData};
+use crate::storage::Storage;
+use crate::timing::{with_time_limit, Timing};
+use crate::tts::{with_speech, Speech};
+use crate::typing::{TypingData, TypingQuestion};
+use crate::{db, depgraph, encoding, spacing};
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Days, Local, NaiveDate, Utc};
 use core::fmt;
-use inquire::validator::{ErrorMessage, Validation};
-use inquire::{Confirm, Text};
 use num_format::{Locale, ToFormattedString};
+use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
-use rand::thread_rng;
+use rand::{Rng, SeedableRng};
+use regex::{Regex, RegexBuilder};
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fmt::Debug;
 use std::fs;
-use std::io::{stdin, stdout, Read, Write};
-use std::path::PathBuf;
-use std::str::FromStr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
+use tracing::{debug, info, instrument, warn};
+
+/// How well a question was answered, from 0 (wrong) to 1 (fully correct).
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub struct Score(f64);
+
+impl Score {
+    pub const WRONG: Score = Score(0.);
+    pub const CORRECT: Score = Score(1.);
+
+    pub fn new(value: f64) -> Score {
+        Score(value.clamp(0., 1.))
+    }
+
+    pub fn value(self) -> f64 {
+        self.0
+    }
+
+    /// Whether the answer counts as correct, e.g. for retrying wrong answers
+    /// at the end of a session. Half credit isn't enough.
+    pub fn is_correct(self) -> bool {
+        self.0 > 0.5
+    }
+}
+
+impl From<bool> for Score {
+    fn from(correct: bool) -> Score {
+        if correct {
+            Score::CORRECT
+        } else {
+            Score::WRONG
+        }
+    }
+}
+
+/// A self-assessed grade, for questions where the user judges their own recall.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Grade {
+    Again,
+    Hard,
+    Good,
+    Easy,
+}
+
+impl Grade {
+    pub const ALL: [Grade; 4] = [Grade::Again, Grade::Hard, Grade::Good, Grade::Easy];
+}
+
+impl fmt::Display for Grade {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Grade::Again => write!(f, "Again"),
+            Grade::Hard => write!(f, "Hard"),
+            Grade::Good => write!(f, "Good"),
+            Grade::Easy => write!(f, "Easy"),
+        }
+    }
+}
+
+impl From<Grade> for Score {
+    fn from(grade: Grade) -> Score {
+        match grade {
+            Grade::Again => Score::WRONG,
+            Grade::Hard => Score(0.6),
+            Grade::Good => Score(0.8),
+            Grade::Easy => Score::CORRECT,
+        }
+    }
+}
 
 pub trait QuestionRunner {
-    fn run(&self) -> Result<bool>;
+    fn run(&self) -> Result<Score>;
     fn name(&self) -> String;
+    /// The question as plain text, e.g. to read it aloud.
+    fn question_text(&self) -> String;
+    /// The expected answer as plain text.
+    fn answer_text(&self) -> String;
+    /// Gives the question the expected answers of the others built by its
+    /// factory, e.g. to show some of them as distractors.
+    fn set_siblings(&mut self, _answers: Arc<[String]>) {}
+    /// Tells the question how many times in a row it has been answered
+    /// correctly, see `Service::streak`.
+    fn set_streak(&mut self, _streak: usize) {}
 }
 
 pub trait QuestionFactory {
@@ -26,14 +131,122 @@ pub trait QuestionFactory {
 }
 
 pub trait QuestionSetFactory {
-    fn build_set(&self, s: &Service, set_name: &str) -> Vec<QuestionID>;
+    fn build_set(&self, s: &SetIndex, set_name: &str) -> Vec<QuestionKey>;
     fn depends_on(&self) -> &Vec<String>;
+    /// Questions to take out of the set, including ones added by an earlier
+    /// import.
+    fn excludes(&self, _: &SetIndex) -> Vec<QuestionKey> {
+        Vec::new()
+    }
+}
+
+/// Settings every question factory accepts, flattened into its data.
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct FactoryOptions {
+    #[serde(flatten)]
+    timing: Timing,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    speech: Option<Speech>,
+    /// Parameters of the probability model for the set's questions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    model: Option<ModelOverrides>,
+    /// The set to offer to continue with when a session of this one ends,
+    /// e.g. the next chapter of a course.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    next: Option<String>,
+}
+
+impl FactoryOptions {
+    /// Wraps the factory with the behaviour the options ask for.
+    fn apply(self, factory: Box<dyn QuestionFactory>) -> Result<Box<dyn QuestionFactory>> {
+        Ok(with_speech(
+            with_time_limit(factory, self.timing)?,
+            self.speech,
+        ))
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct BaseQuestionSet {
     name: String,
     type_: String,
+    #[serde(default)]
+    exclude: Exclude,
+}
+
+/// Questions left out of a set of any type, given next to its `data`, e.g.
+///
+/// ```yaml
+/// exclude:
+///   sets: [too-easy]
+///   questions: [spanish/hola]
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Exclude {
+    /// Sets whose questions are left out.
+    #[serde(default)]
+    sets: Vec<String>,
+    /// Questions left out, as `<factory>/<name>`.
+    #[serde(default)]
+    questions: Vec<String>,
+}
+
+impl Exclude {
+    fn is_empty(&self) -> bool {
+        self.sets.is_empty() && self.questions.is_empty()
+    }
+}
+
+/// A set with the questions of `exclude` left out.
+struct Excluding {
+    set: Box<dyn QuestionSetFactory>,
+    exclude: Exclude,
+    depends: Vec<String>,
+}
+
+impl Excluding {
+    fn new(set: Box<dyn QuestionSetFactory>, exclude: Exclude) -> Excluding {
+        let mut depends = set.depends_on().clone();
+        depends.extend(exclude.sets.iter().cloned());
+        Excluding {
+            set,
+            exclude,
+            depends,
+        }
+    }
+}
+
+impl QuestionSetFactory for Excluding {
+    fn build_set(&self, s: &SetIndex, set_name: &str) -> Vec<QuestionKey> {
+        let excluded = self.excludes(s).into_iter().collect::<HashSet<_>>();
+        let mut keys = self.set.build_set(s, set_name);
+        keys.retain(|k| !excluded.contains(k));
+        keys
+    }
+
+    fn depends_on(&self) -> &Vec<String> {
+        &self.depends
+    }
+
+    fn excludes(&self, s: &SetIndex) -> Vec<QuestionKey> {
+        let mut keys = self
+            .exclude
+            .sets
+            .iter()
+            .flat_map(|set| s.sets.get(set).into_iter().flatten().cloned())
+            .collect::<Vec<_>>();
+        keys.extend(
+            self.exclude
+                .questions
+                .iter()
+                .filter_map(|q| q.split_once('/'))
+                .map(|(factory, name)| QuestionKey {
+                    factory: factory.to_string(),
+                    name: name.to_string(),
+                }),
+        );
+        keys
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -55,22 +268,42 @@ pub struct QuestionSetFactoryModel<T> {
 pub struct NumericRangeData {
     question_prefix: String,
     range: f64,
+    /// Ranges to accept instead of `range` as the correct answers in a row
+    /// add up, e.g. `[0.3, 0.1, 0.05]` accepts guesses 30% off at first, 10%
+    /// off after a correct one and 5% off after two or more.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tighten: Vec<f64>,
+    /// The unit or currency of the answers, shown with them, e.g. `km²`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    unit: Option<String>,
+    /// Other units guesses can be given in and how many of `unit` each one
+    /// is, e.g. `{mi²: 2.59}`. Guesses without one are in `unit`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    units: BTreeMap<String, f64>,
+    #[serde(flatten)]
+    options: FactoryOptions,
     #[serde(skip)]
     depends: Vec<String>,
 }
 
 impl QuestionFactory for NumericRangeData {
     fn build(&self, data: &[u8]) -> Result<Box<dyn QuestionRunner>> {
-        let mut question = serde_yaml::from_slice::<NumericRangeQuestion>(data)?;
+        let mut question = encoding::decode::<NumericRangeQuestion>(data)?;
         question.range = self.range;
+        question.tighten = self.tighten.clone();
+        question.unit = self.unit.clone();
+        question.units = self.units.clone();
+        if let Some(unit) = &self.unit {
+            question.units.insert(unit.clone(), 1.);
+        }
         question.question = format!("{}{}?", self.question_prefix, question.question);
         Ok(Box::new(question) as Box<dyn QuestionRunner>)
     }
 }
 
 impl QuestionSetFactory for NumericRangeData {
-    fn build_set(&self, s: &Service, set_name: &str) -> Vec<QuestionID> {
-        s.get_factory(set_name).clone()
+    fn build_set(&self, s: &SetIndex, set_name: &str) -> Vec<QuestionKey> {
+        s.get_factory(set_name).to_vec()
     }
 
     fn depends_on(&self) -> &Vec<String> {
@@ -82,28 +315,6 @@ fn default_range() -> f64 {
     0.
 }
 
-fn si_parse(s: &str) -> Result<i64> {
-    let c = if let Some(c) = s.chars().last() {
-        c
-    } else {
-        bail!("empty string");
-    };
-    if c.is_digit(10) {
-        return s.parse::<i64>().map_err(|err| anyhow::Error::from(err));
-    }
-
-    let factor: i64 = match c {
-        'k' | 'K' => 1_000,
-        'm' | 'M' => 1_000_000,
-        'g' | 'G' | 'b' | 'B' => 1_000_000_000,
-        'T' => 1_000_000_000_000,
-        _ => bail!("unexpected last char {}", c),
-    };
-    let ss = s.get(..s.len() - 1).unwrap();
-    let n = (ss.parse::<f64>()? * (factor as f64)) as i64;
-    Ok(n)
-}
-
 #[derive(Deserialize, Serialize, Debug, Clone)]
 struct NumericRangeQuestion {
     id: String,
@@ -111,64 +322,253 @@ struct NumericRangeQuestion {
     answer: i64,
     #[serde(default = "default_range")]
     range: f64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    hints: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    /// Questions to know before this one is introduced, see
+    /// `Question::requires`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    requires: Vec<String>,
+    #[serde(skip)]
+    tighten: Vec<f64>,
+    #[serde(skip)]
+    streak: usize,
+    #[serde(skip)]
+    unit: Option<String>,
+    #[serde(skip)]
+    units: BTreeMap<String, f64>,
+}
+
+impl NumericRangeQuestion {
+    /// The range accepted with the current streak, see
+    /// `NumericRangeData::tighten`.
+    fn tolerance(&self) -> f64 {
+        match self.tighten.last() {
+            Some(last) => *self.tighten.get(self.streak).unwrap_or(last),
+            None => self.range,
+        }
+    }
 }
 
 impl QuestionRunner for NumericRangeQuestion {
-    fn run(&self) -> Result<bool> {
+    fn run(&self) -> Result<Score> {
         let validator = |input: &str| match si_parse(input) {
-            Ok(_) => Ok(Validation::Valid),
-            Err(err) => Ok(Validation::Invalid(ErrorMessage::Custom(format!(
-                "{:?}",
-                err
-            )))),
+            _ if is_shortcut(input) => Ok(()),
+            Ok(_) => Ok(()),
+            Err(err) => Err(format!("{:?}", err)),
+        };
+        // Validators can't see the units, guesses with them are checked
+        // after they're given instead.
+        let validator = match self.units.is_empty() {
+            true => Some(validator as Validator),
+            false => None,
         };
 
-        let answer = Text::new(&self.question)
-            .with_validator(validator)
-            .prompt()?;
+        let mut hints = Hints::new(&self.hints);
+        let units = self.units.keys().cloned().collect::<Vec<_>>().join(", ");
+        let question = match self.units.len() {
+            0 | 1 => self.question.clone(),
+            _ => format!("{} ({})", self.question, units),
+        };
+        let a = loop {
+            let answer = hints.prompt(&question, validator)?;
+            match parse_with_unit(&answer, &self.units) {
+                Ok(a) => break a.round() as i64,
+                Err(_) => println!(
+                    "{}",
+                    output::wrong(&format!("Give a number, optionally in one of {}", units))
+                ),
+            }
+        };
 
-        let min = ((self.answer as f64) * (1. - self.range)) as i64;
-        let max = ((self.answer as f64) * (1. + self.range)) as i64;
-        let a = si_parse(&answer)?;
+        let tolerance = self.tolerance();
+        let min = ((self.answer as f64) * (1. - tolerance)) as i64;
+        let max = ((self.answer as f64) * (1. + tolerance)) as i64;
         let correct = min <= a && a <= max;
+        let error = relative_error(a, self.answer);
         let (min_s, area_s, max_s) = (
             min.to_formatted_string(&Locale::en),
             self.answer.to_formatted_string(&Locale::en),
             max.to_formatted_string(&Locale::en),
         );
-        let bound = format!("[{} <= {} <= {}]", min_s, area_s, max_s);
+        let unit = self
+            .unit
+            .as_ref()
+            .map(|unit| format!(" {}", unit))
+            .unwrap_or_default();
+        let bound = format!("[{} <= {} <= {}{}]", min_s, area_s, max_s, unit);
         if correct {
-            println!("Within accepted bounds! {}", bound);
+            output::feedback(output::correct(&format!(
+                "Within accepted bounds! {}",
+                bound
+            )));
         } else {
-            println!("Wrong. Accepted bounds: {}", bound);
+            output::feedback(output::wrong(&format!("Wrong. Accepted bounds: {}", bound)));
+        }
+        if let Some(error) = error {
+            input::record_error(error);
         }
-        println!("");
-        Ok(correct)
+        input::record_tolerance(tolerance);
+        if let Some(error) = error.filter(|&e| e != 0.) {
+            output::feedback(output::aside(&format!(
+                "Your guess was {:.1}% too {}",
+                error.abs() * 100.,
+                if error < 0. { "low" } else { "high" }
+            )));
+        }
+        println!();
+        Ok(hints.score(correct.into()))
     }
 
     fn name(&self) -> String {
         self.id.clone()
     }
+
+    fn question_text(&self) -> String {
+        self.question.clone()
+    }
+
+    fn answer_text(&self) -> String {
+        self.answer.to_string()
+    }
+
+    fn set_streak(&mut self, streak: usize) {
+        self.streak = streak;
+    }
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
 struct DefaultData {
     question_prefix: String,
+    /// Show the answer masked while answering, see `Hints::with_mask`.
+    #[serde(default)]
+    assist: bool,
+    /// Let a language model judge answers that don't match exactly.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    grader: Option<GraderConfig>,
+    /// Answer in an editor, for answers spanning several lines such as
+    /// definitions or code.
+    #[serde(default)]
+    multiline: bool,
+    /// After a wrong answer, show this many answers of other questions in the
+    /// set next to the right one, e.g. 3, to learn to tell them apart.
+    #[serde(default)]
+    distractors: usize,
+    #[serde(default)]
+    normalize: Normalize,
+    /// Also ask every question the other way around, see `Reverse`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    reverse: Option<Reverse>,
+    #[serde(flatten)]
+    options: FactoryOptions,
     #[serde(skip)]
     depends: Vec<String>,
 }
 
+/// Appended to the name of a default set to get the name of its reversed
+/// questions.
+pub const REVERSE_SUFFIX: &str = "-reversed";
+
+/// Turns a default set into a second set prompting with the answer and
+/// expecting the question, e.g. capital to country after country to capital.
+/// The reversed questions keep the ids of the originals but are stored
+/// separately, so they have their own history.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+struct Reverse {
+    /// Put in front of every reversed question, e.g. 'Which country has the
+    /// capital '.
+    question_prefix: String,
+}
+
+/// The reversed set of a default set with `reverse` set. Questions without
+/// an answer to show have nothing to prompt with and are left out, as are
+/// those asking for several answers.
+fn reversed(
+    model: &QuestionFactoryModel<DefaultQuestion, DefaultData>,
+    reverse: &Reverse,
+) -> QuestionFactoryModel<DefaultQuestion, DefaultData> {
+    let items = model
+        .items
+        .iter()
+        .filter(|q| q.required.is_none())
+        .filter_map(|q| Some((q, q.expected()?)))
+        .map(|(q, expected)| DefaultQuestion {
+            id: q.id.clone(),
+            question: expected.to_string(),
+            answers: vec![q.question.clone()],
+            display: None,
+            required: None,
+            // Shown with the original question, they'd give the answer away.
+            assets: Vec::new(),
+            hints: Vec::new(),
+            tags: q.tags.clone(),
+            requires: q.requires.clone(),
+            // They're about the original answer, which is now the question.
+            overrides: None,
+            assist: false,
+            grader: None,
+            multiline: false,
+            distractors: 0,
+            siblings: Arc::default(),
+            normalize: Normalize::default(),
+            patterns: Vec::new(),
+        })
+        .collect();
+    QuestionFactoryModel {
+        name: format!("{}{}", model.name, REVERSE_SUFFIX),
+        type_: model.type_.clone(),
+        items,
+        data: DefaultData {
+            question_prefix: reverse.question_prefix.clone(),
+            reverse: None,
+            ..model.data.clone()
+        },
+    }
+}
+
 impl QuestionFactory for DefaultData {
     fn build(&self, data: &[u8]) -> Result<Box<dyn QuestionRunner>> {
-        let mut question = serde_yaml::from_slice::<DefaultQuestion>(data)?;
+        let mut question = encoding::decode::<DefaultQuestion>(data)?;
         question.question = format!("{}{}?", self.question_prefix, question.question);
+        question.assist = self.assist;
+        question.grader = self.grader.clone();
+        question.multiline = self.multiline;
+        question.distractors = self.distractors;
+        question.normalize = match &question.overrides {
+            Some(overrides) => overrides.apply(&self.normalize),
+            None => self.normalize.clone(),
+        };
+        if let Some(required) = question.required {
+            if required == 0 || required > question.answers.len() {
+                bail!(
+                    "{} asks for {} answers but has {}",
+                    question.id,
+                    required,
+                    question.answers.len()
+                );
+            }
+        }
+        question.patterns = question
+            .answers
+            .iter()
+            .map(|a| {
+                a.strip_prefix(REGEX_PREFIX)
+                    .map(|pattern| {
+                        RegexBuilder::new(pattern)
+                            .case_insensitive(question.normalize.case_fold)
+                            .build()
+                    })
+                    .transpose()
+            })
+            .collect::<Result<_, _>>()?;
         Ok(Box::new(question) as Box<dyn QuestionRunner>)
     }
 }
 
 impl QuestionSetFactory for DefaultData {
-    fn build_set(&self, s: &Service, set_name: &str) -> Vec<QuestionID> {
-        s.get_factory(set_name).clone()
+    fn build_set(&self, s: &SetIndex, set_name: &str) -> Vec<QuestionKey> {
+        s.get_factory(set_name).to_vec()
     }
 
     fn depends_on(&self) -> &Vec<String> {
@@ -176,44 +576,225 @@ impl QuestionSetFactory for DefaultData {
     }
 }
 
+/// Marks an accepted answer as a regular expression matched against the
+/// normalized answer, e.g. `re:^colou?r$`.
+const REGEX_PREFIX: &str = "re:";
+
 #[derive(Deserialize, Serialize, Debug, Clone)]
 struct DefaultQuestion {
     id: String,
     question: String,
+    /// Accepted answers, the first one shown when the answer is wrong.
+    /// Entries starting with `re:` are regular expressions.
     answers: Vec<String>,
+    /// Shown as the answer when every accepted one is a regular expression,
+    /// e.g. `colour (or color)`. Without it no answer is shown.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    display: Option<String>,
+    /// Ask for this many different answers instead of one, in any order,
+    /// e.g. 3 for "Name the three branches of government". Each entry of
+    /// `answers` is then a separate one, use a regular expression for one
+    /// with several spellings.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    required: Option<usize>,
+    /// Images or sounds shown with the question, e.g.
+    /// `assets/flags/fr.png`, see `assets::DIR`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    assets: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    hints: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    /// Questions to know before this one is introduced, see
+    /// `Question::requires`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    requires: Vec<String>,
+    /// How this answer is compared, where it differs from the set's.
+    #[serde(rename = "normalize", default, skip_serializing_if = "Option::is_none")]
+    overrides: Option<NormalizeOverrides>,
+    #[serde(skip)]
+    assist: bool,
+    #[serde(skip)]
+    grader: Option<GraderConfig>,
+    #[serde(skip)]
+    multiline: bool,
+    #[serde(skip)]
+    distractors: usize,
+    /// The expected answers of the other questions in the set.
+    #[serde(skip)]
+    siblings: Arc<[String]>,
+    #[serde(skip)]
+    normalize: Normalize,
+    /// The compiled regular expression of each answer that is one.
+    #[serde(skip)]
+    patterns: Vec<Option<Regex>>,
 }
 
-impl QuestionRunner for DefaultQuestion {
-    fn run(&self) -> Result<bool> {
-        let answer = Text::new(&self.question).prompt()?;
-        let correct = self
-            .answers
+impl DefaultQuestion {
+    /// The answer to show, the first one that isn't a regular expression or
+    /// else `display`.
+    fn expected(&self) -> Option<&str> {
+        self.answers
+            .iter()
+            .find(|a| !a.starts_with(REGEX_PREFIX))
+            .or(self.display.as_ref())
+            .map(String::as_str)
+    }
+
+    fn accepts(&self, answer: &str) -> bool {
+        (0..self.answers.len()).any(|i| self.matches(i, answer))
+    }
+
+    /// Whether the answer is the `i`th one once normalized. Patterns are
+    /// written against the answer as typed, so they see its numbers in words.
+    fn matches(&self, i: usize, answer: &str) -> bool {
+        match &self.patterns[i] {
+            Some(pattern) => pattern.is_match(&self.normalize.apply_literal(answer)),
+            None => self.normalize.eq(&self.answers[i], answer),
+        }
+    }
+
+    /// Up to `distractors` expected answers of other questions, leaving out
+    /// ones this question accepts too.
+    fn pick_distractors(&self) -> Vec<&str> {
+        let mut candidates = self
+            .siblings
             .iter()
-            .any(|a| a.to_lowercase() == answer.to_lowercase());
+            .filter(|a| !a.is_empty() && !self.accepts(a))
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        candidates.sort_unstable();
+        candidates.dedup();
+        candidates
+            .choose_multiple(&mut rand::thread_rng(), self.distractors)
+            .copied()
+            .collect()
+    }
+
+    /// Asks for `required` different answers, scoring the share of them
+    /// that were right.
+    fn run_all(&self, required: usize) -> Result<Score> {
+        let mut hints = Hints::new(&self.hints);
+        let mut named = Vec::new();
+        for n in 1..=required {
+            let answer = hints.prompt(&format!("{} ({}/{})", self.question, n, required), None)?;
+            let mut matching = (0..self.answers.len()).filter(|&i| self.matches(i, &answer));
+            match matching.clone().find(|i| !named.contains(i)) {
+                Some(i) => {
+                    named.push(i);
+                    output::feedback(output::correct("Correct!"));
+                }
+                None if matching.next().is_some() => {
+                    output::feedback(output::wrong("Already named"));
+                }
+                None => output::feedback(output::wrong("Wrong")),
+            }
+        }
+        if named.len() < required {
+            let others = (0..self.answers.len())
+                .filter(|i| !named.contains(i) && self.patterns[*i].is_none())
+                .map(|i| self.answers[i].as_str())
+                .collect::<Vec<_>>();
+            if !others.is_empty() {
+                output::feedback(output::wrong(&format!(
+                    "Missed {} of {}, the others are {}",
+                    required - named.len(),
+                    required,
+                    others.join(", ")
+                )));
+            }
+        }
+        println!();
+        Ok(hints.score(Score::new(named.len() as f64 / required as f64)))
+    }
+}
+
+impl QuestionRunner for DefaultQuestion {
+    fn run(&self) -> Result<Score> {
+        for asset in &self.assets {
+            println!("{} {}", output::label("See:"), assets::locate(asset));
+        }
+        if let Some(required) = self.required {
+            return self.run_all(required);
+        }
+        let mut hints = Hints::new(&self.hints).with_editor(self.multiline);
+        if let (true, Some(expected)) = (self.assist, self.expected()) {
+            hints = hints.with_mask(expected);
+        }
+        let answer = hints.prompt(&self.question, None)?;
+        let mut correct = self.accepts(&answer);
+        if let (false, Some(grader)) = (correct, &self.grader) {
+            // A pattern still tells the grader what's accepted.
+            let expected = self.expected().unwrap_or(&self.answers[0]);
+            match grader.grader().grade(&self.question, expected, &answer) {
+                Ok(verdict) => {
+                    correct = verdict.correct;
+                    output::feedback(output::aside(&verdict.explanation));
+                }
+                Err(err) => println!("{}", output::wrong(&format!("Grading failed: {:#}", err))),
+            }
+        }
         if correct {
-            println!("Correct!");
+            output::feedback(output::correct("Correct!"));
+        } else if let (true, Some(expected)) = (self.multiline, self.expected()) {
+            output::feedback(output::wrong("Wrong. The answer is:"));
+            output::feedback(expected.trim_end());
         } else {
-            println!("Wrong. The answer is {:?}", self.answers[0]);
+            match self.expected() {
+                Some(expected) => output::feedback(output::wrong(&format!(
+                    "Wrong. The answer is {:?}",
+                    expected
+                ))),
+                None => output::feedback(output::wrong("Wrong.")),
+            }
+            let distractors = self.pick_distractors();
+            if !distractors.is_empty() {
+                output::feedback(output::aside(&format!(
+                    "Not to be confused with {}",
+                    distractors.join(", ")
+                )));
+            }
         }
-        println!("");
-        Ok(correct)
+        println!();
+        Ok(hints.score(correct.into()))
     }
 
     fn name(&self) -> String {
-        return self.id.clone();
+        self.id.clone()
+    }
+
+    fn question_text(&self) -> String {
+        self.question.clone()
+    }
+
+    fn answer_text(&self) -> String {
+        match self.required {
+            Some(_) => self.answers.join(", "),
+            None => self.expected().unwrap_or_default().to_string(),
+        }
+    }
+
+    fn set_siblings(&mut self, answers: Arc<[String]>) {
+        self.siblings = answers;
     }
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
 struct UnionData {
     sets: Vec<String>,
+    /// How many questions each member contributes to a session relative to
+    /// the others, e.g. 2 for twice as many. Members without a weight count
+    /// as 1. Without any weights a session is drawn from all the questions
+    /// as if they were one set.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    weights: BTreeMap<String, f64>,
 }
 
 impl QuestionSetFactory for UnionData {
-    fn build_set(&self, s: &Service, _: &str) -> Vec<QuestionID> {
+    fn build_set(&self, s: &SetIndex, _: &str) -> Vec<QuestionKey> {
         let mut res = Vec::new();
         for set in &self.sets {
-            res.extend_from_slice(&s.get_set(set));
+            res.extend_from_slice(s.get_set(set));
         }
         res
     }
@@ -223,6 +804,150 @@ impl QuestionSetFactory for UnionData {
     }
 }
 
+/// The questions of another set, or of all sets, whose probability of being
+/// answered correctly is below a threshold, e.g. to drill the current weak
+/// spots. The questions are picked again at the start of every session
+/// rather than when importing, so a threshold set included in a union adds
+/// nothing to it.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+struct ThresholdData {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    set: Option<String>,
+    below: f64,
+    #[serde(skip)]
+    depends: Vec<String>,
+}
+
+/// The factory's data with references to the set `old` renamed to `new`, or
+/// None if it has none: union members and their weights, the set of a
+/// threshold and the set to continue with of any.
+pub fn rename_set_references(
+    factory: &db::QuestionFactory,
+    old: &str,
+    new: &str,
+) -> Result<Option<Vec<u8>>> {
+    let mut data = encoding::decode::<serde_json::Value>(&factory.data)?;
+    let Some(fields) = data.as_object_mut() else {
+        return Ok(None);
+    };
+    let mut changed = false;
+    let mut rename = |value: &mut serde_json::Value| {
+        if value.as_str() == Some(old) {
+            *value = new.into();
+            changed = true;
+        }
+    };
+    if let Some(next) = fields.get_mut("next") {
+        rename(next);
+    }
+    match factory.factory_type.as_str() {
+        "union" => {
+            if let Some(sets) = fields.get_mut("sets").and_then(|s| s.as_array_mut()) {
+                sets.iter_mut().for_each(&mut rename);
+            }
+            let weights = fields.get_mut("weights").and_then(|w| w.as_object_mut());
+            if let Some(weight) = weights.and_then(|w| w.remove(old)) {
+                fields["weights"][new] = weight;
+                changed = true;
+            }
+        }
+        "threshold" => {
+            if let Some(set) = fields.get_mut("set") {
+                rename(set);
+            }
+        }
+        _ => {}
+    }
+    match changed {
+        true => Ok(Some(encoding::encode(&data)?)),
+        false => Ok(None),
+    }
+}
+
+impl QuestionSetFactory for ThresholdData {
+    fn build_set(&self, _: &SetIndex, _: &str) -> Vec<QuestionKey> {
+        Vec::new()
+    }
+
+    fn depends_on(&self) -> &Vec<String> {
+        &self.depends
+    }
+}
+
+/// Splits `num` between members given as their weight and size, in
+/// proportion to the weights. Members that run out of questions leave their
+/// share to the others.
+pub fn weighted_shares(num: usize, members: &[(f64, usize)]) -> Vec<usize> {
+    let mut shares = vec![0; members.len()];
+    for _ in 0..num {
+        let next = members
+            .iter()
+            .enumerate()
+            .filter(|(i, (weight, size))| *weight > 0. && shares[*i] < *size)
+            .min_by(|(i, (a, _)), (j, (b, _))| {
+                ((shares[*i] + 1) as f64 / a).total_cmp(&((shares[*j] + 1) as f64 / b))
+            });
+        match next {
+            Some((i, _)) => shares[i] += 1,
+            None => break,
+        }
+    }
+    shares
+}
+
+/// Questions picked by key from other sets, e.g. to drill words that are
+/// confused with each other.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+struct QuestionsData {
+    /// The questions as `<factory>/<name>`.
+    questions: Vec<String>,
+    #[serde(skip)]
+    depends: Vec<String>,
+}
+
+impl QuestionsData {
+    fn new(questions: Vec<String>) -> QuestionsData {
+        let mut data = QuestionsData {
+            questions,
+            depends: Vec::new(),
+        };
+        data.depends = data.factories();
+        data
+    }
+
+    fn factories(&self) -> Vec<String> {
+        let mut factories = self
+            .questions
+            .iter()
+            .filter_map(|q| q.split_once('/'))
+            .map(|(factory, _)| factory.to_string())
+            .collect::<Vec<_>>();
+        factories.sort();
+        factories.dedup();
+        factories
+    }
+}
+
+impl QuestionSetFactory for QuestionsData {
+    fn build_set(&self, s: &SetIndex, _: &str) -> Vec<QuestionKey> {
+        self.questions
+            .iter()
+            .filter_map(|q| q.split_once('/'))
+            .filter_map(|(factory, name)| {
+                s.factories
+                    .get(factory)?
+                    .iter()
+                    .find(|k| k.name == name)
+                    .cloned()
+            })
+            .collect()
+    }
+
+    fn depends_on(&self) -> &Vec<String> {
+        &self.depends
+    }
+}
+
 #[derive(Deserialize, Serialize, Debug, Clone)]
 struct Word {
     id: String,
@@ -230,41 +955,87 @@ struct Word {
     definition: String,
     example: String,
     translations: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    hints: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    /// Questions to know before this one is introduced, see
+    /// `Question::requires`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    requires: Vec<String>,
+    #[serde(skip)]
+    assist: bool,
+    #[serde(skip)]
+    normalize: Normalize,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
 struct VocabData {
+    /// Show the first translation masked while answering, see `Hints::with_mask`.
+    #[serde(default)]
+    assist: bool,
+    #[serde(default)]
+    normalize: Normalize,
+    #[serde(flatten)]
+    options: FactoryOptions,
     #[serde(skip)]
     depends: Vec<String>,
 }
 
 impl QuestionRunner for Word {
-    fn run(&self) -> Result<bool> {
-        let answer = Text::new(&format!("Translation of '{}': ", self.word.bold())).prompt()?;
+    fn run(&self) -> Result<Score> {
+        let mut hints = Hints::new(&self.hints);
+        if self.assist {
+            hints = hints.with_mask(&self.translations[0]);
+        }
+        let answer = hints.prompt(
+            &format!("Translation of '{}': ", output::label(&self.word)),
+            None,
+        )?;
         let mut correct = true;
-        if self.translations.contains(&answer) {
-            println!("Valid translation");
+        if self
+            .translations
+            .iter()
+            .any(|t| self.normalize.eq(t, &answer))
+        {
+            output::feedback(output::correct("Valid translation"));
         } else {
             correct = false;
-            println!("Invalid translation. The accepted ones are:");
+            output::feedback(output::wrong("Invalid translation. The accepted ones are:"));
             for s in &self.translations {
-                println!("\t{}", s);
+                output::feedback(format!("\t{}", s));
             }
         }
 
         pause_with_message("Press any key to see an english definition and example.")?;
-        print!("{}", "Definition: ".bold());
+        print!("{}", output::label("Definition: "));
         println!("{}", &self.definition);
-        print!("{}", "Example: ".bold());
+        print!("{}", output::label("Example: "));
         println!("{}", &self.example);
 
-        let ans = Confirm::new("Did you know the definition?").prompt()?;
-        Ok(correct && ans)
+        let ans = frontend::current().confirm("Did you know the definition?")?;
+        // The translation and the definition each count for half.
+        let mut score = 0.;
+        if correct {
+            score += 0.5;
+        }
+        if ans {
+            score += 0.5;
+        }
+        Ok(hints.score(Score::new(score)))
     }
 
     fn name(&self) -> String {
         self.id.clone()
     }
+
+    fn question_text(&self) -> String {
+        format!("Translation of {}", self.word)
+    }
+
+    fn answer_text(&self) -> String {
+        self.translations[0].clone()
+    }
 }
 
 pub fn pause() -> Result<()> {
@@ -273,14 +1044,16 @@ pub fn pause() -> Result<()> {
 
 impl QuestionFactory for VocabData {
     fn build(&self, data: &[u8]) -> Result<Box<dyn QuestionRunner>> {
-        let question = serde_yaml::from_slice::<Word>(data)?;
+        let mut question = encoding::decode::<Word>(data)?;
+        question.assist = self.assist;
+        question.normalize = self.normalize.clone();
         Ok(Box::new(question) as Box<dyn QuestionRunner>)
     }
 }
 
 impl QuestionSetFactory for VocabData {
-    fn build_set(&self, s: &Service, set_name: &str) -> Vec<QuestionID> {
-        s.get_factory(set_name).clone()
+    fn build_set(&self, s: &SetIndex, set_name: &str) -> Vec<QuestionKey> {
+        s.get_factory(set_name).to_vec()
     }
 
     fn depends_on(&self) -> &Vec<String> {
@@ -288,16 +1061,142 @@ impl QuestionSetFactory for VocabData {
     }
 }
 
-fn pause_with_message(msg: &str) -> Result<()> {
-    let mut stdout = stdout();
-    stdout.write(msg.as_bytes())?;
-    stdout.flush().unwrap();
-    stdin().read(&mut [0])?;
-    Ok(())
+pub fn pause_with_message(msg: &str) -> Result<()> {
+    frontend::current().pause(msg)
 }
 
 type QuestionID = i64;
 
+/// Days until a question answered correctly once is due again.
+const FIRST_INTERVAL_DAYS: f64 = 1.;
+/// How much the interval grows with every further correct answer in a row.
+const INTERVAL_GROWTH: f64 = 2.5;
+const MAX_INTERVAL_DAYS: f64 = 365.;
+
+/// Identifies a question by its factory and name. Unlike the id, this is
+/// known before the question has been stored.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct QuestionKey {
+    pub factory: String,
+    pub name: String,
+}
+
+/// Hash of a question's data without its `id`, so a question keeps its hash
+/// when it's renamed.
+pub fn content_hash(data: &[u8]) -> String {
+    let mut value = encoding::decode::<serde_yaml::Value>(data).unwrap_or_default();
+    if let serde_yaml::Value::Mapping(m) = &mut value {
+        m.remove(&serde_yaml::Value::from("id"));
+    }
+    let canonical = serde_yaml::to_vec(&value).unwrap_or_else(|_| data.to_vec());
+    format!("{:x}", Sha256::digest(canonical))
+}
+
+#[derive(Deserialize, Default)]
+struct Tagged {
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    requires: Vec<String>,
+}
+
+/// The tags in a question's data, whatever its type.
+pub fn question_tags(data: &[u8]) -> Vec<String> {
+    encoding::decode::<Tagged>(data).unwrap_or_default().tags
+}
+
+fn stored_hash(q: &db::Question) -> String {
+    q.content_hash
+        .clone()
+        .unwrap_or_else(|| content_hash(&q.data))
+}
+
+/// Question keys per factory and per set, used to resolve set memberships
+/// while importing. Seeded from the database so sets may refer to questions
+/// that are not part of the import.
+pub struct SetIndex {
+    factories: HashMap<String, Vec<QuestionKey>>,
+    sets: HashMap<String, Vec<QuestionKey>>,
+}
+
+impl SetIndex {
+    /// The renamed questions are indexed under their new names, which the
+    /// import gives them.
+    pub async fn new(
+        repo: &dyn Storage,
+        questions: &[db::Question],
+        renames: &[Rename],
+    ) -> Result<SetIndex> {
+        let renamed = renames
+            .iter()
+            .map(|r| (r.id, &r.to))
+            .collect::<HashMap<QuestionID, &QuestionKey>>();
+        let mut factories = HashMap::<String, Vec<QuestionKey>>::new();
+        let mut seen = HashSet::new();
+        let mut by_id = HashMap::new();
+        for q in repo.get_all_questions().await?.iter().chain(questions) {
+            let key = match renamed.get(&q.id) {
+                Some(&to) => to.clone(),
+                _ => QuestionKey {
+                    factory: q.factory.clone(),
+                    name: q.name.clone(),
+                },
+            };
+            if q.id != 0 {
+                by_id.insert(q.id, key.clone());
+            }
+            if seen.insert(key.clone()) {
+                factories.entry(q.factory.clone()).or_default().push(key);
+            }
+        }
+
+        let mut sets = HashMap::<String, Vec<QuestionKey>>::new();
+        for qset in repo.get_all_question_sets().await? {
+            if let Some(key) = by_id.get(&qset.question_id) {
+                sets.entry(qset.name).or_default().push(key.clone());
+            }
+        }
+
+        Ok(SetIndex { factories, sets })
+    }
+
+    /// The keys of the factory's questions, none if it has none.
+    pub fn get_factory(&self, factory: &str) -> &[QuestionKey] {
+        self.factories
+            .get(factory)
+            .map_or(&[][..], |f| f.as_slice())
+    }
+
+    /// The keys of the set, none if it's empty or was skipped.
+    pub fn get_set(&self, set: &str) -> &[QuestionKey] {
+        self.sets.get(set).map_or(&[][..], |s| s.as_slice())
+    }
+
+    /// Takes the keys out of the set and returns the ones that were in it.
+    pub fn remove_from_set(&mut self, set: &str, keys: &[QuestionKey]) -> Vec<QuestionKey> {
+        let Some(s) = self.sets.get_mut(set) else {
+            return Vec::new();
+        };
+        let (removed, kept) = s.drain(..).partition(|k| keys.contains(k));
+        *s = kept;
+        removed
+    }
+
+    /// Adds the keys to the set and returns the ones that weren't already in it.
+    pub fn add_to_set(&mut self, set: &str, keys: Vec<QuestionKey>) -> Vec<QuestionKey> {
+        let s = self.sets.entry(set.to_string()).or_default();
+        let mut existing = s.iter().cloned().collect::<HashSet<QuestionKey>>();
+        let mut added = Vec::new();
+        for key in keys {
+            if existing.insert(key.clone()) {
+                s.push(key.clone());
+                added.push(key);
+            }
+        }
+        added
+    }
+}
+
 pub struct Question {
     pub id: QuestionID,
     pub factory: String,
@@ -305,10 +1204,22 @@ pub struct Question {
     pub probability: f64,
     pub num_correct: u32,
     pub num_incorrect: u32,
+    /// The `tags` given to the question in its set file.
+    pub tags: Vec<String>,
+    /// Questions whose probability has to be above the prerequisite
+    /// threshold before this one is introduced, given in the set file as
+    /// `<factory>/<id>`, or just the id for one in the same factory. Once
+    /// answered, a question is selected whatever its prerequisites.
+    pub requires: Vec<QuestionID>,
     pub runner: Box<dyn QuestionRunner>,
 }
 
-#[derive(Clone, Copy)]
+/// How likely a prerequisite has to be answered correctly before the
+/// questions requiring it are introduced, unless set with
+/// `Service::set_prerequisite_probability`.
+pub const PREREQUISITE_PROBABILITY: f64 = 0.8;
+
+#[derive(Clone, Copy, Debug)]
 pub enum Selection {
     All,
     Practiced,
@@ -323,23 +1234,193 @@ impl fmt::Display for Selection {
     }
 }
 
-pub struct Service<'a> {
-    questions: HashMap<QuestionID, Question>,
-    factories: HashMap<String, Vec<QuestionID>>,
+/// How far a set is from its goal.
+pub struct GoalProgress {
+    pub goal: db::Goal,
+    /// Questions at or above the target probability.
+    pub reached: usize,
+    pub total: usize,
+}
+
+impl fmt::Display for GoalProgress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}/{} at {:.0}%",
+            self.reached,
+            self.total,
+            self.goal.target * 100.
+        )?;
+        let days = (self.goal.deadline - chrono::Local::now().date_naive()).num_days();
+        if self.reached == self.total {
+            write!(f, ", reached")
+        } else if days < 0 {
+            write!(f, ", missed {}", self.goal.deadline)
+        } else {
+            write!(f, " by {} ({} days left)", self.goal.deadline, days)
+        }
+    }
+}
+
+/// How hard a set or the questions with a tag are, see
+/// `Service::difficulty_by_tag`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Difficulty {
+    pub name: String,
+    pub questions: usize,
+    pub mean_probability: f64,
+    /// Average score of the first answer to each question, if any has been
+    /// answered.
+    pub first_try_accuracy: Option<f64>,
+}
+
+/// How the questions of a session are picked from a set.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Method {
+    Bottom,
+    WeightedRandom,
+    UniformRandom,
+    OldestAnswer,
+    /// Questions answered wrong in the last `RECENTLY_WRONG_DAYS` first,
+    /// whatever their probability, then the rest like `Bottom`.
+    RecentlyWrong,
+    /// Questions with the hardest tags first, those with the lowest average
+    /// probability, and within a tag like `Bottom`.
+    HardestTags,
+}
+
+impl Method {
+    pub const ALL: [Method; 6] = [
+        Method::Bottom,
+        Method::WeightedRandom,
+        Method::UniformRandom,
+        Method::OldestAnswer,
+        Method::RecentlyWrong,
+        Method::HardestTags,
+    ];
+}
+
+/// How far back `Method::RecentlyWrong` looks for wrong answers, counted
+/// from the latest answer rather than now, so a break doesn't empty it.
+pub const RECENTLY_WRONG_DAYS: i64 = 7;
+
+impl fmt::Display for Method {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Method::Bottom => write!(f, "Bottom"),
+            Method::WeightedRandom => write!(f, "Weighted random"),
+            Method::UniformRandom => write!(f, "Uniform random"),
+            Method::OldestAnswer => write!(f, "Oldest answer"),
+            Method::RecentlyWrong => write!(f, "Recently wrong"),
+            Method::HardestTags => write!(f, "Hardest tags"),
+        }
+    }
+}
+
+/// Rows `Service::new` left out because they refer to rows that don't exist
+/// or can't be read, instead of failing to load the rest.
+#[derive(Clone, Debug, Default)]
+pub struct Skipped {
+    /// The sets whose data can't be read, e.g. of a type provided by a
+    /// plugin that isn't installed. Their questions are skipped too.
+    pub factories: Vec<String>,
+    /// The questions, as factory/name.
+    pub questions: Vec<String>,
+    pub memberships: usize,
+    pub answers: usize,
+}
+
+impl Skipped {
+    pub fn is_empty(&self) -> bool {
+        self.factories.is_empty()
+            && self.questions.is_empty()
+            && self.memberships == 0
+            && self.answers == 0
+    }
+}
+
+pub struct Service<'a> {
+    questions: HashMap<QuestionID, Question>,
+    factories: HashMap<String, Vec<QuestionID>>,
     sets: HashMap<String, Vec<QuestionID>>,
-    repo: &'a db::Repository,
+    repo: &'a dyn Storage,
     prob_computer: ProbabilityComputer,
+    goals: HashMap<String, db::Goal>,
+    /// Sets left out of `get_sets`, their questions can still be selected.
+    archived: HashSet<String>,
+    /// The members of unions with weights, and their weights.
+    unions: HashMap<String, Vec<(String, f64)>>,
+    thresholds: HashMap<String, ThresholdData>,
+    /// The set each set is followed by, see `next_set`.
+    next: HashMap<String, String>,
+    prerequisite_probability: f64,
+    /// Writes the answers in the background when set, see `set_autosave`.
+    autosave: Option<&'a Autosave>,
+    skipped: Skipped,
+    rng: StdRng,
 }
 
 impl<'a> Service<'a> {
-    pub async fn new(repo: &db::Repository) -> Result<Service> {
+    #[instrument(level = "debug", skip_all)]
+    pub async fn new(repo: &'a dyn Storage) -> Result<Service<'a>> {
+        let start = Instant::now();
         let questionsdb = repo.get_all_questions().await?;
-        let factories = load_factories(&repo.get_all_question_factories().await?)?;
+        let factory_models = repo.get_all_question_factories().await?;
+        let mut skipped = Skipped::default();
+        let mut factories = HashMap::new();
+        let mut next = HashMap::new();
+        let mut params = HashMap::new();
+        let mut unions = HashMap::new();
+        let mut thresholds = HashMap::new();
+        for f in &factory_models {
+            let set = match load_set(f) {
+                Ok(set) => set,
+                Err(err) => {
+                    debug!(factory = f.name, "unreadable set: {:#}", err);
+                    skipped.factories.push(f.name.clone());
+                    continue;
+                }
+            };
+            if let Some(factory) = set.factory {
+                factories.insert(f.name.clone(), factory);
+            }
+            if let Some(set) = set.next {
+                next.insert(f.name.clone(), set);
+            }
+            params.insert(f.name.clone(), set.params);
+            if let Some(members) = set.union {
+                unions.insert(f.name.clone(), members);
+            }
+            if let Some(threshold) = set.threshold {
+                thresholds.insert(f.name.clone(), threshold);
+            }
+        }
         let mut questions = HashMap::new();
         let mut by_factories = HashMap::new();
+        let mut requires = Vec::new();
         for q in questionsdb {
-            let factory = factories.get(&q.factory).unwrap();
-            let runner = factory.build(&q.data)?;
+            let Some(factory) = factories.get(&q.factory) else {
+                debug!(factory = q.factory, question = q.name, "unknown factory");
+                skipped.questions.push(format!("{}/{}", q.factory, q.name));
+                continue;
+            };
+            let runner = match factory.build(&q.data) {
+                Ok(runner) => runner,
+                Err(err) => {
+                    debug!(
+                        factory = q.factory,
+                        question = q.name,
+                        "unreadable data: {:#}",
+                        err
+                    );
+                    skipped.questions.push(format!("{}/{}", q.factory, q.name));
+                    continue;
+                }
+            };
+            let tagged = encoding::decode::<Tagged>(&q.data).unwrap_or_default();
+            if !tagged.requires.is_empty() {
+                requires.push((q.id, tagged.requires));
+            }
             by_factories
                 .entry(q.factory.clone())
                 .or_insert(Vec::new())
@@ -353,15 +1434,46 @@ impl<'a> Service<'a> {
                     probability: q.probability,
                     num_correct: q.num_correct,
                     num_incorrect: q.num_incorrect,
+                    tags: tagged.tags,
+                    requires: Vec::new(),
                     runner,
                 },
             );
         }
+        for members in by_factories.values() {
+            let answers = members
+                .iter()
+                .map(|id| questions[id].runner.answer_text())
+                .collect::<Arc<[String]>>();
+            for id in members {
+                let runner = &mut questions.get_mut(id).unwrap().runner;
+                runner.set_siblings(answers.clone());
+            }
+        }
+        let ids = questions
+            .values()
+            .map(|q| ((q.factory.clone(), q.name.clone()), q.id))
+            .collect::<HashMap<_, _>>();
+        for (id, data) in &requires {
+            let q = questions.get_mut(id).unwrap();
+            for required in data {
+                let (factory, name) = required
+                    .split_once('/')
+                    .unwrap_or((q.factory.as_str(), required.as_str()));
+                match ids.get(&(factory.to_string(), name.to_string())) {
+                    Some(&required) => q.requires.push(required),
+                    None => warn!(question = q.name, required, "unknown prerequisite"),
+                }
+            }
+        }
 
         let mut sets = HashMap::<String, Vec<QuestionID>>::new();
         let questions_in_set = repo.get_all_question_sets().await?;
         for qset in questions_in_set {
-            let q = questions.get(&qset.question_id).unwrap();
+            let Some(q) = questions.get(&qset.question_id) else {
+                skipped.memberships += 1;
+                continue;
+            };
             if !sets.contains_key(&qset.name) {
                 sets.insert(qset.name.clone(), Vec::new());
             }
@@ -372,69 +1484,380 @@ impl<'a> Service<'a> {
             .get_all_answers()
             .await?
             .iter()
+            .filter(|a| {
+                let known = questions.contains_key(&a.question_id);
+                skipped.answers += usize::from(!known);
+                known
+            })
             .map(|a| Answer {
                 question_id: a.question_id,
                 time: a.time,
-                correct: a.correct,
+                score: Score::new(a.score),
             })
             .collect::<Vec<Answer>>();
-        let prob_computer =
-            ProbabilityComputer::new(answers, &questions.values().collect::<Vec<&Question>>());
+        let num_answers = answers.len();
+        let ids = questions
+            .values()
+            .map(|q| {
+                let params = params.get(&q.factory).copied();
+                (q.id, params.unwrap_or_else(model::defaults))
+            })
+            .collect::<Vec<_>>();
+        let mut prob_computer = ProbabilityComputer::with_params(answers, &ids);
+        prob_computer.link(&repo.get_links().await?);
         for &id in questions.keys() {
             repo.set_probability(id, prob_computer.get_prob(id)).await?;
         }
 
-        Ok(Service {
+        let goals = repo
+            .get_all_goals()
+            .await?
+            .into_iter()
+            .map(|g| (g.set_name.clone(), g))
+            .collect();
+        let archived = repo.get_archived_sets().await?.into_iter().collect();
+
+        if !skipped.is_empty() {
+            warn!(
+                sets = skipped.factories.len(),
+                questions = skipped.questions.len(),
+                memberships = skipped.memberships,
+                answers = skipped.answers,
+                "skipped broken rows, run `trivial fsck` to see them"
+            );
+        }
+        info!(
+            questions = questions.len(),
+            answers = num_answers,
+            elapsed = ?start.elapsed(),
+            "loaded questions"
+        );
+        let mut service = Service {
             questions,
             sets,
             prob_computer,
             repo,
             factories: by_factories,
-        })
+            goals,
+            archived,
+            unions,
+            thresholds,
+            next,
+            prerequisite_probability: PREREQUISITE_PROBABILITY,
+            autosave: None,
+            skipped,
+            rng: StdRng::from_entropy(),
+        };
+        for id in service.questions.keys().copied().collect::<Vec<_>>() {
+            service.update_streak(id);
+        }
+        service.refresh_thresholds();
+        Ok(service)
+    }
+
+    /// Picks the questions of the threshold sets again from the current
+    /// probabilities, call it at the start of a session.
+    pub fn refresh_thresholds(&mut self) {
+        let mut names = self.thresholds.keys().cloned().collect::<Vec<_>>();
+        // A threshold set may be based on another one.
+        names.sort_by_key(|name| {
+            let mut depth = 0;
+            let mut set = name;
+            while let Some(Some(base)) = self.thresholds.get(set).map(|t| &t.set) {
+                depth += 1;
+                set = base;
+                if depth > self.thresholds.len() {
+                    break;
+                }
+            }
+            depth
+        });
+        for name in names {
+            let threshold = &self.thresholds[&name];
+            let mut ids = match &threshold.set {
+                Some(set) => self.sets.get(set).cloned().unwrap_or_default(),
+                None => self.questions.keys().copied().collect(),
+            };
+            ids.retain(|id| self.questions[id].probability < threshold.below);
+            self.sets.insert(name, ids);
+        }
+    }
+
+    /// The parameters of the model estimating the question's probability.
+    pub fn model_params(&self, id: QuestionID) -> ModelParams {
+        self.prob_computer.params(id)
+    }
+
+    /// The rows left out when loading, because they're broken.
+    pub fn skipped(&self) -> &Skipped {
+        &self.skipped
+    }
+
+    pub fn set_prerequisite_probability(&mut self, probability: f64) {
+        self.prerequisite_probability = probability;
+    }
+
+    /// Whether the question hasn't been answered yet and one of its
+    /// prerequisites isn't known well enough.
+    pub fn is_locked(&self, id: QuestionID) -> bool {
+        self.prob_computer.get_answers(id).is_empty()
+            && self
+                .get(id)
+                .requires
+                .iter()
+                .any(|&r| self.get(r).probability <= self.prerequisite_probability)
+    }
+
+    /// Hands the answers to `autosave` to write instead of waiting for the
+    /// database.
+    pub fn set_autosave(&mut self, autosave: &'a Autosave) {
+        self.autosave = Some(autosave);
+    }
+
+    /// Waits for the answers given so far to reach the database.
+    pub async fn flush(&self) -> Result<()> {
+        match self.autosave {
+            Some(autosave) => autosave.flush().await,
+            None => Ok(()),
+        }
+    }
+
+    /// Makes the random selection methods deterministic.
+    pub fn seed(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
     }
 
-    pub async fn add_answer(&mut self, id: QuestionID, correct: bool) -> Result<()> {
-        let now = chrono::offset::Utc::now();
+    pub async fn add_answer(&mut self, id: QuestionID, score: Score) -> Result<()> {
+        self.add_answer_with(id, score, &AnswerDetails::default())
+            .await
+    }
+
+    /// Records an answer along with what the question recorded about it
+    /// while it was answered.
+    pub async fn add_answer_with(
+        &mut self,
+        id: QuestionID,
+        score: Score,
+        details: &AnswerDetails,
+    ) -> Result<()> {
+        self.record_answer(id, score, details, chrono::offset::Utc::now())
+            .await
+    }
+
+    /// Records an answer given at another time than now, e.g. in a simulation.
+    pub async fn add_answer_at(
+        &mut self,
+        id: QuestionID,
+        score: Score,
+        now: DateTime<Utc>,
+    ) -> Result<()> {
+        self.record_answer(id, score, &AnswerDetails::default(), now)
+            .await
+    }
+
+    #[instrument(level = "debug", skip(self))]
+    async fn record_answer(
+        &mut self,
+        id: QuestionID,
+        score: Score,
+        details: &AnswerDetails,
+        now: DateTime<Utc>,
+    ) -> Result<()> {
+        let details = &AnswerDetails {
+            params: Some(self.prob_computer.params(id).to_json()),
+            ..details.clone()
+        };
         let q = self.questions.get_mut(&id).unwrap();
         q.probability = self.prob_computer.add_answer(Answer {
-            question_id: q.id.clone(),
+            question_id: q.id,
             time: now,
-            correct,
+            score,
         });
-        self.repo
-            .add_answer(q.id, now, correct, q.probability)
-            .await?;
+        if score.is_correct() {
+            q.num_correct += 1;
+        } else {
+            q.num_incorrect += 1;
+        }
+        match self.autosave {
+            Some(autosave) => {
+                autosave
+                    .save(PendingAnswer {
+                        question_id: q.id,
+                        time: now,
+                        correct: score.is_correct(),
+                        score: score.value(),
+                        error: details.error,
+                        text: details.text.clone(),
+                        steps: details.steps.clone(),
+                        params: details.params.clone(),
+                        tolerance: details.tolerance,
+                        probability: q.probability,
+                    })
+                    .await?
+            }
+            None => {
+                self.repo
+                    .add_answer(
+                        q.id,
+                        now,
+                        score.is_correct(),
+                        score.value(),
+                        details,
+                        q.probability,
+                    )
+                    .await?
+            }
+        }
+        self.update_streak(id);
+        self.update_linked(id).await
+    }
+
+    /// Passes the question's streak on to its runner.
+    fn update_streak(&mut self, id: QuestionID) {
+        let streak = self.streak(id);
+        self.questions
+            .get_mut(&id)
+            .unwrap()
+            .runner
+            .set_streak(streak);
+    }
+
+    /// Stores the probabilities of the questions linked to the question,
+    /// after its answers changed.
+    async fn update_linked(&mut self, id: QuestionID) -> Result<()> {
+        for &linked in self.prob_computer.linked(id) {
+            let probability = self.prob_computer.get_prob(linked);
+            self.questions.get_mut(&linked).unwrap().probability = probability;
+            self.repo.set_probability(linked, probability).await?;
+        }
         Ok(())
     }
 
-    fn filter_questions(
-        &self,
-        questions: &Vec<QuestionID>,
+    /// The questions whose answers count for the question, see
+    /// `ProbabilityComputer::link`.
+    pub fn linked(&self, id: QuestionID) -> &[QuestionID] {
+        self.prob_computer.linked(id)
+    }
+
+    /// The mean absolute relative error of the question's numeric guesses
+    /// and how many there were, or None if none has been recorded.
+    pub async fn average_error(&self, id: QuestionID) -> Result<Option<(f64, usize)>> {
+        self.flush().await?;
+        let errors = self
+            .repo
+            .get_answers(id)
+            .await?
+            .iter()
+            .filter_map(|a| a.error)
+            .collect::<Vec<_>>();
+        if errors.is_empty() {
+            return Ok(None);
+        }
+        let mean = errors.iter().map(|e| e.abs()).sum::<f64>() / errors.len() as f64;
+        Ok(Some((mean, errors.len())))
+    }
+
+    /// Takes back the question's most recent answer. Returns false if it had
+    /// none.
+    pub async fn undo_answer(&mut self, id: QuestionID) -> Result<bool> {
+        let Some(correct) = self.last_answer(id).map(|a| a.score.is_correct()) else {
+            return Ok(false);
+        };
+        self.flush().await?;
+        let probability = self.prob_computer.remove_last_answer(id).unwrap();
+        let q = self.questions.get_mut(&id).unwrap();
+        q.probability = probability;
+        if correct {
+            q.num_correct -= 1;
+        } else {
+            q.num_incorrect -= 1;
+        }
+        let deleted = self.repo.delete_last_answer(id, probability).await?;
+        self.update_streak(id);
+        self.update_linked(id).await?;
+        Ok(deleted)
+    }
+
+    fn filter_questions(&self, questions: &[QuestionID], selection: Selection) -> Vec<QuestionID> {
+        questions
+            .iter()
+            .copied()
+            .filter(|&q| !self.is_locked(q))
+            .filter(|&q| match selection {
+                Selection::All => true,
+                Selection::Practiced => !self.prob_computer.get_answers(q).is_empty(),
+            })
+            .collect()
+    }
+
+    /// Whether the questions shouldn't be asked right after each other:
+    /// they share a tag, or each asks for the other's answer, like a word
+    /// and its reverse.
+    pub fn are_siblings(&self, a: QuestionID, b: QuestionID) -> bool {
+        let (a, b) = (self.get(a), self.get(b));
+        if a.tags.iter().any(|t| b.tags.contains(t)) {
+            return true;
+        }
+        let asks_for = |q: &Question, other: &Question| {
+            spacing::contains_words(
+                &spacing::words(&q.runner.question_text()),
+                &spacing::words(&other.runner.answer_text()),
+            )
+        };
+        asks_for(a, b) && asks_for(b, a)
+    }
+
+    /// Shuffles the questions of a session, keeping siblings at least
+    /// `min_distance` apart, see `are_siblings` and `spacing::space`.
+    pub fn shuffle(&mut self, ids: &mut Vec<QuestionID>, min_distance: usize) {
+        ids.shuffle(&mut self.rng);
+        if min_distance > 1 {
+            *ids = spacing::space(ids, min_distance, |a, b| self.are_siblings(a, b));
+        }
+    }
+
+    #[instrument(level = "debug", skip(self))]
+    pub fn select(
+        &mut self,
+        set: &str,
+        method: Method,
+        num: usize,
         selection: Selection,
     ) -> Vec<QuestionID> {
-        match selection {
-            Selection::All => questions.clone(),
-            Selection::Practiced => questions
-                .iter()
-                .filter_map(|q| {
-                    if self.prob_computer.questions.get(q).unwrap().answers.len() > 0 {
-                        Some(*q)
-                    } else {
-                        None
+        if let Some(shares) = self.union_shares(set, num, selection) {
+            let mut ids = Vec::new();
+            for (member, n) in shares {
+                for id in self.select(&member, method, n, selection) {
+                    if !ids.contains(&id) {
+                        ids.push(id);
                     }
-                })
-                .collect::<Vec<QuestionID>>(),
+                }
+            }
+            return ids;
+        }
+        // Fewer questions may be selectable than were counted when the
+        // number was picked, e.g. when prerequisites were forgotten since.
+        let num = num.min(self.get_set_size(set, selection));
+        match method {
+            Method::Bottom => self.get_bottom_selection(set, num, selection),
+            Method::WeightedRandom => self.get_weighted_random_selection(set, num, selection),
+            Method::UniformRandom => self.get_uniform_random_selection(set, num, selection),
+            Method::OldestAnswer => self.get_oldest_answer(set, num, selection),
+            Method::RecentlyWrong => self.get_recently_wrong(set, num, selection),
+            Method::HardestTags => self.get_hardest_tags(set, num, selection),
         }
     }
 
     pub fn get_weighted_random_selection(
-        &self,
+        &mut self,
         set: &str,
         mut num: usize,
         selection: Selection,
     ) -> Vec<QuestionID> {
-        let questions = self.filter_questions(self.sets.get(set).unwrap(), selection);
+        let questions = self.filter_questions(self.get_set(set), selection);
         let mut stack = Vec::new();
+        // In the order they're drawn, so a seeded selection is reproducible.
+        let mut picks = Vec::new();
         let mut chosen = HashSet::new();
         num = std::cmp::min(num, questions.len());
         // O(nk). Can be done in O(nlog(n)) using an augmented balanced search tree
@@ -445,20 +1868,24 @@ impl<'a> Service<'a> {
                     continue;
                 }
                 let q = self.get(*qid);
-                total += (1. - q.probability + 0.05).powf(1.5);
+                total += self
+                    .prob_computer
+                    .params(*qid)
+                    .selection_weight(q.probability);
                 stack.push((*qid, total));
             }
-            let x = rand::random::<f64>() * total;
+            let x = self.rng.gen::<f64>() * total;
             for (name, v) in &stack {
                 if *v >= x {
                     chosen.insert(*name);
+                    picks.push(*name);
                     break;
                 }
             }
             stack.clear();
         }
 
-        chosen.iter().map(|&qid| qid).collect::<Vec<QuestionID>>()
+        picks
     }
 
     pub fn get_bottom_selection(
@@ -467,7 +1894,7 @@ impl<'a> Service<'a> {
         num: usize,
         selection: Selection,
     ) -> Vec<QuestionID> {
-        let mut question_ids = self.filter_questions(self.sets.get(set).unwrap(), selection);
+        let mut question_ids = self.filter_questions(self.get_set(set), selection);
         question_ids.sort_by(|&id1, &id2| {
             self.get(id1)
                 .probability
@@ -477,13 +1904,13 @@ impl<'a> Service<'a> {
     }
 
     pub fn get_uniform_random_selection(
-        &self,
+        &mut self,
         set: &str,
         num: usize,
         selection: Selection,
     ) -> Vec<QuestionID> {
-        let mut question_ids = self.filter_questions(self.sets.get(set).unwrap(), selection);
-        question_ids.shuffle(&mut thread_rng());
+        let mut question_ids = self.filter_questions(self.get_set(set), selection);
+        question_ids.shuffle(&mut self.rng);
         question_ids[..num].to_vec()
     }
 
@@ -493,7 +1920,7 @@ impl<'a> Service<'a> {
         num: usize,
         selection: Selection,
     ) -> Vec<QuestionID> {
-        let question_ids = self.filter_questions(self.sets.get(set).unwrap(), selection);
+        let question_ids = self.filter_questions(self.get_set(set), selection);
         let mut times = Vec::new();
         for id in question_ids {
             let answers = self.prob_computer.get_answers(id);
@@ -507,19 +1934,223 @@ impl<'a> Service<'a> {
         times[..num].iter().map(|&(_, id)| id).collect()
     }
 
-    pub fn get_set_size(&self, name: &str, selection: Selection) -> usize {
-        let set = self.get_set(name);
-        match selection {
-            Selection::All => set.len(),
-            Selection::Practiced => set
+    /// Questions with the most wrong answers in the last
+    /// `RECENTLY_WRONG_DAYS` first, the most recently wrong first among
+    /// equals, then the rest by probability.
+    pub fn get_recently_wrong(
+        &self,
+        set: &str,
+        num: usize,
+        selection: Selection,
+    ) -> Vec<QuestionID> {
+        let question_ids = self.filter_questions(self.get_set(set), selection);
+        let latest = question_ids
+            .iter()
+            .filter_map(|&id| self.prob_computer.get_answers(id).last())
+            .map(|a| a.time)
+            .max();
+        let since = latest.map(|t| t - chrono::Duration::days(RECENTLY_WRONG_DAYS));
+        let mut ranked = Vec::new();
+        for id in question_ids {
+            let wrong = self
+                .prob_computer
+                .get_answers(id)
                 .iter()
-                .filter(|&q| self.prob_computer.questions.get(q).unwrap().answers.len() > 0)
-                .count(),
+                .filter(|a| !a.score.is_correct() && since.is_some_and(|since| a.time >= since))
+                .map(|a| a.time)
+                .collect::<Vec<_>>();
+            ranked.push((wrong.len(), wrong.last().copied(), id));
         }
+        ranked.sort_by(|a, b| {
+            b.0.cmp(&a.0).then_with(|| b.1.cmp(&a.1)).then_with(|| {
+                self.get(a.2)
+                    .probability
+                    .total_cmp(&self.get(b.2).probability)
+            })
+        });
+        ranked[..num].iter().map(|&(_, _, id)| id).collect()
+    }
+
+    pub fn get_hardest_tags(&self, set: &str, num: usize, selection: Selection) -> Vec<QuestionID> {
+        let mut question_ids = self.filter_questions(self.get_set(set), selection);
+        let tags = self
+            .difficulty_by_tag(&question_ids)
+            .into_iter()
+            .map(|d| (d.name, d.mean_probability))
+            .collect::<HashMap<_, _>>();
+        // Questions without tags count as the set as a whole.
+        let untagged = self.difficulty("", &question_ids).mean_probability;
+        let hardest_tag = |id: QuestionID| {
+            self.get(id)
+                .tags
+                .iter()
+                .map(|t| tags[t])
+                .min_by(f64::total_cmp)
+                .unwrap_or(untagged)
+        };
+        question_ids.sort_by(|&a, &b| {
+            hardest_tag(a)
+                .total_cmp(&hardest_tag(b))
+                .then_with(|| self.get(a).probability.total_cmp(&self.get(b).probability))
+        });
+        question_ids[..num].to_vec()
     }
 
+    /// Questions with the highest value of the method's expression first.
+    pub fn get_custom_selection(
+        &self,
+        set: &str,
+        method: &CustomMethod,
+        num: usize,
+        selection: Selection,
+    ) -> Result<Vec<QuestionID>> {
+        if let Some(shares) = self.union_shares(set, num, selection) {
+            let mut ids = Vec::new();
+            for (member, n) in shares {
+                for id in self.get_custom_selection(&member, method, n, selection)? {
+                    if !ids.contains(&id) {
+                        ids.push(id);
+                    }
+                }
+            }
+            return Ok(ids);
+        }
+        let now = Utc::now();
+        let mut ranked = Vec::new();
+        for id in self.filter_questions(self.get_set(set), selection) {
+            let q = self.get(id);
+            let answers = self.prob_computer.get_answers(id);
+            let days_since_last = match answers.last() {
+                Some(a) => (now - a.time).num_seconds() as f64 / 86400.,
+                None => NEVER_ANSWERED_DAYS,
+            };
+            let value = method.score(&Attributes {
+                probability: q.probability,
+                days_since_last,
+                num_correct: q.num_correct,
+                num_incorrect: q.num_incorrect,
+                num_answers: answers.len(),
+                tags: &q.tags,
+            })?;
+            ranked.push((value, id));
+        }
+        ranked.sort_by(|a, b| b.0.total_cmp(&a.0));
+        Ok(ranked.iter().take(num).map(|&(_, id)| id).collect())
+    }
+
+    /// How many of `num` questions each member of a union with weights
+    /// contributes, or None if the set isn't one. Questions in several
+    /// members are only asked once, so a session can come out shorter.
+    pub fn union_shares(
+        &self,
+        set: &str,
+        num: usize,
+        selection: Selection,
+    ) -> Option<Vec<(String, usize)>> {
+        let members = self.unions.get(set)?;
+        let sizes = members
+            .iter()
+            .map(|(member, weight)| (*weight, self.get_set_size(member, selection)))
+            .collect::<Vec<_>>();
+        let shares = weighted_shares(num, &sizes);
+        Some(
+            members
+                .iter()
+                .zip(shares)
+                .map(|((member, _), n)| (member.clone(), n))
+                .collect(),
+        )
+    }
+
+    pub fn get_set_size(&self, name: &str, selection: Selection) -> usize {
+        self.filter_questions(self.get_set(name), selection).len()
+    }
+
+    pub fn get_questions(&self) -> Vec<&Question> {
+        self.questions.values().collect()
+    }
+
+    pub fn goal_progress(&self, set: &str) -> Option<GoalProgress> {
+        let goal = self.goals.get(set)?;
+        let questions = self.sets.get(set)?;
+        let reached = questions
+            .iter()
+            .filter(|&&id| self.get(id).probability >= goal.target)
+            .count();
+        Some(GoalProgress {
+            goal: goal.clone(),
+            reached,
+            total: questions.len(),
+        })
+    }
+
+    /// How hard the questions are together.
+    pub fn difficulty(&self, name: &str, ids: &[QuestionID]) -> Difficulty {
+        let first_tries = ids
+            .iter()
+            .filter_map(|&id| self.prob_computer.get_answers(id).first())
+            .map(|a| a.score.value())
+            .collect::<Vec<_>>();
+        Difficulty {
+            name: name.to_string(),
+            questions: ids.len(),
+            mean_probability: ids.iter().map(|&id| self.get(id).probability).sum::<f64>()
+                / ids.len().max(1) as f64,
+            first_try_accuracy: (!first_tries.is_empty())
+                .then(|| first_tries.iter().sum::<f64>() / first_tries.len() as f64),
+        }
+    }
+
+    /// The difficulty of every set that isn't archived, hardest first.
+    pub fn difficulty_by_set(&self) -> Vec<Difficulty> {
+        let mut difficulties = self
+            .get_sets()
+            .into_iter()
+            .map(|set| self.difficulty(set, &self.sets[set]))
+            .collect::<Vec<_>>();
+        difficulties.sort_by(|a, b| a.mean_probability.total_cmp(&b.mean_probability));
+        difficulties
+    }
+
+    /// The difficulty of every tag of the questions, hardest first.
+    pub fn difficulty_by_tag(&self, ids: &[QuestionID]) -> Vec<Difficulty> {
+        let mut tags = BTreeMap::<&str, Vec<QuestionID>>::new();
+        for &id in ids {
+            for tag in &self.get(id).tags {
+                tags.entry(tag).or_default().push(id);
+            }
+        }
+        let mut difficulties = tags
+            .into_iter()
+            .map(|(tag, ids)| self.difficulty(tag, &ids))
+            .collect::<Vec<_>>();
+        difficulties.sort_by(|a, b| a.mean_probability.total_cmp(&b.mean_probability));
+        difficulties
+    }
+
+    /// The sets that aren't archived.
     pub fn get_sets(&self) -> Vec<&String> {
-        self.sets.keys().collect()
+        self.sets
+            .keys()
+            .filter(|s| !self.archived.contains(*s))
+            .collect()
+    }
+
+    pub fn has_set(&self, name: &str) -> bool {
+        self.sets.contains_key(name)
+    }
+
+    /// The set to continue with after a session of this one, given as
+    /// `next` in its data, if it exists.
+    pub fn next_set(&self, name: &str) -> Option<&str> {
+        self.next
+            .get(name)
+            .filter(|next| self.has_set(next))
+            .map(String::as_str)
+    }
+
+    pub fn is_archived(&self, name: &str) -> bool {
+        self.archived.contains(name)
     }
 
     pub fn get(&self, id: QuestionID) -> &Question {
@@ -530,91 +2161,287 @@ impl<'a> Service<'a> {
         self.prob_computer.get_answers(id).last()
     }
 
+    /// Number of correct answers in a row, up to the latest answer.
+    pub fn streak(&self, id: QuestionID) -> usize {
+        self.prob_computer
+            .get_answers(id)
+            .iter()
+            .rev()
+            .take_while(|a| a.score.is_correct())
+            .count()
+    }
+
+    /// Number of days in a row up to `today` with answers, counting from
+    /// the day before if nothing was answered on `today` yet.
+    pub fn day_streak(&self, today: NaiveDate) -> usize {
+        let days = self
+            .prob_computer
+            .questions
+            .values()
+            .flat_map(|q| &q.answers)
+            .map(|a| a.time.with_timezone(&Local).date_naive())
+            .collect::<BTreeSet<_>>();
+        let mut day = today;
+        if !days.contains(&day) {
+            day = day - Days::new(1);
+        }
+        let mut streak = 0;
+        while days.contains(&day) {
+            streak += 1;
+            day = day - Days::new(1);
+        }
+        streak
+    }
+
+    /// When the question should be reviewed again. The interval starts at a
+    /// day and grows with every correct answer in a row, a wrong answer makes
+    /// it due right away. Questions that were never answered aren't due.
+    pub fn due_date(&self, id: QuestionID) -> Option<DateTime<Utc>> {
+        let last = self.last_answer(id)?;
+        let streak = self.streak(id);
+        if streak == 0 {
+            return Some(last.time);
+        }
+        let days = FIRST_INTERVAL_DAYS * INTERVAL_GROWTH.powi(streak as i32 - 1);
+        let interval = chrono::Duration::minutes((days.min(MAX_INTERVAL_DAYS) * 24. * 60.) as i64);
+        Some(last.time + interval)
+    }
+
     pub fn get_factory(&self, factory: &str) -> &Vec<QuestionID> {
         self.factories.get(factory).unwrap()
     }
 
-    pub fn get_set(&self, set: &str) -> &Vec<QuestionID> {
-        self.sets.get(set).unwrap()
+    /// The questions of the set, none if it's empty or was skipped.
+    pub fn get_set(&self, set: &str) -> &[QuestionID] {
+        self.sets.get(set).map_or(&[][..], |s| s.as_slice())
     }
 
-    pub async fn add_question_in_set(&mut self, id: QuestionID, set: &str) -> Result<bool> {
-        let s = if let Some(s) = self.sets.get_mut(set) {
-            s
-        } else {
-            self.sets.insert(String::from_str(set)?, Vec::new());
-            self.sets.get_mut(set).unwrap()
+    /// Writes the models to the database, resolving the sets in dependency
+    /// order, and reloads the service from the result.
+    #[instrument(level = "debug", skip(self, models))]
+    pub async fn import(&mut self, models: &Models, prune: bool) -> Result<ImportReport> {
+        // Reloading reads the answers back from the database.
+        self.flush().await?;
+        let diff = diff_models(self.repo, models).await?;
+        let renames = diff
+            .renamed
+            .iter()
+            .map(|r| (r.id, r.to.name.clone()))
+            .collect::<Vec<(i64, String)>>();
+        let pruned = match prune {
+            true => diff.orphaned.iter().map(|q| q.id).collect::<Vec<i64>>(),
+            false => Vec::new(),
         };
+        let counts = self
+            .repo
+            .import(
+                &models.questions,
+                &models.factories,
+                &memberships(&diff.joined),
+                &renames,
+                &memberships(&diff.left),
+                &pruned,
+            )
+            .await?;
 
-        // TODO Ass linear scan
-        if s.contains(&id) {
-            return Ok(false);
-        }
-
-        let q = self.questions.get(&id).unwrap();
-        self.repo.insert_question_in_set(set, q.id).await?;
-        s.push(id);
-        Ok(true)
+        let autosave = self.autosave;
+        *self = Service::new(self.repo).await?;
+        self.autosave = autosave;
+        Ok(ImportReport {
+            diff,
+            factories: counts.factories,
+            pruned: pruned.len(),
+        })
     }
 }
 
+/// What `Service::new` reads from the data of a set.
+struct LoadedSet {
+    factory: Option<Box<dyn QuestionFactory>>,
+    next: Option<String>,
+    params: ModelParams,
+    /// The members of a union with weights, and their weights.
+    union: Option<Vec<(String, f64)>>,
+    threshold: Option<ThresholdData>,
+}
+
+fn load_set(f: &db::QuestionFactory) -> Result<LoadedSet> {
+    let factory = load_factory(f)?;
+    let options = encoding::decode::<FactoryOptions>(&f.data)?;
+    let params = options
+        .model
+        .unwrap_or_default()
+        .apply(model::defaults())
+        .with_context(|| format!("model of {}", f.name))?;
+    let union = match f.factory_type.as_str() {
+        "union" => {
+            let union = encoding::decode::<UnionData>(&f.data)?;
+            let members = union
+                .sets
+                .iter()
+                .map(|set| (set.clone(), union.weights.get(set).copied().unwrap_or(1.)))
+                .collect::<Vec<_>>();
+            Some(members).filter(|_| !union.weights.is_empty())
+        }
+        _ => None,
+    };
+    let threshold = match f.factory_type.as_str() {
+        "threshold" => Some(encoding::decode(&f.data)?),
+        _ => None,
+    };
+    Ok(LoadedSet {
+        factory,
+        next: options.next,
+        params,
+        union,
+        threshold,
+    })
+}
+
 pub fn load_factories(
     factory_models: &Vec<db::QuestionFactory>,
 ) -> Result<HashMap<String, Box<dyn QuestionFactory>>> {
     let mut factories = HashMap::new();
     for f in factory_models {
-        let factory = match f.factory_type.as_str() {
-            "default" => {
-                let f = serde_yaml::from_slice::<DefaultData>(&f.data)?;
-                Box::new(f) as Box<dyn QuestionFactory>
-            }
-            "numeric_range" => {
-                let f = serde_yaml::from_slice::<NumericRangeData>(&f.data)?;
-                Box::new(f) as Box<dyn QuestionFactory>
-            }
-            "vocab" => {
-                let f = serde_yaml::from_slice::<VocabData>(&f.data)?;
-                Box::new(f) as Box<dyn QuestionFactory>
-            }
-            "union" => {
-                continue;
-            }
-            _ => {
-                panic!("unexpected question type {:?}", f.factory_type);
-            }
-        };
-        factories.insert(f.name.clone(), factory);
+        if let Some(factory) = load_factory(f).with_context(|| format!("set {}", f.name))? {
+            factories.insert(f.name.clone(), factory);
+        }
     }
 
     Ok(factories)
 }
 
+/// The factory of the set, with its options applied, or None for the types
+/// of sets without questions of their own.
+fn load_factory(f: &db::QuestionFactory) -> Result<Option<Box<dyn QuestionFactory>>> {
+    let factory = match f.factory_type.as_str() {
+        "default" => {
+            let f = encoding::decode::<DefaultData>(&f.data)?;
+            Box::new(f) as Box<dyn QuestionFactory>
+        }
+        "numeric_range" => {
+            let f = encoding::decode::<NumericRangeData>(&f.data)?;
+            Box::new(f) as Box<dyn QuestionFactory>
+        }
+        "vocab" => {
+            let f = encoding::decode::<VocabData>(&f.data)?;
+            Box::new(f) as Box<dyn QuestionFactory>
+        }
+        "typing" => {
+            let f = encoding::decode::<TypingData>(&f.data)?;
+            Box::new(f) as Box<dyn QuestionFactory>
+        }
+        "code" => {
+            let f = encoding::decode::<CodeData>(&f.data)?;
+            Box::new(f) as Box<dyn QuestionFactory>
+        }
+        "script" => {
+            let f = encoding::decode::<ScriptData>(&f.data)?;
+            Box::new(f) as Box<dyn QuestionFactory>
+        }
+        "math" => {
+            let f = encoding::decode::<MathData>(&f.data)?;
+            Box::new(f) as Box<dyn QuestionFactory>
+        }
+        "flashcard" => {
+            let f = encoding::decode::<FlashcardData>(&f.data)?;
+            Box::new(f) as Box<dyn QuestionFactory>
+        }
+        "sequence" => {
+            let f = encoding::decode::<SequenceData>(&f.data)?;
+            Box::new(f) as Box<dyn QuestionFactory>
+        }
+        "geo" => {
+            let f = encoding::decode::<GeoData>(&f.data)?;
+            Box::new(f) as Box<dyn QuestionFactory>
+        }
+        "records" | "template" => {
+            let f = encoding::decode::<RecordsData>(&f.data)?;
+            Box::new(f) as Box<dyn QuestionFactory>
+        }
+        "arithmetic" => {
+            let f = encoding::decode::<ArithmeticData>(&f.data)?;
+            Box::new(f) as Box<dyn QuestionFactory>
+        }
+        "union" | "questions" | "threshold" => {
+            return Ok(None);
+        }
+        t if plugins::is_registered(t) => {
+            let f = encoding::decode::<PluginData>(&f.data)?.with_type(t);
+            Box::new(f) as Box<dyn QuestionFactory>
+        }
+        _ => {
+            bail!("unexpected question type {:?}", f.factory_type);
+        }
+    };
+    let options = encoding::decode::<FactoryOptions>(&f.data)?;
+    Ok(Some(options.apply(factory)?))
+}
+
+#[derive(Clone)]
 pub struct Answer {
     pub question_id: QuestionID,
     pub time: DateTime<Utc>,
-    pub correct: bool,
+    pub score: Score,
 }
 
 struct ProbQuestion {
     answers: Vec<Answer>,
     weighted_total: f64,
     weighted_correct: f64,
+    /// How likely the question is known, for `ModelKind::Bkt`.
+    known: f64,
+    params: ModelParams,
 }
 
-struct ProbabilityComputer {
+/// Estimates the probability of answering each question correctly from its
+/// answers, weighting recent ones the most.
+pub struct ProbabilityComputer {
     questions: HashMap<QuestionID, ProbQuestion>,
+    /// The other questions of each linked question's group, whose answers
+    /// count as its own, see `link`.
+    linked: HashMap<QuestionID, Vec<QuestionID>>,
 }
 
 impl ProbabilityComputer {
-    fn new(answers: Vec<Answer>, questions: &[&Question]) -> ProbabilityComputer {
+    /// Uses `model::defaults` for every question.
+    pub fn new(answers: Vec<Answer>, ids: &[QuestionID]) -> ProbabilityComputer {
+        let ids = ids
+            .iter()
+            .map(|&id| (id, model::defaults()))
+            .collect::<Vec<_>>();
+        ProbabilityComputer::with_params(answers, &ids)
+    }
+
+    /// The probability of a question with the answers under the default
+    /// model, for storage changing a question's answers on its own.
+    /// `Service::new` works it out again with the model of its set.
+    pub fn default_probability(answers: &[db::Answer]) -> f64 {
+        let answers = answers
+            .iter()
+            .map(|a| Answer {
+                question_id: 0,
+                time: a.time,
+                score: Score::new(a.score),
+            })
+            .collect();
+        ProbabilityComputer::new(answers, &[0]).get_prob(0)
+    }
+
+    pub fn with_params(
+        answers: Vec<Answer>,
+        ids: &[(QuestionID, ModelParams)],
+    ) -> ProbabilityComputer {
         let mut questions2 = HashMap::new();
-        for q in questions {
+        for &(id, params) in ids {
             questions2.insert(
-                q.id.clone(),
+                id,
                 ProbQuestion {
                     answers: Vec::new(),
                     weighted_total: 0.,
                     weighted_correct: 0.,
+                    known: params.known,
+                    params,
                 },
             );
         }
@@ -625,104 +2452,657 @@ impl ProbabilityComputer {
 
         for (_, q) in questions2.iter_mut() {
             q.answers.sort_by_key(|a| a.time);
-            for c in q.answers.iter().map(|a| a.correct).collect::<Vec<bool>>() {
-                ProbabilityComputer::add_to_question(q, c);
+            for score in q.answers.iter().map(|a| a.score).collect::<Vec<Score>>() {
+                ProbabilityComputer::add_to_question(q, score);
             }
         }
 
         ProbabilityComputer {
             questions: questions2,
+            linked: HashMap::new(),
         }
     }
 
-    fn add_to_question(q: &mut ProbQuestion, correct: bool) {
-        let p = 0.9;
-        q.weighted_total = q.weighted_total * p + 1.;
-        q.weighted_correct *= p;
-        if correct {
-            q.weighted_correct += 1.;
+    /// Makes the answers to each question count for the questions it's
+    /// linked to, and for those they're linked to in turn. Links to unknown
+    /// questions are ignored.
+    pub fn link(&mut self, links: &[(QuestionID, QuestionID)]) {
+        let mut groups = HashMap::<QuestionID, QuestionID>::new();
+        fn root(groups: &HashMap<QuestionID, QuestionID>, mut id: QuestionID) -> QuestionID {
+            while let Some(&parent) = groups.get(&id) {
+                id = parent;
+            }
+            id
+        }
+        for &(a, b) in links {
+            if !self.questions.contains_key(&a) || !self.questions.contains_key(&b) {
+                continue;
+            }
+            let (a, b) = (root(&groups, a), root(&groups, b));
+            if a != b {
+                groups.insert(a, b);
+            }
+        }
+        let mut members = HashMap::<QuestionID, Vec<QuestionID>>::new();
+        for &id in groups.keys() {
+            members.entry(root(&groups, id)).or_default().push(id);
+        }
+        self.linked.clear();
+        for (root, mut group) in members {
+            group.push(root);
+            for &id in &group {
+                let others = group.iter().copied().filter(|&o| o != id).collect();
+                self.linked.insert(id, others);
+            }
+        }
+        for id in self.linked.keys().copied().collect::<Vec<_>>() {
+            self.replay(id);
         }
     }
 
-    fn add_answer(&mut self, answer: Answer) -> f64 {
-        let q = self.questions.get_mut(&answer.question_id).unwrap();
-        ProbabilityComputer::add_to_question(q, answer.correct);
+    /// The questions whose answers count for the question.
+    pub fn linked(&self, id: QuestionID) -> &[QuestionID] {
+        self.linked.get(&id).map_or(&[], |l| l.as_slice())
+    }
+
+    /// Works the question's estimate out again from its answers and those of
+    /// the questions linked to it.
+    fn replay(&mut self, id: QuestionID) {
+        let mut answers = std::iter::once(id)
+            .chain(self.linked(id).iter().copied())
+            .flat_map(|id| self.questions[&id].answers.iter())
+            .map(|a| (a.time, a.score))
+            .collect::<Vec<_>>();
+        answers.sort_by_key(|&(time, _)| time);
+        let q = self.questions.get_mut(&id).unwrap();
+        q.weighted_total = 0.;
+        q.weighted_correct = 0.;
+        q.known = q.params.known;
+        for (_, score) in answers {
+            ProbabilityComputer::add_to_question(q, score);
+        }
+    }
+
+    fn add_to_question(q: &mut ProbQuestion, score: Score) {
+        let p = q.params.decay;
+        q.weighted_total = q.weighted_total * p + 1.;
+        q.weighted_correct = q.weighted_correct * p + score.value();
+        q.known = q.params.bkt_update(q.known, score.value());
+    }
+
+    /// Returns the new probability of the answer's question. The
+    /// probabilities of the questions linked to it change too.
+    pub fn add_answer(&mut self, answer: Answer) -> f64 {
+        let id = answer.question_id;
+        for linked in self.linked.get(&id).into_iter().flatten() {
+            let q = self.questions.get_mut(linked).unwrap();
+            ProbabilityComputer::add_to_question(q, answer.score);
+        }
+        let q = self.questions.get_mut(&id).unwrap();
+        ProbabilityComputer::add_to_question(q, answer.score);
         q.answers.push(answer);
         ProbabilityComputer::prob(q)
     }
 
+    /// Returns the new probability of the question, or None if it had no
+    /// answers. The probabilities of the questions linked to it change too.
+    pub fn remove_last_answer(&mut self, id: QuestionID) -> Option<f64> {
+        self.questions.get_mut(&id).unwrap().answers.pop()?;
+        for linked in self.linked(id).to_vec() {
+            self.replay(linked);
+        }
+        self.replay(id);
+        Some(self.get_prob(id))
+    }
+
     fn prob(q: &ProbQuestion) -> f64 {
-        (q.weighted_correct + 1.) / (q.weighted_total + 2.)
+        match q.params.kind {
+            ModelKind::Weighted => {
+                (q.weighted_correct + q.params.prior_correct)
+                    / (q.weighted_total + q.params.prior_total)
+            }
+            ModelKind::Bkt => q.params.bkt_probability(q.known),
+        }
     }
 
-    fn get_prob(&self, id: QuestionID) -> f64 {
+    pub fn get_prob(&self, id: QuestionID) -> f64 {
         ProbabilityComputer::prob(self.questions.get(&id).unwrap())
     }
 
+    /// Replays the answers in the order they were given, scoring how well
+    /// each was predicted from the ones before it. Answers to other questions
+    /// than `ids` are left out.
+    pub fn evaluate(mut answers: Vec<Answer>, ids: &[(QuestionID, ModelParams)]) -> Evaluation {
+        let known = ids.iter().map(|&(id, _)| id).collect::<HashSet<_>>();
+        answers.retain(|a| known.contains(&a.question_id));
+        answers.sort_by_key(|a| a.time);
+        let mut computer = ProbabilityComputer::with_params(Vec::new(), ids);
+        let mut evaluation = Evaluation::default();
+        for answer in answers {
+            evaluation.add(computer.get_prob(answer.question_id), answer.score.value());
+            computer.add_answer(answer);
+        }
+        evaluation
+    }
+
+    /// The model parameters of the question.
+    pub fn params(&self, id: QuestionID) -> ModelParams {
+        self.questions.get(&id).unwrap().params
+    }
+
     fn get_answers(&self, id: QuestionID) -> &Vec<Answer> {
         &self.questions.get(&id).unwrap().answers
     }
 }
 
+#[derive(Default)]
 pub struct Models {
     pub questions: Vec<db::Question>,
     pub factories: Vec<db::QuestionFactory>,
     pub sets: HashMap<String, Box<dyn QuestionSetFactory>>,
 }
 
+pub struct ImportReport {
+    pub diff: ModelDiff,
+    pub factories: u64,
+    pub pruned: usize,
+}
+
+impl ImportReport {
+    /// A record of the import for the `loads` table, see `db::Load`.
+    pub fn to_load(&self, files: &[PathBuf]) -> db::Load {
+        let keys = |keys: &[QuestionKey]| {
+            keys.iter()
+                .map(|k| format!("{}/{}\n", k.factory, k.name))
+                .collect()
+        };
+        db::Load {
+            id: 0,
+            time: Utc::now(),
+            files: files.iter().map(|f| format!("{}\n", f.display())).collect(),
+            added: keys(&self.diff.added),
+            updated: keys(&self.diff.updated),
+            renamed: self
+                .diff
+                .renamed
+                .iter()
+                .map(|r| format!("{}/{} -> {}\n", r.from.factory, r.from.name, r.to.name))
+                .collect(),
+            pruned: match self.pruned {
+                0 => String::new(),
+                _ => self
+                    .diff
+                    .orphaned
+                    .iter()
+                    .map(|q| format!("{}/{}\n", q.factory, q.name))
+                    .collect(),
+            },
+        }
+    }
+}
+
+/// A question whose id was changed in the models without changing anything
+/// else, so it keeps its history.
+pub struct Rename {
+    pub id: QuestionID,
+    pub from: QuestionKey,
+    pub to: QuestionKey,
+}
+
+/// How the questions in a set of loaded models differ from the database.
+#[derive(Default)]
+pub struct ModelDiff {
+    pub added: Vec<QuestionKey>,
+    pub updated: Vec<QuestionKey>,
+    pub renamed: Vec<Rename>,
+    /// Questions in the database that are missing from the models.
+    pub orphaned: Vec<db::Question>,
+    /// Questions that join each set, in the order the sets are built. Sets
+    /// nothing joins are left out.
+    pub joined: Vec<(String, Vec<QuestionKey>)>,
+    /// Questions that leave each set because it excludes them.
+    pub left: Vec<(String, Vec<QuestionKey>)>,
+}
+
+impl ModelDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.updated.is_empty()
+            && self.renamed.is_empty()
+            && self.orphaned.is_empty()
+            && self.joined.is_empty()
+            && self.left.is_empty()
+    }
+}
+
+fn memberships(sets: &[(String, Vec<QuestionKey>)]) -> Vec<db::SetMembership> {
+    sets.iter()
+        .flat_map(|(set, keys)| {
+            keys.iter().map(|k| db::SetMembership {
+                set: set.clone(),
+                factory: k.factory.clone(),
+                name: k.name.clone(),
+            })
+        })
+        .collect()
+}
+
+pub async fn diff_models(repo: &dyn Storage, models: &Models) -> Result<ModelDiff> {
+    let mut existing = repo
+        .get_all_questions()
+        .await?
+        .into_iter()
+        .map(|q| ((q.factory.clone(), q.name.clone()), q))
+        .collect::<HashMap<(String, String), db::Question>>();
+
+    let mut diff = ModelDiff::default();
+    let mut added = Vec::new();
+    for q in &models.questions {
+        let key = QuestionKey {
+            factory: q.factory.clone(),
+            name: q.name.clone(),
+        };
+        match existing.remove(&(q.factory.clone(), q.name.clone())) {
+            None => added.push((key, q)),
+            Some(old) if !encoding::same(&old.data, &q.data) => diff.updated.push(key),
+            Some(_) => {}
+        }
+    }
+
+    // A new question with the same content as a missing one in the same
+    // factory is that question under a new name. Missing questions can share
+    // their content, each is taken by one new question at most.
+    let mut missing = HashMap::<(String, String), Vec<db::Question>>::new();
+    for ((factory, _), q) in existing {
+        missing
+            .entry((factory, stored_hash(&q)))
+            .or_default()
+            .push(q);
+    }
+    for orphans in missing.values_mut() {
+        // Popped from the end, so the first by name is taken first.
+        orphans.sort_by(|a, b| b.name.cmp(&a.name));
+    }
+    for (key, q) in added {
+        let hash = q
+            .content_hash
+            .clone()
+            .unwrap_or_else(|| content_hash(&q.data));
+        let old = missing
+            .get_mut(&(key.factory.clone(), hash))
+            .and_then(|orphans| orphans.pop());
+        match old {
+            Some(old) => diff.renamed.push(Rename {
+                id: old.id,
+                from: QuestionKey {
+                    factory: old.factory,
+                    name: old.name,
+                },
+                to: key,
+            }),
+            None => diff.added.push(key),
+        }
+    }
+    diff.orphaned = missing.into_values().flatten().collect();
+    diff.orphaned
+        .sort_by(|a, b| (&a.factory, &a.name).cmp(&(&b.factory, &b.name)));
+
+    // The sets are resolved in dependency order, as each can be built from
+    // the ones before it.
+    let mut index = SetIndex::new(repo, &models.questions, &diff.renamed).await?;
+    let edges: HashMap<&str, &Vec<String>> = models
+        .sets
+        .iter()
+        .map(|(name, fac)| (name.as_str(), fac.depends_on()))
+        .collect();
+    for set_name in depgraph::topsort(&edges)? {
+        let factory = models.sets.get(set_name).unwrap();
+        let excluded = factory.excludes(&index);
+        let left = index.remove_from_set(set_name, &excluded);
+        if !left.is_empty() {
+            diff.left.push((set_name.to_string(), left));
+        }
+        let keys = factory.build_set(&index, set_name);
+        let joined = index.add_to_set(set_name, keys);
+        if !joined.is_empty() {
+            diff.joined.push((set_name.to_string(), joined));
+        }
+    }
+    Ok(diff)
+}
+
+/// A question whose history was left behind under an old name, and the
+/// question in the models with the same content it belongs to.
+pub struct Relink {
+    pub from: db::Question,
+    pub to: db::Question,
+}
+
+/// Finds orphaned questions that have the same content as a question in the
+/// models, e.g. because the question was renamed before renames were detected.
+pub async fn find_relinks(repo: &dyn Storage, models: &Models) -> Result<Vec<Relink>> {
+    let hashes = models
+        .questions
+        .iter()
+        .map(|q| {
+            let hash = q
+                .content_hash
+                .clone()
+                .unwrap_or_else(|| content_hash(&q.data));
+            ((q.factory.clone(), q.name.clone()), hash)
+        })
+        .collect::<HashMap<(String, String), String>>();
+
+    let mut current = HashMap::new();
+    let mut orphans = Vec::new();
+    for q in repo.get_all_questions().await? {
+        match hashes.get(&(q.factory.clone(), q.name.clone())) {
+            Some(hash) => {
+                current.insert((q.factory.clone(), hash.clone()), q);
+            }
+            None => orphans.push(q),
+        }
+    }
+
+    let mut relinks = Vec::new();
+    for q in orphans {
+        if let Some(to) = current.get(&(q.factory.clone(), stored_hash(&q))) {
+            relinks.push(Relink {
+                to: to.clone(),
+                from: q,
+            });
+        }
+    }
+    relinks.sort_by(|a, b| (&a.from.factory, &a.from.name).cmp(&(&b.from.factory, &b.from.name)));
+    Ok(relinks)
+}
+
+/// Loads the models from every YAML file in the directory, leaving out
+/// anything else, such as the assets directory.
+pub fn load_models_dir(dir: &Path) -> Result<Models> {
+    let mut paths = Vec::new();
+    for path in fs::read_dir(dir)? {
+        let path = path?.path();
+        let yaml = path
+            .extension()
+            .is_some_and(|ext| ext == "yaml" || ext == "yml");
+        if yaml && path.is_file() {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+    load_models(&paths)
+}
+
 pub fn load_models(paths: &[PathBuf]) -> Result<Models> {
-    let mut models = Models {
-        questions: Vec::new(),
-        factories: Vec::new(),
-        sets: HashMap::new(),
-    };
+    let mut models = Models::default();
     for p in paths {
-        println!("path: {:?}", p);
-        let data = fs::read(p)?;
-        let set = serde_yaml::from_slice::<BaseQuestionSet>(&data)?;
-        match set.type_.as_str() {
-            "default" => {
-                let stuff = serde_yaml::from_slice::<
-                    QuestionFactoryModel<DefaultQuestion, DefaultData>,
-                >(&data)?;
-                parse_factory::<DefaultQuestion, DefaultData>(&mut models, &stuff)?;
-                models.sets.insert(
-                    stuff.name.clone(),
-                    Box::new(stuff.data.clone()) as Box<dyn QuestionSetFactory>,
-                );
-            }
-            "numeric_range" => {
-                let stuff = serde_yaml::from_slice::<
-                    QuestionFactoryModel<NumericRangeQuestion, NumericRangeData>,
-                >(&data)?;
-                parse_factory::<NumericRangeQuestion, NumericRangeData>(&mut models, &stuff)?;
+        debug!(path = ?p, "loading question set");
+        parse_set(&mut models, &fs::read(p)?).with_context(|| p.display().to_string())?;
+    }
+    Ok(models)
+}
+
+/// Parses the models from the contents of question set files.
+pub fn parse_models<T: AsRef<[u8]>>(sets: &[T]) -> Result<Models> {
+    let mut models = Models::default();
+    for data in sets {
+        parse_set(&mut models, data.as_ref())?;
+    }
+    Ok(models)
+}
+
+/// Adds the questions, factories and sets of a question set file.
+fn parse_set(models: &mut Models, data: &[u8]) -> Result<()> {
+    let set = serde_yaml::from_slice::<BaseQuestionSet>(data)?;
+    match set.type_.as_str() {
+        "default" => {
+            let stuff =
+                serde_yaml::from_slice::<QuestionFactoryModel<DefaultQuestion, DefaultData>>(data)?;
+            parse_factory::<DefaultQuestion, DefaultData>(models, &stuff)?;
+            models.sets.insert(
+                stuff.name.clone(),
+                Box::new(stuff.data.clone()) as Box<dyn QuestionSetFactory>,
+            );
+            if let Some(reverse) = &stuff.data.reverse {
+                let reversed = reversed(&stuff, reverse);
+                parse_factory::<DefaultQuestion, DefaultData>(models, &reversed)?;
                 models.sets.insert(
-                    stuff.name.clone(),
-                    Box::new(stuff.data.clone()) as Box<dyn QuestionSetFactory>,
+                    reversed.name.clone(),
+                    Box::new(reversed.data.clone()) as Box<dyn QuestionSetFactory>,
                 );
             }
-            "vocab" => {
-                let stuff = serde_yaml::from_slice::<QuestionFactoryModel<Word, VocabData>>(&data)?;
-                parse_factory::<Word, VocabData>(&mut models, &stuff)?;
-                models.sets.insert(
-                    stuff.name.clone(),
-                    Box::new(stuff.data.clone()) as Box<dyn QuestionSetFactory>,
+        }
+        "numeric_range" => {
+            let stuff = serde_yaml::from_slice::<
+                QuestionFactoryModel<NumericRangeQuestion, NumericRangeData>,
+            >(data)?;
+            parse_factory::<NumericRangeQuestion, NumericRangeData>(models, &stuff)?;
+            models.sets.insert(
+                stuff.name.clone(),
+                Box::new(stuff.data.clone()) as Box<dyn QuestionSetFactory>,
+            );
+        }
+        "vocab" => {
+            let stuff = serde_yaml::from_slice::<QuestionFactoryModel<Word, VocabData>>(data)?;
+            parse_factory::<Word, VocabData>(models, &stuff)?;
+            models.sets.insert(
+                stuff.name.clone(),
+                Box::new(stuff.data.clone()) as Box<dyn QuestionSetFactory>,
+            );
+        }
+        "typing" => {
+            let stuff =
+                serde_yaml::from_slice::<QuestionFactoryModel<TypingQuestion, TypingData>>(data)?;
+            parse_factory::<TypingQuestion, TypingData>(models, &stuff)?;
+            models.sets.insert(
+                stuff.name.clone(),
+                Box::new(stuff.data.clone()) as Box<dyn QuestionSetFactory>,
+            );
+        }
+        "code" => {
+            let stuff =
+                serde_yaml::from_slice::<QuestionFactoryModel<CodeQuestion, CodeData>>(data)?;
+            parse_factory::<CodeQuestion, CodeData>(models, &stuff)?;
+            models.sets.insert(
+                stuff.name.clone(),
+                Box::new(stuff.data.clone()) as Box<dyn QuestionSetFactory>,
+            );
+        }
+        "script" => {
+            let stuff =
+                serde_yaml::from_slice::<QuestionFactoryModel<ScriptQuestion, ScriptData>>(data)?;
+            parse_factory::<ScriptQuestion, ScriptData>(models, &stuff)?;
+            models.sets.insert(
+                stuff.name.clone(),
+                Box::new(stuff.data.clone()) as Box<dyn QuestionSetFactory>,
+            );
+        }
+        "math" => {
+            let stuff =
+                serde_yaml::from_slice::<QuestionFactoryModel<MathQuestion, MathData>>(data)?;
+            parse_factory::<MathQuestion, MathData>(models, &stuff)?;
+            models.sets.insert(
+                stuff.name.clone(),
+                Box::new(stuff.data.clone()) as Box<dyn QuestionSetFactory>,
+            );
+        }
+        "flashcard" => {
+            let stuff =
+                serde_yaml::from_slice::<QuestionFactoryModel<Flashcard, FlashcardData>>(data)?;
+            parse_factory::<Flashcard, FlashcardData>(models, &stuff)?;
+            models.sets.insert(
+                stuff.name.clone(),
+                Box::new(stuff.data.clone()) as Box<dyn QuestionSetFactory>,
+            );
+        }
+        "sequence" => {
+            let stuff =
+                serde_yaml::from_slice::<QuestionFactoryModel<Sequence, SequenceData>>(data)?;
+            parse_factory::<Sequence, SequenceData>(models, &stuff)?;
+            models.sets.insert(
+                stuff.name.clone(),
+                Box::new(stuff.data.clone()) as Box<dyn QuestionSetFactory>,
+            );
+        }
+        "geo" => {
+            let stuff = serde_yaml::from_slice::<QuestionFactoryModel<GeoQuestion, GeoData>>(data)?;
+            parse_factory::<GeoQuestion, GeoData>(models, &stuff)?;
+            models.sets.insert(
+                stuff.name.clone(),
+                Box::new(stuff.data.clone()) as Box<dyn QuestionSetFactory>,
+            );
+        }
+        "records" | "template" => {
+            let set = serde_yaml::from_slice::<RecordSet>(data)?;
+            let stuff = QuestionFactoryModel {
+                items: set.questions()?,
+                name: set.name,
+                type_: set.type_,
+                data: set.data,
+            };
+            parse_factory::<RecordQuestion, RecordsData>(models, &stuff)?;
+            models.sets.insert(
+                stuff.name.clone(),
+                Box::new(stuff.data.clone()) as Box<dyn QuestionSetFactory>,
+            );
+        }
+        "remote" => {
+            let set = serde_yaml::from_slice::<RemoteSet>(data)?;
+            let fetched = set
+                .fetch()
+                .with_context(|| format!("fetching {}", set.name))?;
+            let fetched = parse_models(&[fetched])?;
+            models.questions.extend(fetched.questions);
+            models.factories.extend(fetched.factories);
+            models.sets.extend(fetched.sets);
+        }
+        "arithmetic" => {
+            let stuff =
+                serde_yaml::from_slice::<QuestionFactoryModel<Arithmetic, ArithmeticData>>(data)?;
+            parse_factory::<Arithmetic, ArithmeticData>(models, &stuff)?;
+            models.sets.insert(
+                stuff.name.clone(),
+                Box::new(stuff.data.clone()) as Box<dyn QuestionSetFactory>,
+            );
+        }
+        "union" => {
+            let stuff = serde_yaml::from_slice::<QuestionSetFactoryModel<UnionData>>(data)?;
+            if let Some(name) = stuff
+                .data
+                .weights
+                .keys()
+                .find(|name| !stuff.data.sets.contains(name))
+            {
+                bail!(
+                    "{}: weight for {:?}, which isn't a member",
+                    stuff.name,
+                    name
                 );
             }
-            "union" => {
-                let stuff = serde_yaml::from_slice::<QuestionSetFactoryModel<UnionData>>(&data)?;
-                models.sets.insert(
-                    stuff.name.clone(),
-                    Box::new(stuff.data.clone()) as Box<dyn QuestionSetFactory>,
-                );
+            // Stored so sessions can be drawn by weight.
+            models.factories.push(db::QuestionFactory {
+                id: 0,
+                name: stuff.name.clone(),
+                factory_type: stuff.type_.clone(),
+                data: encoding::encode(&stuff.data)?,
+            });
+            models.sets.insert(
+                stuff.name.clone(),
+                Box::new(stuff.data.clone()) as Box<dyn QuestionSetFactory>,
+            );
+        }
+        "threshold" => {
+            let mut stuff = serde_yaml::from_slice::<QuestionSetFactoryModel<ThresholdData>>(data)?;
+            stuff.data.depends = stuff.data.set.iter().cloned().collect();
+            // Stored so the questions can be picked at the start of a session.
+            models.factories.push(db::QuestionFactory {
+                id: 0,
+                name: stuff.name.clone(),
+                factory_type: stuff.type_.clone(),
+                data: encoding::encode(&stuff.data)?,
+            });
+            models.sets.insert(
+                stuff.name.clone(),
+                Box::new(stuff.data.clone()) as Box<dyn QuestionSetFactory>,
+            );
+        }
+        "questions" => {
+            let stuff = serde_yaml::from_slice::<QuestionSetFactoryModel<QuestionsData>>(data)?;
+            models.sets.insert(
+                stuff.name.clone(),
+                Box::new(QuestionsData::new(stuff.data.questions)) as Box<dyn QuestionSetFactory>,
+            );
+        }
+        t if plugins::is_registered(t) => {
+            let stuff =
+                serde_yaml::from_slice::<QuestionFactoryModel<ScriptQuestion, PluginData>>(data)?;
+            parse_factory::<ScriptQuestion, PluginData>(models, &stuff)?;
+            models.sets.insert(
+                stuff.name.clone(),
+                Box::new(stuff.data.clone()) as Box<dyn QuestionSetFactory>,
+            );
+        }
+        _ => bail!("{}: unexpected question type {:?}", set.name, set.type_),
+    };
+    if !set.exclude.is_empty() {
+        if let Some(factory) = models.sets.remove(&set.name) {
+            models.sets.insert(
+                set.name.clone(),
+                Box::new(Excluding::new(factory, set.exclude)) as Box<dyn QuestionSetFactory>,
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Checks that every question can be built and every set can be assembled,
+/// returning a description of each problem found.
+pub fn validate_models(models: &Models) -> Vec<String> {
+    let mut problems = Vec::new();
+    let mut seen = HashSet::new();
+    for q in &models.questions {
+        if !seen.insert((&q.factory, &q.name)) {
+            problems.push(format!("{}/{}: duplicate id", q.factory, q.name));
+        }
+    }
+
+    match load_factories(&models.factories) {
+        Ok(factories) => {
+            for q in &models.questions {
+                if let Err(err) = factories[&q.factory].build(&q.data) {
+                    problems.push(format!("{}/{}: {:#}", q.factory, q.name, err));
+                }
             }
-            _ => {
-                panic!("unexpected question type {:?}", set.type_);
+        }
+        Err(err) => problems.push(format!("{:#}", err)),
+    }
+
+    for f in &models.factories {
+        if let Ok(FactoryOptions {
+            next: Some(next), ..
+        }) = encoding::decode::<FactoryOptions>(&f.data)
+        {
+            if !models.sets.contains_key(&next) {
+                problems.push(format!("{}: next set {:?} doesn't exist", f.name, next));
             }
-        };
+        }
     }
 
-    Ok(models)
+    let mut edges = HashMap::new();
+    for (name, set) in &models.sets {
+        for dep in set.depends_on() {
+            if !models.sets.contains_key(dep) {
+                problems.push(format!("{}: includes unknown set {:?}", name, dep));
+            }
+        }
+        edges.insert(name.as_str(), set.depends_on());
+    }
+    if let Err(err) = depgraph::topsort(&edges) {
+        problems.push(format!("{:#}", err));
+    }
+    problems.sort();
+    problems
 }
 
 fn parse_factory<T1, T2>(models: &mut Models, stuff: &QuestionFactoryModel<T1, T2>) -> Result<()>
@@ -731,10 +3111,11 @@ where
     T2: Serialize,
 {
     for q in &stuff.items {
-        let data = serde_yaml::to_vec(&q)?;
+        let data = encoding::encode(&q)?;
         models.questions.push(db::Question {
             factory: stuff.name.clone(),
             name: q.name(),
+            content_hash: Some(content_hash(&data)),
             data,
             ..Default::default()
         });
@@ -744,7 +3125,7 @@ where
         id: 0,
         name: stuff.name.clone(),
         factory_type: stuff.type_.clone(),
-        data: serde_yaml::to_vec(&stuff.data)?,
+        data: encoding::encode(&stuff.data)?,
     });
     Ok(())
 }
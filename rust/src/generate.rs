@@ -0,0 +1,94 @@
+use crate::llm::Endpoint;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+const INSTRUCTIONS: &str = "You write quiz questions with short, unambiguous \
+    answers. Respond with a JSON object with the field \"questions\", a list of \
+    objects with the fields \"id\" (a short snake_case identifier), \"question\" \
+    (without a trailing question mark) and \"answers\" (a list of accepted \
+    answers, the preferred one first).";
+
+/// A generated question, in the schema of the `default` question type.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Draft {
+    pub id: String,
+    pub question: String,
+    pub answers: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct Reply {
+    questions: Vec<Draft>,
+}
+
+#[derive(Serialize)]
+struct DraftData {
+    question_prefix: String,
+}
+
+#[derive(Serialize)]
+struct DraftSet<'a> {
+    name: &'a str,
+    type_: &'a str,
+    data: DraftData,
+    items: &'a [Draft],
+}
+
+/// Asks the model for `count` questions about the topic. Questions without
+/// answers are dropped and the ids are made unique.
+pub fn generate(endpoint: &Endpoint, topic: &str, count: usize) -> Result<Vec<Draft>> {
+    let message = format!("Write {} questions about: {}", count, topic);
+    let reply = endpoint.chat_json(INSTRUCTIONS, &message)?;
+    let reply = serde_json::from_str::<Reply>(&reply)
+        .with_context(|| format!("the model responded with {:?}", reply))?;
+
+    let mut ids = HashSet::new();
+    let mut drafts = Vec::new();
+    for mut draft in reply.questions {
+        if draft.answers.is_empty() {
+            continue;
+        }
+        let base = slug(if draft.id.is_empty() {
+            &draft.question
+        } else {
+            &draft.id
+        });
+        draft.id = unique_id(&mut ids, &base);
+        draft.question = draft.question.trim_end_matches('?').to_string();
+        drafts.push(draft);
+    }
+    Ok(drafts)
+}
+
+/// Formats the drafts as a question set file of the `default` type.
+pub fn to_yaml(name: &str, drafts: &[Draft]) -> Result<String> {
+    Ok(serde_yaml::to_string(&DraftSet {
+        name,
+        type_: "default",
+        data: DraftData {
+            question_prefix: String::new(),
+        },
+        items: drafts,
+    })?)
+}
+
+/// The base, or the base with a number appended if it's taken, added to the
+/// taken ids.
+pub(crate) fn unique_id(ids: &mut HashSet<String>, base: &str) -> String {
+    let mut id = base.to_string();
+    let mut n = 1;
+    while !ids.insert(id.clone()) {
+        n += 1;
+        id = format!("{}_{}", base, n);
+    }
+    id
+}
+
+pub(crate) fn slug(s: &str) -> String {
+    s.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect::<Vec<&str>>()
+        .join("_")
+}
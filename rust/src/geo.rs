@@ -0,0 +1,310 @@
+use crate::encoding;
+use crate::functionality::{
+    FactoryOptions, QuestionFactory, QuestionKey, QuestionRunner, QuestionSetFactory, Score,
+    SetIndex,
+};
+use crate::hints::Hints;
+use crate::input::is_shortcut;
+use crate::output;
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+/// Mean radius of the earth.
+const EARTH_RADIUS_KM: f64 = 6371.;
+
+fn default_tolerance_km() -> f64 {
+    500.
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct GeoData {
+    #[serde(default)]
+    question_prefix: String,
+    /// How far from the place a guess may be to count as correct. Guesses up
+    /// to three times as far get partial credit.
+    #[serde(default = "default_tolerance_km")]
+    tolerance_km: f64,
+    /// Only ask which quarter of the globe the place is in, e.g. `NE` for
+    /// north of the equator and east of Greenwich.
+    #[serde(default)]
+    quadrant: bool,
+    #[serde(flatten)]
+    options: FactoryOptions,
+    #[serde(skip)]
+    depends: Vec<String>,
+}
+
+impl QuestionFactory for GeoData {
+    fn build(&self, data: &[u8]) -> Result<Box<dyn QuestionRunner>> {
+        let mut question = encoding::decode::<GeoQuestion>(data)?;
+        if !(-90. ..=90.).contains(&question.lat) || !(-180. ..=180.).contains(&question.lon) {
+            bail!("{} isn't on the globe", question.id);
+        }
+        question.tolerance_km = question.tolerance_km.or(Some(self.tolerance_km));
+        question.quadrant = self.quadrant;
+        question.question = format!("{}{}", self.question_prefix, question.question);
+        Ok(Box::new(question) as Box<dyn QuestionRunner>)
+    }
+}
+
+impl QuestionSetFactory for GeoData {
+    fn build_set(&self, s: &SetIndex, set_name: &str) -> Vec<QuestionKey> {
+        s.get_factory(set_name).to_vec()
+    }
+
+    fn depends_on(&self) -> &Vec<String> {
+        &self.depends
+    }
+}
+
+/// A question answered with where a place is, as coordinates such as
+/// `48.86, 2.35` or `48°51'N 2°21'E`, scored by how far the guess is from
+/// it along the surface of the earth.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct GeoQuestion {
+    id: String,
+    question: String,
+    /// Latitude in degrees, negative south of the equator.
+    lat: f64,
+    /// Longitude in degrees, negative west of Greenwich.
+    lon: f64,
+    /// Overrides the set's, e.g. for a country larger than the tolerance.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tolerance_km: Option<f64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    hints: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    /// Questions to know before this one is introduced, see
+    /// `Question::requires`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    requires: Vec<String>,
+    #[serde(skip)]
+    quadrant: bool,
+}
+
+impl GeoQuestion {
+    fn run_quadrant(&self, hints: &mut Hints) -> Result<Score> {
+        let validator = |input: &str| match parse_quadrant(input) {
+            _ if is_shortcut(input) => Ok(()),
+            Ok(_) => Ok(()),
+            Err(err) => Err(format!("{:#}", err)),
+        };
+        let answer = hints.prompt(&self.question, Some(validator))?;
+        let expected = quadrant_name(self.lat >= 0., self.lon >= 0.);
+        let correct = parse_quadrant(&answer)? == (self.lat >= 0., self.lon >= 0.);
+        if correct {
+            output::feedback(output::correct("Correct!"));
+        } else {
+            output::feedback(output::wrong(&format!("Wrong. It's {}", expected)));
+        }
+        Ok(correct.into())
+    }
+
+    fn run_coordinates(&self, hints: &mut Hints) -> Result<Score> {
+        let validator = |input: &str| match parse_coordinates(input) {
+            _ if is_shortcut(input) => Ok(()),
+            Ok(_) => Ok(()),
+            Err(err) => Err(format!("{:#}", err)),
+        };
+        let answer = hints.prompt(&self.question, Some(validator))?;
+        let (lat, lon) = parse_coordinates(&answer)?;
+        let distance = distance_km((lat, lon), (self.lat, self.lon));
+        let tolerance = self.tolerance_km.unwrap_or_else(default_tolerance_km);
+        let score = Score::new(1. - (distance - tolerance).max(0.) / (2. * tolerance));
+        let direction = compass(bearing((lat, lon), (self.lat, self.lon)));
+        if distance <= tolerance {
+            output::feedback(output::correct(&format!("Correct! {:.0} km off", distance)));
+        } else {
+            output::feedback(output::wrong(&format!(
+                "{}. It's at {}, {:.0} km {} of your guess",
+                if score.value() > 0. { "Close" } else { "Wrong" },
+                format_coordinates(self.lat, self.lon),
+                distance,
+                direction
+            )));
+        }
+        Ok(score)
+    }
+}
+
+impl QuestionRunner for GeoQuestion {
+    fn run(&self) -> Result<Score> {
+        let mut hints = Hints::new(&self.hints);
+        let score = match self.quadrant {
+            true => self.run_quadrant(&mut hints)?,
+            false => self.run_coordinates(&mut hints)?,
+        };
+        println!();
+        Ok(hints.score(score))
+    }
+
+    fn name(&self) -> String {
+        self.id.clone()
+    }
+
+    fn question_text(&self) -> String {
+        self.question.clone()
+    }
+
+    fn answer_text(&self) -> String {
+        match self.quadrant {
+            true => quadrant_name(self.lat >= 0., self.lon >= 0.).to_string(),
+            false => format_coordinates(self.lat, self.lon),
+        }
+    }
+}
+
+/// Great-circle distance between two points given as latitude and
+/// longitude in degrees.
+pub fn distance_km(a: (f64, f64), b: (f64, f64)) -> f64 {
+    let (lat1, lat2) = (a.0.to_radians(), b.0.to_radians());
+    let dlat = lat2 - lat1;
+    let dlon = (b.1 - a.1).to_radians();
+    let h = (dlat / 2.).sin().powi(2) + lat1.cos() * lat2.cos() * (dlon / 2.).sin().powi(2);
+    2. * EARTH_RADIUS_KM * h.sqrt().min(1.).asin()
+}
+
+/// Initial bearing from `a` towards `b` in degrees clockwise from north.
+pub fn bearing(a: (f64, f64), b: (f64, f64)) -> f64 {
+    let (lat1, lat2) = (a.0.to_radians(), b.0.to_radians());
+    let dlon = (b.1 - a.1).to_radians();
+    let y = dlon.sin() * lat2.cos();
+    let x = lat1.cos() * lat2.sin() - lat1.sin() * lat2.cos() * dlon.cos();
+    y.atan2(x).to_degrees().rem_euclid(360.)
+}
+
+/// The nearest of the eight compass directions, e.g. `north-east`.
+pub fn compass(bearing: f64) -> &'static str {
+    const DIRECTIONS: [&str; 8] = [
+        "north",
+        "north-east",
+        "east",
+        "south-east",
+        "south",
+        "south-west",
+        "west",
+        "north-west",
+    ];
+    DIRECTIONS[((bearing / 45.).round() as usize) % 8]
+}
+
+pub fn format_coordinates(lat: f64, lon: f64) -> String {
+    format!(
+        "{:.2}°{} {:.2}°{}",
+        lat.abs(),
+        if lat < 0. { 'S' } else { 'N' },
+        lon.abs(),
+        if lon < 0. { 'W' } else { 'E' }
+    )
+}
+
+/// Parses a latitude and longitude, e.g. `48.86, 2.35`, `-33.9 151.2`,
+/// `48.86N 2.35E` or `48°51'N, 2°21'E`. With hemisphere letters they may
+/// come in either order.
+pub fn parse_coordinates(s: &str) -> Result<(f64, f64)> {
+    let parts = match s.contains(',') {
+        true => s.split(',').map(str::trim).collect::<Vec<_>>(),
+        false => split_pair(s.trim()),
+    };
+    let [first, second] = parts[..] else {
+        bail!("expected a latitude and a longitude, e.g. 48.86, 2.35");
+    };
+    let (first, second) = (parse_angle(first)?, parse_angle(second)?);
+    let (lat, lon) = match (first.1, second.1) {
+        (Some(Axis::Lon), _) | (_, Some(Axis::Lat)) => (second.0, first.0),
+        _ => (first.0, second.0),
+    };
+    if !(-90. ..=90.).contains(&lat) {
+        bail!("the latitude must be from -90 to 90");
+    }
+    if !(-180. ..=180.).contains(&lon) {
+        bail!("the longitude must be from -180 to 180");
+    }
+    Ok((lat, lon))
+}
+
+/// Splits coordinates without a comma after the first hemisphere letter,
+/// or else at the whitespace.
+fn split_pair(s: &str) -> Vec<&str> {
+    if let Some(i) = s.find(['N', 'S', 'E', 'W', 'n', 's', 'e', 'w']) {
+        let (first, second) = s.split_at(i + 1);
+        if !second.trim().is_empty() {
+            return vec![first.trim(), second.trim()];
+        }
+    }
+    s.split_whitespace().collect()
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Axis {
+    Lat,
+    Lon,
+}
+
+/// Parses an angle in degrees, optionally with minutes and seconds and a
+/// hemisphere letter, returning which axis the letter says it's on.
+fn parse_angle(s: &str) -> Result<(f64, Option<Axis>)> {
+    let mut s = s.trim();
+    let mut sign = 1.;
+    let mut axis = None;
+    let last = s.chars().last().map(|c| c.to_ascii_uppercase());
+    if let Some(c @ ('N' | 'S' | 'E' | 'W')) = last {
+        s = s[..s.len() - 1].trim_end();
+        axis = Some(if matches!(c, 'N' | 'S') {
+            Axis::Lat
+        } else {
+            Axis::Lon
+        });
+        if matches!(c, 'S' | 'W') {
+            sign = -1.;
+        }
+    }
+    let parts = s
+        .split(['°', '\'', '"', '′', '″', ' '])
+        .filter(|p| !p.is_empty())
+        .map(|p| p.parse::<f64>())
+        .collect::<Result<Vec<_>, _>>()
+        .with_context(|| format!("{:?} isn't an angle", s))?;
+    let angle = match parts[..] {
+        [d] => d,
+        [d, m] => d.signum() * (d.abs() + m / 60.),
+        [d, m, sec] => d.signum() * (d.abs() + m / 60. + sec / 3600.),
+        _ => bail!("{:?} isn't an angle", s),
+    };
+    if axis.is_some() && angle < 0. {
+        bail!("{:?} has both a sign and a hemisphere", s);
+    }
+    Ok((sign * angle, axis))
+}
+
+/// Parses a quarter of the globe, e.g. `NE`, `south west` or `north-west`,
+/// returning whether it's north and whether it's east.
+pub fn parse_quadrant(s: &str) -> Result<(bool, bool)> {
+    let letters = s
+        .chars()
+        .filter(|c| c.is_alphabetic())
+        .collect::<String>()
+        .to_lowercase();
+    let letters = letters
+        .replace("north", "n")
+        .replace("south", "s")
+        .replace("east", "e")
+        .replace("west", "w");
+    match letters.as_str() {
+        "ne" => Ok((true, true)),
+        "nw" => Ok((true, false)),
+        "se" => Ok((false, true)),
+        "sw" => Ok((false, false)),
+        _ => bail!("expected NE, NW, SE or SW"),
+    }
+}
+
+fn quadrant_name(north: bool, east: bool) -> &'static str {
+    match (north, east) {
+        (true, true) => "NE",
+        (true, false) => "NW",
+        (false, true) => "SE",
+        (false, false) => "SW",
+    }
+}
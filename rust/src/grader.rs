@@ -0,0 +1,55 @@
+use crate::llm::Endpoint;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+const INSTRUCTIONS: &str = "You grade answers to quiz questions. The answer \
+    doesn't have to match the expected answer word for word, but it has to mean \
+    the same thing. Respond with a JSON object with the fields \"correct\" (a \
+    boolean) and \"explanation\" (one or two sentences addressed to the person \
+    who answered).";
+
+/// The outcome of grading a free text answer.
+#[derive(Deserialize, Debug, Clone)]
+pub struct Verdict {
+    pub correct: bool,
+    /// Why the answer was accepted or not, shown to the user.
+    pub explanation: String,
+}
+
+/// Judges answers that can't be checked by exact matching, e.g. a definition
+/// given in your own words.
+pub trait Grader {
+    fn grade(&self, question: &str, expected: &str, answer: &str) -> Result<Verdict>;
+}
+
+/// Grader settings of a set, e.g. `grader: {backend: ollama, model: llama3}`.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct GraderConfig {
+    #[serde(flatten)]
+    endpoint: Endpoint,
+}
+
+impl GraderConfig {
+    pub fn grader(&self) -> Box<dyn Grader> {
+        Box::new(LlmGrader {
+            endpoint: self.endpoint.clone(),
+        })
+    }
+}
+
+/// Asks a language model whether the answer means the same as the expected one.
+pub struct LlmGrader {
+    endpoint: Endpoint,
+}
+
+impl Grader for LlmGrader {
+    fn grade(&self, question: &str, expected: &str, answer: &str) -> Result<Verdict> {
+        let message = format!(
+            "Question: {}\nExpected answer: {}\nGiven answer: {}",
+            question, expected, answer
+        );
+        let reply = self.endpoint.chat_json(INSTRUCTIONS, &message)?;
+        serde_json::from_str(&reply)
+            .with_context(|| format!("the grader responded with {:?}", reply))
+    }
+}
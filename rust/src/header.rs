@@ -0,0 +1,51 @@
+use anyhow::{bail, Result};
+use std::collections::HashMap;
+
+/// The header shown above every question unless the config file sets
+/// `header`.
+pub const DEFAULT_TEMPLATE: &str = "prob: {probability}, last answered: {last_answered}";
+
+/// The fields a header template can show as `{name}`.
+pub const FIELDS: [&str; 10] = [
+    "name",
+    "set",
+    "probability",
+    "last_answered",
+    "streak",
+    "box",
+    "due",
+    "tags",
+    "correct",
+    "incorrect",
+];
+
+/// Fills in the `{field}` placeholders of the template. An empty template
+/// hides the header.
+pub fn render(template: &str, values: &HashMap<&str, String>) -> Result<String> {
+    let mut out = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('}') else {
+            bail!("unclosed '{{' in header template {:?}", template);
+        };
+        let field = &rest[start + 1..start + end];
+        match values.get(field) {
+            Some(value) => out.push_str(value),
+            None => bail!(
+                "unknown field {:?} in header template, expected one of {}",
+                field,
+                FIELDS.join(", ")
+            ),
+        }
+        rest = &rest[start + end + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Checks that the template only uses known fields.
+pub fn validate(template: &str) -> Result<()> {
+    let values = FIELDS.iter().map(|&f| (f, String::new())).collect();
+    render(template, &values).map(|_| ())
+}
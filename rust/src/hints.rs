@@ -0,0 +1,134 @@
+use crate::frontend::{self, Validator};
+use crate::functionality::Score;
+use crate::input::{self, Shortcut};
+use crate::output;
+use anyhow::Result;
+use core::fmt;
+
+/// Credit lost per hint shown.
+const HINT_PENALTY: f64 = 0.15;
+/// An answer that needed hints is still correct, just not more than barely.
+const MIN_HINTED_SCORE: f64 = 0.6;
+
+/// Tracks the hints revealed while a question is being answered.
+pub struct Hints<'a> {
+    hints: &'a [String],
+    shown: usize,
+    mask: Option<Mask>,
+    editor: bool,
+}
+
+impl<'a> Hints<'a> {
+    pub fn new(hints: &'a [String]) -> Hints<'a> {
+        Hints {
+            hints,
+            shown: 0,
+            mask: None,
+            editor: false,
+        }
+    }
+
+    /// Shows the answer masked, e.g. `a____ e____`, and reveals one more
+    /// letter of it for every hint requested after the written hints run out.
+    pub fn with_mask(mut self, answer: &str) -> Hints<'a> {
+        self.mask = Some(Mask::new(answer));
+        self
+    }
+
+    /// Asks for the answer in an editor, for answers spanning several lines.
+    /// Validators don't apply there.
+    pub fn with_editor(mut self, editor: bool) -> Hints<'a> {
+        self.editor = editor;
+        self
+    }
+
+    /// Prompts until something other than a hint request is answered,
+    /// revealing the next hint each time one is requested. Other shortcuts
+    /// are returned as the error, see `input::interrupted`.
+    pub fn prompt(&mut self, message: &str, validator: Option<Validator>) -> Result<String> {
+        let frontend = frontend::current();
+        if let Some(mask) = &self.mask {
+            println!("{} {}", output::label("Answer:"), mask);
+        }
+        loop {
+            let answer = if self.editor {
+                frontend.editor(message, Some(input::HELP))?
+            } else {
+                frontend.text(message, Some(input::HELP), validator)?
+            };
+            match Shortcut::parse(&answer) {
+                None => {
+                    input::record_text(&answer);
+                    return Ok(answer);
+                }
+                Some(Shortcut::Hint) => {}
+                Some(shortcut) => return Err(shortcut.into()),
+            }
+            if let Some(hint) = self.hints.get(self.shown) {
+                self.shown += 1;
+                println!(
+                    "{} {}",
+                    output::label(&format!("Hint {}:", self.shown)),
+                    hint
+                );
+            } else if let Some(mask) = self.mask.as_mut() {
+                if mask.reveal_next() {
+                    self.shown += 1;
+                    println!("{} {}", output::label("Answer:"), mask);
+                } else {
+                    println!("No more hints");
+                }
+            } else {
+                println!("No more hints");
+            }
+        }
+    }
+
+    /// Reduces the score of a correct answer by the hints that were needed.
+    pub fn score(&self, score: Score) -> Score {
+        if self.shown == 0 || !score.is_correct() {
+            return score;
+        }
+        let penalized = score.value() - HINT_PENALTY * self.shown as f64;
+        Score::new(penalized.max(MIN_HINTED_SCORE.min(score.value())))
+    }
+}
+
+/// An answer with some of its letters hidden.
+struct Mask {
+    chars: Vec<char>,
+    revealed: Vec<bool>,
+}
+
+impl Mask {
+    /// Starts out with the first letter of every word revealed.
+    fn new(answer: &str) -> Mask {
+        let chars = answer.chars().collect::<Vec<char>>();
+        let revealed = chars
+            .iter()
+            .enumerate()
+            .map(|(i, c)| !c.is_alphanumeric() || i == 0 || !chars[i - 1].is_alphanumeric())
+            .collect();
+        Mask { chars, revealed }
+    }
+
+    /// Reveals the first hidden letter. Returns false if there was none.
+    fn reveal_next(&mut self) -> bool {
+        match self.revealed.iter().position(|r| !r) {
+            Some(i) => {
+                self.revealed[i] = true;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl fmt::Display for Mask {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (c, &revealed) in self.chars.iter().zip(&self.revealed) {
+            write!(f, "{}", if revealed { *c } else { '_' })?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,107 @@
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Days of practice in a row that fire `Event::StreakMilestone`.
+pub const STREAK_MILESTONES: [usize; 8] = [3, 7, 14, 30, 50, 100, 200, 365];
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Event {
+    /// A session of `run` ended with answers recorded.
+    SessionCompleted,
+    /// The days of practice in a row reached one of `STREAK_MILESTONES`.
+    StreakMilestone,
+}
+
+/// Something to run when an event happens, set in the config file, e.g.
+///
+/// ```yaml
+/// hooks:
+/// - on: session_completed
+///   command: habit-tracker log trivia
+/// - on: streak_milestone
+///   url: https://example.com/trivia
+/// ```
+///
+/// A command gets the payload as JSON on stdin, a URL gets it POSTed.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Hook {
+    pub on: Event,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+/// What a hook is told about the event.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Payload {
+    SessionCompleted {
+        set: String,
+        answers: usize,
+        correct: usize,
+        seconds: u64,
+        time: DateTime<Utc>,
+    },
+    StreakMilestone {
+        days: usize,
+        time: DateTime<Utc>,
+    },
+}
+
+impl Payload {
+    pub fn event(&self) -> Event {
+        match self {
+            Payload::SessionCompleted { .. } => Event::SessionCompleted,
+            Payload::StreakMilestone { .. } => Event::StreakMilestone,
+        }
+    }
+}
+
+impl Hook {
+    /// Checks that the hook has either a command or a URL.
+    pub fn validate(&self) -> Result<()> {
+        match (&self.command, &self.url) {
+            (Some(_), None) | (None, Some(_)) => Ok(()),
+            _ => bail!("a hook needs either a command or a url"),
+        }
+    }
+
+    fn fire(&self, payload: &Payload) -> Result<()> {
+        let json = serde_json::to_string(payload)?;
+        if let Some(command) = &self.command {
+            let mut child = Command::new("sh")
+                .arg("-c")
+                .arg(command)
+                .stdin(Stdio::piped())
+                .spawn()
+                .with_context(|| format!("running {:?}", command))?;
+            child.stdin.take().unwrap().write_all(json.as_bytes())?;
+            let status = child.wait()?;
+            if !status.success() {
+                bail!("{:?} exited with {}", command, status);
+            }
+        }
+        if let Some(url) = &self.url {
+            ureq::post(url)
+                .set("Content-Type", "application/json")
+                .send_string(&json)
+                .with_context(|| format!("posting to {}", url))?;
+        }
+        Ok(())
+    }
+}
+
+/// Runs the hooks for the payload's event, returning what went wrong, so a
+/// broken hook doesn't end the session.
+pub fn fire(hooks: &[Hook], payload: &Payload) -> Vec<anyhow::Error> {
+    hooks
+        .iter()
+        .filter(|h| h.on == payload.event())
+        .filter_map(|h| h.fire(payload).err())
+        .collect()
+}
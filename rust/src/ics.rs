@@ -0,0 +1,72 @@
+use chrono::{DateTime, Days, NaiveDate, Utc};
+
+/// Reviews of a set due on a day, an all-day event in the calendar.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DueReviews {
+    pub set: String,
+    pub day: NaiveDate,
+    pub count: usize,
+}
+
+/// Formats the reviews as an iCalendar file, with a reminder at 9 in the
+/// morning of every day. The events keep their ids between exports, so a
+/// calendar subscribed to the file updates them rather than adding copies.
+pub fn calendar(reviews: &[DueReviews], stamp: DateTime<Utc>) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//trivial//forecast//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+    ];
+    for r in reviews {
+        let summary = format!(
+            "{}: {} {} due",
+            r.set,
+            r.count,
+            if r.count == 1 { "review" } else { "reviews" }
+        );
+        lines.extend([
+            "BEGIN:VEVENT".to_string(),
+            format!("UID:{}-{}@trivial", r.day.format("%Y%m%d"), escape(&r.set)),
+            format!("DTSTAMP:{}", stamp.format("%Y%m%dT%H%M%SZ")),
+            format!("DTSTART;VALUE=DATE:{}", r.day.format("%Y%m%d")),
+            format!(
+                "DTEND;VALUE=DATE:{}",
+                (r.day + Days::new(1)).format("%Y%m%d")
+            ),
+            format!("SUMMARY:{}", escape(&summary)),
+            "TRANSP:TRANSPARENT".to_string(),
+            "BEGIN:VALARM".to_string(),
+            "ACTION:DISPLAY".to_string(),
+            format!("DESCRIPTION:{}", escape(&summary)),
+            "TRIGGER:PT9H".to_string(),
+            "END:VALARM".to_string(),
+            "END:VEVENT".to_string(),
+        ]);
+    }
+    lines.push("END:VCALENDAR".to_string());
+    lines.iter().map(|l| fold(l) + "\r\n").collect()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Breaks lines longer than 75 bytes, continuing them on lines starting
+/// with a space.
+fn fold(line: &str) -> String {
+    let mut out = String::new();
+    let mut len = 0;
+    for c in line.chars() {
+        if len + c.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            len = 1;
+        }
+        out.push(c);
+        len += c.len_utf8();
+    }
+    out
+}
@@ -0,0 +1,91 @@
+use crate::db::AnswerDetails;
+use core::fmt;
+use std::cell::RefCell;
+
+/// Commands typed instead of an answer. Hints are handled while prompting,
+/// the others interrupt the question: runners return them as their error so
+/// the session can act on them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shortcut {
+    /// End the session.
+    Quit,
+    /// Move on without answering.
+    Skip,
+    /// Open the question's set file in an editor.
+    Edit,
+    /// Show the next hint, also `?`.
+    Hint,
+    /// Take back the previous answer and ask its question again.
+    Undo,
+}
+
+/// Help shown under prompts that accept shortcuts.
+pub const HELP: &str = ":h hint, :s skip, :u undo, :e edit, :q quit";
+
+impl Shortcut {
+    pub fn parse(input: &str) -> Option<Shortcut> {
+        match input.trim() {
+            ":q" => Some(Shortcut::Quit),
+            ":s" => Some(Shortcut::Skip),
+            ":e" => Some(Shortcut::Edit),
+            ":h" | "?" => Some(Shortcut::Hint),
+            ":u" => Some(Shortcut::Undo),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Shortcut {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Shortcut::Quit => write!(f, "quit"),
+            Shortcut::Skip => write!(f, "skip"),
+            Shortcut::Edit => write!(f, "edit"),
+            Shortcut::Hint => write!(f, "hint"),
+            Shortcut::Undo => write!(f, "undo"),
+        }
+    }
+}
+
+impl std::error::Error for Shortcut {}
+
+/// Lets a validator accept shortcuts regardless of the answer format.
+pub fn is_shortcut(input: &str) -> bool {
+    Shortcut::parse(input).is_some()
+}
+
+/// The shortcut a runner was interrupted with, if that is what the error is.
+pub fn interrupted(err: &anyhow::Error) -> Option<Shortcut> {
+    err.downcast_ref::<Shortcut>().copied()
+}
+
+thread_local! {
+    static DETAILS: RefCell<AnswerDetails> = RefCell::new(AnswerDetails::default());
+}
+
+/// Records what was typed as the answer being given.
+pub fn record_text(text: &str) {
+    DETAILS.with(|d| d.borrow_mut().text = Some(text.to_string()));
+}
+
+/// Records the relative error of a numeric guess for the answer being given.
+pub fn record_error(error: f64) {
+    DETAILS.with(|d| d.borrow_mut().error = Some(error));
+}
+
+/// Records how far off the numeric guess being given was allowed to be.
+pub fn record_tolerance(tolerance: f64) {
+    DETAILS.with(|d| d.borrow_mut().tolerance = Some(tolerance));
+}
+
+/// Records how the steps of a sequence went for the answer being given.
+pub fn record_steps(steps: &str) {
+    DETAILS.with(|d| d.borrow_mut().steps = Some(steps.to_string()));
+}
+
+/// Returns what was recorded while the last question was answered and
+/// starts over for the next one. Call it after every run, so nothing
+/// carries over to a question that doesn't record anything.
+pub fn take_details() -> AnswerDetails {
+    DETAILS.with(|d| d.take())
+}
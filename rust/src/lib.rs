@@ -1,2 +1,58 @@
+pub mod aging;
+pub mod arithmetic;
+pub mod assets;
+pub mod autosave;
+pub mod bulk;
+pub mod codeeval;
+pub mod compress;
+pub mod config;
+pub mod confusion;
 pub mod db;
+pub mod dedupe;
+pub mod depgraph;
+pub mod encoding;
+pub mod enrich;
+pub mod expr;
+pub mod flashcard;
+pub mod frontend;
 pub mod functionality;
+pub mod generate;
+pub mod geo;
+pub mod grader;
+pub mod header;
+pub mod hints;
+pub mod hooks;
+pub mod ics;
+pub mod input;
+pub mod llm;
+pub mod logging;
+pub mod markdown;
+pub mod math;
+pub mod metrics;
+pub mod model;
+pub mod normalize;
+pub mod notify;
+pub mod numeric;
+pub mod output;
+pub mod pack;
+pub mod plugins;
+pub mod profile;
+pub mod progress;
+pub mod ranking;
+pub mod records;
+pub mod remote;
+pub mod rename;
+pub mod retention;
+pub mod room;
+pub mod scaffold;
+pub mod script;
+pub mod sequence;
+pub mod spacing;
+pub mod storage;
+pub mod sync;
+pub mod testing;
+pub mod timing;
+pub mod tts;
+pub mod typing;
+pub mod versus;
+pub mod worksheet;
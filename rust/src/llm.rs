@@ -0,0 +1,82 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+    /// The OpenAI chat completions API, or anything compatible with it. The
+    /// key is read from `OPENAI_API_KEY`.
+    #[value(name = "openai")]
+    OpenAI,
+    Ollama,
+}
+
+/// A language model to talk to, e.g. `{backend: ollama, model: llama3}`.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Endpoint {
+    pub backend: Backend,
+    pub model: String,
+    /// Base URL of the API, defaults to the backend's public or local one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+impl Endpoint {
+    /// Sends the instructions and the message and returns the reply, which the
+    /// model is asked to format as a JSON object.
+    #[cfg(feature = "llm")]
+    pub fn chat_json(&self, instructions: &str, message: &str) -> Result<String> {
+        use anyhow::Context;
+        use serde_json::{json, Value};
+
+        let messages = json!([
+            {"role": "system", "content": instructions},
+            {"role": "user", "content": message},
+        ]);
+        let (request, body) = match self.backend {
+            Backend::OpenAI => {
+                let url = self.url.as_deref().unwrap_or("https://api.openai.com/v1");
+                let key = std::env::var("OPENAI_API_KEY").context("OPENAI_API_KEY is not set")?;
+                (
+                    ureq::post(&format!("{}/chat/completions", url.trim_end_matches('/')))
+                        .set("Authorization", &format!("Bearer {}", key)),
+                    json!({
+                        "model": self.model,
+                        "messages": messages,
+                        "response_format": {"type": "json_object"},
+                    }),
+                )
+            }
+            Backend::Ollama => {
+                let url = self.url.as_deref().unwrap_or("http://localhost:11434");
+                (
+                    ureq::post(&format!("{}/api/chat", url.trim_end_matches('/'))),
+                    json!({
+                        "model": self.model,
+                        "messages": messages,
+                        "format": "json",
+                        "stream": false,
+                    }),
+                )
+            }
+        };
+        let response = request
+            .set("Content-Type", "application/json")
+            .send_string(&body.to_string())?
+            .into_string()?;
+        let response = serde_json::from_str::<Value>(&response)?;
+        let content = match self.backend {
+            Backend::OpenAI => &response["choices"][0]["message"]["content"],
+            Backend::Ollama => &response["message"]["content"],
+        };
+        content
+            .as_str()
+            .map(String::from)
+            .context("the response has no message")
+    }
+
+    #[cfg(not(feature = "llm"))]
+    pub fn chat_json(&self, _instructions: &str, _message: &str) -> Result<String> {
+        anyhow::bail!("using {:?} needs the llm feature", self.backend)
+    }
+}
@@ -0,0 +1,31 @@
+use clap::Args;
+use tracing::Level;
+use tracing_subscriber::fmt::format::FmtSpan;
+
+/// Logging flags shared by the binaries, flattened into their arguments.
+#[derive(Args, Debug, Clone)]
+pub struct LogArgs {
+    /// Most detailed level to log to stderr: error, warn, info, debug or trace.
+    /// Every database query is logged with its duration at debug
+    #[arg(long, global = true, default_value_t = Level::WARN)]
+    log_level: Level,
+    /// Same as --log-level debug
+    #[arg(short, long, global = true)]
+    verbose: bool,
+}
+
+impl LogArgs {
+    pub fn init(&self) {
+        let level = if self.verbose {
+            self.log_level.max(Level::DEBUG)
+        } else {
+            self.log_level
+        };
+        tracing_subscriber::fmt()
+            .with_max_level(level)
+            // Closing spans report how long instrumented calls took.
+            .with_span_events(FmtSpan::CLOSE)
+            .with_writer(std::io::stderr)
+            .init();
+    }
+}
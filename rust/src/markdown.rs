@@ -0,0 +1,87 @@
+use crate::generate::{slug, unique_id, Draft};
+use anyhow::{bail, Result};
+use std::collections::HashSet;
+
+/// The questions under one heading of a Markdown file.
+#[derive(Debug, Clone)]
+pub struct Deck {
+    /// The heading as a set name, e.g. `european_capitals`.
+    pub name: String,
+    pub questions: Vec<Draft>,
+}
+
+/// Reads questions from Markdown notes. Every heading starts a set, and a
+/// question is either a `Q:` line followed by one `A:` line per accepted
+/// answer, or a table row with the question in the first column and the
+/// accepted answers in the others. The header row of a table is skipped.
+/// Questions before the first heading go in a set named `default_name`.
+/// Everything else is ignored, so the notes can have prose in between.
+pub fn parse(text: &str, default_name: &str) -> Result<Vec<Deck>> {
+    let mut decks = vec![Deck {
+        name: slug(default_name),
+        questions: Vec::new(),
+    }];
+    let mut ids = HashSet::new();
+    // Whether the next table row is a header.
+    let mut header = true;
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        let deck = decks.last_mut().unwrap();
+        if let Some(heading) = line.strip_prefix('#') {
+            decks.push(Deck {
+                name: slug(heading.trim_start_matches('#')),
+                questions: Vec::new(),
+            });
+            ids.clear();
+        } else if let Some(question) = strip_label(line, "Q:") {
+            deck.questions.push(Draft {
+                id: unique_id(&mut ids, &slug(question)),
+                question: question.trim_end_matches('?').to_string(),
+                answers: Vec::new(),
+            });
+        } else if let Some(answer) = strip_label(line, "A:") {
+            let Some(draft) = deck.questions.last_mut() else {
+                bail!("line {}: an answer without a question", i + 1);
+            };
+            draft.answers.push(answer.to_string());
+        } else if line.starts_with('|') {
+            let cells = line
+                .trim_matches('|')
+                .split('|')
+                .map(str::trim)
+                .collect::<Vec<_>>();
+            let separator = cells
+                .iter()
+                .all(|c| !c.is_empty() && c.chars().all(|c| matches!(c, '-' | ':')));
+            if separator || std::mem::replace(&mut header, false) {
+                continue;
+            }
+            let (question, answers) = cells.split_first().unwrap();
+            deck.questions.push(Draft {
+                id: unique_id(&mut ids, &slug(question)),
+                question: question.trim_end_matches('?').to_string(),
+                answers: answers
+                    .iter()
+                    .filter(|a| !a.is_empty())
+                    .map(|a| a.to_string())
+                    .collect(),
+            });
+        }
+        if !line.starts_with('|') {
+            header = true;
+        }
+    }
+
+    for deck in &decks {
+        if let Some(q) = deck.questions.iter().find(|q| q.answers.is_empty()) {
+            bail!("{}: {:?} has no answer", deck.name, q.question);
+        }
+    }
+    decks.retain(|d| !d.questions.is_empty());
+    Ok(decks)
+}
+
+fn strip_label<'a>(line: &'a str, label: &str) -> Option<&'a str> {
+    let rest = line.strip_prefix(label)?.trim();
+    (!rest.is_empty()).then_some(rest)
+}
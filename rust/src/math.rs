@@ -0,0 +1,104 @@
+use crate::encoding;
+use crate::expr;
+use crate::functionality::{
+    FactoryOptions, QuestionFactory, QuestionKey, QuestionRunner, QuestionSetFactory, Score,
+    SetIndex,
+};
+use crate::hints::Hints;
+use crate::input::is_shortcut;
+use crate::output;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+fn default_tolerance() -> f64 {
+    1e-9
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct MathData {
+    #[serde(default)]
+    question_prefix: String,
+    /// Relative difference allowed between the answer and the expected value.
+    #[serde(default = "default_tolerance")]
+    tolerance: f64,
+    #[serde(flatten)]
+    options: FactoryOptions,
+    #[serde(skip)]
+    depends: Vec<String>,
+}
+
+impl QuestionFactory for MathData {
+    fn build(&self, data: &[u8]) -> Result<Box<dyn QuestionRunner>> {
+        let mut question = encoding::decode::<MathQuestion>(data)?;
+        question.tolerance = self.tolerance;
+        question.question = format!("{}{}", self.question_prefix, question.question);
+        Ok(Box::new(question) as Box<dyn QuestionRunner>)
+    }
+}
+
+impl QuestionSetFactory for MathData {
+    fn build_set(&self, s: &SetIndex, set_name: &str) -> Vec<QuestionKey> {
+        s.get_factory(set_name).to_vec()
+    }
+
+    fn depends_on(&self) -> &Vec<String> {
+        &self.depends
+    }
+}
+
+/// A question whose answer is an expression. Any answer evaluating to the
+/// same value is accepted, so `3/4` and `0.75` are equivalent.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct MathQuestion {
+    id: String,
+    question: String,
+    answer: String,
+    #[serde(default = "default_tolerance")]
+    tolerance: f64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    hints: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    /// Questions to know before this one is introduced, see
+    /// `Question::requires`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    requires: Vec<String>,
+}
+
+impl QuestionRunner for MathQuestion {
+    fn run(&self) -> Result<Score> {
+        let expected = expr::eval(&self.answer)?;
+        let validator = |input: &str| match expr::eval(input) {
+            _ if is_shortcut(input) => Ok(()),
+            Ok(_) => Ok(()),
+            Err(err) => Err(format!("{:#}", err)),
+        };
+
+        let mut hints = Hints::new(&self.hints);
+        let answer = hints.prompt(&self.question, Some(validator))?;
+        let value = expr::eval(&answer)?;
+        let correct = (value - expected).abs() <= self.tolerance * expected.abs().max(1.);
+        if correct {
+            output::feedback(output::correct("Correct!"));
+        } else {
+            output::feedback(output::wrong(&format!(
+                "Wrong. The answer is {} = {}",
+                self.answer, expected
+            )));
+        }
+        println!();
+        Ok(hints.score(correct.into()))
+    }
+
+    fn name(&self) -> String {
+        self.id.clone()
+    }
+
+    fn question_text(&self) -> String {
+        self.question.clone()
+    }
+
+    fn answer_text(&self) -> String {
+        self.answer.clone()
+    }
+}
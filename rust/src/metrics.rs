@@ -0,0 +1,115 @@
+use crate::functionality::Service;
+use crate::storage::Storage;
+use anyhow::Result;
+use chrono::Utc;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// Study activity per set in the Prometheus text format, served on
+/// `/metrics` by `trivial serve`. Archived sets are left out.
+pub async fn render(repo: &dyn Storage) -> Result<String> {
+    let service = Service::new(repo).await?;
+    let mut answers = HashMap::<i64, (u64, u64)>::new();
+    for a in repo.get_all_answers().await? {
+        let entry = answers.entry(a.question_id).or_default();
+        entry.0 += 1;
+        entry.1 += a.correct as u64;
+    }
+    let mut names = service.get_sets();
+    names.sort();
+
+    let now = Utc::now();
+    let mut metrics = Metrics::default();
+    for set in names {
+        let ids = service.get_set(set);
+        let (total, correct) = ids
+            .iter()
+            .filter_map(|id| answers.get(id))
+            .fold((0, 0), |(t, c), (at, ac)| (t + at, c + ac));
+        let due = ids
+            .iter()
+            .filter(|&&id| service.due_date(id).is_some_and(|d| d <= now))
+            .count();
+        let probability = ids
+            .iter()
+            .map(|&id| service.get(id).probability)
+            .sum::<f64>()
+            / ids.len().max(1) as f64;
+        metrics.add(
+            "answers_total",
+            "counter",
+            "Answers given",
+            set,
+            total as f64,
+        );
+        metrics.add(
+            "correct_answers_total",
+            "counter",
+            "Answers given that were correct",
+            set,
+            correct as f64,
+        );
+        if total > 0 {
+            metrics.add(
+                "accuracy",
+                "gauge",
+                "Share of the answers that were correct",
+                set,
+                correct as f64 / total as f64,
+            );
+        }
+        metrics.add("questions", "gauge", "Questions", set, ids.len() as f64);
+        metrics.add(
+            "due_questions",
+            "gauge",
+            "Questions due for review",
+            set,
+            due as f64,
+        );
+        metrics.add(
+            "mean_probability",
+            "gauge",
+            "Mean probability of answering a question correctly",
+            set,
+            probability,
+        );
+    }
+    Ok(metrics.finish())
+}
+
+/// Samples grouped by metric, since the format wants every metric's samples
+/// together under its help and type lines.
+#[derive(Default)]
+struct Metrics {
+    order: Vec<&'static str>,
+    metrics: HashMap<&'static str, (&'static str, &'static str, String)>,
+}
+
+impl Metrics {
+    fn add(
+        &mut self,
+        name: &'static str,
+        kind: &'static str,
+        help: &'static str,
+        set: &str,
+        value: f64,
+    ) {
+        let (_, _, samples) = self.metrics.entry(name).or_insert_with(|| {
+            self.order.push(name);
+            (kind, help, String::new())
+        });
+        let set = set.replace('\\', "\\\\").replace('"', "\\\"");
+        writeln!(samples, "trivial_{}{{set=\"{}\"}} {}", name, set, value).unwrap();
+    }
+
+    fn finish(self) -> String {
+        let mut out = String::new();
+        for name in self.order {
+            let (kind, help, samples) = &self.metrics[name];
+            writeln!(out, "# HELP trivial_{} {}", name, help).unwrap();
+            writeln!(out, "# TYPE trivial_{} {}", name, kind).unwrap();
+            out.push_str(samples);
+        }
+        out
+    }
+}
@@ -0,0 +1,225 @@
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::OnceLock;
+
+/// How the probability of answering a question correctly is estimated.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelKind {
+    /// An average of the scores, weighting recent ones the most.
+    #[default]
+    Weighted,
+    /// Bayesian Knowledge Tracing: the question is either known or not,
+    /// answers are evidence of which, and every answer may teach it.
+    Bkt,
+}
+
+impl ModelKind {
+    pub const ALL: [ModelKind; 2] = [ModelKind::Weighted, ModelKind::Bkt];
+}
+
+impl fmt::Display for ModelKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ModelKind::Weighted => write!(f, "weighted"),
+            ModelKind::Bkt => write!(f, "bkt"),
+        }
+    }
+}
+
+/// Parameters of the model estimating how likely a question is answered
+/// correctly, see `functionality::ProbabilityComputer`. Every answer records
+/// the ones in effect, so experiments with them can be told apart later.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct ModelParams {
+    pub kind: ModelKind,
+    /// How much an answer counts compared to the one after it.
+    pub decay: f64,
+    /// Correct answers assumed before the first one.
+    pub prior_correct: f64,
+    /// Answers assumed before the first one, a new question starts at
+    /// `prior_correct / prior_total`.
+    pub prior_total: f64,
+    /// Weighted random selection picks a question with weight
+    /// `(1 - probability + selection_floor) ^ selection_exponent`.
+    pub selection_exponent: f64,
+    /// Keeps questions that are known perfectly from never being picked.
+    pub selection_floor: f64,
+    /// For `ModelKind::Bkt`, how likely a question is known before the first
+    /// answer.
+    pub known: f64,
+    /// For `ModelKind::Bkt`, how likely answering a question that isn't
+    /// known teaches it.
+    pub learn: f64,
+    /// For `ModelKind::Bkt`, how likely a known question is answered wrong.
+    pub slip: f64,
+    /// For `ModelKind::Bkt`, how likely a question that isn't known is
+    /// answered correctly.
+    pub guess: f64,
+}
+
+impl Default for ModelParams {
+    fn default() -> Self {
+        ModelParams {
+            kind: ModelKind::Weighted,
+            decay: 0.9,
+            prior_correct: 1.,
+            prior_total: 2.,
+            selection_exponent: 1.5,
+            selection_floor: 0.05,
+            known: 0.3,
+            learn: 0.15,
+            slip: 0.1,
+            guess: 0.1,
+        }
+    }
+}
+
+impl ModelParams {
+    /// How likely the question is picked by weighted random selection,
+    /// relative to the others.
+    pub fn selection_weight(&self, probability: f64) -> f64 {
+        (1. - probability + self.selection_floor).powf(self.selection_exponent)
+    }
+
+    pub fn check(&self) -> Result<()> {
+        if !(self.decay > 0. && self.decay <= 1.) {
+            bail!("decay must be above 0 and at most 1, not {}", self.decay);
+        }
+        if !(self.prior_correct >= 0. && self.prior_total > self.prior_correct) {
+            bail!(
+                "prior_correct must be at least 0 and below prior_total, not {} and {}",
+                self.prior_correct,
+                self.prior_total
+            );
+        }
+        if !(self.selection_exponent > 0. && self.selection_floor > 0.) {
+            bail!("selection_exponent and selection_floor must be above 0");
+        }
+        for (name, value) in [
+            ("known", self.known),
+            ("learn", self.learn),
+            ("slip", self.slip),
+            ("guess", self.guess),
+        ] {
+            if !(0. ..=1.).contains(&value) {
+                bail!("{} must be from 0 to 1, not {}", name, value);
+            }
+        }
+        if self.slip + self.guess >= 1. {
+            bail!("slip and guess must add up to less than 1, or answers say nothing");
+        }
+        Ok(())
+    }
+
+    /// For `ModelKind::Bkt`, how likely the question is known after an
+    /// answer with the score, from how likely it was known before. Partial
+    /// scores count as that much of a correct answer.
+    pub fn bkt_update(&self, known: f64, score: f64) -> f64 {
+        let right = known * (1. - self.slip);
+        let right = right / (right + (1. - known) * self.guess);
+        let wrong = known * self.slip;
+        let wrong = wrong / (wrong + (1. - known) * (1. - self.guess));
+        let known = score * right + (1. - score) * wrong;
+        known + (1. - known) * self.learn
+    }
+
+    /// For `ModelKind::Bkt`, how likely the question is answered correctly.
+    pub fn bkt_probability(&self, known: f64) -> f64 {
+        known * (1. - self.slip) + (1. - known) * self.guess
+    }
+
+    /// The parameters as stored with answers.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+}
+
+/// Parameters changed from the ones they're applied to, as given in the
+/// config file or in the `data` of a question set, e.g.
+///
+/// ```yaml
+/// model:
+///   decay: 0.8
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub struct ModelOverrides {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<ModelKind>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decay: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prior_correct: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prior_total: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selection_exponent: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selection_floor: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub known: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub learn: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slip: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub guess: Option<f64>,
+}
+
+impl ModelOverrides {
+    pub fn apply(&self, params: ModelParams) -> Result<ModelParams> {
+        let params = ModelParams {
+            kind: self.kind.unwrap_or(params.kind),
+            decay: self.decay.unwrap_or(params.decay),
+            prior_correct: self.prior_correct.unwrap_or(params.prior_correct),
+            prior_total: self.prior_total.unwrap_or(params.prior_total),
+            selection_exponent: self.selection_exponent.unwrap_or(params.selection_exponent),
+            selection_floor: self.selection_floor.unwrap_or(params.selection_floor),
+            known: self.known.unwrap_or(params.known),
+            learn: self.learn.unwrap_or(params.learn),
+            slip: self.slip.unwrap_or(params.slip),
+            guess: self.guess.unwrap_or(params.guess),
+        };
+        params.check()?;
+        Ok(params)
+    }
+}
+
+/// How well a model predicted answers, each predicted from the ones before
+/// it. Lower is better for both scores.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Evaluation {
+    pub answers: usize,
+    /// Mean squared difference between the probability and the score.
+    pub brier: f64,
+    /// Mean negative log likelihood of the scores.
+    pub log_loss: f64,
+}
+
+impl Evaluation {
+    /// Adds an answer with the score that was predicted with the
+    /// probability.
+    pub fn add(&mut self, probability: f64, score: f64) {
+        let p = probability.clamp(1e-6, 1. - 1e-6);
+        let n = self.answers as f64;
+        let log_loss = -(score * p.ln() + (1. - score) * (1. - p).ln());
+        self.brier = (self.brier * n + (p - score).powi(2)) / (n + 1.);
+        self.log_loss = (self.log_loss * n + log_loss) / (n + 1.);
+        self.answers += 1;
+    }
+}
+
+static DEFAULTS: OnceLock<ModelParams> = OnceLock::new();
+
+/// Sets the parameters of sets that don't override them for the rest of the
+/// program, from the config file.
+pub fn init(overrides: &ModelOverrides) -> Result<()> {
+    let _ = DEFAULTS.set(overrides.apply(ModelParams::default())?);
+    Ok(())
+}
+
+/// The parameters of sets that don't override them.
+pub fn defaults() -> ModelParams {
+    *DEFAULTS.get_or_init(ModelParams::default)
+}
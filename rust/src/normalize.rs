@@ -0,0 +1,157 @@
+use crate::numeric;
+use serde::{Deserialize, Serialize};
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+/// How answers and the expected answers are cleaned up before they are
+/// compared, set per factory under `normalize`.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct Normalize {
+    /// Ignore leading and trailing whitespace.
+    pub trim: bool,
+    /// Treat any run of whitespace, including line breaks, as one space.
+    pub collapse_whitespace: bool,
+    /// Compose characters (NFC), so an `e` followed by a combining accent
+    /// matches a precomposed `é`.
+    pub nfc: bool,
+    /// Ignore case.
+    pub case_fold: bool,
+    /// Ignore accents and other diacritics, e.g. `cafe` matches `café`.
+    pub strip_diacritics: bool,
+    /// Ignore punctuation, e.g. `St Louis` matches `St. Louis!`.
+    pub ignore_punctuation: bool,
+    /// Read numbers written out in words as their digits, e.g. `seven`
+    /// matches `7` and `twenty-one` matches `21`. Regular expression answers
+    /// are matched against the words as typed.
+    pub spelled_numbers: bool,
+    /// Leading words to ignore, e.g. `[the, a, la, le]`. Compared after the
+    /// other rules have been applied.
+    pub ignore_articles: Vec<String>,
+}
+
+impl Default for Normalize {
+    fn default() -> Normalize {
+        Normalize {
+            trim: true,
+            collapse_whitespace: true,
+            nfc: true,
+            case_fold: true,
+            strip_diacritics: false,
+            ignore_punctuation: false,
+            spelled_numbers: false,
+            ignore_articles: Vec::new(),
+        }
+    }
+}
+
+impl Normalize {
+    pub fn apply(&self, s: &str) -> String {
+        let s = self.apply_literal(s);
+        match self.spelled_numbers {
+            true => numeric::words_to_digits(&s),
+            false => s,
+        }
+    }
+
+    /// Like `apply`, leaving numbers written out in words as they are.
+    pub fn apply_literal(&self, s: &str) -> String {
+        let mut s = if self.strip_diacritics {
+            s.nfd().filter(|&c| !is_combining_mark(c)).nfc().collect()
+        } else if self.nfc {
+            s.nfc().collect()
+        } else {
+            s.to_string()
+        };
+        if self.case_fold {
+            s = s.to_lowercase();
+        }
+        if self.ignore_punctuation {
+            s.retain(|c| !is_punctuation(c));
+        }
+        if self.collapse_whitespace {
+            s = s.split_whitespace().collect::<Vec<_>>().join(" ");
+        }
+        if self.trim {
+            s = s.trim().to_string();
+        }
+        if let Some((first, rest)) = s.trim_start().split_once(char::is_whitespace) {
+            // Articles are written the way they're typed, so they get the
+            // same treatment as the answer before being compared.
+            let words = Normalize {
+                ignore_articles: Vec::new(),
+                ..self.clone()
+            };
+            if self
+                .ignore_articles
+                .iter()
+                .any(|a| words.apply_literal(a) == first)
+            {
+                s = rest.trim_start().to_string();
+            }
+        }
+        s
+    }
+
+    /// Whether the answers are the same once normalized.
+    pub fn eq(&self, a: &str, b: &str) -> bool {
+        self.apply(a) == self.apply(b)
+    }
+}
+
+fn is_punctuation(c: char) -> bool {
+    c.is_ascii_punctuation() || "¡¿«»‹›‘’‚“”„…–—·".contains(c)
+}
+
+/// Rules of `Normalize` changed for a single question, given under its
+/// `normalize`, e.g. a chemical symbol that has to be typed in the right
+/// case:
+///
+/// ```yaml
+/// - id: sodium
+///   question: Sodium
+///   answers: [Na]
+///   normalize:
+///     case_fold: false
+/// ```
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+pub struct NormalizeOverrides {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trim: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub collapse_whitespace: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nfc: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub case_fold: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strip_diacritics: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ignore_punctuation: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spelled_numbers: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ignore_articles: Option<Vec<String>>,
+}
+
+impl NormalizeOverrides {
+    pub fn apply(&self, normalize: &Normalize) -> Normalize {
+        Normalize {
+            trim: self.trim.unwrap_or(normalize.trim),
+            collapse_whitespace: self
+                .collapse_whitespace
+                .unwrap_or(normalize.collapse_whitespace),
+            nfc: self.nfc.unwrap_or(normalize.nfc),
+            case_fold: self.case_fold.unwrap_or(normalize.case_fold),
+            strip_diacritics: self.strip_diacritics.unwrap_or(normalize.strip_diacritics),
+            ignore_punctuation: self
+                .ignore_punctuation
+                .unwrap_or(normalize.ignore_punctuation),
+            spelled_numbers: self.spelled_numbers.unwrap_or(normalize.spelled_numbers),
+            ignore_articles: self
+                .ignore_articles
+                .clone()
+                .unwrap_or_else(|| normalize.ignore_articles.clone()),
+        }
+    }
+}
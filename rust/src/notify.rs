@@ -0,0 +1,90 @@
+use crate::functionality::Service;
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use std::collections::HashSet;
+use std::process::Command;
+
+/// The questions due for review at some time, see `Service::due_date`.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Due {
+    /// Questions due in any set that isn't archived, each counted once.
+    pub total: usize,
+    /// The sets with questions due and how many, the most first.
+    pub sets: Vec<(String, usize)>,
+}
+
+impl Due {
+    pub fn new(service: &Service, now: DateTime<Utc>) -> Due {
+        let is_due = |id| matches!(service.due_date(id), Some(due) if due <= now);
+        let mut ids = HashSet::new();
+        let mut sets = Vec::new();
+        for set in service.get_sets() {
+            let due = service
+                .get_set(set)
+                .iter()
+                .copied()
+                .filter(|&id| is_due(id))
+                .collect::<Vec<_>>();
+            if !due.is_empty() {
+                sets.push((set.clone(), due.len()));
+                ids.extend(due);
+            }
+        }
+        sets.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        Due {
+            total: ids.len(),
+            sets,
+        }
+    }
+
+    /// The title and body of a notification about the reviews, telling to
+    /// run `command`. Lists the sets with the most due first, up to `max`.
+    pub fn message(&self, command: &str, max: usize) -> (String, String) {
+        let title = match self.total {
+            1 => "1 question due for review".to_string(),
+            n => format!("{} questions due for review", n),
+        };
+        let mut sets = self
+            .sets
+            .iter()
+            .take(max)
+            .map(|(set, n)| format!("{} {}", set, n))
+            .collect::<Vec<_>>();
+        match self.sets.len().saturating_sub(max) {
+            0 => {}
+            1 => sets.push("1 more set".to_string()),
+            n => sets.push(format!("{} more sets", n)),
+        }
+        let body = format!("{}\nRun `{}`", sets.join(", "), command);
+        (title, body)
+    }
+}
+
+/// Shows a desktop notification with `notify-send` on Linux and the like,
+/// or with `osascript` on macOS.
+pub fn send(title: &str, body: &str) -> Result<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        let quote = |s: &str| format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""));
+        let script = format!(
+            "display notification {} with title {}",
+            quote(body),
+            quote(title)
+        );
+        let mut command = Command::new("osascript");
+        command.arg("-e").arg(script);
+        command
+    } else if cfg!(unix) {
+        let mut command = Command::new("notify-send");
+        command.arg("--app-name=trivial").arg(title).arg(body);
+        command
+    } else {
+        bail!("desktop notifications aren't supported here, use --bell");
+    };
+    let status = command
+        .status()
+        .with_context(|| format!("running {:?}", command.get_program()))?;
+    if !status.success() {
+        bail!("{:?} exited with {}", command.get_program(), status);
+    }
+    Ok(())
+}
@@ -0,0 +1,240 @@
+use anyhow::{bail, Result};
+use std::collections::BTreeMap;
+
+/// Parses a number as guesses are typed, e.g. `40000`, `40,000`, `40_000`,
+/// `40k`, `1.5M`, `4e4` or `-12`, rounded to the nearest integer. Commas and
+/// underscores separate thousands, so `1,5` is rejected rather than read as
+/// 15. The suffixes are `k`, `M`, `G` or `B` for billions, and `T`; all but
+/// `T` in either case.
+pub fn si_parse(s: &str) -> Result<i64> {
+    if let Some(n) = parse_words(s) {
+        return Ok(n);
+    }
+    let (number, factor) = split_suffix(s)?;
+    if factor == 1. {
+        if let Ok(n) = number.parse::<i64>() {
+            return Ok(n);
+        }
+    }
+    let value = (parse_float(s, &number)? * factor).round();
+    // Also rules out `inf` and `NaN`, which parse as floats.
+    if !(value >= i64::MIN as f64 && value <= i64::MAX as f64) {
+        bail!("{:?} is out of range", s.trim());
+    }
+    Ok(value as i64)
+}
+
+/// Like `si_parse`, without rounding.
+pub fn si_parse_f64(s: &str) -> Result<f64> {
+    if let Some(n) = parse_words(s) {
+        return Ok(n as f64);
+    }
+    let (number, factor) = split_suffix(s)?;
+    let value = parse_float(s, &number)? * factor;
+    if !value.is_finite() {
+        bail!("{:?} is out of range", s.trim());
+    }
+    Ok(value)
+}
+
+const SMALL: [&str; 20] = [
+    "zero",
+    "one",
+    "two",
+    "three",
+    "four",
+    "five",
+    "six",
+    "seven",
+    "eight",
+    "nine",
+    "ten",
+    "eleven",
+    "twelve",
+    "thirteen",
+    "fourteen",
+    "fifteen",
+    "sixteen",
+    "seventeen",
+    "eighteen",
+    "nineteen",
+];
+
+const TENS: [&str; 8] = [
+    "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+];
+
+/// Parses a number written out in English, e.g. `seven`, `twenty-one` or
+/// `a hundred and five`, up to the millions. None if it isn't one.
+pub fn parse_words(s: &str) -> Option<i64> {
+    let s = s.trim().to_lowercase();
+    let mut words = s
+        .split(|c: char| c.is_whitespace() || c == '-')
+        .filter(|w| !w.is_empty())
+        .collect::<Vec<_>>();
+    if words.is_empty() {
+        return None;
+    }
+    let mut total = 0;
+    for (scale, value) in [("million", 1_000_000), ("thousand", 1_000)] {
+        if let Some(i) = words.iter().position(|&w| w == scale) {
+            let n = match words[..i] {
+                ["a"] => 1,
+                _ => below_thousand(&words[..i])?,
+            };
+            if n == 0 {
+                return None;
+            }
+            total += n * value;
+            words.drain(..=i);
+            if words.first() == Some(&"and") {
+                words.remove(0);
+            }
+        }
+    }
+    if total == 0 || !words.is_empty() {
+        total += below_thousand(&words)?;
+    }
+    Some(total)
+}
+
+fn below_thousand(words: &[&str]) -> Option<i64> {
+    let (hundreds, words) = match words.iter().position(|&w| w == "hundred") {
+        Some(i) => {
+            let n = match words[..i] {
+                ["a"] => 1,
+                [w] => digit(w)?,
+                _ => return None,
+            };
+            let rest = &words[i + 1..];
+            (n * 100, rest.strip_prefix(&["and"][..]).unwrap_or(rest))
+        }
+        None => (0, words),
+    };
+    let rest = match *words {
+        [] if hundreds > 0 => 0,
+        [w] => match SMALL.iter().position(|&s| s == w) {
+            Some(n) => n as i64,
+            None => tens(w)?,
+        },
+        [t, u] => tens(t)? + digit(u)?,
+        _ => return None,
+    };
+    Some(hundreds + rest)
+}
+
+/// The value of `one` to `nine`.
+fn digit(word: &str) -> Option<i64> {
+    match SMALL.iter().position(|&s| s == word)? {
+        0 | 10.. => None,
+        n => Some(n as i64),
+    }
+}
+
+fn tens(word: &str) -> Option<i64> {
+    let i = TENS.iter().position(|&t| t == word)?;
+    Some(20 + 10 * i as i64)
+}
+
+/// The text with every number written out in words replaced by its digits,
+/// e.g. `seven wonders` becomes `7 wonders`.
+pub fn words_to_digits(s: &str) -> String {
+    let words = s.split_whitespace().collect::<Vec<_>>();
+    let mut out = Vec::new();
+    let mut replaced = false;
+    let mut i = 0;
+    while i < words.len() {
+        // The longest run of words from here that is a number.
+        let number = (i + 1..=words.len())
+            .rev()
+            .find_map(|end| Some((end, parse_words(&words[i..end].join(" "))?)));
+        match number {
+            Some((end, n)) => {
+                out.push(n.to_string());
+                replaced = true;
+                i = end;
+            }
+            None => {
+                out.push(words[i].to_string());
+                i += 1;
+            }
+        }
+    }
+    match replaced {
+        true => out.join(" "),
+        false => s.to_string(),
+    }
+}
+
+/// Parses a number followed by one of the units, e.g. `15k mi²`, into the
+/// unit they're relative to: `units` has how many of it each unit is. A
+/// number without a unit is already in it.
+pub fn parse_with_unit(s: &str, units: &BTreeMap<String, f64>) -> Result<f64> {
+    let s = s.trim();
+    let mut by_length = units.iter().collect::<Vec<_>>();
+    by_length.sort_by_key(|(unit, _)| std::cmp::Reverse(unit.len()));
+    for (unit, factor) in by_length {
+        if let Some(number) = s.strip_suffix(unit.as_str()) {
+            if !number.trim().is_empty() {
+                return Ok(si_parse_f64(number)? * factor);
+            }
+        }
+    }
+    si_parse_f64(s)
+}
+
+/// The number without its suffix and what the suffix multiplies it by.
+fn split_suffix(s: &str) -> Result<(String, f64)> {
+    let s = s.trim();
+    let Some(last) = s.chars().last() else {
+        bail!("empty number");
+    };
+    let (number, factor) = match last {
+        'k' | 'K' => (&s[..s.len() - 1], 1e3),
+        'm' | 'M' => (&s[..s.len() - 1], 1e6),
+        'g' | 'G' | 'b' | 'B' => (&s[..s.len() - 1], 1e9),
+        'T' => (&s[..s.len() - 1], 1e12),
+        c if c.is_ascii_digit() || c == '.' => (s, 1.),
+        c => bail!("unexpected last char {}", c),
+    };
+    Ok((strip_separators(number.trim_end())?, factor))
+}
+
+fn parse_float(s: &str, number: &str) -> Result<f64> {
+    match number.parse::<f64>() {
+        Ok(value) => Ok(value),
+        Err(_) => bail!("{:?} isn't a number", s.trim()),
+    }
+}
+
+/// Removes the thousands separators from the integer part, checking that
+/// they separate groups of three digits.
+fn strip_separators(s: &str) -> Result<String> {
+    let end = s.find(['.', 'e', 'E']).unwrap_or(s.len());
+    let (int, rest) = s.split_at(end);
+    if !int.contains([',', '_']) {
+        return Ok(s.to_string());
+    }
+    let digits = int.strip_prefix(['-', '+']).unwrap_or(int);
+    let groups = digits.split([',', '_']).collect::<Vec<_>>();
+    let grouped = groups.iter().enumerate().all(|(i, g)| {
+        g.chars().all(|c| c.is_ascii_digit())
+            && match i {
+                0 => (1..=3).contains(&g.len()),
+                _ => g.len() == 3,
+            }
+    });
+    if !grouped {
+        bail!("{:?} doesn't separate the thousands in groups of three", s);
+    }
+    Ok(format!("{}{}", int.replace([',', '_'], ""), rest))
+}
+
+/// How far off the guess is relative to the answer, e.g. -0.1 for 10% too
+/// low. None when the answer is 0.
+pub fn relative_error(guess: i64, answer: i64) -> Option<f64> {
+    if answer == 0 {
+        return None;
+    }
+    Some((guess - answer) as f64 / answer as f64)
+}
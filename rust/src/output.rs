@@ -0,0 +1,222 @@
+use anyhow::{bail, Result};
+use colored::{Color, ColoredString, Colorize};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fmt::Display;
+use std::io::{stdout, IsTerminal};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+
+/// How each kind of output looks. Every style is a color name (`green`,
+/// `bright red`, `#ff8800`) and/or attributes (`bold`, `dimmed`, `italic`,
+/// `underline`, `strikethrough`), e.g. `bold bright blue`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct Theme {
+    /// Correct answers and expected output.
+    pub correct: String,
+    /// Wrong answers and errors.
+    pub wrong: String,
+    /// Table headers and titles.
+    pub header: String,
+    /// Labels in front of values, e.g. `Answer:`.
+    pub label: String,
+    /// Filler and empty cells.
+    pub muted: String,
+    /// Explanations and other asides.
+    pub aside: String,
+}
+
+impl Default for Theme {
+    fn default() -> Theme {
+        Theme {
+            correct: "green".to_string(),
+            wrong: "red".to_string(),
+            header: "bold".to_string(),
+            label: "bold".to_string(),
+            muted: "dimmed".to_string(),
+            aside: "italic".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct Style {
+    color: Option<Color>,
+    bold: bool,
+    dimmed: bool,
+    italic: bool,
+    underline: bool,
+    strikethrough: bool,
+}
+
+impl FromStr for Style {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Style> {
+        let mut style = Style::default();
+        let mut color = Vec::new();
+        for word in s.split_whitespace() {
+            match word {
+                "bold" => style.bold = true,
+                "dimmed" => style.dimmed = true,
+                "italic" => style.italic = true,
+                "underline" => style.underline = true,
+                "strikethrough" => style.strikethrough = true,
+                word => color.push(word),
+            }
+        }
+        if !color.is_empty() {
+            let name = color.join(" ");
+            style.color = Some(match parse_hex(&name) {
+                Some(color) => color,
+                None => match Color::from_str(&name) {
+                    Ok(color) => color,
+                    Err(()) => bail!("unknown color {:?}", name),
+                },
+            });
+        }
+        Ok(style)
+    }
+}
+
+impl Style {
+    fn apply(&self, text: &str) -> ColoredString {
+        let mut s = text.normal();
+        if let Some(color) = self.color {
+            s = s.color(color);
+        }
+        if self.bold {
+            s = s.bold();
+        }
+        if self.dimmed {
+            s = s.dimmed();
+        }
+        if self.italic {
+            s = s.italic();
+        }
+        if self.underline {
+            s = s.underline();
+        }
+        if self.strikethrough {
+            s = s.strikethrough();
+        }
+        s
+    }
+}
+
+fn parse_hex(s: &str) -> Option<Color> {
+    let hex = s.strip_prefix('#')?;
+    if hex.len() != 6 {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some(Color::TrueColor {
+        r: channel(0)?,
+        g: channel(2)?,
+        b: channel(4)?,
+    })
+}
+
+struct Styles {
+    correct: Style,
+    wrong: Style,
+    header: Style,
+    label: Style,
+    muted: Style,
+    aside: Style,
+}
+
+impl Styles {
+    fn new(theme: &Theme) -> Result<Styles> {
+        let parse = |name: &str, s: &str| -> Result<Style> {
+            s.parse()
+                .map_err(|err| anyhow::anyhow!("theme.{}: {:#}", name, err))
+        };
+        Ok(Styles {
+            correct: parse("correct", &theme.correct)?,
+            wrong: parse("wrong", &theme.wrong)?,
+            header: parse("header", &theme.header)?,
+            label: parse("label", &theme.label)?,
+            muted: parse("muted", &theme.muted)?,
+            aside: parse("aside", &theme.aside)?,
+        })
+    }
+}
+
+static STYLES: OnceLock<Styles> = OnceLock::new();
+
+/// Sets the theme for the rest of the program. Colors are turned off when
+/// `color` is false or stdout isn't a terminal, unless `CLICOLOR_FORCE` is
+/// set, and when `NO_COLOR` is set.
+pub fn init(theme: &Theme, color: bool) -> Result<()> {
+    let _ = STYLES.set(Styles::new(theme)?);
+    if !color || (env::var_os("CLICOLOR_FORCE").is_none() && !stdout().is_terminal()) {
+        colored::control::set_override(false);
+    }
+    Ok(())
+}
+
+fn styles() -> &'static Styles {
+    STYLES.get_or_init(|| Styles::new(&Theme::default()).unwrap())
+}
+
+pub fn correct(text: &str) -> ColoredString {
+    styles().correct.apply(text)
+}
+
+pub fn wrong(text: &str) -> ColoredString {
+    styles().wrong.apply(text)
+}
+
+pub fn header(text: &str) -> ColoredString {
+    styles().header.apply(text)
+}
+
+pub fn label(text: &str) -> ColoredString {
+    styles().label.apply(text)
+}
+
+pub fn muted(text: &str) -> ColoredString {
+    styles().muted.apply(text)
+}
+
+pub fn aside(text: &str) -> ColoredString {
+    styles().aside.apply(text)
+}
+
+static SILENT: AtomicBool = AtomicBool::new(false);
+
+/// Withholds the feedback on answers, e.g. for an exam where the results are
+/// only shown at the end.
+pub fn set_silent(silent: bool) {
+    SILENT.store(silent, Ordering::Relaxed);
+}
+
+pub fn is_silent() -> bool {
+    SILENT.load(Ordering::Relaxed)
+}
+
+/// Withholds the feedback on answers until dropped, so it's shown again
+/// however the exam ends.
+pub struct Silenced(());
+
+pub fn silence() -> Silenced {
+    set_silent(true);
+    Silenced(())
+}
+
+impl Drop for Silenced {
+    fn drop(&mut self) {
+        set_silent(false);
+    }
+}
+
+/// Prints a line telling whether an answer was right, unless feedback is
+/// withheld.
+pub fn feedback(line: impl Display) {
+    if !is_silent() {
+        println!("{}", line);
+    }
+}
@@ -0,0 +1,313 @@
+use crate::assets;
+use crate::functionality::{parse_models, validate_models, Models};
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+
+/// File extension of packs.
+pub const EXTENSION: &str = "trivialpack";
+/// Version of the pack layout written by this build. Version 2 added the
+/// assets.
+pub const FORMAT: u32 = 2;
+
+const MANIFEST: &str = "pack.yaml";
+const SETS_DIR: &str = "sets";
+const MEDIA_DIR: &str = "media";
+
+/// Describes a pack, stored as `pack.yaml` at its root.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Manifest {
+    pub format: u32,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    pub created: DateTime<Utc>,
+    /// File names of the question sets, kept under `sets/`.
+    pub sets: Vec<String>,
+    /// Paths of the media files, kept under `media/`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub media: Vec<String>,
+    /// The SHA-256 of every asset the sets refer to, by path, see
+    /// `assets::DIR`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub assets: BTreeMap<String, String>,
+}
+
+/// A portable archive of question sets: a gzipped tar with the manifest, the
+/// set files, the assets they refer to and optionally other media. It never contains answers,
+/// so sets can be shared without sharing the database.
+pub struct Pack {
+    pub manifest: Manifest,
+    /// Contents of the set files, by file name.
+    pub sets: BTreeMap<String, Vec<u8>>,
+    /// Contents of the media files, by path relative to the media directory.
+    pub media: BTreeMap<String, Vec<u8>>,
+    /// Contents of the assets, by path, e.g. `assets/flags/fr.png`.
+    pub assets: BTreeMap<String, Vec<u8>>,
+}
+
+impl Pack {
+    /// Creates a pack with the set files, the assets they refer to and every
+    /// file in the media directory, checking that the sets are valid on their
+    /// own.
+    pub fn create(manifest: Manifest, sets: &[PathBuf], media: Option<&Path>) -> Result<Pack> {
+        let mut pack = Pack {
+            manifest,
+            sets: BTreeMap::new(),
+            media: BTreeMap::new(),
+            assets: BTreeMap::new(),
+        };
+        for path in sets {
+            let name = file_name(path)?;
+            let data = fs::read(path).with_context(|| format!("reading {}", path.display()))?;
+            let dir = path.parent().unwrap_or(Path::new("."));
+            for asset in assets::references(&parse_models(&[&data])?)? {
+                let data = fs::read(dir.join(&asset))
+                    .with_context(|| format!("reading {} for {}", asset, name))?;
+                pack.assets.insert(asset, data);
+            }
+            pack.sets.insert(name, data);
+        }
+        if let Some(dir) = media {
+            add_media(&mut pack.media, dir, dir)?;
+        }
+        pack.manifest.format = FORMAT;
+        pack.manifest.sets = pack.sets.keys().cloned().collect();
+        pack.manifest.media = pack.media.keys().cloned().collect();
+        pack.manifest.assets = pack
+            .assets
+            .iter()
+            .map(|(path, data)| (path.clone(), assets::hash(data)))
+            .collect();
+        pack.validate()?;
+        Ok(pack)
+    }
+
+    /// Parses the sets in the pack, failing if any of them is invalid or
+    /// refers to an asset that isn't in the pack.
+    pub fn validate(&self) -> Result<Models> {
+        let models = parse_models(&self.sets.values().collect::<Vec<_>>())?;
+        let mut problems = validate_models(&models);
+        for asset in assets::references(&models)? {
+            if !self.assets.contains_key(&asset) {
+                problems.push(format!("{} is missing from the pack", asset));
+            }
+        }
+        if !problems.is_empty() {
+            bail!("the sets are invalid:\n{}", problems.join("\n"));
+        }
+        Ok(models)
+    }
+
+    pub fn write<W: Write>(&self, writer: W) -> Result<()> {
+        let mut tar = tar::Builder::new(GzEncoder::new(writer, Compression::default()));
+        append(
+            &mut tar,
+            MANIFEST,
+            serde_yaml::to_string(&self.manifest)?.as_bytes(),
+        )?;
+        for (name, data) in &self.sets {
+            append(&mut tar, &format!("{}/{}", SETS_DIR, name), data)?;
+        }
+        for (name, data) in &self.media {
+            append(&mut tar, &format!("{}/{}", MEDIA_DIR, name), data)?;
+        }
+        for (path, data) in &self.assets {
+            append(&mut tar, path, data)?;
+        }
+        tar.into_inner()?.finish()?;
+        Ok(())
+    }
+
+    /// Reads a pack, rejecting entries outside of the layout so a pack can't
+    /// write anywhere else when unpacked, and assets that don't match their
+    /// hashes in the manifest.
+    pub fn read<R: Read>(reader: R) -> Result<Pack> {
+        let mut manifest = None;
+        let mut sets = BTreeMap::new();
+        let mut media = BTreeMap::new();
+        let mut assets = BTreeMap::new();
+        let mut archive = tar::Archive::new(GzDecoder::new(reader));
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            if !entry.header().entry_type().is_file() {
+                continue;
+            }
+            let path = entry.path()?.into_owned();
+            let mut data = Vec::new();
+            entry.read_to_end(&mut data)?;
+            if path == Path::new(MANIFEST) {
+                manifest = Some(serde_yaml::from_slice::<Manifest>(&data)?);
+            } else if let Ok(name) = path.strip_prefix(SETS_DIR) {
+                sets.insert(safe_name(name, false)?, data);
+            } else if let Ok(name) = path.strip_prefix(MEDIA_DIR) {
+                media.insert(safe_name(name, true)?, data);
+            } else if path.starts_with(assets::DIR) {
+                assets.insert(safe_name(&path, true)?, data);
+            } else {
+                bail!("unexpected file {} in pack", path.display());
+            }
+        }
+        let Some(manifest) = manifest else {
+            bail!("not a pack, {} is missing", MANIFEST);
+        };
+        if manifest.format > FORMAT {
+            bail!(
+                "the pack has format {}, this version of trivial only reads up to {}",
+                manifest.format,
+                FORMAT
+            );
+        }
+        let hashes = assets
+            .iter()
+            .map(|(path, data)| (path.clone(), assets::hash(data)))
+            .collect::<BTreeMap<_, _>>();
+        if hashes != manifest.assets {
+            bail!("the assets in the pack don't match its manifest");
+        }
+        Ok(Pack {
+            manifest,
+            sets,
+            media,
+            assets,
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let file =
+            fs::File::create(path).with_context(|| format!("creating {}", path.display()))?;
+        self.write(file)
+    }
+
+    pub fn open(path: &Path) -> Result<Pack> {
+        let file = fs::File::open(path).with_context(|| format!("opening {}", path.display()))?;
+        Pack::read(file).with_context(|| format!("reading {}", path.display()))
+    }
+
+    /// Writes the set files to the directory, the assets to its `assets`
+    /// subdirectory and the media files to its `media` subdirectory,
+    /// returning the paths written. Files that already
+    /// exist with other contents are only replaced when `overwrite` is set.
+    pub fn unpack(&self, dir: &Path, overwrite: bool) -> Result<Vec<PathBuf>> {
+        let files = self
+            .sets
+            .iter()
+            .map(|(name, data)| (dir.join(name), data))
+            .chain(
+                self.media
+                    .iter()
+                    .map(|(name, data)| (dir.join(MEDIA_DIR).join(name), data)),
+            )
+            .chain(
+                self.assets
+                    .iter()
+                    .map(|(path, data)| (dir.join(path), data)),
+            )
+            .collect::<Vec<_>>();
+        if !overwrite {
+            for (path, data) in &files {
+                if path.exists() && fs::read(path)? != **data {
+                    bail!("{} already exists", path.display());
+                }
+            }
+        }
+        for (path, data) in &files {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(path, data)?;
+        }
+        Ok(files.into_iter().map(|(path, _)| path).collect())
+    }
+}
+
+/// Finds the files in the directory defining the named sets, along with the
+/// files of every set they include.
+pub fn set_files(dir: &Path, names: &[String]) -> Result<Vec<PathBuf>> {
+    let mut files = HashMap::new();
+    let mut depends_on = HashMap::new();
+    for path in fs::read_dir(dir)? {
+        let path = path?.path();
+        if !path.is_file() {
+            continue;
+        }
+        let data = fs::read(&path)?;
+        let models =
+            parse_models(&[data]).with_context(|| format!("parsing {}", path.display()))?;
+        for (name, set) in models.sets {
+            depends_on.insert(name.clone(), set.depends_on().clone());
+            files.insert(name, path.clone());
+        }
+    }
+
+    let mut paths = Vec::new();
+    let mut todo = names.to_vec();
+    while let Some(name) = todo.pop() {
+        let Some(path) = files.get(&name) else {
+            bail!("there is no set named {:?} in {}", name, dir.display());
+        };
+        if !paths.contains(path) {
+            paths.push(path.clone());
+            todo.extend(depends_on[&name].iter().cloned());
+        }
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+fn append<W: Write>(tar: &mut tar::Builder<W>, path: &str, data: &[u8]) -> Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    tar.append_data(&mut header, path, data)?;
+    Ok(())
+}
+
+fn add_media(media: &mut BTreeMap<String, Vec<u8>>, root: &Path, dir: &Path) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            add_media(media, root, &path)?;
+        } else {
+            let name = safe_name(path.strip_prefix(root)?, true)?;
+            media.insert(name, fs::read(&path)?);
+        }
+    }
+    Ok(())
+}
+
+fn file_name(path: &Path) -> Result<String> {
+    match path.file_name().and_then(|n| n.to_str()) {
+        Some(name) => Ok(name.to_string()),
+        None => bail!("{} is not a file", path.display()),
+    }
+}
+
+/// Turns a relative path into a name with `/` separators, rejecting anything
+/// that could point outside of the directory it is relative to.
+fn safe_name(path: &Path, nested: bool) -> Result<String> {
+    let mut parts = Vec::new();
+    for c in path.components() {
+        match c {
+            Component::Normal(part) => match part.to_str() {
+                Some(part) => parts.push(part),
+                None => bail!("{} is not a valid name", path.display()),
+            },
+            _ => bail!("{} is not a valid name", path.display()),
+        }
+    }
+    if parts.is_empty() || (!nested && parts.len() > 1) {
+        bail!("{} is not a valid name", path.display());
+    }
+    Ok(parts.join("/"))
+}
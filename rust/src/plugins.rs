@@ -0,0 +1,195 @@
+use crate::encoding;
+use crate::functionality::{
+    QuestionFactory, QuestionKey, QuestionRunner, QuestionSetFactory, SetIndex,
+};
+use crate::script::ScriptQuestion;
+use anyhow::{bail, Context, Result};
+use libloading::Library;
+use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
+use std::collections::BTreeMap;
+use std::ffi::{c_char, CStr, CString};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, RwLock};
+use tracing::warn;
+
+/// The version of the plugin interface below, which plugins have to match.
+pub const ABI_VERSION: u32 = 1;
+
+/// The function every plugin exports, returning what it provides:
+/// `extern "C" fn trivial_plugin() -> *const Plugin`.
+pub const ENTRY_POINT: &str = "trivial_plugin";
+
+/// What a plugin provides, returned by its `trivial_plugin` function and
+/// kept alive as long as the plugin is loaded.
+#[repr(C)]
+pub struct Plugin {
+    pub abi_version: u32,
+    pub types: *const PluginType,
+    pub count: usize,
+}
+
+/// A question type, used by sets with `type_` set to its name. Its questions
+/// are scripted like those of a `script` set: `call` gets the JSON request a
+/// script reads on stdin, with the set's data added as `data`, and returns
+/// the reply a script would write, see `script::ScriptData`. The reply is
+/// handed back to `free` once read; null means the call failed.
+#[repr(C)]
+pub struct PluginType {
+    pub name: *const c_char,
+    pub call: unsafe extern "C" fn(request: *const c_char) -> *mut c_char,
+    pub free: unsafe extern "C" fn(reply: *mut c_char),
+}
+
+struct Registered {
+    call: unsafe extern "C" fn(*const c_char) -> *mut c_char,
+    free: unsafe extern "C" fn(*mut c_char),
+}
+
+static TYPES: RwLock<BTreeMap<String, Registered>> = RwLock::new(BTreeMap::new());
+
+/// The loaded plugins, which stay loaded until the program exits.
+static LIBRARIES: Mutex<Vec<Library>> = Mutex::new(Vec::new());
+
+/// Where plugins are looked for when the config file doesn't say, e.g.
+/// `~/.local/share/trivial/plugins` on Linux.
+pub fn default_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("trivial").join("plugins"))
+}
+
+/// Loads every plugin in the directory, returning the question types they
+/// provide. Plugins that fail to load are skipped with a warning.
+pub fn load_dir(dir: &Path) -> Result<Vec<String>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut paths = fs::read_dir(dir)
+        .with_context(|| format!("reading {}", dir.display()))?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<Result<Vec<_>, _>>()?;
+    paths.sort();
+    let mut types = Vec::new();
+    for path in paths {
+        if path.extension() != Some(std::env::consts::DLL_EXTENSION.as_ref()) {
+            continue;
+        }
+        match load(&path) {
+            Ok(loaded) => types.extend(loaded),
+            Err(err) => warn!("skipping plugin {}: {:#}", path.display(), err),
+        }
+    }
+    Ok(types)
+}
+
+/// Loads the plugin and registers its question types, returning their names.
+pub fn load(path: &Path) -> Result<Vec<String>> {
+    // Plugins are trusted like the program itself: loading one runs its code.
+    let library = unsafe { Library::new(path) }?;
+    let entry =
+        unsafe { library.get::<unsafe extern "C" fn() -> *const Plugin>(ENTRY_POINT.as_bytes()) }
+            .with_context(|| format!("no {} function", ENTRY_POINT))?;
+    let Some(plugin) = (unsafe { entry().as_ref() }) else {
+        bail!("{} returned nothing", ENTRY_POINT);
+    };
+    if plugin.abi_version != ABI_VERSION {
+        bail!(
+            "built for version {} of the plugin interface, not {}",
+            plugin.abi_version,
+            ABI_VERSION
+        );
+    }
+    let types = match plugin.count {
+        0 => &[][..],
+        count => unsafe { std::slice::from_raw_parts(plugin.types, count) },
+    };
+
+    let mut registry = TYPES.write().unwrap();
+    let mut names = Vec::new();
+    for t in types {
+        let name = unsafe { CStr::from_ptr(t.name) }
+            .to_str()
+            .context("the name of a question type isn't UTF-8")?
+            .to_string();
+        if registry.contains_key(&name) || names.contains(&name) {
+            bail!("the question type {:?} is already provided", name);
+        }
+        names.push(name);
+    }
+    for (name, t) in names.iter().zip(types) {
+        registry.insert(
+            name.clone(),
+            Registered {
+                call: t.call,
+                free: t.free,
+            },
+        );
+    }
+    LIBRARIES.lock().unwrap().push(library);
+    Ok(names)
+}
+
+/// Whether a loaded plugin provides the question type. Built-in types take
+/// precedence over plugins of the same name.
+pub fn is_registered(type_: &str) -> bool {
+    TYPES.read().unwrap().contains_key(type_)
+}
+
+/// Passes the request to the question type, returning its reply.
+pub fn call(type_: &str, request: &str) -> Result<String> {
+    let (call, free) = match TYPES.read().unwrap().get(type_) {
+        Some(t) => (t.call, t.free),
+        None => bail!("no plugin provides the question type {:?}", type_),
+    };
+    let request = CString::new(request)?;
+    let reply = unsafe { call(request.as_ptr()) };
+    if reply.is_null() {
+        bail!("the {} plugin failed", type_);
+    }
+    let text = unsafe { CStr::from_ptr(reply) }
+        .to_string_lossy()
+        .into_owned();
+    unsafe { free(reply) };
+    Ok(text)
+}
+
+/// The data of a set whose type a plugin provides, all of which is passed on
+/// to the plugin.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct PluginData {
+    /// Let the plugin write the prompt, see `script::ScriptData::prompt`.
+    #[serde(default)]
+    prompt: bool,
+    #[serde(flatten)]
+    fields: BTreeMap<String, Value>,
+    #[serde(skip)]
+    type_: String,
+    #[serde(skip)]
+    depends: Vec<String>,
+}
+
+impl PluginData {
+    pub fn with_type(mut self, type_: &str) -> Self {
+        self.type_ = type_.to_string();
+        self
+    }
+}
+
+impl QuestionFactory for PluginData {
+    fn build(&self, data: &[u8]) -> Result<Box<dyn QuestionRunner>> {
+        let question = encoding::decode::<ScriptQuestion>(data)?;
+        let data = serde_json::to_value(&self.fields)?;
+        let question = question.with_plugin(&self.type_, data, self.prompt);
+        Ok(Box::new(question) as Box<dyn QuestionRunner>)
+    }
+}
+
+impl QuestionSetFactory for PluginData {
+    fn build_set(&self, s: &SetIndex, set_name: &str) -> Vec<QuestionKey> {
+        s.get_factory(set_name).to_vec()
+    }
+
+    fn depends_on(&self) -> &Vec<String> {
+        &self.depends
+    }
+}
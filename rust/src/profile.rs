@@ -0,0 +1,74 @@
+use anyhow::{bail, Result};
+use chrono::NaiveTime;
+use serde::{Deserialize, Serialize};
+
+/// Settings a session of `run` starts from, given in the config file and
+/// picked by the time of day or by name with --profile, e.g.
+///
+/// ```yaml
+/// profiles:
+/// - name: morning
+///   from: "06:00"
+///   to: "12:00"
+///   set: vocabulary
+///   num: 20
+/// - name: evening
+///   from: "18:00"
+///   to: "02:00"
+///   set: populations
+///   ranking: Weighted random
+///   selection: Practiced
+/// ```
+///
+/// A time range may run past midnight. `ranking` and `selection` are the
+/// names shown when starting a session, the custom ranking methods' too.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Profile {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from: Option<NaiveTime>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub to: Option<NaiveTime>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub set: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub num: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ranking: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selection: Option<String>,
+}
+
+impl Profile {
+    /// Whether the profile is picked at the time, profiles without a time
+    /// range only being picked by name.
+    pub fn covers(&self, time: NaiveTime) -> bool {
+        match (self.from, self.to) {
+            (Some(from), Some(to)) if from <= to => from <= time && time < to,
+            (Some(from), Some(to)) => from <= time || time < to,
+            _ => false,
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.from.is_some() != self.to.is_some() {
+            bail!("profile {}: give both from and to, or neither", self.name);
+        }
+        Ok(())
+    }
+}
+
+/// The profile with the name, or else the first one covering the time.
+pub fn pick<'a>(
+    profiles: &'a [Profile],
+    name: Option<&str>,
+    time: NaiveTime,
+) -> Result<Option<&'a Profile>> {
+    match name {
+        Some(name) => match profiles.iter().find(|p| p.name == name) {
+            Some(profile) => Ok(Some(profile)),
+            None => bail!("there is no profile named {} in the config file", name),
+        },
+        None => Ok(profiles.iter().find(|p| p.covers(time))),
+    }
+}
@@ -0,0 +1,120 @@
+use core::fmt;
+use std::time::{Duration, Instant};
+
+const WIDTH: usize = 20;
+
+/// Where a session is at, shown above every question. A session goes through
+/// the picked questions, then again through the ones answered wrong until
+/// there are none left.
+pub struct Progress {
+    round: usize,
+    total: usize,
+    done: usize,
+    wrong: usize,
+    answered: usize,
+    correct: usize,
+    hide_score: bool,
+    start: Instant,
+}
+
+impl Progress {
+    pub fn new(total: usize) -> Progress {
+        Progress {
+            round: 1,
+            total,
+            done: 0,
+            wrong: 0,
+            answered: 0,
+            correct: 0,
+            hide_score: false,
+            start: Instant::now(),
+        }
+    }
+
+    /// Leaves out how many answers were correct, e.g. in an exam.
+    pub fn hide_score(&mut self) {
+        self.hide_score = true;
+    }
+
+    pub fn record(&mut self, correct: bool) {
+        self.done += 1;
+        self.answered += 1;
+        if correct {
+            self.correct += 1;
+        } else {
+            self.wrong += 1;
+        }
+    }
+
+    pub fn skip(&mut self) {
+        self.done += 1;
+    }
+
+    /// Takes a question that's yet to come out of the session, e.g. one
+    /// deleted while editing it.
+    pub fn remove(&mut self) {
+        self.total -= 1;
+    }
+
+    /// Takes back the last question, given whether it was answered correctly
+    /// or skipped.
+    pub fn undo(&mut self, answer: Option<bool>) {
+        self.done -= 1;
+        if let Some(correct) = answer {
+            self.answered -= 1;
+            if correct {
+                self.correct -= 1;
+            } else {
+                self.wrong -= 1;
+            }
+        }
+    }
+
+    /// Starts going through the questions answered wrong.
+    pub fn next_round(&mut self) {
+        self.round += 1;
+        self.total = self.wrong;
+        self.done = 0;
+        self.wrong = 0;
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+impl fmt::Display for Progress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let filled = (self.done * WIDTH).checked_div(self.total).unwrap_or(WIDTH);
+        let bar = format!("{}{}", "█".repeat(filled), "░".repeat(WIDTH - filled));
+        if self.round > 1 {
+            write!(f, "Round {} ", self.round)?;
+        }
+        write!(
+            f,
+            "{} {}/{} · {} left",
+            bar,
+            self.done,
+            self.total,
+            self.total - self.done
+        )?;
+        let secs = self.elapsed().as_secs();
+        if self.hide_score {
+            return write!(f, " · {}:{:02}", secs / 60, secs % 60);
+        }
+        if self.answered > 0 {
+            write!(
+                f,
+                " · {:.0}% correct",
+                self.correct as f64 / self.answered as f64 * 100.
+            )?;
+        }
+        write!(
+            f,
+            " · {} wrong · {}:{:02}",
+            self.wrong,
+            secs / 60,
+            secs % 60
+        )
+    }
+}
@@ -0,0 +1,82 @@
+use crate::expr;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Days since the last answer of a question that has never been answered.
+pub const NEVER_ANSWERED_DAYS: f64 = 10_000.;
+
+/// A ranking method defined in the config file by an expression over a
+/// question's attributes, see `expr::eval`. Questions with the highest value
+/// are picked first, e.g.
+///
+/// ```yaml
+/// ranking_methods:
+/// - name: Struggling
+///   expression: (1 - probability) * tag_weight + num_incorrect / 10
+///   tag_weights:
+///     verbs: 2
+/// ```
+///
+/// The attributes are `probability`, `days_since_last` (`NEVER_ANSWERED_DAYS`
+/// if never answered), `num_correct`, `num_incorrect`, `num_answers` and
+/// `tag_weight`, the product of the weights of the question's tags, where
+/// tags without a weight count as 1.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CustomMethod {
+    pub name: String,
+    pub expression: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tag_weights: BTreeMap<String, f64>,
+}
+
+/// What a custom method's expression can refer to.
+pub struct Attributes<'a> {
+    pub probability: f64,
+    pub days_since_last: f64,
+    pub num_correct: u32,
+    pub num_incorrect: u32,
+    pub num_answers: usize,
+    pub tags: &'a [String],
+}
+
+impl CustomMethod {
+    pub fn score(&self, a: &Attributes) -> Result<f64> {
+        let tag_weight = a
+            .tags
+            .iter()
+            .filter_map(|t| self.tag_weights.get(t))
+            .product::<f64>();
+        let vars = |name: &str| match name {
+            "probability" => Some(a.probability),
+            "days_since_last" => Some(a.days_since_last),
+            "num_correct" => Some(a.num_correct as f64),
+            "num_incorrect" => Some(a.num_incorrect as f64),
+            "num_answers" => Some(a.num_answers as f64),
+            "tag_weight" => Some(tag_weight),
+            _ => None,
+        };
+        expr::eval_with(&self.expression, &vars)
+            .with_context(|| format!("ranking method {:?}", self.name))
+    }
+
+    /// Checks that the expression only refers to known attributes.
+    pub fn validate(&self) -> Result<()> {
+        self.score(&Attributes {
+            probability: 0.5,
+            days_since_last: 1.,
+            num_correct: 1,
+            num_incorrect: 1,
+            num_answers: 2,
+            tags: &[],
+        })
+        .map(|_| ())
+    }
+}
+
+impl fmt::Display for CustomMethod {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
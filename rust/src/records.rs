@@ -0,0 +1,230 @@
+use crate::encoding;
+use crate::functionality::{
+    FactoryOptions, QuestionFactory, QuestionKey, QuestionRunner, QuestionSetFactory, Score,
+    SetIndex,
+};
+use crate::hints::Hints;
+use crate::normalize::Normalize;
+use crate::output;
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
+use std::collections::BTreeMap;
+
+/// A question set of records with the same fields, e.g. the elements of the
+/// periodic table, asking about every record in each of the `variants`. The
+/// type is `records` or `template`, which are the same:
+///
+/// ```yaml
+/// name: elements
+/// type_: records
+/// data:
+///   variants:
+///   - id: name
+///     question: 'Which element has the symbol {symbol}?'
+///     answer: name
+///   - id: number
+///     question: 'What is the atomic number of {name}?'
+///     answer: number
+/// items:
+/// - {id: hydrogen, name: Hydrogen, symbol: H, number: 1}
+/// - {id: helium, name: Helium, symbol: He, number: 2}
+/// ```
+#[derive(Deserialize, Serialize, Debug)]
+pub struct RecordSet {
+    pub name: String,
+    pub type_: String,
+    pub items: Vec<Record>,
+    pub data: RecordsData,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Record {
+    id: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    #[serde(flatten)]
+    fields: BTreeMap<String, Value>,
+}
+
+/// A kind of question asked about every record.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Variant {
+    /// Appended to the id of the record to get the id of its question, e.g.
+    /// `hydrogen-name`.
+    id: String,
+    /// The question, with `{field}` standing for the record's field.
+    question: String,
+    /// The field that answers it, or a template like the question, e.g.
+    /// `{city}, {country}`.
+    answer: String,
+}
+
+impl Variant {
+    /// The fields the question and the answer use.
+    fn fields(&self) -> Result<Vec<&str>> {
+        let mut fields = placeholders(&self.question)?;
+        match self.answer.contains('{') {
+            true => fields.extend(placeholders(&self.answer)?),
+            false => fields.push(&self.answer),
+        }
+        Ok(fields)
+    }
+
+    fn answer(&self, fields: &BTreeMap<String, String>) -> Result<String> {
+        if self.answer.contains('{') {
+            return render(&self.answer, fields);
+        }
+        match fields.get(&self.answer) {
+            Some(answer) => Ok(answer.clone()),
+            None => bail!("no field {:?}", self.answer),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct RecordsData {
+    variants: Vec<Variant>,
+    #[serde(default)]
+    normalize: Normalize,
+    #[serde(flatten)]
+    options: FactoryOptions,
+    #[serde(skip)]
+    depends: Vec<String>,
+}
+
+impl RecordSet {
+    /// A question for every record and variant, leaving out the variants
+    /// using a field the record doesn't have.
+    pub fn questions(&self) -> Result<Vec<RecordQuestion>> {
+        let mut questions = Vec::new();
+        for record in &self.items {
+            for variant in &self.data.variants {
+                let fields = variant
+                    .fields()?
+                    .iter()
+                    .map(|&field| Some((field.to_string(), text(record.fields.get(field)?)?)))
+                    .collect::<Option<BTreeMap<_, _>>>();
+                let Some(fields) = fields else {
+                    continue;
+                };
+                questions.push(RecordQuestion {
+                    id: format!("{}-{}", record.id, variant.id),
+                    variant: variant.id.clone(),
+                    fields,
+                    tags: record.tags.clone(),
+                    question: String::new(),
+                    answer: String::new(),
+                    normalize: Normalize::default(),
+                });
+            }
+        }
+        Ok(questions)
+    }
+}
+
+/// A field as text, or None if it isn't a plain value.
+fn text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+/// The fields the template refers to as `{field}`.
+fn placeholders(template: &str) -> Result<Vec<&str>> {
+    let mut fields = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let Some(end) = rest[start..].find('}') else {
+            bail!("unclosed '{{' in {:?}", template);
+        };
+        fields.push(&rest[start + 1..start + end]);
+        rest = &rest[start + end + 1..];
+    }
+    Ok(fields)
+}
+
+/// The template with its placeholders filled in from the fields.
+fn render(template: &str, fields: &BTreeMap<String, String>) -> Result<String> {
+    let mut out = template.to_string();
+    for field in placeholders(template)? {
+        let Some(value) = fields.get(field) else {
+            bail!("no field {:?} for {:?}", field, template);
+        };
+        out = out.replacen(&format!("{{{}}}", field), value, 1);
+    }
+    Ok(out)
+}
+
+impl QuestionFactory for RecordsData {
+    fn build(&self, data: &[u8]) -> Result<Box<dyn QuestionRunner>> {
+        let mut question = encoding::decode::<RecordQuestion>(data)?;
+        let Some(variant) = self.variants.iter().find(|v| v.id == question.variant) else {
+            bail!("{}: no variant {:?}", question.id, question.variant);
+        };
+        question.question = render(&variant.question, &question.fields)?;
+        question.answer = variant.answer(&question.fields)?;
+        question.normalize = self.normalize.clone();
+        Ok(Box::new(question) as Box<dyn QuestionRunner>)
+    }
+}
+
+impl QuestionSetFactory for RecordsData {
+    fn build_set(&self, s: &SetIndex, set_name: &str) -> Vec<QuestionKey> {
+        s.get_factory(set_name).to_vec()
+    }
+
+    fn depends_on(&self) -> &Vec<String> {
+        &self.depends
+    }
+}
+
+/// One variant asked about one record. Only the fields the variant uses are
+/// stored, so changing another field of the record leaves it alone.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct RecordQuestion {
+    id: String,
+    variant: String,
+    fields: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    #[serde(skip)]
+    question: String,
+    #[serde(skip)]
+    answer: String,
+    #[serde(skip)]
+    normalize: Normalize,
+}
+
+impl QuestionRunner for RecordQuestion {
+    fn run(&self) -> Result<Score> {
+        let mut hints = Hints::new(&[]);
+        let answer = hints.prompt(&self.question, None)?;
+        let correct = self.normalize.eq(&answer, &self.answer);
+        if correct {
+            output::feedback(output::correct("Correct!"));
+        } else {
+            output::feedback(output::wrong(&format!(
+                "Wrong. The answer is {:?}",
+                self.answer
+            )));
+        }
+        println!();
+        Ok(hints.score(correct.into()))
+    }
+
+    fn name(&self) -> String {
+        self.id.clone()
+    }
+
+    fn question_text(&self) -> String {
+        self.question.clone()
+    }
+
+    fn answer_text(&self) -> String {
+        self.answer.clone()
+    }
+}
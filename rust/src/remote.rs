@@ -0,0 +1,214 @@
+use crate::pack::Pack;
+use crate::plugins;
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::Duration;
+use tracing::warn;
+
+/// A list of packs that can be installed by name, served as YAML (or JSON).
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct Index {
+    #[serde(default)]
+    pub packs: Vec<IndexEntry>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct IndexEntry {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Where the pack is downloaded from, relative to the index unless it is
+    /// a full URL.
+    pub url: String,
+    /// Hex encoded SHA-256 of the pack file.
+    pub sha256: String,
+}
+
+impl Index {
+    pub fn fetch(url: &str) -> Result<Index> {
+        let data = download(url)?;
+        let mut index = serde_yaml::from_slice::<Index>(&data)
+            .with_context(|| format!("parsing the index at {}", url))?;
+        for entry in &mut index.packs {
+            entry.url = resolve(url, &entry.url);
+        }
+        Ok(index)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&IndexEntry> {
+        self.packs.iter().find(|p| p.name == name)
+    }
+}
+
+/// Downloads a pack, failing unless its SHA-256 matches the expected one.
+pub fn fetch_pack(url: &str, sha256: &str) -> Result<Pack> {
+    let data = download(url)?;
+    let actual = format!("{:x}", Sha256::digest(&data));
+    if !actual.eq_ignore_ascii_case(sha256.trim()) {
+        bail!(
+            "checksum mismatch for {}: expected {}, got {}",
+            url,
+            sha256,
+            actual
+        );
+    }
+    Pack::read(&data[..]).with_context(|| format!("reading the pack at {}", url))
+}
+
+/// Types whose sets run commands they give themselves, which whoever serves
+/// a remote set, or anyone between it and us, could change at any time.
+/// Types provided by plugins are refused as well, as are sets with their own
+/// speech command.
+pub const EXECUTABLE_TYPES: &[&str] = &["script", "code"];
+
+fn default_max_age_hours() -> f64 {
+    24.
+}
+
+/// A question set maintained by someone else, fetched from `url` whenever
+/// the sets are loaded, e.g.
+///
+/// ```yaml
+/// name: biology
+/// type_: remote
+/// data:
+///   url: https://example.com/sets/biology.yaml
+/// ```
+///
+/// The fetched file is a question set of any other type that doesn't run
+/// commands, see `EXECUTABLE_TYPES`. It takes the name given here, so it
+/// can't clash with the local sets.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RemoteSet {
+    pub name: String,
+    pub data: RemoteData,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RemoteData {
+    pub url: String,
+    /// Hours the fetched set is reused before it's fetched again. It's also
+    /// used when fetching fails, however old.
+    #[serde(default = "default_max_age_hours")]
+    pub max_age_hours: f64,
+}
+
+static CACHE_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Sets where fetched sets are kept for the rest of the program, from the
+/// config file. The user's cache directory by default.
+pub fn init_cache(dir: PathBuf) {
+    let _ = CACHE_DIR.set(dir);
+}
+
+fn cache_dir() -> Option<PathBuf> {
+    match CACHE_DIR.get() {
+        Some(dir) => Some(dir.clone()),
+        None => dirs::cache_dir().map(|dir| dir.join("trivial").join("remote")),
+    }
+}
+
+impl RemoteSet {
+    /// The set from its URL, or from the cache while it's fresh, renamed to
+    /// this one's name.
+    pub fn fetch(&self) -> Result<Vec<u8>> {
+        let cached = cache_dir().map(|dir| {
+            dir.join(format!(
+                "{:x}.yaml",
+                Sha256::digest(self.data.url.as_bytes())
+            ))
+        });
+        let max_age = Duration::from_secs_f64(self.data.max_age_hours.max(0.) * 3600.);
+        let fresh = cached.as_ref().is_some_and(|path| {
+            fs::metadata(path)
+                .and_then(|m| m.modified())
+                .is_ok_and(|time| time.elapsed().is_ok_and(|age| age < max_age))
+        });
+        let mut set = match (fresh, &cached) {
+            (true, Some(path)) => self.parse(&fs::read(path)?)?,
+            _ => match download(&self.data.url) {
+                Ok(data) => {
+                    // Checked before it's cached, so a refused set isn't kept.
+                    let set = self.parse(&data)?;
+                    if let Some(path) = &cached {
+                        if let Err(err) = cache(path, &data) {
+                            warn!("caching {}: {:#}", self.data.url, err);
+                        }
+                    }
+                    set
+                }
+                Err(err) => match cached.as_ref().and_then(|path| fs::read(path).ok()) {
+                    Some(data) => {
+                        warn!("using the cached copy: {:#}", err);
+                        self.parse(&data)?
+                    }
+                    None => return Err(err),
+                },
+            },
+        };
+        set["name"] = self.name.clone().into();
+        Ok(serde_yaml::to_string(&set)?.into_bytes())
+    }
+
+    /// The fetched set, failing if it's remote itself or could run commands.
+    fn parse(&self, data: &[u8]) -> Result<serde_yaml::Value> {
+        let set = serde_yaml::from_slice::<serde_yaml::Value>(data)
+            .with_context(|| format!("parsing the set at {}", self.data.url))?;
+        let type_ = set["type_"].as_str().unwrap_or_default();
+        if type_ == "remote" {
+            bail!("{} is a remote set itself", self.data.url);
+        }
+        if EXECUTABLE_TYPES.contains(&type_) || plugins::is_registered(type_) {
+            bail!(
+                "{} is a {} set, which runs commands and can't be fetched",
+                self.data.url,
+                type_
+            );
+        }
+        let speech = &set["data"]["speech"];
+        if !speech["command"].is_null() || !speech["player"].is_null() {
+            bail!(
+                "{} gives its own speech command, which can't be fetched",
+                self.data.url
+            );
+        }
+        Ok(set)
+    }
+}
+
+fn cache(path: &Path, data: &[u8]) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, data)?;
+    Ok(())
+}
+
+pub fn is_url(s: &str) -> bool {
+    s.starts_with("http://") || s.starts_with("https://")
+}
+
+fn download(url: &str) -> Result<Vec<u8>> {
+    let response = ureq::get(url)
+        .call()
+        .with_context(|| format!("downloading {}", url))?;
+    let mut data = Vec::new();
+    response.into_reader().read_to_end(&mut data)?;
+    Ok(data)
+}
+
+/// Resolves a URL from the index against the index's own URL.
+fn resolve(base: &str, url: &str) -> String {
+    if is_url(url) {
+        return url.to_string();
+    }
+    match base.rfind('/') {
+        Some(i) => format!("{}/{}", &base[..i], url.trim_start_matches("./")),
+        None => url.to_string(),
+    }
+}
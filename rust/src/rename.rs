@@ -0,0 +1,143 @@
+use crate::functionality::parse_models;
+use anyhow::{bail, Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// New contents for a question set file.
+pub struct Edit {
+    pub path: PathBuf,
+    pub contents: String,
+}
+
+/// Finds the changes to the files in the directory that rename the set: the
+/// `name` of the file defining it and its entries in unions. The files are
+/// edited line by line so comments and formatting are kept.
+pub fn rename_set(dir: &Path, old: &str, new: &str) -> Result<Vec<Edit>> {
+    let mut edits = Vec::new();
+    for (path, text) in read_dir(dir)? {
+        let models =
+            parse_models(&[&text]).with_context(|| format!("parsing {}", path.display()))?;
+        let defines = models.sets.contains_key(old);
+        let includes = models
+            .sets
+            .values()
+            .any(|s| s.depends_on().iter().any(|d| d == old));
+        if !defines && !includes {
+            continue;
+        }
+        let contents = edit_lines(&text, |line| {
+            if defines && !line.starts_with(' ') {
+                if let Some(line) = replace_scalar(line, "name:", old, new) {
+                    return Some(line);
+                }
+            }
+            if includes {
+                return replace_scalar(line, "-", old, new);
+            }
+            None
+        });
+        edits.push(Edit { path, contents });
+    }
+    if !edits.is_empty() {
+        check(&edits)?;
+    }
+    Ok(edits)
+}
+
+/// Finds the change to the file defining the set that renames the question's
+/// `id`.
+pub fn rename_question(dir: &Path, set: &str, old: &str, new: &str) -> Result<Vec<Edit>> {
+    for (path, text) in read_dir(dir)? {
+        let models =
+            parse_models(&[&text]).with_context(|| format!("parsing {}", path.display()))?;
+        if !models
+            .questions
+            .iter()
+            .any(|q| q.factory == set && q.name == old)
+        {
+            continue;
+        }
+        let mut done = false;
+        let contents = edit_lines(&text, |line| {
+            if done {
+                return None;
+            }
+            let line = replace_scalar(line, "- id:", old, new)
+                .or_else(|| replace_scalar(line, "id:", old, new));
+            done = line.is_some();
+            line
+        });
+        let edits = vec![Edit { path, contents }];
+        check(&edits)?;
+        return Ok(edits);
+    }
+    Ok(Vec::new())
+}
+
+pub fn apply(edits: &[Edit]) -> Result<()> {
+    for edit in edits {
+        fs::write(&edit.path, &edit.contents)?;
+    }
+    Ok(())
+}
+
+fn read_dir(dir: &Path) -> Result<Vec<(PathBuf, String)>> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_file() {
+            let text = fs::read_to_string(&path)?;
+            files.push((path, text));
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Makes sure the edited files still parse before anything is written.
+fn check(edits: &[Edit]) -> Result<()> {
+    for edit in edits {
+        if let Err(err) = parse_models(&[&edit.contents]) {
+            bail!("renaming would break {}: {:#}", edit.path.display(), err);
+        }
+    }
+    Ok(())
+}
+
+fn edit_lines(text: &str, mut f: impl FnMut(&str) -> Option<String>) -> String {
+    text.split_inclusive('\n')
+        .map(|line| {
+            let (body, end) = match line.strip_suffix('\n') {
+                Some(body) => (body, "\n"),
+                None => (line, ""),
+            };
+            match f(body) {
+                Some(body) => format!("{}{}", body, end),
+                None => line.to_string(),
+            }
+        })
+        .collect()
+}
+
+/// Replaces the YAML scalar after `prefix` on the line if it is `old`,
+/// keeping the indentation, quotes and any trailing comment.
+fn replace_scalar(line: &str, prefix: &str, old: &str, new: &str) -> Option<String> {
+    let rest = line.trim_start();
+    let indent = &line[..line.len() - rest.len()];
+    let value = rest.strip_prefix(prefix)?;
+    let (value, comment) = match value.find(" #") {
+        Some(i) => value.split_at(i),
+        None => (value, ""),
+    };
+    let value = value.trim();
+    if value.trim_matches(|c| c == '\'' || c == '"') != old {
+        return None;
+    }
+    Some(format!(
+        "{}{} {}{}",
+        indent,
+        prefix,
+        value.replacen(old, new, 1),
+        comment
+    ))
+}
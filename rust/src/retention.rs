@@ -0,0 +1,45 @@
+/// An exponential forgetting curve, `R(t) = exp(-t / stability)`, with `t`
+/// in days.
+#[derive(Clone, Copy, Debug)]
+pub struct ForgettingCurve {
+    /// Days after which recall drops to 1/e, about 37%.
+    pub stability: f64,
+}
+
+impl ForgettingCurve {
+    /// Estimated probability of recalling something after `days`.
+    pub fn retention(&self, days: f64) -> f64 {
+        (-days / self.stability).exp()
+    }
+
+    /// Fits the curve to `(days since last review, recalled)` samples by
+    /// maximum likelihood. Returns None if there are no samples.
+    pub fn fit(samples: &[(f64, bool)]) -> Option<ForgettingCurve> {
+        if samples.is_empty() {
+            return None;
+        }
+        // The likelihood is smooth in the stability, so a fine log spaced grid
+        // from about 15 minutes to 30 years is plenty.
+        (0..=600)
+            .map(|i| 10f64.powf(-2. + i as f64 * 6. / 600.))
+            .map(|stability| ForgettingCurve { stability })
+            .max_by(|a, b| {
+                a.log_likelihood(samples)
+                    .total_cmp(&b.log_likelihood(samples))
+            })
+    }
+
+    fn log_likelihood(&self, samples: &[(f64, bool)]) -> f64 {
+        samples
+            .iter()
+            .map(|&(days, recalled)| {
+                let r = self.retention(days).clamp(1e-9, 1. - 1e-9);
+                if recalled {
+                    r.ln()
+                } else {
+                    (1. - r).ln()
+                }
+            })
+            .sum()
+    }
+}
@@ -0,0 +1,444 @@
+use crate::frontend::{self, Frontend, Validator};
+use crate::functionality::{QuestionRunner, Score};
+use crate::output;
+use anyhow::{bail, Context, Result};
+use rand::distributions::{Alphanumeric, Uniform};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+/// Points for a correct answer given right away, answers given when time is
+/// up get half of them.
+pub const MAX_POINTS: u32 = 1000;
+
+/// A question of a room, as shown to the players.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RoomQuestion {
+    pub id: i64,
+    pub text: String,
+    pub answer: String,
+}
+
+/// What every player of a room sees, see `Rooms::view`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct View {
+    pub code: String,
+    pub set: String,
+    /// The question being asked, counting from 1.
+    pub number: usize,
+    pub questions: usize,
+    /// The text of the question being asked, if time isn't up yet.
+    pub question: Option<String>,
+    pub seconds_left: u64,
+    /// The previous question and its answer, once its time is up or the
+    /// next one is asked.
+    pub previous: Option<RoomQuestion>,
+    /// Players that answered the question being asked.
+    pub answered: usize,
+    /// Names and points of the players, the most points first.
+    pub scores: Vec<(String, u32)>,
+    pub finished: bool,
+}
+
+/// The result of an answer, see `Rooms::answer`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Scored {
+    pub correct: bool,
+    pub points: u32,
+}
+
+struct Player {
+    name: String,
+    token: String,
+    points: u32,
+}
+
+struct Round {
+    started: Instant,
+    /// Tokens of the players that answered.
+    answered: Vec<String>,
+}
+
+struct Room {
+    set: String,
+    host: String,
+    questions: Vec<RoomQuestion>,
+    time_limit: Duration,
+    players: Vec<Player>,
+    /// How many questions were asked, the last one is the current one.
+    asked: usize,
+    round: Option<Round>,
+}
+
+impl Room {
+    /// Whether the current question can still be answered, until time is up
+    /// or every player has answered it.
+    fn is_open(&self, now: Instant) -> bool {
+        matches!(&self.round, Some(r) if now.duration_since(r.started) < self.time_limit
+            && (self.players.is_empty() || r.answered.len() < self.players.len()))
+    }
+}
+
+/// Quiz rooms for `trivial serve`, like a pub quiz: a host opens a room on
+/// a set and asks its questions one at a time, every player answers them at
+/// the same time and faster correct answers get more points. Players join
+/// with the room's code and act with the token they get.
+///
+/// Times are passed in, so the rooms can be driven by a test.
+pub struct Rooms {
+    rooms: HashMap<String, Room>,
+    rng: StdRng,
+}
+
+impl Default for Rooms {
+    fn default() -> Self {
+        Rooms::new()
+    }
+}
+
+impl Rooms {
+    pub fn new() -> Rooms {
+        Rooms {
+            rooms: HashMap::new(),
+            rng: StdRng::from_entropy(),
+        }
+    }
+
+    /// Opens a room asking the questions in order, returning its code and
+    /// the token of the host.
+    pub fn create(
+        &mut self,
+        set: &str,
+        questions: Vec<RoomQuestion>,
+        time_limit: Duration,
+    ) -> Result<(String, String)> {
+        if questions.is_empty() {
+            bail!("{} has no questions", set);
+        }
+        let code = loop {
+            let code = (&mut self.rng)
+                .sample_iter(Uniform::new_inclusive(b'A', b'Z'))
+                .take(4)
+                .map(char::from)
+                .collect::<String>();
+            if !self.rooms.contains_key(&code) {
+                break code;
+            }
+        };
+        let host = self.token();
+        self.rooms.insert(
+            code.clone(),
+            Room {
+                set: set.to_string(),
+                host: host.clone(),
+                questions,
+                time_limit,
+                players: Vec::new(),
+                asked: 0,
+                round: None,
+            },
+        );
+        Ok((code, host))
+    }
+
+    /// Adds a player to the room, returning their token.
+    pub fn join(&mut self, code: &str, name: &str) -> Result<String> {
+        let token = self.token();
+        let room = self.room(code)?;
+        let name = name.trim();
+        if name.is_empty() {
+            bail!("players need a name");
+        }
+        if room.players.iter().any(|p| p.name == name) {
+            bail!("{} is already playing", name);
+        }
+        room.players.push(Player {
+            name: name.to_string(),
+            token: token.clone(),
+            points: 0,
+        });
+        Ok(token)
+    }
+
+    /// Asks the next question, ending the current one. Only the host can.
+    pub fn next(&mut self, code: &str, host: &str, now: Instant) -> Result<()> {
+        let room = self.room(code)?;
+        if room.host != host {
+            bail!("only the host can ask the next question");
+        }
+        if room.asked == room.questions.len() {
+            bail!("all the questions were asked");
+        }
+        room.asked += 1;
+        room.round = Some(Round {
+            started: now,
+            answered: Vec::new(),
+        });
+        Ok(())
+    }
+
+    /// The question the player is asked, to score their answer against.
+    pub fn question(&mut self, code: &str, player: &str, now: Instant) -> Result<i64> {
+        let room = self.room(code)?;
+        if !room.players.iter().any(|p| p.token == player) {
+            bail!("not a player of the room");
+        }
+        let Some(round) = &room.round else {
+            bail!("no question asked yet");
+        };
+        if round.answered.iter().any(|t| t == player) {
+            bail!("already answered");
+        }
+        if !room.is_open(now) {
+            bail!("time is up");
+        }
+        Ok(room.questions[room.asked - 1].id)
+    }
+
+    /// Scores the player's answer to the current question, the points going
+    /// down from `MAX_POINTS` to half of them as time runs out.
+    pub fn answer(
+        &mut self,
+        code: &str,
+        player: &str,
+        score: Score,
+        now: Instant,
+    ) -> Result<Scored> {
+        self.question(code, player, now)?;
+        let room = self.room(code)?;
+        let round = room.round.as_mut().unwrap();
+        round.answered.push(player.to_string());
+        let elapsed = now.duration_since(round.started).as_secs_f64();
+        let speed = 1. - elapsed / room.time_limit.as_secs_f64() / 2.;
+        let points = (MAX_POINTS as f64 * speed * score.value()).round() as u32;
+        let p = room.players.iter_mut().find(|p| p.token == player).unwrap();
+        p.points += points;
+        Ok(Scored {
+            correct: score.is_correct(),
+            points,
+        })
+    }
+
+    pub fn view(&mut self, code: &str, now: Instant) -> Result<View> {
+        let room = self.room(code)?;
+        let open = room.is_open(now);
+        let mut scores = room
+            .players
+            .iter()
+            .map(|p| (p.name.clone(), p.points))
+            .collect::<Vec<_>>();
+        scores.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        let previous = match open {
+            true => room.asked.checked_sub(2),
+            false => room.asked.checked_sub(1),
+        };
+        let seconds_left = match (&room.round, open) {
+            (Some(r), true) => (room.time_limit - now.duration_since(r.started)).as_secs(),
+            _ => 0,
+        };
+        Ok(View {
+            code: code.to_string(),
+            set: room.set.clone(),
+            number: room.asked,
+            questions: room.questions.len(),
+            question: match open {
+                true => Some(room.questions[room.asked - 1].text.clone()),
+                false => None,
+            },
+            seconds_left,
+            previous: previous.map(|i| room.questions[i].clone()),
+            answered: room.round.as_ref().map_or(0, |r| r.answered.len()),
+            scores,
+            finished: room.asked == room.questions.len() && !open,
+        })
+    }
+
+    fn room(&mut self, code: &str) -> Result<&mut Room> {
+        match self.rooms.get_mut(&code.to_uppercase()) {
+            Some(room) => Ok(room),
+            None => bail!("there is no room {}", code),
+        }
+    }
+
+    fn token(&mut self) -> String {
+        (&mut self.rng)
+            .sample_iter(Alphanumeric)
+            .take(16)
+            .map(char::from)
+            .collect()
+    }
+}
+
+/// Answers every prompt of a question with a player's reply, for scoring it
+/// away from the terminal.
+struct Reply(RefCell<Option<String>>);
+
+impl Reply {
+    fn take(&self) -> Result<String> {
+        match self.0.borrow_mut().take() {
+            Some(reply) => Ok(reply),
+            None => bail!("the question asks for more than one answer"),
+        }
+    }
+}
+
+impl Frontend for Reply {
+    fn text(&self, _: &str, _: Option<&str>, validator: Option<Validator>) -> Result<String> {
+        let reply = self.take()?;
+        if let Some(Err(err)) = validator.map(|v| v(&reply)) {
+            bail!("{}", err);
+        }
+        Ok(reply)
+    }
+
+    fn editor(&self, _: &str, _: Option<&str>) -> Result<String> {
+        self.take()
+    }
+
+    fn confirm(&self, _: &str) -> Result<bool> {
+        bail!("the question can't be answered in a room");
+    }
+
+    fn select(&self, _: &str, options: &[String]) -> Result<usize> {
+        let reply = self.take()?;
+        match options
+            .iter()
+            .position(|o| o.eq_ignore_ascii_case(reply.trim()))
+        {
+            Some(i) => Ok(i),
+            None => bail!("{:?} isn't one of the options", reply),
+        }
+    }
+
+    fn pause(&self, _: &str) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Scores a reply to the question the way the terminal would, without its
+/// feedback. Questions that need more than one answer, or a confirmation
+/// like flashcards, score wrong.
+pub fn score_reply(runner: &dyn QuestionRunner, reply: &str) -> Score {
+    let previous = frontend::set(Rc::new(Reply(RefCell::new(Some(reply.to_string())))));
+    let silent = output::is_silent();
+    output::set_silent(true);
+    let score = runner.run().unwrap_or(Score::WRONG);
+    output::set_silent(silent);
+    frontend::set(previous);
+    score
+}
+
+/// Body of `POST /rooms`, answered with `Created`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CreateRoom {
+    pub set: String,
+    pub num: usize,
+    pub seconds: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Created {
+    pub code: String,
+    pub host: String,
+}
+
+/// Body of `POST /rooms/<code>/join`, answered with `Joined`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Join {
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Joined {
+    pub player: String,
+}
+
+/// Body of `POST /rooms/<code>/next`, answered with the `View`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Next {
+    pub host: String,
+}
+
+/// Body of `POST /rooms/<code>/answer`, answered with `Scored`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Answer {
+    pub player: String,
+    pub answer: String,
+}
+
+/// Talks to the rooms of a `trivial serve`.
+pub struct Client {
+    server: String,
+}
+
+impl Client {
+    pub fn new(server: &str) -> Client {
+        Client {
+            server: server.trim_end_matches('/').to_string(),
+        }
+    }
+
+    pub fn create(&self, room: &CreateRoom) -> Result<Created> {
+        self.post("/rooms", room)
+    }
+
+    pub fn join(&self, code: &str, name: &str) -> Result<Joined> {
+        let join = Join {
+            name: name.to_string(),
+        };
+        self.post(&format!("/rooms/{}/join", code), &join)
+    }
+
+    pub fn next(&self, code: &str, host: &str) -> Result<View> {
+        let next = Next {
+            host: host.to_string(),
+        };
+        self.post(&format!("/rooms/{}/next", code), &next)
+    }
+
+    pub fn view(&self, code: &str) -> Result<View> {
+        let url = format!("{}/rooms/{}", self.server, code);
+        read(ureq::get(&url).call(), &url)
+    }
+
+    pub fn answer(&self, code: &str, player: &str, answer: &str) -> Result<Scored> {
+        let answer = Answer {
+            player: player.to_string(),
+            answer: answer.to_string(),
+        };
+        self.post(&format!("/rooms/{}/answer", code), &answer)
+    }
+
+    fn post<B: Serialize, T: DeserializeOwned>(&self, path: &str, body: &B) -> Result<T> {
+        let url = format!("{}{}", self.server, path);
+        let response = ureq::post(&url)
+            .set("Content-Type", "application/json")
+            .send_string(&serde_json::to_string(body)?);
+        read(response, &url)
+    }
+}
+
+/// The body of a response, or the error the server answered with.
+fn read<T: DeserializeOwned>(
+    response: Result<ureq::Response, ureq::Error>,
+    url: &str,
+) -> Result<T> {
+    match response {
+        Ok(response) => Ok(serde_json::from_str(&response.into_string()?)?),
+        Err(ureq::Error::Status(_, response)) => {
+            let body = response.into_string()?;
+            match serde_json::from_str::<serde_json::Value>(&body) {
+                Ok(serde_json::Value::Object(error)) if error.contains_key("error") => {
+                    bail!("{}", error["error"].as_str().unwrap_or_default())
+                }
+                _ => bail!("{}: {}", url, body.trim()),
+            }
+        }
+        Err(err) => Err(err).with_context(|| format!("connecting to {}", url)),
+    }
+}
@@ -0,0 +1,38 @@
+use anyhow::{bail, Result};
+
+/// A commented example set for every type of question set, see `templates/`.
+const TEMPLATES: [(&str, &str); 16] = [
+    ("default", include_str!("../templates/default.yaml")),
+    (
+        "numeric_range",
+        include_str!("../templates/numeric_range.yaml"),
+    ),
+    ("vocab", include_str!("../templates/vocab.yaml")),
+    ("typing", include_str!("../templates/typing.yaml")),
+    ("code", include_str!("../templates/code.yaml")),
+    ("script", include_str!("../templates/script.yaml")),
+    ("math", include_str!("../templates/math.yaml")),
+    ("arithmetic", include_str!("../templates/arithmetic.yaml")),
+    ("flashcard", include_str!("../templates/flashcard.yaml")),
+    ("sequence", include_str!("../templates/sequence.yaml")),
+    ("geo", include_str!("../templates/geo.yaml")),
+    ("records", include_str!("../templates/records.yaml")),
+    ("template", include_str!("../templates/template.yaml")),
+    ("union", include_str!("../templates/union.yaml")),
+    ("questions", include_str!("../templates/questions.yaml")),
+    ("threshold", include_str!("../templates/threshold.yaml")),
+];
+
+/// The types of question sets there are templates for.
+pub fn types() -> Vec<&'static str> {
+    TEMPLATES.iter().map(|(type_, _)| *type_).collect()
+}
+
+/// A question set file of the type, with one example question and comments
+/// explaining the fields.
+pub fn scaffold(type_: &str, name: &str) -> Result<String> {
+    match TEMPLATES.iter().find(|(t, _)| *t == type_) {
+        Some((_, template)) => Ok(template.replace("{name}", name)),
+        None => bail!("unknown question set type {:?}", type_),
+    }
+}
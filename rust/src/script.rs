@@ -0,0 +1,223 @@
+use crate::codeeval::run_command;
+use crate::encoding;
+use crate::functionality::{
+    FactoryOptions, QuestionFactory, QuestionKey, QuestionRunner, QuestionSetFactory, Score,
+    SetIndex,
+};
+use crate::hints::Hints;
+use crate::output;
+use crate::plugins;
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
+use std::collections::BTreeMap;
+
+fn default_timeout() -> u64 {
+    5
+}
+
+/// Questions checked by a script shipped with the set, for answers the other
+/// types can't score. The script runs with `interpreter`, which gets it as
+/// its last argument, so any interpreter on PATH works, e.g. `[lua, -e]`,
+/// `[python3, -c]` or `[sh, -c]`:
+///
+/// ```yaml
+/// name: primes
+/// type_: script
+/// data:
+///   interpreter: [python3, -c]
+///   script: |
+///     import json, sys
+///     request = json.load(sys.stdin)
+///     n = int(request["answer"])
+///     prime = n > 1 and all(n % d for d in range(2, n))
+///     print(json.dumps({"score": 1 if prime and n > request["item"]["above"] else 0}))
+/// items:
+/// - id: prime-above-50
+///   question: Name a prime above 50
+///   above: 50
+/// ```
+///
+/// The script reads a JSON object on stdin with the question as `item` and
+/// the answer as `answer`, and writes a JSON object to stdout:
+/// `{"score": 1}` with a score from 0 to 1, optionally with `feedback` to
+/// show, or `{"invalid": "why"}` to ask for the answer again. With `prompt`
+/// set, it's first run without an answer and writes `{"prompt": "..."}`,
+/// the text to ask instead of the question.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct ScriptData {
+    interpreter: Vec<String>,
+    script: String,
+    /// Let the script write the prompt, e.g. to ask about a random value.
+    #[serde(default)]
+    prompt: bool,
+    /// Seconds every run of the script may take before it's killed.
+    #[serde(default = "default_timeout")]
+    timeout: u64,
+    #[serde(flatten)]
+    options: FactoryOptions,
+    #[serde(skip)]
+    depends: Vec<String>,
+}
+
+impl QuestionFactory for ScriptData {
+    fn build(&self, data: &[u8]) -> Result<Box<dyn QuestionRunner>> {
+        if self.interpreter.is_empty() {
+            bail!("the question set doesn't configure an interpreter");
+        }
+        let mut question = encoding::decode::<ScriptQuestion>(data)?;
+        let mut command = self.interpreter.clone();
+        command.push(self.script.clone());
+        question.backend = Backend::Command {
+            command,
+            timeout: self.timeout,
+        };
+        question.prompt = self.prompt;
+        Ok(Box::new(question) as Box<dyn QuestionRunner>)
+    }
+}
+
+impl QuestionSetFactory for ScriptData {
+    fn build_set(&self, s: &SetIndex, set_name: &str) -> Vec<QuestionKey> {
+        s.get_factory(set_name).to_vec()
+    }
+
+    fn depends_on(&self) -> &Vec<String> {
+        &self.depends
+    }
+}
+
+/// A question of a script set. Fields besides the usual ones are passed on to
+/// the script with the rest of the question.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct ScriptQuestion {
+    id: String,
+    #[serde(default)]
+    question: String,
+    /// Shown as the answer; the script decides what's correct.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    answer: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    hints: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    /// Questions to know before this one is introduced, see
+    /// `Question::requires`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    requires: Vec<String>,
+    #[serde(flatten)]
+    fields: BTreeMap<String, Value>,
+    #[serde(skip)]
+    backend: Backend,
+    #[serde(skip)]
+    prompt: bool,
+}
+
+/// What checks the answers.
+#[derive(Debug, Clone, Default)]
+enum Backend {
+    #[default]
+    None,
+    /// The set's script, run with its interpreter.
+    Command { command: Vec<String>, timeout: u64 },
+    /// A question type of a plugin, passed the set's data with every call.
+    Plugin {
+        type_: String,
+        data: serde_json::Value,
+    },
+}
+
+/// What the script writes to stdout.
+#[derive(Deserialize, Debug)]
+struct Reply {
+    prompt: Option<String>,
+    score: Option<f64>,
+    feedback: Option<String>,
+    invalid: Option<String>,
+}
+
+impl ScriptQuestion {
+    /// The question checked by the plugin's question type instead of a
+    /// script, see `plugins::PluginType`.
+    pub(crate) fn with_plugin(
+        mut self,
+        type_: &str,
+        data: serde_json::Value,
+        prompt: bool,
+    ) -> Self {
+        self.backend = Backend::Plugin {
+            type_: type_.to_string(),
+            data,
+        };
+        self.prompt = prompt;
+        self
+    }
+
+    /// Runs the script on the question and the answer, if there is one.
+    fn call(&self, answer: Option<&str>) -> Result<Reply> {
+        let mut request = serde_json::json!({ "item": self, "answer": answer });
+        let output = match &self.backend {
+            Backend::None => bail!("{}: nothing checks the answers", self.id),
+            Backend::Command { command, timeout } => {
+                run_command(command, &request.to_string(), *timeout)?
+            }
+            Backend::Plugin { type_, data } => {
+                request["data"] = data.clone();
+                plugins::call(type_, &request.to_string())?
+            }
+        };
+        serde_json::from_str(output.trim())
+            .with_context(|| format!("the script wrote {:?}, not a reply", output.trim()))
+    }
+}
+
+impl QuestionRunner for ScriptQuestion {
+    fn run(&self) -> Result<Score> {
+        let question = match self.prompt {
+            true => match self.call(None)?.prompt {
+                Some(prompt) => prompt,
+                None => bail!("{}: the script didn't write a prompt", self.id),
+            },
+            false => self.question.clone(),
+        };
+        let mut hints = Hints::new(&self.hints);
+        let reply = loop {
+            let answer = hints.prompt(&question, None)?;
+            let reply = self.call(Some(&answer))?;
+            match &reply.invalid {
+                Some(invalid) => println!("{}", output::wrong(invalid)),
+                None => break reply,
+            }
+        };
+        let Some(score) = reply.score else {
+            bail!("{}: the script didn't write a score", self.id);
+        };
+        let score = Score::new(score);
+        let feedback =
+            reply
+                .feedback
+                .unwrap_or_else(|| match (score.is_correct(), self.answer.is_empty()) {
+                    (true, _) => "Correct!".to_string(),
+                    (false, true) => "Wrong.".to_string(),
+                    (false, false) => format!("Wrong. The answer is {:?}", self.answer),
+                });
+        match score.is_correct() {
+            true => output::feedback(output::correct(&feedback)),
+            false => output::feedback(output::wrong(&feedback)),
+        }
+        println!();
+        Ok(hints.score(score))
+    }
+
+    fn name(&self) -> String {
+        self.id.clone()
+    }
+
+    fn question_text(&self) -> String {
+        self.question.clone()
+    }
+
+    fn answer_text(&self) -> String {
+        self.answer.clone()
+    }
+}
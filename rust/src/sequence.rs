@@ -0,0 +1,127 @@
+use crate::encoding;
+use crate::functionality::{
+    FactoryOptions, QuestionFactory, QuestionKey, QuestionRunner, QuestionSetFactory, Score,
+    SetIndex,
+};
+use crate::hints::Hints;
+use crate::input;
+use crate::normalize::Normalize;
+use crate::output;
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct SequenceData {
+    #[serde(default)]
+    normalize: Normalize,
+    #[serde(flatten)]
+    options: FactoryOptions,
+    #[serde(skip)]
+    depends: Vec<String>,
+}
+
+impl QuestionFactory for SequenceData {
+    fn build(&self, data: &[u8]) -> Result<Box<dyn QuestionRunner>> {
+        let mut question = encoding::decode::<Sequence>(data)?;
+        if question.steps.is_empty() {
+            bail!("a sequence needs at least one step");
+        }
+        question.normalize = self.normalize.clone();
+        Ok(Box::new(question) as Box<dyn QuestionRunner>)
+    }
+}
+
+impl QuestionSetFactory for SequenceData {
+    fn build_set(&self, s: &SetIndex, set_name: &str) -> Vec<QuestionKey> {
+        s.get_factory(set_name).to_vec()
+    }
+
+    fn depends_on(&self) -> &Vec<String> {
+        &self.depends
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+struct Step {
+    question: String,
+    /// Accepted answers, the first one shown when the answer is wrong.
+    answers: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    hints: Vec<String>,
+}
+
+/// Questions asked one after another in a fixed order, e.g. the steps of a
+/// proof. The sequence is scheduled as one question and scores the share of
+/// steps answered correctly. A wrong step reveals its answer before moving
+/// on, so the later steps can build on it.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Sequence {
+    id: String,
+    /// Shown before the first step.
+    question: String,
+    steps: Vec<Step>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    /// Questions to know before this one is introduced, see
+    /// `Question::requires`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    requires: Vec<String>,
+    #[serde(skip)]
+    normalize: Normalize,
+}
+
+/// Formats whether each step was answered correctly, for `Answer::steps`.
+pub fn format_steps(correct: &[bool]) -> String {
+    correct.iter().map(|&c| if c { '+' } else { '-' }).collect()
+}
+
+/// The inverse of `format_steps`.
+pub fn parse_steps(steps: &str) -> Vec<bool> {
+    steps.chars().map(|c| c == '+').collect()
+}
+
+impl QuestionRunner for Sequence {
+    fn run(&self) -> Result<Score> {
+        println!("{}", self.question);
+        let mut results = Vec::new();
+        let mut answers = Vec::new();
+        let mut total = 0.;
+        for (i, step) in self.steps.iter().enumerate() {
+            let mut hints = Hints::new(&step.hints);
+            let prompt = format!("{}/{} {}", i + 1, self.steps.len(), step.question);
+            let answer = hints.prompt(&prompt, None)?;
+            let correct = step.answers.iter().any(|a| self.normalize.eq(a, &answer));
+            if correct {
+                output::feedback(output::correct("Correct!"));
+            } else {
+                output::feedback(output::wrong(&format!(
+                    "Wrong. The answer is {:?}",
+                    step.answers[0]
+                )));
+            }
+            total += hints.score(correct.into()).value();
+            results.push(correct);
+            answers.push(answer);
+        }
+        input::record_text(&answers.join("\n"));
+        input::record_steps(&format_steps(&results));
+        println!();
+        Ok(Score::new(total / self.steps.len() as f64))
+    }
+
+    fn name(&self) -> String {
+        self.id.clone()
+    }
+
+    fn question_text(&self) -> String {
+        self.question.clone()
+    }
+
+    fn answer_text(&self) -> String {
+        self.steps
+            .iter()
+            .map(|s| s.answers[0].as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
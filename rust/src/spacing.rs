@@ -0,0 +1,31 @@
+/// Reorders the items so that related ones are at least `min_distance`
+/// apart, e.g. 2 to never ask them right after each other. Items keep their
+/// order as far as the constraint allows. When nothing left fits, the next
+/// item is placed anyway, so the constraint is best effort.
+pub fn space<T: Copy>(items: &[T], min_distance: usize, related: impl Fn(T, T) -> bool) -> Vec<T> {
+    let mut remaining = items.to_vec();
+    let mut placed: Vec<T> = Vec::with_capacity(items.len());
+    while !remaining.is_empty() {
+        let recent = &placed[placed.len().saturating_sub(min_distance.saturating_sub(1))..];
+        let next = remaining
+            .iter()
+            .position(|&item| !recent.iter().any(|&p| related(p, item)))
+            .unwrap_or(0);
+        placed.push(remaining.remove(next));
+    }
+    placed
+}
+
+/// The lowercase words of the text, ignoring punctuation.
+pub fn words(s: &str) -> Vec<String> {
+    s.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Whether `needle` appears as a run of whole words in `haystack`.
+pub fn contains_words(haystack: &[String], needle: &[String]) -> bool {
+    !needle.is_empty() && haystack.windows(needle.len()).any(|w| w == needle)
+}
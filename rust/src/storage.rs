@@ -0,0 +1,743 @@
+use crate::db::{
+    Answer, AnswerDetails, Asset, Goal, ImportCounts, Load, Question, QuestionFactory, QuestionSet,
+    SetMembership, User,
+};
+use crate::functionality::{rename_set_references, ProbabilityComputer};
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Mutex;
+
+/// Where questions, answers and sets are kept. `db::Repository` stores them
+/// in SQLite; other backends only have to implement this for the service
+/// layer to use them.
+#[async_trait]
+pub trait Storage: Send + Sync {
+    async fn get_all_questions(&self) -> Result<Vec<Question>>;
+    async fn has_question(&self, factory: &str, name: &str) -> Result<bool>;
+    async fn get_question_by_name(&self, factory: &str, name: &str) -> Result<Question>;
+    async fn get_question_by_id(&self, id: i64) -> Result<Question>;
+    async fn insert_question(&self, factory: &str, name: &str, data: &[u8]) -> Result<()>;
+    async fn set_probability(&self, question_id: i64, probability: f64) -> Result<()>;
+    /// Records the answer and updates the question's counts atomically.
+    async fn add_answer(
+        &self,
+        question_id: i64,
+        time: DateTime<Utc>,
+        correct: bool,
+        score: f64,
+        details: &AnswerDetails,
+        new_prob: f64,
+    ) -> Result<()>;
+    /// Deletes the question's most recent answer and takes it out of the
+    /// question's counts atomically. Returns false if it had no answers.
+    async fn delete_last_answer(&self, question_id: i64, new_prob: f64) -> Result<bool>;
+    async fn get_all_answers(&self) -> Result<Vec<Answer>>;
+    /// Answers to the question, oldest first.
+    async fn get_answers(&self, question_id: i64) -> Result<Vec<Answer>>;
+    /// Answers to the questions in the set.
+    async fn get_answers_in_set(&self, name: &str) -> Result<Vec<Answer>>;
+    /// Names of the sets the question is in.
+    async fn get_sets_of_question(&self, question_id: i64) -> Result<Vec<String>>;
+    /// Questions in the set, ordered by factory and name.
+    async fn get_questions_in_set(&self, name: &str) -> Result<Vec<Question>>;
+    async fn has_question_in_set(&self, name: &str, question_id: i64) -> Result<bool>;
+    async fn insert_question_in_set(&self, name: &str, question_id: i64) -> Result<()>;
+    async fn get_all_question_sets(&self) -> Result<Vec<QuestionSet>>;
+    async fn has_question_factory(&self, name: &str) -> Result<bool>;
+    async fn insert_question_factory(
+        &self,
+        name: &str,
+        factory_type: &str,
+        data: &[u8],
+    ) -> Result<()>;
+    async fn get_question_factory(&self, name: &str) -> Result<Option<QuestionFactory>>;
+    async fn get_all_question_factories(&self) -> Result<Vec<QuestionFactory>>;
+    /// Goals ordered by set name.
+    async fn get_all_goals(&self) -> Result<Vec<Goal>>;
+    /// Sets the set's goal, replacing any previous one.
+    async fn set_goal(&self, goal: &Goal) -> Result<()>;
+    /// Returns false if the set had no goal.
+    async fn delete_goal(&self, set_name: &str) -> Result<bool>;
+    /// Keeps a record of a run of `trivial load`, the id is assigned.
+    async fn record_load(&self, load: &Load) -> Result<()>;
+    /// The recorded loads, oldest first.
+    async fn get_loads(&self) -> Result<Vec<Load>>;
+    /// The players of versus games, ordered by name.
+    async fn get_users(&self) -> Result<Vec<User>>;
+    /// Adds the totals of a game to those of its players, in a single
+    /// transaction, creating the players that are new.
+    async fn record_game(&self, players: &[User]) -> Result<()>;
+    /// Names of the archived sets, ordered by name.
+    async fn get_archived_sets(&self) -> Result<Vec<String>>;
+    /// Archives or unarchives the set, returning false if it already was.
+    async fn set_archived(&self, name: &str, archived: bool) -> Result<bool>;
+    /// Inserts questions, factories and set memberships in a single
+    /// transaction. Existing questions and factories get their data updated
+    /// if it changed, after the questions renamed by id got their new names.
+    /// Then takes the `removed` memberships out of their sets and deletes the
+    /// `pruned` questions, see `delete_questions`. Either everything is
+    /// imported or, on error, nothing is.
+    async fn import(
+        &self,
+        questions: &[Question],
+        factories: &[QuestionFactory],
+        memberships: &[SetMembership],
+        renames: &[(i64, String)],
+        removed: &[SetMembership],
+        pruned: &[i64],
+    ) -> Result<ImportCounts>;
+    /// Takes the questions out of the sets, returning how many were in them.
+    async fn remove_memberships(&self, memberships: &[SetMembership]) -> Result<u64>;
+    /// Updates the data of the questions, matched by id, and adds them to
+    /// and takes them out of sets, all in a single transaction.
+    async fn edit_questions(
+        &self,
+        questions: &[Question],
+        added: &[SetMembership],
+        removed: &[SetMembership],
+    ) -> Result<()>;
+    /// Renames a set everywhere its name is stored: its memberships and goal,
+    /// for sets with questions of their own the factory and the questions,
+    /// and the data of the sets referring to it, see
+    /// `functionality::rename_set_references`. Fails if a set or factory with
+    /// the new name exists.
+    async fn rename_set(&self, old: &str, new: &str) -> Result<()>;
+    /// Renames a question, failing if it doesn't exist or the new name is
+    /// taken.
+    async fn rename_question(&self, factory: &str, old: &str, new: &str) -> Result<()>;
+    /// Deletes the questions along with their answers and set memberships.
+    async fn delete_questions(&self, question_ids: &[i64]) -> Result<()>;
+    /// Moves the answers and set memberships of the duplicates to the canonical
+    /// question and deletes the duplicates. The canonical question's counts
+    /// and probability then cover the answers of all of them.
+    async fn merge_questions(&self, canonical: i64, duplicates: &[i64]) -> Result<()>;
+    /// The assets recorded by the last load, by path.
+    async fn get_assets(&self) -> Result<Vec<Asset>>;
+    /// Replaces the recorded assets.
+    async fn set_assets(&self, assets: &[Asset]) -> Result<()>;
+    /// Pairs of linked questions, each with the lower id first, see
+    /// `functionality::ProbabilityComputer::link`.
+    async fn get_links(&self) -> Result<Vec<(i64, i64)>>;
+    /// Links the questions, returning false if they already were.
+    async fn link_questions(&self, a: i64, b: i64) -> Result<bool>;
+    /// Returns false if the questions weren't linked.
+    async fn unlink_questions(&self, a: i64, b: i64) -> Result<bool>;
+}
+
+/// Keeps everything in memory, for tests that don't need SQLite.
+#[derive(Default)]
+pub struct MemoryStorage {
+    state: Mutex<State>,
+}
+
+#[derive(Default, Clone)]
+struct State {
+    questions: BTreeMap<i64, Question>,
+    answers: Vec<Answer>,
+    sets: Vec<QuestionSet>,
+    factories: Vec<QuestionFactory>,
+    goals: BTreeMap<String, Goal>,
+    archived: BTreeSet<String>,
+    loads: Vec<Load>,
+    links: BTreeSet<(i64, i64)>,
+    assets: BTreeMap<String, Asset>,
+    users: BTreeMap<String, User>,
+    next_id: i64,
+}
+
+impl State {
+    fn next_id(&mut self) -> i64 {
+        self.next_id += 1;
+        self.next_id
+    }
+
+    fn find(&self, factory: &str, name: &str) -> Option<&Question> {
+        self.questions
+            .values()
+            .find(|q| q.factory == factory && q.name == name)
+    }
+
+    fn question_mut(&mut self, id: i64) -> Result<&mut Question> {
+        match self.questions.get_mut(&id) {
+            Some(q) => Ok(q),
+            None => bail!("no question with id {}", id),
+        }
+    }
+
+    fn insert_question(&mut self, q: Question) -> Result<()> {
+        if self.find(&q.factory, &q.name).is_some() {
+            bail!("question {}/{} already exists", q.factory, q.name);
+        }
+        let id = self.next_id();
+        self.questions.insert(id, Question { id, ..q });
+        Ok(())
+    }
+
+    /// Returns false if the question already was in the set.
+    fn insert_in_set(&mut self, name: &str, question_id: i64) -> bool {
+        if self
+            .sets
+            .iter()
+            .any(|s| s.name == name && s.question_id == question_id)
+        {
+            return false;
+        }
+        let id = self.next_id();
+        self.sets.push(QuestionSet {
+            id,
+            name: name.to_string(),
+            question_id,
+        });
+        true
+    }
+
+    /// Returns how many of the questions were in the sets.
+    fn remove_memberships(&mut self, memberships: &[SetMembership]) -> u64 {
+        let before = self.sets.len();
+        for m in memberships {
+            if let Some(id) = self.find(&m.factory, &m.name).map(|q| q.id) {
+                self.sets
+                    .retain(|qs| qs.name != m.set || qs.question_id != id);
+            }
+        }
+        (before - self.sets.len()) as u64
+    }
+
+    fn delete_questions(&mut self, question_ids: &[i64]) {
+        self.answers
+            .retain(|a| !question_ids.contains(&a.question_id));
+        self.sets
+            .retain(|qs| !question_ids.contains(&qs.question_id));
+        self.links
+            .retain(|(a, b)| !question_ids.contains(a) && !question_ids.contains(b));
+        self.questions.retain(|id, _| !question_ids.contains(id));
+    }
+}
+
+impl MemoryStorage {
+    pub fn new() -> MemoryStorage {
+        MemoryStorage::default()
+    }
+
+    fn with<T>(&self, f: impl FnOnce(&mut State) -> Result<T>) -> Result<T> {
+        f(&mut self.state.lock().unwrap())
+    }
+
+    /// Runs `f` on a copy of the state, keeping its changes only if it
+    /// succeeds, like a transaction.
+    fn atomically<T>(&self, f: impl FnOnce(&mut State) -> Result<T>) -> Result<T> {
+        let mut state = self.state.lock().unwrap();
+        let mut copy = state.clone();
+        let res = f(&mut copy)?;
+        *state = copy;
+        Ok(res)
+    }
+}
+
+#[async_trait]
+impl Storage for MemoryStorage {
+    async fn get_all_questions(&self) -> Result<Vec<Question>> {
+        self.with(|s| Ok(s.questions.values().cloned().collect()))
+    }
+
+    async fn has_question(&self, factory: &str, name: &str) -> Result<bool> {
+        self.with(|s| Ok(s.find(factory, name).is_some()))
+    }
+
+    async fn get_question_by_name(&self, factory: &str, name: &str) -> Result<Question> {
+        self.with(|s| match s.find(factory, name) {
+            Some(q) => Ok(q.clone()),
+            None => bail!("no question {}/{}", factory, name),
+        })
+    }
+
+    async fn get_question_by_id(&self, id: i64) -> Result<Question> {
+        self.with(|s| Ok(s.question_mut(id)?.clone()))
+    }
+
+    async fn insert_question(&self, factory: &str, name: &str, data: &[u8]) -> Result<()> {
+        self.with(|s| {
+            s.insert_question(Question {
+                factory: factory.to_string(),
+                name: name.to_string(),
+                created_at: Utc::now(),
+                probability: 0.5,
+                num_correct: 1,
+                num_incorrect: 1,
+                data: data.to_vec(),
+                ..Default::default()
+            })
+        })
+    }
+
+    async fn set_probability(&self, question_id: i64, probability: f64) -> Result<()> {
+        self.with(|s| {
+            s.question_mut(question_id)?.probability = probability;
+            Ok(())
+        })
+    }
+
+    async fn add_answer(
+        &self,
+        question_id: i64,
+        time: DateTime<Utc>,
+        correct: bool,
+        score: f64,
+        details: &AnswerDetails,
+        new_prob: f64,
+    ) -> Result<()> {
+        self.atomically(|s| {
+            let q = s.question_mut(question_id)?;
+            q.probability = new_prob;
+            q.last_answered_at = q.last_answered_at.max(Some(time));
+            if correct {
+                q.num_correct += 1;
+            } else {
+                q.num_incorrect += 1;
+            }
+            let id = s.next_id();
+            s.answers.push(Answer {
+                id,
+                question_id,
+                time,
+                correct,
+                score,
+                error: details.error,
+                text: details.text.clone(),
+                steps: details.steps.clone(),
+                params: details.params.clone(),
+                tolerance: details.tolerance,
+            });
+            Ok(())
+        })
+    }
+
+    async fn delete_last_answer(&self, question_id: i64, new_prob: f64) -> Result<bool> {
+        self.atomically(|s| {
+            let last = s
+                .answers
+                .iter()
+                .enumerate()
+                .filter(|(_, a)| a.question_id == question_id)
+                .max_by_key(|(_, a)| a.time)
+                .map(|(i, _)| i);
+            let Some(i) = last else {
+                return Ok(false);
+            };
+            let answer = s.answers.remove(i);
+            let previous = s
+                .answers
+                .iter()
+                .filter(|a| a.question_id == question_id)
+                .map(|a| a.time)
+                .max();
+            let q = s.question_mut(question_id)?;
+            if answer.correct {
+                q.num_correct -= 1;
+            } else {
+                q.num_incorrect -= 1;
+            }
+            q.probability = new_prob;
+            q.last_answered_at = previous;
+            Ok(true)
+        })
+    }
+
+    async fn get_all_answers(&self) -> Result<Vec<Answer>> {
+        self.with(|s| Ok(s.answers.clone()))
+    }
+
+    async fn get_answers(&self, question_id: i64) -> Result<Vec<Answer>> {
+        self.with(|s| {
+            let mut answers = s
+                .answers
+                .iter()
+                .filter(|a| a.question_id == question_id)
+                .cloned()
+                .collect::<Vec<_>>();
+            answers.sort_by_key(|a| a.time);
+            Ok(answers)
+        })
+    }
+
+    async fn get_answers_in_set(&self, name: &str) -> Result<Vec<Answer>> {
+        self.with(|s| {
+            let ids = s
+                .sets
+                .iter()
+                .filter(|qs| qs.name == name)
+                .map(|qs| qs.question_id)
+                .collect::<Vec<_>>();
+            Ok(s.answers
+                .iter()
+                .filter(|a| ids.contains(&a.question_id))
+                .cloned()
+                .collect())
+        })
+    }
+
+    async fn get_sets_of_question(&self, question_id: i64) -> Result<Vec<String>> {
+        self.with(|s| {
+            let mut names = s
+                .sets
+                .iter()
+                .filter(|qs| qs.question_id == question_id)
+                .map(|qs| qs.name.clone())
+                .collect::<Vec<_>>();
+            names.sort();
+            Ok(names)
+        })
+    }
+
+    async fn get_questions_in_set(&self, name: &str) -> Result<Vec<Question>> {
+        self.with(|s| {
+            let mut questions = s
+                .sets
+                .iter()
+                .filter(|qs| qs.name == name)
+                .filter_map(|qs| s.questions.get(&qs.question_id).cloned())
+                .collect::<Vec<_>>();
+            questions.sort_by(|a, b| (&a.factory, &a.name).cmp(&(&b.factory, &b.name)));
+            Ok(questions)
+        })
+    }
+
+    async fn has_question_in_set(&self, name: &str, question_id: i64) -> Result<bool> {
+        self.with(|s| {
+            Ok(s.sets
+                .iter()
+                .any(|qs| qs.name == name && qs.question_id == question_id))
+        })
+    }
+
+    async fn insert_question_in_set(&self, name: &str, question_id: i64) -> Result<()> {
+        self.with(|s| {
+            if !s.insert_in_set(name, question_id) {
+                bail!("question {} is already in {}", question_id, name);
+            }
+            Ok(())
+        })
+    }
+
+    async fn get_all_question_sets(&self) -> Result<Vec<QuestionSet>> {
+        self.with(|s| Ok(s.sets.clone()))
+    }
+
+    async fn has_question_factory(&self, name: &str) -> Result<bool> {
+        self.with(|s| Ok(s.factories.iter().any(|f| f.name == name)))
+    }
+
+    async fn insert_question_factory(
+        &self,
+        name: &str,
+        factory_type: &str,
+        data: &[u8],
+    ) -> Result<()> {
+        self.with(|s| {
+            if s.factories.iter().any(|f| f.name == name) {
+                bail!("factory {} already exists", name);
+            }
+            let id = s.next_id();
+            s.factories.push(QuestionFactory {
+                id,
+                name: name.to_string(),
+                factory_type: factory_type.to_string(),
+                data: data.to_vec(),
+            });
+            Ok(())
+        })
+    }
+
+    async fn get_question_factory(&self, name: &str) -> Result<Option<QuestionFactory>> {
+        self.with(|s| Ok(s.factories.iter().find(|f| f.name == name).cloned()))
+    }
+
+    async fn get_all_question_factories(&self) -> Result<Vec<QuestionFactory>> {
+        self.with(|s| Ok(s.factories.clone()))
+    }
+
+    async fn get_all_goals(&self) -> Result<Vec<Goal>> {
+        self.with(|s| Ok(s.goals.values().cloned().collect()))
+    }
+
+    async fn set_goal(&self, goal: &Goal) -> Result<()> {
+        self.with(|s| {
+            s.goals.insert(goal.set_name.clone(), goal.clone());
+            Ok(())
+        })
+    }
+
+    async fn delete_goal(&self, set_name: &str) -> Result<bool> {
+        self.with(|s| Ok(s.goals.remove(set_name).is_some()))
+    }
+
+    async fn record_load(&self, load: &Load) -> Result<()> {
+        self.with(|s| {
+            let id = s.next_id();
+            s.loads.push(Load { id, ..load.clone() });
+            Ok(())
+        })
+    }
+
+    async fn get_loads(&self) -> Result<Vec<Load>> {
+        self.with(|s| {
+            let mut loads = s.loads.clone();
+            loads.sort_by_key(|l| (l.time, l.id));
+            Ok(loads)
+        })
+    }
+
+    async fn get_users(&self) -> Result<Vec<User>> {
+        self.with(|s| Ok(s.users.values().cloned().collect()))
+    }
+
+    async fn record_game(&self, players: &[User]) -> Result<()> {
+        self.with(|s| {
+            for p in players {
+                let user = s.users.entry(p.name.clone()).or_insert_with(|| User {
+                    name: p.name.clone(),
+                    ..Default::default()
+                });
+                user.games += p.games;
+                user.wins += p.wins;
+                user.answers += p.answers;
+                user.correct += p.correct;
+                user.points += p.points;
+            }
+            Ok(())
+        })
+    }
+
+    async fn get_archived_sets(&self) -> Result<Vec<String>> {
+        self.with(|s| Ok(s.archived.iter().cloned().collect()))
+    }
+
+    async fn set_archived(&self, name: &str, archived: bool) -> Result<bool> {
+        self.with(|s| match archived {
+            true => Ok(s.archived.insert(name.to_string())),
+            false => Ok(s.archived.remove(name)),
+        })
+    }
+
+    async fn import(
+        &self,
+        questions: &[Question],
+        factories: &[QuestionFactory],
+        memberships: &[SetMembership],
+        renames: &[(i64, String)],
+        removed: &[SetMembership],
+        pruned: &[i64],
+    ) -> Result<ImportCounts> {
+        let created_at = Utc::now();
+        self.atomically(|s| {
+            let mut counts = ImportCounts::default();
+            for (id, name) in renames {
+                s.question_mut(*id)?.name = name.clone();
+            }
+            for q in questions {
+                let existing = s.find(&q.factory, &q.name).map(|e| e.id);
+                match existing {
+                    Some(id) => {
+                        let e = s.question_mut(id)?;
+                        if e.data != q.data || e.content_hash != q.content_hash {
+                            e.data = q.data.clone();
+                            e.content_hash = q.content_hash.clone();
+                            counts.questions += 1;
+                        }
+                    }
+                    None => {
+                        s.insert_question(Question {
+                            created_at,
+                            probability: 0.5,
+                            num_correct: 1,
+                            num_incorrect: 1,
+                            last_answered_at: None,
+                            ..q.clone()
+                        })?;
+                        counts.questions += 1;
+                    }
+                }
+            }
+
+            for f in factories {
+                match s.factories.iter_mut().find(|e| e.name == f.name) {
+                    Some(e) => {
+                        if e.factory_type != f.factory_type || e.data != f.data {
+                            e.factory_type = f.factory_type.clone();
+                            e.data = f.data.clone();
+                            counts.factories += 1;
+                        }
+                    }
+                    None => {
+                        let id = s.next_id();
+                        s.factories.push(QuestionFactory { id, ..f.clone() });
+                        counts.factories += 1;
+                    }
+                }
+            }
+
+            for m in memberships {
+                if let Some(id) = s.find(&m.factory, &m.name).map(|q| q.id) {
+                    if s.insert_in_set(&m.set, id) {
+                        counts.memberships += 1;
+                    }
+                }
+            }
+            s.remove_memberships(removed);
+            s.delete_questions(pruned);
+            Ok(counts)
+        })
+    }
+
+    async fn remove_memberships(&self, memberships: &[SetMembership]) -> Result<u64> {
+        self.atomically(|s| Ok(s.remove_memberships(memberships)))
+    }
+
+    async fn edit_questions(
+        &self,
+        questions: &[Question],
+        added: &[SetMembership],
+        removed: &[SetMembership],
+    ) -> Result<()> {
+        self.atomically(|s| {
+            for q in questions {
+                let e = s.question_mut(q.id)?;
+                e.data = q.data.clone();
+                e.content_hash = q.content_hash.clone();
+            }
+            for m in added {
+                if let Some(id) = s.find(&m.factory, &m.name).map(|q| q.id) {
+                    s.insert_in_set(&m.set, id);
+                }
+            }
+            for m in removed {
+                if let Some(id) = s.find(&m.factory, &m.name).map(|q| q.id) {
+                    s.sets.retain(|qs| qs.name != m.set || qs.question_id != id);
+                }
+            }
+            Ok(())
+        })
+    }
+
+    async fn rename_set(&self, old: &str, new: &str) -> Result<()> {
+        self.atomically(|s| {
+            if s.sets.iter().any(|qs| qs.name == new) || s.factories.iter().any(|f| f.name == new) {
+                bail!("a set named {} already exists", new);
+            }
+            if !s.sets.iter().any(|qs| qs.name == old) && !s.factories.iter().any(|f| f.name == old)
+            {
+                bail!("there is no set named {}", old);
+            }
+            for qs in s.sets.iter_mut().filter(|qs| qs.name == old) {
+                qs.name = new.to_string();
+            }
+            for f in s.factories.iter_mut() {
+                if f.name == old {
+                    f.name = new.to_string();
+                }
+                if let Some(data) = rename_set_references(f, old, new)? {
+                    f.data = data;
+                }
+            }
+            for q in s.questions.values_mut().filter(|q| q.factory == old) {
+                q.factory = new.to_string();
+            }
+            if let Some(mut goal) = s.goals.remove(old) {
+                goal.set_name = new.to_string();
+                s.goals.insert(new.to_string(), goal);
+            }
+            if s.archived.remove(old) {
+                s.archived.insert(new.to_string());
+            }
+            Ok(())
+        })
+    }
+
+    async fn rename_question(&self, factory: &str, old: &str, new: &str) -> Result<()> {
+        self.with(|s| {
+            if s.find(factory, new).is_some() {
+                bail!("{}/{} already exists", factory, new);
+            }
+            let Some(id) = s.find(factory, old).map(|q| q.id) else {
+                bail!("there is no question {}/{}", factory, old);
+            };
+            s.question_mut(id)?.name = new.to_string();
+            Ok(())
+        })
+    }
+
+    async fn delete_questions(&self, question_ids: &[i64]) -> Result<()> {
+        self.with(|s| {
+            s.delete_questions(question_ids);
+            Ok(())
+        })
+    }
+
+    async fn merge_questions(&self, canonical: i64, duplicates: &[i64]) -> Result<()> {
+        self.atomically(|s| {
+            for &id in duplicates {
+                for a in s.answers.iter_mut().filter(|a| a.question_id == id) {
+                    a.question_id = canonical;
+                }
+                let names = s
+                    .sets
+                    .iter()
+                    .filter(|qs| qs.question_id == id)
+                    .map(|qs| qs.name.clone())
+                    .collect::<Vec<_>>();
+                for name in names {
+                    s.insert_in_set(&name, canonical);
+                }
+                s.sets.retain(|qs| qs.question_id != id);
+                s.links.retain(|&(a, b)| a != id && b != id);
+                let Some(d) = s.questions.remove(&id) else {
+                    continue;
+                };
+                // Every question starts at 1/1, which only counts once.
+                let q = s.question_mut(canonical)?;
+                q.num_correct += d.num_correct - 1;
+                q.num_incorrect += d.num_incorrect - 1;
+                q.last_answered_at = q.last_answered_at.max(d.last_answered_at);
+            }
+            let mut answers = s
+                .answers
+                .iter()
+                .filter(|a| a.question_id == canonical)
+                .cloned()
+                .collect::<Vec<_>>();
+            answers.sort_by_key(|a| a.time);
+            s.question_mut(canonical)?.probability =
+                ProbabilityComputer::default_probability(&answers);
+            Ok(())
+        })
+    }
+
+    async fn get_assets(&self) -> Result<Vec<Asset>> {
+        self.with(|s| Ok(s.assets.values().cloned().collect()))
+    }
+
+    async fn set_assets(&self, assets: &[Asset]) -> Result<()> {
+        self.with(|s| {
+            s.assets = assets.iter().map(|a| (a.path.clone(), a.clone())).collect();
+            Ok(())
+        })
+    }
+
+    async fn get_links(&self) -> Result<Vec<(i64, i64)>> {
+        self.with(|s| Ok(s.links.iter().copied().collect()))
+    }
+
+    async fn link_questions(&self, a: i64, b: i64) -> Result<bool> {
+        self.with(|s| {
+            s.question_mut(a)?;
+            s.question_mut(b)?;
+            Ok(s.links.insert((a.min(b), a.max(b))))
+        })
+    }
+
+    async fn unlink_questions(&self, a: i64, b: i64) -> Result<bool> {
+        self.with(|s| Ok(s.links.remove(&(a.min(b), a.max(b)))))
+    }
+}
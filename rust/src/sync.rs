@@ -0,0 +1,174 @@
+use crate::db::AnswerDetails;
+use crate::functionality::Service;
+use crate::storage::Storage;
+use anyhow::{Context, Result};
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+/// One answer in the log shared between devices, a line of JSON.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LogEntry {
+    /// Identifies the answer across devices, see `LogEntry::key_for`.
+    pub key: String,
+    /// The device the answer was given on.
+    pub device: String,
+    pub factory: String,
+    pub question: String,
+    pub time: DateTime<Utc>,
+    pub correct: bool,
+    pub score: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub steps: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub params: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tolerance: Option<f64>,
+}
+
+impl LogEntry {
+    /// The question and the time it was answered, to the microsecond. Two
+    /// answers to a question can't be given at the same time, so this stays
+    /// the same however often the answer travels between devices.
+    pub fn key_for(factory: &str, question: &str, time: DateTime<Utc>) -> String {
+        format!(
+            "{}/{}@{}",
+            factory,
+            question,
+            time.to_rfc3339_opts(SecondsFormat::Micros, true)
+        )
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct SyncCounts {
+    /// Answers from the log added to the database.
+    pub imported: usize,
+    /// Answers from the database appended to the log.
+    pub exported: usize,
+    /// Answers in the log to questions the database doesn't have, e.g. from
+    /// a set that hasn't been loaded here.
+    pub unknown: usize,
+    /// Answers in the database to questions it doesn't have, which are left
+    /// out of the log, see `trivial fsck`.
+    pub orphaned: usize,
+}
+
+/// Reads the log, which is empty if the file doesn't exist yet.
+pub fn read_log(path: &Path) -> Result<Vec<LogEntry>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let text = fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            serde_json::from_str(line).with_context(|| format!("{} line {}", path.display(), i + 1))
+        })
+        .collect()
+}
+
+/// Adds the answers in the log that the database doesn't have, then appends
+/// the answers the log doesn't have, marked as given on `device`. The log is
+/// only ever appended to, so copies of it kept in sync by e.g. Dropbox or
+/// git don't conflict as long as devices don't sync at the same time.
+pub async fn sync(repo: &dyn Storage, path: &Path, device: &str) -> Result<SyncCounts> {
+    let questions = repo.get_all_questions().await?;
+    let ids = questions
+        .iter()
+        .map(|q| ((q.factory.as_str(), q.name.as_str()), q.id))
+        .collect::<HashMap<_, _>>();
+    let names = questions
+        .iter()
+        .map(|q| (q.id, q))
+        .collect::<HashMap<_, _>>();
+
+    let answers = repo.get_all_answers().await?;
+    let mut local = answers
+        .iter()
+        .filter_map(|a| {
+            let q = names.get(&a.question_id)?;
+            Some(LogEntry::key_for(&q.factory, &q.name, a.time))
+        })
+        .collect::<HashSet<_>>();
+
+    let mut log = read_log(path)?;
+    log.sort_by_key(|e| e.time);
+    let mut counts = SyncCounts::default();
+    let mut logged = HashSet::new();
+    for entry in log {
+        if !logged.insert(entry.key.clone()) || local.contains(&entry.key) {
+            continue;
+        }
+        let Some(&id) = ids.get(&(entry.factory.as_str(), entry.question.as_str())) else {
+            counts.unknown += 1;
+            continue;
+        };
+        let details = AnswerDetails {
+            error: entry.error,
+            text: entry.text,
+            steps: entry.steps,
+            params: entry.params,
+            tolerance: entry.tolerance,
+        };
+        let probability = names[&id].probability;
+        repo.add_answer(
+            id,
+            entry.time,
+            entry.correct,
+            entry.score,
+            &details,
+            probability,
+        )
+        .await?;
+        local.insert(entry.key);
+        counts.imported += 1;
+    }
+    if counts.imported > 0 {
+        // Works the probabilities out again from all the answers.
+        Service::new(repo).await?;
+    }
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("opening {}", path.display()))?;
+    let mut answers = answers;
+    answers.sort_by_key(|a| a.time);
+    for a in answers {
+        let Some(q) = names.get(&a.question_id) else {
+            counts.orphaned += 1;
+            continue;
+        };
+        let key = LogEntry::key_for(&q.factory, &q.name, a.time);
+        if !logged.insert(key.clone()) {
+            continue;
+        }
+        let entry = LogEntry {
+            key,
+            device: device.to_string(),
+            factory: q.factory.clone(),
+            question: q.name.clone(),
+            time: a.time,
+            correct: a.correct,
+            score: a.score,
+            error: a.error,
+            text: a.text,
+            steps: a.steps,
+            params: a.params,
+            tolerance: a.tolerance,
+        };
+        writeln!(file, "{}", serde_json::to_string(&entry)?)?;
+        counts.exported += 1;
+    }
+    Ok(counts)
+}
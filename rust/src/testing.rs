@@ -0,0 +1,96 @@
+use crate::db::Repository;
+use crate::frontend::{self, Frontend, Validator};
+use crate::functionality::{parse_models, Models, Service};
+use crate::storage::Storage;
+use anyhow::{bail, Result};
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+/// An empty database with every migration applied, gone when dropped.
+pub async fn memory_repository() -> Result<Repository> {
+    Repository::new("sqlite::memory:").await
+}
+
+/// Parses question sets written as YAML, in the format of the set files.
+pub fn fixture(sets: &[&str]) -> Result<Models> {
+    parse_models(sets)
+}
+
+/// Imports the question sets into the database.
+pub async fn import_fixture(repo: &dyn Storage, sets: &[&str]) -> Result<()> {
+    let mut service = Service::new(repo).await?;
+    service.import(&fixture(sets)?, false).await?;
+    Ok(())
+}
+
+/// Answers prompts with the given lines, in order. Confirmations take `y` or
+/// `n` and selections the text of an option. Pauses don't use a line, and
+/// an answer typed in an editor can span several.
+pub struct Scripted {
+    answers: RefCell<VecDeque<String>>,
+}
+
+impl Scripted {
+    pub fn new(answers: &[&str]) -> Rc<Scripted> {
+        Rc::new(Scripted {
+            answers: RefCell::new(answers.iter().map(|s| s.to_string()).collect()),
+        })
+    }
+
+    /// Makes questions on this thread answer from the script.
+    pub fn install(self: &Rc<Scripted>) {
+        frontend::set(self.clone());
+    }
+
+    /// Number of answers that haven't been used yet.
+    pub fn remaining(&self) -> usize {
+        self.answers.borrow().len()
+    }
+
+    fn next(&self, message: &str) -> Result<String> {
+        match self.answers.borrow_mut().pop_front() {
+            Some(answer) => Ok(answer),
+            None => bail!("no scripted answer left for {:?}", message),
+        }
+    }
+}
+
+impl Frontend for Scripted {
+    fn text(
+        &self,
+        message: &str,
+        _help: Option<&str>,
+        validator: Option<Validator>,
+    ) -> Result<String> {
+        let answer = self.next(message)?;
+        if let Some(Err(err)) = validator.map(|v| v(&answer)) {
+            bail!("scripted answer {:?} was rejected: {}", answer, err);
+        }
+        Ok(answer)
+    }
+
+    fn editor(&self, message: &str, _help: Option<&str>) -> Result<String> {
+        self.next(message)
+    }
+
+    fn confirm(&self, message: &str) -> Result<bool> {
+        match self.next(message)?.as_str() {
+            "y" => Ok(true),
+            "n" => Ok(false),
+            answer => bail!("expected y or n to confirm {:?}, got {:?}", message, answer),
+        }
+    }
+
+    fn select(&self, message: &str, options: &[String]) -> Result<usize> {
+        let answer = self.next(message)?;
+        match options.iter().position(|o| *o == answer) {
+            Some(i) => Ok(i),
+            None => bail!("{:?} is not one of {:?}", answer, options),
+        }
+    }
+
+    fn pause(&self, _message: &str) -> Result<()> {
+        Ok(())
+    }
+}
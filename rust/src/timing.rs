@@ -0,0 +1,176 @@
+use crate::frontend::{self, Frontend, Validator};
+use crate::functionality::{QuestionFactory, QuestionRunner, Score};
+use crate::output;
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Time limit settings shared by all question factories, flattened into their
+/// data so a set can declare e.g. `time_limit: 10`.
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct Timing {
+    /// Seconds allowed to answer a question. Reading the feedback doesn't
+    /// count.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    time_limit: Option<f64>,
+    /// The most an answer given after the time limit can score. Defaults to 0,
+    /// marking it wrong.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    late_score: Option<f64>,
+}
+
+/// Wraps the factory so its questions enforce the time limit, if there is one.
+pub fn with_time_limit(
+    factory: Box<dyn QuestionFactory>,
+    timing: Timing,
+) -> Result<Box<dyn QuestionFactory>> {
+    let Some(limit) = timing.time_limit else {
+        return Ok(factory);
+    };
+    let Ok(limit) = Duration::try_from_secs_f64(limit) else {
+        bail!("time_limit {} isn't a number of seconds", limit);
+    };
+    let late_score = timing.late_score.unwrap_or(0.);
+    if !(0. ..=1.).contains(&late_score) {
+        bail!("late_score {} isn't between 0 and 1", late_score);
+    }
+    Ok(Box::new(TimedFactory {
+        inner: factory,
+        limit,
+        late_score: Score::new(late_score),
+    }))
+}
+
+struct TimedFactory {
+    inner: Box<dyn QuestionFactory>,
+    limit: Duration,
+    late_score: Score,
+}
+
+impl QuestionFactory for TimedFactory {
+    fn build(&self, data: &[u8]) -> Result<Box<dyn QuestionRunner>> {
+        Ok(Box::new(TimedQuestion {
+            inner: self.inner.build(data)?,
+            limit: self.limit,
+            late_score: self.late_score,
+        }))
+    }
+}
+
+struct TimedQuestion {
+    inner: Box<dyn QuestionRunner>,
+    limit: Duration,
+    late_score: Score,
+}
+
+impl QuestionRunner for TimedQuestion {
+    fn run(&self) -> Result<Score> {
+        let previous = frontend::current();
+        let stopwatch = Rc::new(Stopwatch::new(previous.clone()));
+        frontend::set(stopwatch.clone());
+        let score = self.inner.run();
+        frontend::set(previous);
+        let score = score?;
+        let elapsed = stopwatch.elapsed();
+        if elapsed <= self.limit || score <= self.late_score {
+            return Ok(score);
+        }
+        output::feedback(output::wrong(&format!(
+            "Too slow! Took {:.1}s, the limit is {:.1}s.",
+            elapsed.as_secs_f64(),
+            self.limit.as_secs_f64()
+        )));
+        let answer = self.inner.answer_text();
+        if !answer.is_empty() {
+            output::feedback(format!("The answer is {}", answer));
+        }
+        println!();
+        Ok(self.late_score)
+    }
+
+    fn name(&self) -> String {
+        self.inner.name()
+    }
+
+    fn question_text(&self) -> String {
+        self.inner.question_text()
+    }
+
+    fn answer_text(&self) -> String {
+        self.inner.answer_text()
+    }
+
+    fn set_siblings(&mut self, answers: Arc<[String]>) {
+        self.inner.set_siblings(answers)
+    }
+
+    fn set_streak(&mut self, streak: usize) {
+        self.inner.set_streak(streak)
+    }
+}
+
+/// Passes everything on to the frontend, keeping the time of the last answer
+/// given before the question pauses, e.g. to show more about the answer.
+/// That is when the answer was submitted: what comes after is feedback.
+struct Stopwatch {
+    inner: Rc<dyn Frontend>,
+    start: Instant,
+    answered: Cell<Option<Instant>>,
+    paused: Cell<bool>,
+}
+
+impl Stopwatch {
+    fn new(inner: Rc<dyn Frontend>) -> Stopwatch {
+        Stopwatch {
+            inner,
+            start: Instant::now(),
+            answered: Cell::new(None),
+            paused: Cell::new(false),
+        }
+    }
+
+    fn answer<T>(&self, answer: Result<T>) -> Result<T> {
+        if !self.paused.get() {
+            self.answered.set(Some(Instant::now()));
+        }
+        answer
+    }
+
+    /// The time taken to answer, or so far if nothing was answered.
+    fn elapsed(&self) -> Duration {
+        self.answered.get().unwrap_or_else(Instant::now) - self.start
+    }
+}
+
+impl Frontend for Stopwatch {
+    fn text(
+        &self,
+        message: &str,
+        help: Option<&str>,
+        validator: Option<Validator>,
+    ) -> Result<String> {
+        self.answer(self.inner.text(message, help, validator))
+    }
+
+    fn editor(&self, message: &str, help: Option<&str>) -> Result<String> {
+        self.answer(self.inner.editor(message, help))
+    }
+
+    fn confirm(&self, message: &str) -> Result<bool> {
+        self.answer(self.inner.confirm(message))
+    }
+
+    fn select(&self, message: &str, options: &[String]) -> Result<usize> {
+        self.answer(self.inner.select(message, options))
+    }
+
+    fn pause(&self, message: &str) -> Result<()> {
+        if self.answered.get().is_some() {
+            self.paused.set(true);
+        }
+        self.inner.pause(message)
+    }
+}
@@ -0,0 +1,203 @@
+use crate::functionality::{QuestionFactory, QuestionRunner, Score};
+use crate::output;
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+use std::thread;
+
+const TEXT_PLACEHOLDER: &str = "{text}";
+
+fn default_true() -> bool {
+    true
+}
+
+/// Text to speech settings of a set. Reading aloud is enabled for a set by
+/// giving it a `speech` entry, `speech: {}` uses the OS speech command.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Speech {
+    /// Command that reads `{text}` aloud. Defaults to `say` on macOS and
+    /// `espeak` elsewhere.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    command: Vec<String>,
+    /// HTTP service to use instead of a command. The text is POSTed to it and
+    /// the audio it responds with is piped into `player`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    url: Option<String>,
+    /// Plays audio from stdin. Defaults to `ffplay`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    player: Vec<String>,
+    /// Also read the correct answer after the question has been answered.
+    #[serde(default = "default_true")]
+    answers: bool,
+}
+
+impl Speech {
+    fn speaker(&self) -> Arc<dyn Speaker> {
+        match &self.url {
+            Some(url) => Arc::new(HttpSpeaker {
+                url: url.clone(),
+                player: if self.player.is_empty() {
+                    ["ffplay", "-nodisp", "-autoexit", "-loglevel", "quiet", "-"]
+                        .map(String::from)
+                        .to_vec()
+                } else {
+                    self.player.clone()
+                },
+            }),
+            None => Arc::new(CommandSpeaker {
+                command: if self.command.is_empty() {
+                    let program = if cfg!(target_os = "macos") {
+                        "say"
+                    } else {
+                        "espeak"
+                    };
+                    vec![program.to_string(), TEXT_PLACEHOLDER.to_string()]
+                } else {
+                    self.command.clone()
+                },
+            }),
+        }
+    }
+}
+
+/// A text to speech backend. `say` blocks until the text has been read.
+pub trait Speaker: Send + Sync {
+    fn say(&self, text: &str) -> Result<()>;
+}
+
+/// Reads text with a local command such as `espeak "{text}"`.
+pub struct CommandSpeaker {
+    command: Vec<String>,
+}
+
+impl Speaker for CommandSpeaker {
+    fn say(&self, text: &str) -> Result<()> {
+        let Some((program, args)) = self.command.split_first() else {
+            bail!("no speech command configured");
+        };
+        let status = Command::new(program)
+            .args(args.iter().map(|a| a.replace(TEXT_PLACEHOLDER, text)))
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()?;
+        if !status.success() {
+            bail!("{} failed with {}", program, status);
+        }
+        Ok(())
+    }
+}
+
+/// Fetches the audio from an HTTP service and plays it with a local player.
+pub struct HttpSpeaker {
+    url: String,
+    player: Vec<String>,
+}
+
+impl Speaker for HttpSpeaker {
+    fn say(&self, text: &str) -> Result<()> {
+        let mut audio = Vec::new();
+        ureq::post(&self.url)
+            .set("Content-Type", "text/plain; charset=utf-8")
+            .send_string(text)?
+            .into_reader()
+            .read_to_end(&mut audio)?;
+
+        let Some((program, args)) = self.player.split_first() else {
+            bail!("no audio player configured");
+        };
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+        child.stdin.take().unwrap().write_all(&audio)?;
+        let status = child.wait()?;
+        if !status.success() {
+            bail!("{} failed with {}", program, status);
+        }
+        Ok(())
+    }
+}
+
+/// Wraps the factory so its questions are read aloud, if the set asks for it.
+pub fn with_speech(
+    factory: Box<dyn QuestionFactory>,
+    speech: Option<Speech>,
+) -> Box<dyn QuestionFactory> {
+    match speech {
+        Some(speech) => Box::new(SpeakingFactory {
+            inner: factory,
+            speaker: speech.speaker(),
+            answers: speech.answers,
+        }),
+        None => factory,
+    }
+}
+
+struct SpeakingFactory {
+    inner: Box<dyn QuestionFactory>,
+    speaker: Arc<dyn Speaker>,
+    answers: bool,
+}
+
+impl QuestionFactory for SpeakingFactory {
+    fn build(&self, data: &[u8]) -> Result<Box<dyn QuestionRunner>> {
+        Ok(Box::new(SpokenQuestion {
+            inner: self.inner.build(data)?,
+            speaker: self.speaker.clone(),
+            answers: self.answers,
+        }))
+    }
+}
+
+struct SpokenQuestion {
+    inner: Box<dyn QuestionRunner>,
+    speaker: Arc<dyn Speaker>,
+    answers: bool,
+}
+
+impl QuestionRunner for SpokenQuestion {
+    fn run(&self) -> Result<Score> {
+        // Read the question in the background so it can be answered right away.
+        let speaker = self.speaker.clone();
+        let question = self.inner.question_text();
+        let reading = thread::spawn(move || speaker.say(&question));
+
+        let score = self.inner.run()?;
+        let mut result = reading.join().unwrap();
+        if result.is_ok() && self.answers && !output::is_silent() {
+            result = self.speaker.say(&self.inner.answer_text());
+        }
+        if let Err(err) = result {
+            println!(
+                "{}",
+                output::wrong(&format!("Could not read aloud: {:#}", err))
+            );
+        }
+        Ok(score)
+    }
+
+    fn name(&self) -> String {
+        self.inner.name()
+    }
+
+    fn question_text(&self) -> String {
+        self.inner.question_text()
+    }
+
+    fn answer_text(&self) -> String {
+        self.inner.answer_text()
+    }
+
+    fn set_siblings(&mut self, answers: Arc<[String]>) {
+        self.inner.set_siblings(answers)
+    }
+
+    fn set_streak(&mut self, streak: usize) {
+        self.inner.set_streak(streak)
+    }
+}
@@ -0,0 +1,107 @@
+use crate::encoding;
+use crate::functionality::{
+    FactoryOptions, QuestionFactory, QuestionKey, QuestionRunner, QuestionSetFactory, Score,
+    SetIndex,
+};
+use crate::hints::Hints;
+use crate::output;
+use anyhow::Result;
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use similar::{ChangeTag, TextDiff};
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct TypingData {
+    #[serde(flatten)]
+    options: FactoryOptions,
+    #[serde(skip)]
+    depends: Vec<String>,
+}
+
+impl QuestionFactory for TypingData {
+    fn build(&self, data: &[u8]) -> Result<Box<dyn QuestionRunner>> {
+        let question = encoding::decode::<TypingQuestion>(data)?;
+        Ok(Box::new(question) as Box<dyn QuestionRunner>)
+    }
+}
+
+impl QuestionSetFactory for TypingData {
+    fn build_set(&self, s: &SetIndex, set_name: &str) -> Vec<QuestionKey> {
+        s.get_factory(set_name).to_vec()
+    }
+
+    fn depends_on(&self) -> &Vec<String> {
+        &self.depends
+    }
+}
+
+/// A question where the text has to be reproduced exactly, e.g. a quote or
+/// a command.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct TypingQuestion {
+    id: String,
+    prompt: String,
+    text: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    hints: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    /// Questions to know before this one is introduced, see
+    /// `Question::requires`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    requires: Vec<String>,
+}
+
+impl QuestionRunner for TypingQuestion {
+    fn run(&self) -> Result<Score> {
+        let mut hints = Hints::new(&self.hints);
+        let answer = hints.prompt(&self.prompt, None)?;
+        let correct = answer.trim_end() == self.text.trim_end();
+        if correct {
+            output::feedback(output::correct("Correct!"));
+        } else {
+            output::feedback(output::wrong("Wrong."));
+            print_diff(&self.text, &answer);
+        }
+        println!();
+        Ok(hints.score(correct.into()))
+    }
+
+    fn name(&self) -> String {
+        self.id.clone()
+    }
+
+    fn question_text(&self) -> String {
+        self.prompt.clone()
+    }
+
+    fn answer_text(&self) -> String {
+        self.text.clone()
+    }
+}
+
+/// Prints the expected text with the characters that were missed highlighted,
+/// followed by the answer with the characters that shouldn't be there
+/// highlighted.
+fn print_diff(expected: &str, answer: &str) {
+    let diff = TextDiff::from_chars(expected, answer);
+    let mut expected_line = String::new();
+    let mut answer_line = String::new();
+    for change in diff.iter_all_changes() {
+        let s = change.value();
+        match change.tag() {
+            ChangeTag::Equal => {
+                expected_line.push_str(s);
+                answer_line.push_str(s);
+            }
+            ChangeTag::Delete => {
+                expected_line.push_str(&output::correct(s).underline().to_string());
+            }
+            ChangeTag::Insert => {
+                answer_line.push_str(&output::wrong(s).strikethrough().to_string());
+            }
+        }
+    }
+    output::feedback(format!("{} {}", output::label("Expected:"), expected_line));
+    output::feedback(format!("{} {}", output::label("Answer:  "), answer_line));
+}
@@ -0,0 +1,96 @@
+use crate::db::User;
+use crate::functionality::Score;
+use crate::storage::Storage;
+use anyhow::{bail, Result};
+
+/// A local game where players take turns answering questions from the same
+/// selection, the one with the most points winning. The answers aren't
+/// recorded as practice, only the totals of the players are kept.
+#[derive(Debug, Clone)]
+pub struct Game {
+    /// The totals of each player in this game.
+    players: Vec<User>,
+    turn: usize,
+}
+
+impl Game {
+    pub fn new(names: &[String]) -> Result<Game> {
+        if names.len() < 2 {
+            bail!("a game needs at least two players");
+        }
+        for (i, name) in names.iter().enumerate() {
+            if name.trim().is_empty() {
+                bail!("players need a name");
+            }
+            if names[..i].contains(name) {
+                bail!("{} can't play against themselves", name);
+            }
+        }
+        let players = names
+            .iter()
+            .map(|name| User {
+                name: name.clone(),
+                games: 1,
+                ..Default::default()
+            })
+            .collect();
+        Ok(Game { players, turn: 0 })
+    }
+
+    /// The player whose turn it is.
+    pub fn current(&self) -> &User {
+        &self.players[self.turn]
+    }
+
+    /// Scores the answer of the current player and passes the turn on.
+    pub fn record(&mut self, score: Score) {
+        let player = &mut self.players[self.turn];
+        player.answers += 1;
+        player.correct += u32::from(score.is_correct());
+        player.points += score.value();
+        self.pass();
+    }
+
+    /// Passes the turn on without an answer.
+    pub fn pass(&mut self) {
+        self.turn = (self.turn + 1) % self.players.len();
+    }
+
+    /// Whether anyone answered anything yet.
+    pub fn started(&self) -> bool {
+        self.players.iter().any(|p| p.answers > 0)
+    }
+
+    /// The players, the most points first.
+    pub fn scoreboard(&self) -> Vec<&User> {
+        let mut players = self.players.iter().collect::<Vec<_>>();
+        players.sort_by(|a, b| b.points.total_cmp(&a.points));
+        players
+    }
+
+    /// The players with the most points, more than one on a draw.
+    pub fn winners(&self) -> Vec<&User> {
+        let best = self.scoreboard()[0].points;
+        self.players.iter().filter(|p| p.points == best).collect()
+    }
+
+    /// Adds the game to the totals of the players, the winners getting a
+    /// win unless everyone drew.
+    pub async fn save(&self, repo: &dyn Storage) -> Result<()> {
+        let winners = self
+            .winners()
+            .iter()
+            .map(|p| p.name.clone())
+            .collect::<Vec<_>>();
+        let draw = winners.len() == self.players.len();
+        let players = self
+            .players
+            .iter()
+            .map(|p| User {
+                wins: u32::from(!draw && winners.contains(&p.name)),
+                ..p.clone()
+            })
+            .collect::<Vec<_>>();
+        repo.record_game(&players).await
+    }
+}
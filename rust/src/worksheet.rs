@@ -0,0 +1,162 @@
+use pdf_writer::{Content, Finish, Name, Pdf, Rect, Ref, Str};
+
+/// Questions to answer on paper, followed by an answer key.
+pub struct Worksheet {
+    pub title: String,
+    /// Each question with its expected answer.
+    pub items: Vec<(String, String)>,
+}
+
+// A4 in points.
+const WIDTH: f32 = 595.;
+const HEIGHT: f32 = 842.;
+const MARGIN: f32 = 56.;
+const TITLE_SIZE: f32 = 16.;
+const SIZE: f32 = 11.;
+const LEADING: f32 = SIZE * 1.4;
+
+impl Worksheet {
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!("# {}\n\n", self.title);
+        for (i, (question, _)) in self.items.iter().enumerate() {
+            out += &format!("{}. {}\n\n   ____________________\n\n", i + 1, question);
+        }
+        out += "## Answer key\n\n";
+        for (i, (_, answer)) in self.items.iter().enumerate() {
+            out += &format!("{}. {}\n", i + 1, answer);
+        }
+        out
+    }
+
+    /// Lays the worksheet out on A4 pages in Helvetica, with the answer key
+    /// starting on a page of its own. Characters outside Latin-1 are printed
+    /// as `?`.
+    pub fn to_pdf(&self) -> Vec<u8> {
+        let mut questions = vec![Line::Title(self.title.clone())];
+        for (i, (question, _)) in self.items.iter().enumerate() {
+            questions.extend(wrap(&format!("{}. {}", i + 1, question)));
+            questions.push(Line::Text("Answer: ____________________".to_string()));
+            questions.push(Line::Blank);
+        }
+        let mut key = vec![Line::Title("Answer key".to_string())];
+        for (i, (_, answer)) in self.items.iter().enumerate() {
+            key.extend(wrap(&format!("{}. {}", i + 1, answer)));
+        }
+        let mut pages = paginate(questions);
+        pages.extend(paginate(key));
+        render(&pages)
+    }
+}
+
+enum Line {
+    Title(String),
+    Text(String),
+    Blank,
+}
+
+impl Line {
+    fn height(&self) -> f32 {
+        match self {
+            Line::Title(_) => TITLE_SIZE * 2.,
+            Line::Text(_) | Line::Blank => LEADING,
+        }
+    }
+}
+
+/// Breaks the text into lines fitting the page, estimating the width of a
+/// character as half the font size, which is generous for Helvetica.
+fn wrap(text: &str) -> Vec<Line> {
+    let max = ((WIDTH - 2. * MARGIN) / (SIZE * 0.5)) as usize;
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > max {
+            lines.push(Line::Text(std::mem::take(&mut line)));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+    }
+    lines.push(Line::Text(line));
+    lines
+}
+
+fn paginate(lines: Vec<Line>) -> Vec<Vec<Line>> {
+    let mut pages = vec![Vec::new()];
+    let mut height = 0.;
+    for line in lines {
+        if height + line.height() > HEIGHT - 2. * MARGIN {
+            pages.push(Vec::new());
+            height = 0.;
+        }
+        height += line.height();
+        pages.last_mut().unwrap().push(line);
+    }
+    pages
+}
+
+/// Encodes the text in WinAnsiEncoding, which matches Latin-1 for the
+/// printable characters.
+fn encode(text: &str) -> Vec<u8> {
+    text.chars()
+        .map(|c| match u8::try_from(c) {
+            Ok(b) if b >= 0x20 => b,
+            _ => b'?',
+        })
+        .collect()
+}
+
+fn render(pages: &[Vec<Line>]) -> Vec<u8> {
+    let catalog_id = Ref::new(1);
+    let tree_id = Ref::new(2);
+    let font_id = Ref::new(3);
+    let bold_id = Ref::new(4);
+    let page_ids = (0..pages.len())
+        .map(|i| Ref::new(5 + 2 * i as i32))
+        .collect::<Vec<_>>();
+
+    let mut pdf = Pdf::new();
+    pdf.catalog(catalog_id).pages(tree_id);
+    pdf.pages(tree_id)
+        .kids(page_ids.iter().copied())
+        .count(pages.len() as i32);
+    for (id, name) in [(font_id, "Helvetica"), (bold_id, "Helvetica-Bold")] {
+        pdf.type1_font(id)
+            .base_font(Name(name.as_bytes()))
+            .encoding_predefined(Name(b"WinAnsiEncoding"));
+    }
+
+    for (lines, &page_id) in pages.iter().zip(&page_ids) {
+        let content_id = Ref::new(page_id.get() + 1);
+        let mut page = pdf.page(page_id);
+        page.media_box(Rect::new(0., 0., WIDTH, HEIGHT));
+        page.parent(tree_id);
+        page.contents(content_id);
+        let mut resources = page.resources();
+        let mut fonts = resources.fonts();
+        fonts.pair(Name(b"F1"), font_id);
+        fonts.pair(Name(b"F2"), bold_id);
+        fonts.finish();
+        resources.finish();
+        page.finish();
+
+        let mut content = Content::new();
+        let mut y = HEIGHT - MARGIN;
+        for line in lines {
+            y -= line.height();
+            let (font, size, text) = match line {
+                Line::Title(text) => (Name(b"F2"), TITLE_SIZE, text),
+                Line::Text(text) => (Name(b"F1"), SIZE, text),
+                Line::Blank => continue,
+            };
+            content.begin_text();
+            content.set_font(font, size);
+            content.next_line(MARGIN, y);
+            content.show(Str(&encode(text)));
+            content.end_text();
+        }
+        pdf.stream(content_id, &content.finish());
+    }
+    pdf.finish()
+}
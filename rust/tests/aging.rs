@@ -0,0 +1,51 @@
+use chrono::{Duration, Utc};
+use rust::aging::aging_report;
+use rust::db::AnswerDetails;
+use rust::storage::Storage;
+use rust::testing::memory_repository;
+
+#[tokio::test]
+async fn never_answered_stale_and_sourceless_sets() {
+    let repo = memory_repository().await.unwrap();
+    for set in ["capitals", "rivers", "old"] {
+        repo.insert_question_factory(set, "default", &[])
+            .await
+            .unwrap();
+    }
+    for (set, name) in [
+        ("capitals", "France"),
+        ("capitals", "Peru"),
+        ("capitals", "Chad"),
+        ("rivers", "Nile"),
+        ("old", "gone"),
+    ] {
+        repo.insert_question(set, name, &[]).await.unwrap();
+    }
+    let now = Utc::now();
+    for (set, name, days) in [
+        ("capitals", "France", 400),
+        ("capitals", "Peru", 10),
+        ("rivers", "Nile", 5),
+    ] {
+        let id = repo.get_question_by_name(set, name).await.unwrap().id;
+        let time = now - Duration::days(days);
+        repo.add_answer(id, time, true, 1., &AnswerDetails::default(), 0.5)
+            .await
+            .unwrap();
+    }
+    repo.set_archived("old", true).await.unwrap();
+
+    let sources = vec!["capitals".to_string()];
+    let report = aging_report(&repo, now - Duration::days(180), Some(&sources))
+        .await
+        .unwrap();
+    let capitals = &report.sets[0];
+    assert_eq!(report.sets.len(), 2);
+    assert_eq!(capitals.set, "capitals");
+    assert_eq!(capitals.questions, 3);
+    assert_eq!(capitals.never_answered, ["Chad"]);
+    assert_eq!(capitals.stale.len(), 1);
+    assert_eq!(capitals.stale[0].0, "France");
+    assert!(report.sets[1].never_answered.is_empty() && report.sets[1].stale.is_empty());
+    assert_eq!(report.missing_sources, ["old", "rivers"]);
+}
@@ -0,0 +1,66 @@
+use rust::functionality::{parse_models, validate_models, Score, Service};
+use rust::testing::{import_fixture, memory_repository, Scripted};
+
+#[tokio::test]
+async fn templates_are_asked_with_their_numbers() {
+    let drills = "
+name: drills
+type_: arithmetic
+data:
+  question_prefix: 'What is '
+items:
+- id: times
+  question: '{a} × {b}?'
+  answer: a * b
+  vars:
+    a: [17, 17]
+    b: [23, 23]
+- id: estimate
+  question: '{a} / {b}?'
+  answer: a / b
+  tolerance: 0.1
+  vars:
+    a: [1000, 1000]
+    b: [7, 7]
+";
+    let repo = memory_repository().await.unwrap();
+    import_fixture(&repo, &[drills]).await.unwrap();
+    let service = Service::new(&repo).await.unwrap();
+    let question = |name: &str| {
+        let q = service
+            .get_questions()
+            .into_iter()
+            .find(|q| q.name == name)
+            .unwrap();
+        &q.runner
+    };
+    Scripted::new(&["391", "17*23+1", "150", "120"]).install();
+    let times = question("times");
+    assert_eq!(times.run().unwrap(), Score::CORRECT);
+    assert_eq!(times.run().unwrap(), Score::WRONG);
+    let estimate = question("estimate");
+    assert_eq!(estimate.run().unwrap(), Score::CORRECT);
+    assert_eq!(estimate.run().unwrap(), Score::WRONG);
+}
+
+#[test]
+fn bad_templates_are_reported() {
+    let drills = "
+name: drills
+type_: arithmetic
+data: {}
+items:
+- id: empty
+  question: '{a}'
+  answer: a
+  vars:
+    a: [9, 1]
+- id: unknown
+  question: '{a}'
+  answer: a + c
+  vars:
+    a: [1, 9]
+";
+    let problems = validate_models(&parse_models(&[drills]).unwrap());
+    assert_eq!(problems.len(), 2, "{:?}", problems);
+}
@@ -0,0 +1,86 @@
+use chrono::Utc;
+use rust::autosave::{Autosave, PendingAnswer};
+use rust::functionality::{Score, Service};
+use rust::storage::Storage;
+use rust::testing::{import_fixture, memory_repository};
+use std::fs;
+use std::sync::Arc;
+
+const CAPITALS: &str = "
+name: capitals
+type_: default
+data:
+  question_prefix: 'The capital of '
+items:
+- id: france
+  question: France
+  answers:
+  - Paris
+";
+
+fn journal(name: &str) -> std::path::PathBuf {
+    let path = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let _ = fs::remove_file(&path);
+    path
+}
+
+#[tokio::test]
+async fn answers_reach_the_database_by_close() {
+    let repo = memory_repository().await.unwrap();
+    import_fixture(&repo, &[CAPITALS]).await.unwrap();
+    let path = journal("close.pending");
+    let (autosave, recovered) = Autosave::start(Arc::new(repo.clone()), &path)
+        .await
+        .unwrap();
+    assert_eq!(recovered, 0);
+    let mut service = Service::new(&repo).await.unwrap();
+    service.set_autosave(&autosave);
+    let id = service.get_questions()[0].id;
+    for _ in 0..10 {
+        service.add_answer(id, Score::CORRECT).await.unwrap();
+    }
+    service.undo_answer(id).await.unwrap();
+    drop(service);
+    autosave.close().await.unwrap();
+
+    assert_eq!(repo.get_answers(id).await.unwrap().len(), 9);
+    assert_eq!(fs::read_to_string(&path).unwrap(), "");
+}
+
+/// A journal left behind by a killed process, with its last line cut short.
+#[tokio::test]
+async fn journaled_answers_are_recovered() {
+    let repo = memory_repository().await.unwrap();
+    import_fixture(&repo, &[CAPITALS]).await.unwrap();
+    let id = repo.get_all_questions().await.unwrap()[0].id;
+    let answer = PendingAnswer {
+        question_id: id,
+        time: Utc::now(),
+        correct: true,
+        score: 1.,
+        error: None,
+        text: Some("Paris".to_string()),
+        steps: None,
+        params: None,
+        tolerance: None,
+        probability: 0.6,
+    };
+    let path = journal("recover.pending");
+    let line = serde_json::to_string(&answer).unwrap();
+    fs::write(&path, format!("{}\n{}", line, &line[..20])).unwrap();
+
+    let (autosave, recovered) = Autosave::start(Arc::new(repo.clone()), &path)
+        .await
+        .unwrap();
+    autosave.close().await.unwrap();
+    assert_eq!(recovered, 1);
+    let answers = repo.get_answers(id).await.unwrap();
+    assert_eq!(answers.len(), 1);
+    assert_eq!(answers[0].text.as_deref(), Some("Paris"));
+
+    let (autosave, recovered) = Autosave::start(Arc::new(repo.clone()), &path)
+        .await
+        .unwrap();
+    autosave.close().await.unwrap();
+    assert_eq!(recovered, 0);
+}
@@ -0,0 +1,56 @@
+use rust::bulk::{bulk_edit, BulkCounts, BulkEdit};
+use rust::db::SetMembership;
+use rust::encoding;
+use rust::functionality::question_tags;
+use rust::storage::Storage;
+use rust::testing::memory_repository;
+use serde_json::json;
+
+#[tokio::test]
+async fn tags_and_sets_change_together() {
+    let repo = memory_repository().await.unwrap();
+    repo.insert_question_factory("capitals", "default", &[])
+        .await
+        .unwrap();
+    for (name, tags) in [("France", json!(["europe"])), ("Peru", json!([]))] {
+        let data = encoding::encode(&json!({"question": name, "tags": tags})).unwrap();
+        repo.insert_question("capitals", name, &data).await.unwrap();
+    }
+    let membership = |set: &str, name: &str| SetMembership {
+        set: set.to_string(),
+        factory: "capitals".to_string(),
+        name: name.to_string(),
+    };
+    repo.import(&[], &[], &[membership("hard", "France")], &[], &[], &[])
+        .await
+        .unwrap();
+    let france = repo.get_question_by_name("capitals", "France").await;
+    let france = france.unwrap().id;
+    let peru = repo.get_question_by_name("capitals", "Peru").await;
+    let peru = peru.unwrap().id;
+
+    let edit = BulkEdit {
+        add_tags: vec!["review".to_string()],
+        remove_tags: vec!["europe".to_string()],
+        add_sets: vec!["capitals".to_string()],
+        remove_sets: vec!["hard".to_string()],
+    };
+    let counts = bulk_edit(&repo, &[france, peru], &edit).await.unwrap();
+    assert_eq!(
+        counts,
+        BulkCounts {
+            retagged: 2,
+            added: 2,
+            removed: 1
+        }
+    );
+    for id in [france, peru] {
+        let q = repo.get_question_by_id(id).await.unwrap();
+        assert_eq!(question_tags(&q.data), ["review"]);
+        assert_eq!(repo.get_sets_of_question(id).await.unwrap(), ["capitals"]);
+    }
+
+    // Applying it again changes nothing.
+    let counts = bulk_edit(&repo, &[france, peru], &edit).await.unwrap();
+    assert_eq!(counts, BulkCounts::default());
+}
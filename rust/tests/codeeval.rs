@@ -0,0 +1,28 @@
+use rust::codeeval::run_command;
+use std::time::{Duration, Instant};
+
+fn sh(script: &str) -> Vec<String> {
+    ["sh", "-c", script].map(String::from).to_vec()
+}
+
+#[test]
+fn commands_get_their_input_and_return_their_output() {
+    let output = run_command(&sh("tr a-z A-Z"), "paris", 5).unwrap();
+    assert_eq!(output, "PARIS");
+    assert!(run_command(&sh("exit 3"), "", 5).is_err());
+}
+
+/// Something started in the background keeps the output open, it's killed
+/// with the command instead of holding up the answer.
+#[test]
+fn whatever_the_command_starts_is_killed_with_it() {
+    let start = Instant::now();
+    let output = run_command(&sh("sleep 30 & echo done"), "", 5).unwrap();
+    assert_eq!(output.trim(), "done");
+    assert!(start.elapsed() < Duration::from_secs(5));
+
+    let start = Instant::now();
+    let err = run_command(&sh("sleep 30 & sleep 30"), "", 1).unwrap_err();
+    assert!(err.to_string().contains("timed out"), "{}", err);
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
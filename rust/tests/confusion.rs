@@ -0,0 +1,56 @@
+use rust::confusion::{drill_yaml, find_confusions};
+use rust::functionality::Service;
+use rust::input::take_details;
+use rust::testing::{fixture, import_fixture, memory_repository, Scripted};
+
+const WORDS: &str = "
+name: words
+type_: vocab
+data: {}
+items:
+- id: pernicious
+  word: pernicious
+  definition: harmful
+  example: a pernicious influence
+  translations:
+  - skadelig
+- id: mendacious
+  word: mendacious
+  definition: untruthful
+  example: a mendacious politician
+  translations:
+  - løgnagtig
+";
+
+#[tokio::test]
+async fn wrong_translations_of_other_words_are_confusions() {
+    let repo = memory_repository().await.unwrap();
+    import_fixture(&repo, &[WORDS]).await.unwrap();
+    let mut service = Service::new(&repo).await.unwrap();
+    let id = service
+        .get_questions()
+        .iter()
+        .find(|q| q.name == "mendacious")
+        .unwrap()
+        .id;
+    Scripted::new(&["Skadelig", "n", "farlig", "n"]).install();
+    for _ in 0..2 {
+        let score = service.get(id).runner.run().unwrap();
+        let details = take_details();
+        service.add_answer_with(id, score, &details).await.unwrap();
+    }
+
+    let confusions = find_confusions(&repo, 1).await.unwrap();
+    assert_eq!(confusions.len(), 1);
+    assert_eq!(confusions[0].word.name, "mendacious");
+    assert_eq!(confusions[0].confused_with.name, "pernicious");
+    assert_eq!(confusions[0].count, 1);
+    assert!(find_confusions(&repo, 2).await.unwrap().is_empty());
+
+    let drill = drill_yaml("drill", &confusions).unwrap();
+    service
+        .import(&fixture(&[WORDS, &drill]).unwrap(), false)
+        .await
+        .unwrap();
+    assert_eq!(service.get_set("drill").len(), 2);
+}
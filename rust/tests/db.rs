@@ -0,0 +1,217 @@
+use chrono::{DateTime, Duration, Utc};
+use rust::db::{AnswerDetails, Repository};
+use rust::storage::Storage;
+use sqlx::sqlite::{SqliteConnectOptions, SqliteConnection};
+use sqlx::ConnectOptions;
+use std::str::FromStr;
+
+/// Two processes, e.g. a session and the web frontend, writing answers to
+/// the same database at the same time.
+#[tokio::test]
+async fn concurrent_writers() {
+    let path = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("concurrent.db");
+    for suffix in ["", "-wal", "-shm"] {
+        let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+    }
+    let url = format!("sqlite://{}?mode=rwc", path.display());
+    let first = Repository::new(&url).await.unwrap();
+    let second = Repository::new(&url).await.unwrap();
+    first
+        .insert_question_factory("f", "default", &[])
+        .await
+        .unwrap();
+    first.insert_question("f", "q", &[]).await.unwrap();
+    let id = first.get_question_by_name("f", "q").await.unwrap().id;
+
+    let start = Utc::now();
+    tokio::join!(write(&first, id, start, 0), write(&second, id, start, 1));
+
+    assert_eq!(second.get_answers(id).await.unwrap().len(), 100);
+    let q = first.get_question_by_id(id).await.unwrap();
+    assert_eq!(q.num_correct + q.num_incorrect, 2 + 100);
+}
+
+async fn write(repo: &Repository, id: i64, start: DateTime<Utc>, offset: i64) {
+    for i in 0..50 {
+        let time = start + Duration::seconds(2 * i + offset);
+        repo.add_answer(id, time, i % 2 == 0, 1., &AnswerDetails::default(), 0.5)
+            .await
+            .unwrap();
+    }
+}
+
+/// Two copies of a set that drifted apart: each side added a question and
+/// one dropped another.
+#[tokio::test]
+async fn membership_diff_finds_both_sides() {
+    let dir = std::path::Path::new(env!("CARGO_TARGET_TMPDIR"));
+    let mut repos = Vec::new();
+    for name in ["here.db", "there.db"] {
+        let path = dir.join(name);
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+        }
+        let repo = Repository::new(&format!("sqlite://{}?mode=rwc", path.display()))
+            .await
+            .unwrap();
+        repo.insert_question_factory("f", "default", &[])
+            .await
+            .unwrap();
+        for q in ["a", "b", "c"] {
+            repo.insert_question("f", q, &[]).await.unwrap();
+        }
+        repos.push((path, repo));
+    }
+    let (_, here) = &repos[0];
+    let (there_path, there) = &repos[1];
+    add(here, "a").await;
+    add(here, "b").await;
+    add(there, "a").await;
+    add(there, "c").await;
+
+    let there_path = there_path.display().to_string();
+    let diff = here.membership_diff(&there_path).await.unwrap();
+    let names =
+        |m: &[rust::db::SetMembership]| m.iter().map(|m| m.name.clone()).collect::<Vec<_>>();
+    assert_eq!(names(&diff.only_here), ["b"]);
+    assert_eq!(names(&diff.only_there), ["c"]);
+
+    here.merge_from(&there_path, false).await.unwrap();
+    assert_eq!(here.get_questions_in_set("s").await.unwrap().len(), 3);
+}
+
+async fn add(repo: &Repository, name: &str) {
+    let id = repo.get_question_by_name("f", name).await.unwrap().id;
+    repo.insert_question_in_set("s", id).await.unwrap();
+}
+
+/// A database last used by an older version is neither upgraded nor left
+/// attached to the one merged into.
+#[tokio::test]
+async fn merging_leaves_outdated_databases_alone() {
+    let dir = std::path::Path::new(env!("CARGO_TARGET_TMPDIR"));
+    let mut urls = Vec::new();
+    for name in ["outdated.db", "current.db"] {
+        let path = dir.join(name);
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+        }
+        let url = format!("sqlite://{}?mode=rwc", path.display());
+        let repo = Repository::new(&url).await.unwrap();
+        repo.insert_question_factory("f", "default", &[])
+            .await
+            .unwrap();
+        repo.insert_question("f", name, &[]).await.unwrap();
+        urls.push((path.display().to_string(), url));
+    }
+    let (outdated, url) = &urls[0];
+    let latest = "SELECT MAX(version) FROM _sqlx_migrations;";
+    let mut conn = connect(url, false).await;
+    let version: i64 = sqlx::query_scalar(latest)
+        .fetch_one(&mut conn)
+        .await
+        .unwrap();
+    sqlx::query("DELETE FROM _sqlx_migrations WHERE version = $1;")
+        .bind(version)
+        .execute(&mut conn)
+        .await
+        .unwrap();
+
+    // An in-memory database has a single connection, which is reused.
+    let here = Repository::new("sqlite::memory:").await.unwrap();
+    let err = here.merge_from(outdated, false).await.unwrap_err();
+    assert!(err.to_string().contains("older version"), "{:#}", err);
+    let unchanged: i64 = sqlx::query_scalar(latest)
+        .fetch_one(&mut conn)
+        .await
+        .unwrap();
+    assert!(unchanged < version);
+    assert!(here.get_all_questions().await.unwrap().is_empty());
+
+    let (current, _) = &urls[1];
+    let counts = here.merge_from(current, false).await.unwrap();
+    assert_eq!(counts.questions, 1);
+}
+
+#[tokio::test]
+async fn large_question_data_is_stored_compressed() {
+    let repo = Repository::new("sqlite::memory:").await.unwrap();
+    let data = "words:\n".to_string() + &"- word: translation\n".repeat(200);
+    repo.insert_question_factory("vocab", "default", &[])
+        .await
+        .unwrap();
+    repo.insert_question("vocab", "all", data.as_bytes())
+        .await
+        .unwrap();
+    let q = repo.get_question_by_name("vocab", "all").await.unwrap();
+    assert_eq!(q.data, data.as_bytes());
+    // Already compressed when it was inserted.
+    assert_eq!(repo.compress_data().await.unwrap().questions, 0);
+
+    // Data stored before compression is read as is.
+    assert_eq!(rust::compress::decompress(b"{}").unwrap(), b"{}");
+}
+
+/// Rows deleted by hand, e.g. with the sqlite3 shell, which leaves foreign
+/// keys off.
+#[tokio::test]
+async fn fsck_finds_and_deletes_orphans() {
+    let path = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("orphans.db");
+    for suffix in ["", "-wal", "-shm"] {
+        let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+    }
+    let url = format!("sqlite://{}?mode=rwc", path.display());
+    let repo = Repository::new(&url).await.unwrap();
+    for f in ["f", "g"] {
+        repo.insert_question_factory(f, "default", &[])
+            .await
+            .unwrap();
+        for q in ["a", "b"] {
+            repo.insert_question(f, q, &[]).await.unwrap();
+            let id = repo.get_question_by_name(f, q).await.unwrap().id;
+            repo.insert_question_in_set("s", id).await.unwrap();
+            repo.add_answer(id, Utc::now(), true, 1., &AnswerDetails::default(), 0.5)
+                .await
+                .unwrap();
+        }
+    }
+    let delete_factory = "DELETE FROM question_factories WHERE name = 'g';";
+    let mut conn = connect(&url, true).await;
+    assert!(sqlx::query(delete_factory)
+        .execute(&mut conn)
+        .await
+        .is_err());
+    assert!(repo.find_orphans().await.unwrap().is_empty());
+
+    let mut conn = connect(&url, false).await;
+    for delete in [
+        "DELETE FROM questions WHERE factory = 'f' AND name = 'a';",
+        delete_factory,
+    ] {
+        sqlx::query(delete).execute(&mut conn).await.unwrap();
+    }
+    let orphans = repo.find_orphans().await.unwrap();
+    assert_eq!(orphans.answers, 1);
+    assert_eq!(orphans.memberships, 1);
+    let names = orphans
+        .questions
+        .iter()
+        .map(|(f, q)| format!("{}/{}", f, q))
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["g/a", "g/b"]);
+
+    repo.delete_orphans().await.unwrap();
+    assert!(repo.find_orphans().await.unwrap().is_empty());
+    assert_eq!(repo.get_all_answers().await.unwrap().len(), 1);
+    assert_eq!(repo.get_all_question_sets().await.unwrap().len(), 1);
+    assert!(repo.integrity_check().await.unwrap().is_empty());
+}
+
+async fn connect(url: &str, foreign_keys: bool) -> SqliteConnection {
+    SqliteConnectOptions::from_str(url)
+        .unwrap()
+        .foreign_keys(foreign_keys)
+        .connect()
+        .await
+        .unwrap()
+}
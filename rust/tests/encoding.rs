@@ -0,0 +1,34 @@
+use rust::encoding::{decode, encode, is_legacy, reencode, same, VERSION};
+use serde_json::Value;
+
+const LEGACY: &str = "---\nid: france\nquestion: France\nanswers:\n  - Paris\n";
+
+#[test]
+fn legacy_yaml_is_read_and_reencoded_in_order() {
+    let data = LEGACY.as_bytes();
+    assert!(is_legacy(data));
+    let value = decode::<Value>(data).unwrap();
+    assert_eq!(value["answers"][0], "Paris");
+
+    let json = reencode(data).unwrap();
+    assert!(!is_legacy(&json));
+    assert_eq!(
+        String::from_utf8(json.clone()).unwrap(),
+        format!(
+            r#"{{"version":{},"data":{{"id":"france","question":"France","answers":["Paris"]}}}}"#,
+            VERSION
+        )
+    );
+    assert!(same(data, &json));
+    assert_eq!(decode::<Value>(&json).unwrap(), value);
+}
+
+#[test]
+fn round_trips_and_rejects_newer_versions() {
+    let data = encode(&vec![1, 2, 3]).unwrap();
+    assert_eq!(decode::<Vec<i32>>(&data).unwrap(), vec![1, 2, 3]);
+    assert!(!same(&data, &encode(&vec![1, 2]).unwrap()));
+
+    let newer = format!(r#"{{"version":{},"data":[1]}}"#, VERSION + 1);
+    assert!(decode::<Vec<i32>>(newer.as_bytes()).is_err());
+}
@@ -0,0 +1,84 @@
+use rust::functionality::{Score, Service};
+use rust::geo::{bearing, compass, distance_km, parse_coordinates, parse_quadrant};
+use rust::testing::{import_fixture, memory_repository, Scripted};
+
+const PARIS: (f64, f64) = (48.8566, 2.3522);
+const LONDON: (f64, f64) = (51.5074, -0.1278);
+
+#[test]
+fn coordinates_are_parsed_in_several_notations() {
+    let close = |a: (f64, f64), b: (f64, f64)| (a.0 - b.0).abs() < 1e-3 && (a.1 - b.1).abs() < 1e-3;
+    assert!(close(
+        parse_coordinates("48.86, 2.35").unwrap(),
+        (48.86, 2.35)
+    ));
+    assert!(close(
+        parse_coordinates("-33.9 151.2").unwrap(),
+        (-33.9, 151.2)
+    ));
+    assert!(close(
+        parse_coordinates("33.9S 151.2E").unwrap(),
+        (-33.9, 151.2)
+    ));
+    assert!(close(
+        parse_coordinates("2.35e, 48.86n").unwrap(),
+        (48.86, 2.35)
+    ));
+    assert!(close(
+        parse_coordinates("48°51'N 2°21'E").unwrap(),
+        (48.85, 2.35)
+    ));
+    assert!(close(
+        parse_coordinates("40°26'46\"N, 79°58'56\"W").unwrap(),
+        (40.4461, -79.9822)
+    ));
+    for s in [
+        "", "48.86", "91, 0", "0, 181", "-3S, 2E", "north, 2", "1, 2, 3",
+    ] {
+        assert!(parse_coordinates(s).is_err(), "{:?} was accepted", s);
+    }
+}
+
+#[test]
+fn distances_and_directions() {
+    let d = distance_km(PARIS, LONDON);
+    assert!((d - 344.).abs() < 2., "{}", d);
+    assert_eq!(distance_km(PARIS, PARIS), 0.);
+    assert_eq!(compass(bearing(PARIS, LONDON)), "north-west");
+    assert_eq!(compass(bearing(LONDON, PARIS)), "south-east");
+    assert_eq!(compass(359.), "north");
+}
+
+#[test]
+fn quadrants() {
+    assert_eq!(parse_quadrant("NE").unwrap(), (true, true));
+    assert_eq!(parse_quadrant("south west").unwrap(), (false, false));
+    assert_eq!(parse_quadrant("North-West").unwrap(), (true, false));
+    assert!(parse_quadrant("up").is_err());
+}
+
+#[tokio::test]
+async fn guesses_score_by_distance() {
+    let cities = "
+name: cities
+type_: geo
+data:
+  question_prefix: 'Where is '
+  tolerance_km: 100
+items:
+- id: paris
+  question: Paris?
+  lat: 48.8566
+  lon: 2.3522
+";
+    let repo = memory_repository().await.unwrap();
+    import_fixture(&repo, &[cities]).await.unwrap();
+    let service = Service::new(&repo).await.unwrap();
+    let paris = &service.get_questions()[0].runner;
+    // On the spot, 200 km off and London.
+    Scripted::new(&["48.86, 2.35", "47.05, 2.35", "51.51, -0.13"]).install();
+    assert_eq!(paris.run().unwrap(), Score::CORRECT);
+    let close = paris.run().unwrap().value();
+    assert!((close - 0.5).abs() < 0.01, "{}", close);
+    assert_eq!(paris.run().unwrap(), Score::WRONG);
+}
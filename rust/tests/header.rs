@@ -0,0 +1,18 @@
+use rust::header::{render, validate, DEFAULT_TEMPLATE};
+use std::collections::HashMap;
+
+#[test]
+fn templates_fill_in_fields() {
+    let values = HashMap::from([
+        ("probability", "0.500".to_string()),
+        ("last_answered", "-".to_string()),
+    ]);
+    assert_eq!(
+        render(DEFAULT_TEMPLATE, &values).unwrap(),
+        "prob: 0.500, last answered: -"
+    );
+    assert_eq!(render("", &values).unwrap(), "");
+    validate("{streak} in a row, box {box}, due {due} [{tags}]").unwrap();
+    assert!(validate("{prob}").is_err());
+    assert!(validate("{probability").is_err());
+}
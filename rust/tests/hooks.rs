@@ -0,0 +1,51 @@
+use chrono::{TimeZone, Utc};
+use rust::hooks::{fire, Event, Hook, Payload};
+use std::fs;
+
+#[test]
+fn commands_get_the_payload_on_stdin() {
+    let dir = std::env::temp_dir().join(format!("trivial-hooks-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let out = dir.join("payload.json");
+    let hooks = [
+        Hook {
+            on: Event::SessionCompleted,
+            command: Some(format!("cat > {}", out.display())),
+            url: None,
+        },
+        Hook {
+            on: Event::StreakMilestone,
+            command: Some("exit 1".to_string()),
+            url: None,
+        },
+    ];
+    let payload = Payload::SessionCompleted {
+        set: "capitals".to_string(),
+        answers: 10,
+        correct: 8,
+        seconds: 95,
+        time: Utc.with_ymd_and_hms(2024, 3, 30, 8, 0, 0).unwrap(),
+    };
+    assert!(fire(&hooks, &payload).is_empty());
+    assert_eq!(
+        fs::read_to_string(&out).unwrap(),
+        r#"{"event":"session_completed","set":"capitals","answers":10,"correct":8,"seconds":95,"time":"2024-03-30T08:00:00Z"}"#
+    );
+
+    let milestone = Payload::StreakMilestone {
+        days: 7,
+        time: Utc::now(),
+    };
+    assert_eq!(fire(&hooks, &milestone).len(), 1);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn hooks_need_a_command_or_a_url() {
+    let hook = Hook {
+        on: Event::SessionCompleted,
+        command: None,
+        url: None,
+    };
+    assert!(hook.validate().is_err());
+}
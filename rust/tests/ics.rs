@@ -0,0 +1,27 @@
+use chrono::{NaiveDate, TimeZone, Utc};
+use rust::ics::{calendar, DueReviews};
+
+#[test]
+fn calendars_have_an_event_per_set_and_day() {
+    let reviews = [
+        DueReviews {
+            set: "capitals, europe".to_string(),
+            day: NaiveDate::from_ymd_opt(2024, 3, 31).unwrap(),
+            count: 12,
+        },
+        DueReviews {
+            set: "verbs".to_string(),
+            day: NaiveDate::from_ymd_opt(2024, 4, 1).unwrap(),
+            count: 1,
+        },
+    ];
+    let stamp = Utc.with_ymd_and_hms(2024, 3, 30, 8, 0, 0).unwrap();
+    let ics = calendar(&reviews, stamp);
+    assert!(ics.starts_with("BEGIN:VCALENDAR\r\n"));
+    assert!(ics.ends_with("END:VCALENDAR\r\n"));
+    assert_eq!(ics.matches("BEGIN:VEVENT").count(), 2);
+    assert!(ics.contains("SUMMARY:capitals\\, europe: 12 reviews due\r\n"));
+    assert!(ics.contains("DTSTART;VALUE=DATE:20240331\r\nDTEND;VALUE=DATE:20240401\r\n"));
+    assert!(ics.contains("SUMMARY:verbs: 1 review due\r\n"));
+    assert!(ics.lines().all(|l| l.len() <= 76));
+}
@@ -0,0 +1,51 @@
+use rust::functionality::{parse_models, validate_models};
+use rust::generate::to_yaml;
+use rust::markdown::parse;
+
+const NOTES: &str = "
+Some prose that isn't a question.
+
+Q: What is 2 + 2?
+A: 4
+A: four
+
+# European capitals
+
+| Country | Capital |
+|---------|:-------:|
+| France  | Paris   |
+| Spain   | Madrid  |
+
+## Rivers
+
+Q: Longest river in France
+A: Loire
+";
+
+#[test]
+fn headings_become_sets() {
+    let decks = parse(NOTES, "My notes").unwrap();
+    let names = decks.iter().map(|d| d.name.as_str()).collect::<Vec<_>>();
+    assert_eq!(names, ["my_notes", "european_capitals", "rivers"]);
+
+    let math = &decks[0].questions[0];
+    assert_eq!(math.id, "what_is_2_2");
+    assert_eq!(math.question, "What is 2 + 2");
+    assert_eq!(math.answers, ["4", "four"]);
+
+    let capitals = &decks[1].questions;
+    assert_eq!(capitals.len(), 2);
+    assert_eq!(capitals[1].question, "Spain");
+    assert_eq!(capitals[1].answers, ["Madrid"]);
+
+    for deck in &decks {
+        let models = parse_models(&[to_yaml(&deck.name, &deck.questions).unwrap()]).unwrap();
+        assert_eq!(validate_models(&models), Vec::<String>::new());
+    }
+}
+
+#[test]
+fn questions_need_answers() {
+    assert!(parse("Q: Unanswered\n", "notes").is_err());
+    assert!(parse("A: Nothing asked\n", "notes").is_err());
+}
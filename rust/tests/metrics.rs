@@ -0,0 +1,45 @@
+use rust::functionality::{Score, Service};
+use rust::metrics::render;
+use rust::testing::{import_fixture, memory_repository};
+
+const CAPITALS: &str = "
+name: capitals
+type_: default
+data:
+  question_prefix: 'The capital of '
+items:
+- id: france
+  question: France
+  answers:
+  - Paris
+- id: spain
+  question: Spain
+  answers:
+  - Madrid
+";
+
+#[tokio::test]
+async fn metrics_count_answers_per_set() {
+    let repo = memory_repository().await.unwrap();
+    import_fixture(&repo, &[CAPITALS]).await.unwrap();
+    let mut service = Service::new(&repo).await.unwrap();
+    let ids = service
+        .get_questions()
+        .iter()
+        .map(|q| q.id)
+        .collect::<Vec<_>>();
+    service.add_answer(ids[0], Score::CORRECT).await.unwrap();
+    service.add_answer(ids[1], Score::WRONG).await.unwrap();
+
+    let text = render(&repo).await.unwrap();
+    for line in [
+        "# TYPE trivial_answers_total counter",
+        "trivial_answers_total{set=\"capitals\"} 2",
+        "trivial_correct_answers_total{set=\"capitals\"} 1",
+        "trivial_accuracy{set=\"capitals\"} 0.5",
+        "trivial_questions{set=\"capitals\"} 2",
+        "trivial_due_questions{set=\"capitals\"} 1",
+    ] {
+        assert!(text.lines().any(|l| l == line), "{:?} in {}", line, text);
+    }
+}
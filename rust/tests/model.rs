@@ -0,0 +1,61 @@
+use chrono::{Duration, Utc};
+use rust::functionality::{Answer, ProbabilityComputer, Score};
+use rust::model::{ModelKind, ModelOverrides, ModelParams};
+
+fn answers(scores: &[Score]) -> Vec<Answer> {
+    let start = Utc::now();
+    scores
+        .iter()
+        .enumerate()
+        .map(|(i, &score)| Answer {
+            question_id: 1,
+            time: start + Duration::minutes(i as i64),
+            score,
+        })
+        .collect()
+}
+
+#[test]
+fn bkt_learns_from_correct_answers() {
+    let params = ModelParams {
+        kind: ModelKind::Bkt,
+        ..Default::default()
+    };
+    let fresh = ProbabilityComputer::with_params(Vec::new(), &[(1, params)]);
+    assert!((fresh.get_prob(1) - 0.34).abs() < 1e-9);
+
+    let scores = [Score::CORRECT, Score::CORRECT, Score::CORRECT];
+    let computer = ProbabilityComputer::with_params(answers(&scores), &[(1, params)]);
+    assert!(computer.get_prob(1) > 0.85);
+    let scores = [Score::CORRECT, Score::CORRECT, Score::WRONG];
+    let wrong = ProbabilityComputer::with_params(answers(&scores), &[(1, params)]);
+    assert!(wrong.get_prob(1) < computer.get_prob(1));
+}
+
+#[test]
+fn evaluation_predicts_each_answer_from_the_earlier_ones() {
+    let params = ModelParams::default();
+    let evaluation =
+        ProbabilityComputer::evaluate(answers(&[Score::CORRECT, Score::CORRECT]), &[(1, params)]);
+    assert_eq!(evaluation.answers, 2);
+    // Predicted 1/2, then (1 + 1) / (1 + 2).
+    let expected = (0.5f64.powi(2) + (1. / 3f64).powi(2)) / 2.;
+    assert!((evaluation.brier - expected).abs() < 1e-9);
+    let other = ProbabilityComputer::evaluate(answers(&[Score::CORRECT]), &[(2, params)]);
+    assert_eq!(other.answers, 0);
+}
+
+#[test]
+fn overrides_are_checked() {
+    let overrides = ModelOverrides {
+        slip: Some(0.6),
+        guess: Some(0.5),
+        ..Default::default()
+    };
+    assert!(overrides.apply(ModelParams::default()).is_err());
+    let overrides = ModelOverrides {
+        decay: Some(0.8),
+        ..Default::default()
+    };
+    assert_eq!(overrides.apply(ModelParams::default()).unwrap().decay, 0.8);
+}
@@ -0,0 +1,43 @@
+use rust::normalize::{Normalize, NormalizeOverrides};
+
+#[test]
+fn default_ignores_case_and_whitespace_but_not_accents() {
+    let n = Normalize::default();
+    assert!(n.eq("  Mexico   City ", "mexico city"));
+    assert!(n.eq("Bogota\u{301}", "Bogotá"));
+    assert!(!n.eq("Bogota", "Bogotá"));
+}
+
+#[test]
+fn diacritics_and_articles_can_be_ignored() {
+    let n: Normalize =
+        serde_yaml::from_str("{strip_diacritics: true, ignore_articles: [the, la, 'él']}").unwrap();
+    assert!(n.eq("Bogota", "Bogotá"));
+    assert!(n.eq("la Paz", "Paz"));
+    assert!(n.eq("The Hague", "hague"));
+    assert!(n.eq("El hombre", "hombre"));
+    assert!(!n.eq("there", "re"));
+    assert!(n.eq("the", "the"));
+}
+
+#[test]
+fn punctuation_can_be_ignored_and_rules_overridden() {
+    let n: Normalize = serde_yaml::from_str("{ignore_punctuation: true}").unwrap();
+    assert!(n.eq("St Louis", "St. Louis!"));
+    assert!(n.eq("¿Qué?", "qué"));
+    let strict: NormalizeOverrides = serde_yaml::from_str("{case_fold: false}").unwrap();
+    let strict = strict.apply(&n);
+    assert!(strict.eq("Na.", "Na"));
+    assert!(!strict.eq("NA", "Na"));
+}
+
+#[test]
+fn spelled_out_numbers_match_their_digits() {
+    assert!(!Normalize::default().eq("seven", "7"));
+    let n: Normalize = serde_yaml::from_str("{spelled_numbers: true}").unwrap();
+    assert!(n.eq("Seven", "7"));
+    assert!(n.eq("21", "twenty-one"));
+    assert!(n.eq("the seven wonders", "The 7 wonders"));
+    let literal: NormalizeOverrides = serde_yaml::from_str("{spelled_numbers: false}").unwrap();
+    assert!(!literal.apply(&n).eq("seven", "7"));
+}
@@ -0,0 +1,41 @@
+use chrono::{Duration, Utc};
+use rust::functionality::{Score, Service};
+use rust::notify::Due;
+use rust::testing::{import_fixture, memory_repository};
+
+const CAPITALS: &str = "
+name: capitals
+type_: default
+data:
+  question_prefix: 'The capital of '
+items:
+- id: france
+  question: France
+  answers:
+  - Paris
+- id: spain
+  question: Spain
+  answers:
+  - Madrid
+";
+
+#[tokio::test]
+async fn wrong_and_old_answers_are_due() {
+    let repo = memory_repository().await.unwrap();
+    import_fixture(&repo, &[CAPITALS]).await.unwrap();
+    let mut service = Service::new(&repo).await.unwrap();
+    assert_eq!(Due::new(&service, Utc::now()), Due::default());
+
+    let ids = service.get_set("capitals").to_vec();
+    service.add_answer(ids[0], Score::WRONG).await.unwrap();
+    service.add_answer(ids[1], Score::CORRECT).await.unwrap();
+    let due = Due::new(&service, Utc::now());
+    assert_eq!(due.total, 1);
+    assert_eq!(due.sets, [("capitals".to_string(), 1)]);
+
+    let due = Due::new(&service, Utc::now() + Duration::days(2));
+    assert_eq!(due.total, 2);
+    let (title, body) = due.message("trivial run", 0);
+    assert_eq!(title, "2 questions due for review");
+    assert_eq!(body, "1 more set\nRun `trivial run`");
+}
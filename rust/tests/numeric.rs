@@ -0,0 +1,82 @@
+use rust::numeric::{parse_with_unit, parse_words, relative_error, si_parse, words_to_digits};
+use std::collections::BTreeMap;
+
+#[test]
+fn plain_and_suffixed_numbers() {
+    assert_eq!(si_parse("40000").unwrap(), 40_000);
+    assert_eq!(si_parse(" 40000 ").unwrap(), 40_000);
+    assert_eq!(si_parse("44k").unwrap(), 44_000);
+    assert_eq!(si_parse("1.5M").unwrap(), 1_500_000);
+    assert_eq!(si_parse("2b").unwrap(), 2_000_000_000);
+    assert_eq!(si_parse("3T").unwrap(), 3_000_000_000_000);
+    assert_eq!(si_parse("9007199254740993").unwrap(), 9_007_199_254_740_993);
+}
+
+#[test]
+fn separators_scientific_notation_and_signs() {
+    assert_eq!(si_parse("40,000").unwrap(), 40_000);
+    assert_eq!(si_parse("1_234_567").unwrap(), 1_234_567);
+    assert_eq!(si_parse("1,234.5k").unwrap(), 1_234_500);
+    assert_eq!(si_parse("4e4").unwrap(), 40_000);
+    assert_eq!(si_parse("2.5E-1k").unwrap(), 250);
+    assert_eq!(si_parse("-12").unwrap(), -12);
+    assert_eq!(si_parse("-1,000").unwrap(), -1_000);
+    assert_eq!(si_parse("-0.5k").unwrap(), -500);
+    assert_eq!(si_parse("2.6").unwrap(), 3);
+}
+
+#[test]
+fn malformed_numbers_are_rejected() {
+    for s in [
+        "", "k", "abc", "12x", "1,5", "1,00,000", ",100", "1__000", "inf", "NaN", "1e30T",
+    ] {
+        assert!(si_parse(s).is_err(), "{:?} was accepted", s);
+    }
+}
+
+#[test]
+fn relative_errors() {
+    assert_eq!(relative_error(44, 40), Some(0.1));
+    assert_eq!(relative_error(30, 40), Some(-0.25));
+    assert_eq!(relative_error(1, 0), None);
+}
+
+#[test]
+fn units_are_converted() {
+    let units = BTreeMap::from([
+        ("m".to_string(), 1.),
+        ("km".to_string(), 1000.),
+        ("mi".to_string(), 1609.344),
+    ]);
+    assert_eq!(parse_with_unit("12", &units).unwrap(), 12.);
+    assert_eq!(parse_with_unit("12 m", &units).unwrap(), 12.);
+    assert_eq!(parse_with_unit("1.5km", &units).unwrap(), 1500.);
+    assert_eq!(parse_with_unit("2k mi", &units).unwrap(), 3_218_688.);
+    assert!(parse_with_unit("3 ft", &units).is_err());
+    assert!(parse_with_unit("km", &units).is_err());
+}
+
+#[test]
+fn spelled_out_numbers() {
+    assert_eq!(parse_words("seven"), Some(7));
+    assert_eq!(parse_words("Twenty-One"), Some(21));
+    assert_eq!(parse_words("a hundred and five"), Some(105));
+    assert_eq!(parse_words("three thousand two hundred"), Some(3_200));
+    assert_eq!(parse_words("two million and one"), Some(2_000_001));
+    assert_eq!(parse_words("zero"), Some(0));
+    for s in [
+        "",
+        "a",
+        "seven seven",
+        "twenty ten",
+        "hundred",
+        "thousand",
+        "zero hundred",
+    ] {
+        assert_eq!(parse_words(s), None, "{:?}", s);
+    }
+    assert_eq!(si_parse("twelve").unwrap(), 12);
+    assert_eq!(words_to_digits("the seven wonders"), "the 7 wonders");
+    assert_eq!(words_to_digits("one two"), "1 2");
+    assert_eq!(words_to_digits("  no  numbers "), "  no  numbers ");
+}
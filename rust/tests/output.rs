@@ -0,0 +1,27 @@
+use rust::output::{self, Theme};
+
+#[test]
+fn themes_are_checked() {
+    let theme = Theme {
+        correct: "bold #00ff88".to_string(),
+        header: "underline bright blue".to_string(),
+        ..Theme::default()
+    };
+    output::init(&theme, false).unwrap();
+    assert_eq!(output::correct("yes").to_string(), "yes");
+
+    let theme = Theme {
+        wrong: "blurple".to_string(),
+        ..Theme::default()
+    };
+    let err = output::init(&theme, false).unwrap_err();
+    assert_eq!(err.to_string(), "theme.wrong: unknown color \"blurple\"");
+}
+
+#[test]
+fn feedback_returns_when_the_silence_ends() {
+    let silenced = output::silence();
+    assert!(output::is_silent());
+    drop(silenced);
+    assert!(!output::is_silent());
+}
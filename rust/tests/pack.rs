@@ -0,0 +1,200 @@
+use std::collections::BTreeMap;
+
+use chrono::Utc;
+use rust::assets;
+use rust::functionality::{load_models_dir, parse_models};
+use rust::pack::{Manifest, Pack, FORMAT};
+use std::path::Path;
+
+const CAPITALS: &str = "
+name: capitals
+type_: default
+data:
+  question_prefix: 'The capital of '
+items:
+- id: france
+  question: France
+  answers:
+  - Paris
+";
+
+fn pack(sets: &[(&str, &str)], media: &[(&str, &str)]) -> Pack {
+    Pack {
+        manifest: Manifest {
+            format: FORMAT,
+            name: "test".to_string(),
+            description: None,
+            author: Some("someone".to_string()),
+            created: Utc::now(),
+            sets: sets.iter().map(|(name, _)| name.to_string()).collect(),
+            media: media.iter().map(|(name, _)| name.to_string()).collect(),
+            assets: BTreeMap::new(),
+        },
+        sets: sets
+            .iter()
+            .map(|(name, data)| (name.to_string(), data.as_bytes().to_vec()))
+            .collect::<BTreeMap<_, _>>(),
+        media: media
+            .iter()
+            .map(|(name, data)| (name.to_string(), data.as_bytes().to_vec()))
+            .collect::<BTreeMap<_, _>>(),
+        assets: BTreeMap::new(),
+    }
+}
+
+#[test]
+fn round_trip() {
+    let original = pack(
+        &[("capitals.yaml", CAPITALS)],
+        &[("flags/fr.svg", "<svg/>")],
+    );
+    let mut data = Vec::new();
+    original.write(&mut data).unwrap();
+
+    let read = Pack::read(&data[..]).unwrap();
+    assert_eq!(read.manifest.name, "test");
+    assert_eq!(read.manifest.author.as_deref(), Some("someone"));
+    assert_eq!(read.sets, original.sets);
+    assert_eq!(read.media, original.media);
+    assert_eq!(read.validate().unwrap().questions.len(), 1);
+}
+
+#[test]
+fn rejects_paths_outside_the_pack() {
+    let mut data = Vec::new();
+    pack(&[("nested/capitals.yaml", CAPITALS)], &[])
+        .write(&mut data)
+        .unwrap();
+    assert!(Pack::read(&data[..]).is_err());
+
+    // The tar builder refuses to write `..`, so the header is written by hand.
+    let mut tar = tar::Builder::new(flate2::write::GzEncoder::new(
+        Vec::new(),
+        flate2::Compression::default(),
+    ));
+    let mut header = tar::Header::new_old();
+    let name = b"sets/../capitals.yaml";
+    header.as_old_mut().name[..name.len()].copy_from_slice(name);
+    header.set_size(CAPITALS.len() as u64);
+    header.set_cksum();
+    tar.append(&header, CAPITALS.as_bytes()).unwrap();
+    let data = tar.into_inner().unwrap().finish().unwrap();
+    assert!(Pack::read(&data[..]).is_err());
+}
+
+#[test]
+fn packs_carry_the_assets_sets_refer_to() {
+    let flags = "
+name: flags
+type_: default
+data:
+  question_prefix: ''
+items:
+- id: france
+  question: Whose flag is this?
+  assets:
+  - assets/flags/fr.svg
+  answers:
+  - France
+";
+    let dir = std::env::temp_dir().join(format!("trivial-assets-{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("assets/flags")).unwrap();
+    std::fs::write(dir.join("flags.yaml"), flags).unwrap();
+    std::fs::write(dir.join("assets/flags/fr.svg"), "<svg/>").unwrap();
+    let manifest = pack(&[], &[]).manifest;
+    let original = Pack::create(manifest, &[dir.join("flags.yaml")], None).unwrap();
+    assert_eq!(original.manifest.assets.len(), 1);
+
+    let models = original.validate().unwrap();
+    let recorded = assets::scan(&dir, &assets::references(&models).unwrap()).unwrap();
+    assert_eq!(recorded[0].path, "assets/flags/fr.svg");
+    assert!(assets::verify(&dir, &recorded).is_empty());
+    std::fs::write(dir.join("assets/flags/fr.svg"), "<svg></svg>").unwrap();
+    assert_eq!(assets::verify(&dir, &recorded).len(), 1);
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    let mut data = Vec::new();
+    original.write(&mut data).unwrap();
+    let read = Pack::read(&data[..]).unwrap();
+    assert_eq!(read.assets, original.assets);
+
+    let mut tampered = original;
+    tampered
+        .assets
+        .insert("assets/flags/fr.svg".to_string(), b"<svg></svg>".to_vec());
+    let mut data = Vec::new();
+    tampered.write(&mut data).unwrap();
+    assert!(Pack::read(&data[..]).is_err());
+
+    tampered.assets.clear();
+    tampered.manifest.assets.clear();
+    assert!(tampered.validate().is_err(), "the asset is missing");
+}
+
+#[test]
+fn only_asset_fields_refer_to_assets() {
+    let files = "
+name: files
+type_: default
+data:
+  question_prefix: ''
+items:
+- id: layout
+  question: Where do the images of a set go?
+  answers:
+  - assets/
+- id: flag
+  question: Whose flag is this?
+  assets:
+  - assets/flags/fr.svg
+  answers:
+  - France
+";
+    let anthem = "
+name: anthems
+type_: script
+data:
+  interpreter: [sh, -c]
+  script: exit 0
+items:
+- id: marseillaise
+  question: Whose anthem is this?
+  sound: assets/anthems/fr.ogg
+";
+    let models = parse_models(&[files, anthem]).unwrap();
+    let paths = assets::references(&models).unwrap();
+    assert_eq!(
+        paths.into_iter().collect::<Vec<_>>(),
+        ["assets/anthems/fr.ogg", "assets/flags/fr.svg"]
+    );
+
+    let outside = files.replace("assets/flags/fr.svg", "flags/fr.svg");
+    assert!(assets::references(&parse_models(&[&outside]).unwrap()).is_err());
+}
+
+#[test]
+fn set_directories_can_hold_assets() {
+    let flags = "
+name: flags
+type_: default
+data:
+  question_prefix: ''
+items:
+- id: france
+  question: Whose flag is this?
+  assets:
+  - assets/flags/fr.svg
+  answers:
+  - France
+";
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("set-dir-with-assets");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(dir.join("assets/flags")).unwrap();
+    std::fs::write(dir.join("flags.yaml"), flags).unwrap();
+    std::fs::write(dir.join("assets/flags/fr.svg"), "<svg/>").unwrap();
+    std::fs::write(dir.join("README.md"), "Flags of the world").unwrap();
+
+    let models = load_models_dir(&dir).unwrap();
+    assert_eq!(models.questions.len(), 1);
+    assert_eq!(assets::references(&models).unwrap().len(), 1);
+}
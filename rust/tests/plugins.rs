@@ -0,0 +1,106 @@
+use rust::functionality::{Score, Service};
+use rust::plugins;
+use rust::testing::{import_fixture, memory_repository, Scripted};
+use std::process::Command;
+
+/// A plugin providing the `yes` question type, which asks to say yes, and
+/// accepts it in the language the set's data gives.
+const PLUGIN: &str = r##"
+use std::ffi::{c_char, CStr, CString};
+
+#[repr(C)]
+pub struct Plugin {
+    abi_version: u32,
+    types: *const PluginType,
+    count: usize,
+}
+
+#[repr(C)]
+pub struct PluginType {
+    name: *const c_char,
+    call: unsafe extern "C" fn(*const c_char) -> *mut c_char,
+    free: unsafe extern "C" fn(*mut c_char),
+}
+
+unsafe impl Sync for Plugin {}
+unsafe impl Sync for PluginType {}
+
+unsafe extern "C" fn call(request: *const c_char) -> *mut c_char {
+    let request = unsafe { CStr::from_ptr(request) }.to_str().unwrap();
+    let yes = match request.contains(r#""language":"fr""#) {
+        true => "oui",
+        false => "yes",
+    };
+    let reply = if request.contains(r#""answer":null"#) {
+        format!(r#"{{"prompt": "Say {}"}}"#, yes)
+    } else if request.contains(&format!(r#""answer":"{}""#, yes)) {
+        r#"{"score": 1}"#.to_string()
+    } else {
+        r#"{"score": 0}"#.to_string()
+    };
+    CString::new(reply).unwrap().into_raw()
+}
+
+unsafe extern "C" fn free(reply: *mut c_char) {
+    drop(unsafe { CString::from_raw(reply) });
+}
+
+static TYPES: [PluginType; 1] = [PluginType {
+    name: c"yes".as_ptr(),
+    call,
+    free,
+}];
+
+static PLUGIN: Plugin = Plugin {
+    abi_version: 1,
+    types: &TYPES as *const PluginType,
+    count: 1,
+};
+
+#[no_mangle]
+pub extern "C" fn trivial_plugin() -> *const Plugin {
+    &PLUGIN
+}
+"##;
+
+#[tokio::test]
+async fn plugins_provide_question_types() {
+    let dir = std::env::temp_dir().join(format!("trivial-plugins-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let source = dir.join("yes.rs");
+    std::fs::write(&source, PLUGIN).unwrap();
+    let library = dir.join(format!(
+        "{}yes.{}",
+        std::env::consts::DLL_PREFIX,
+        std::env::consts::DLL_EXTENSION
+    ));
+    let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let status = Command::new(rustc)
+        .args(["--edition", "2021", "--crate-type", "cdylib", "-o"])
+        .arg(&library)
+        .arg(&source)
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    assert_eq!(plugins::load_dir(&dir).unwrap(), ["yes"]);
+    assert!(plugins::load(&library).is_err(), "types are provided once");
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    let set = "
+name: french
+type_: yes
+data:
+  prompt: true
+  language: fr
+items:
+- id: oui
+";
+    let repo = memory_repository().await.unwrap();
+    import_fixture(&repo, &[set]).await.unwrap();
+    let service = Service::new(&repo).await.unwrap();
+    let question = &service.get_questions()[0].runner;
+    Scripted::new(&["oui", "yes"]).install();
+    assert_eq!(question.run().unwrap(), Score::CORRECT);
+    assert_eq!(question.run().unwrap(), Score::WRONG);
+}
@@ -0,0 +1,43 @@
+use chrono::NaiveTime;
+use rust::config::Config;
+use rust::profile::pick;
+
+const CONFIG: &str = "
+profiles:
+- name: morning
+  from: '06:00'
+  to: '12:00'
+  set: vocabulary
+  num: 20
+- name: night
+  from: '22:00'
+  to: '02:00'
+  ranking: Weighted random
+- name: exam
+  set: capitals
+";
+
+fn at(time: &str) -> NaiveTime {
+    NaiveTime::parse_from_str(time, "%H:%M").unwrap()
+}
+
+#[test]
+fn profiles_are_picked_by_time_or_name() {
+    let config: Config = serde_yaml::from_str(CONFIG).unwrap();
+    let profiles = &config.profiles;
+    let name = |name, time| {
+        pick(profiles, name, at(time))
+            .unwrap()
+            .map(|p| p.name.as_str())
+    };
+    assert_eq!(name(None, "06:00"), Some("morning"));
+    assert_eq!(name(None, "11:59"), Some("morning"));
+    assert_eq!(name(None, "12:00"), None);
+    assert_eq!(name(None, "23:30"), Some("night"));
+    assert_eq!(name(None, "01:00"), Some("night"));
+    assert_eq!(name(Some("exam"), "07:00"), Some("exam"));
+    assert!(pick(profiles, Some("lunch"), at("07:00")).is_err());
+    for profile in profiles {
+        profile.validate().unwrap();
+    }
+}
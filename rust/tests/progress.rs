@@ -0,0 +1,45 @@
+use rust::progress::Progress;
+
+#[test]
+fn progress_through_rounds() {
+    let mut progress = Progress::new(4);
+    assert_eq!(
+        progress.to_string(),
+        "░░░░░░░░░░░░░░░░░░░░ 0/4 · 4 left · 0 wrong · 0:00"
+    );
+    progress.record(true);
+    progress.record(false);
+    assert_eq!(
+        progress.to_string(),
+        "██████████░░░░░░░░░░ 2/4 · 2 left · 50% correct · 1 wrong · 0:00"
+    );
+    progress.record(true);
+    progress.record(true);
+    progress.next_round();
+    assert_eq!(
+        progress.to_string(),
+        "Round 2 ░░░░░░░░░░░░░░░░░░░░ 0/1 · 1 left · 75% correct · 0 wrong · 0:00"
+    );
+}
+
+#[test]
+fn exam_progress_hides_the_score() {
+    let mut progress = Progress::new(2);
+    progress.hide_score();
+    progress.record(false);
+    assert_eq!(
+        progress.to_string(),
+        "██████████░░░░░░░░░░ 1/2 · 1 left · 0:00"
+    );
+}
+
+#[test]
+fn removed_questions_leave_the_total() {
+    let mut progress = Progress::new(3);
+    progress.record(true);
+    progress.remove();
+    assert_eq!(
+        progress.to_string(),
+        "██████████░░░░░░░░░░ 1/2 · 1 left · 100% correct · 0 wrong · 0:00"
+    );
+}
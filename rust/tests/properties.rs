@@ -0,0 +1,19 @@
+use proptest::prelude::*;
+use rust::functionality::{content_hash, Score};
+
+proptest! {
+    #[test]
+    fn score_is_clamped(value in -10f64..10.) {
+        let score = Score::new(value);
+        prop_assert!((0. ..=1.).contains(&score.value()));
+    }
+
+    #[test]
+    fn content_hash_ignores_the_name(a in "[a-z_]{1,12}", b in "[a-z_]{1,12}", answer in "[A-Za-z ]{0,20}") {
+        let question = |id: &str| format!("id: {}\nquestion: q\nanswers:\n- '{}'\n", id, answer);
+        prop_assert_eq!(
+            content_hash(question(&a).as_bytes()),
+            content_hash(question(&b).as_bytes())
+        );
+    }
+}
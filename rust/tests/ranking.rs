@@ -0,0 +1,70 @@
+use rust::functionality::{Score, Selection, Service};
+use rust::ranking::CustomMethod;
+use rust::testing::{import_fixture, memory_repository};
+
+const CAPITALS: &str = "
+name: capitals
+type_: default
+data:
+  question_prefix: 'The capital of '
+items:
+- id: france
+  question: France
+  answers: [Paris]
+- id: spain
+  question: Spain
+  answers: [Madrid]
+  tags: [iberia]
+- id: italy
+  question: Italy
+  answers: [Rome]
+";
+
+fn method(expression: &str) -> CustomMethod {
+    serde_yaml::from_str(&format!(
+        "{{name: test, expression: '{}', tag_weights: {{iberia: 3}}}}",
+        expression
+    ))
+    .unwrap()
+}
+
+#[tokio::test]
+async fn custom_methods_rank_by_their_expression() {
+    let repo = memory_repository().await.unwrap();
+    import_fixture(&repo, &[CAPITALS]).await.unwrap();
+    let mut service = Service::new(&repo).await.unwrap();
+    let id = |service: &Service, name: &str| {
+        service
+            .get_questions()
+            .iter()
+            .find(|q| q.name == name)
+            .unwrap()
+            .id
+    };
+    let (france, spain, italy) = (
+        id(&service, "france"),
+        id(&service, "spain"),
+        id(&service, "italy"),
+    );
+    service.add_answer(france, Score::WRONG).await.unwrap();
+    service.add_answer(italy, Score::CORRECT).await.unwrap();
+
+    let by_tag = method("tag_weight - num_answers");
+    by_tag.validate().unwrap();
+    assert_eq!(
+        service
+            .get_custom_selection("capitals", &by_tag, 3, Selection::All)
+            .unwrap()[0],
+        spain
+    );
+    let hardest = method("(1 - probability) * 10 + min(1)");
+    assert!(hardest.validate().is_err());
+    let hardest = method("(1 - probability) * 10 - days_since_last / 10000");
+    assert_eq!(
+        service
+            .get_custom_selection("capitals", &hardest, 1, Selection::Practiced)
+            .unwrap(),
+        vec![france]
+    );
+    assert!(method("probability + nonsense").validate().is_err());
+}
@@ -0,0 +1,85 @@
+use rust::functionality::{parse_models, validate_models, Score, Service};
+use rust::testing::{import_fixture, memory_repository, Scripted};
+
+const ELEMENTS: &str = "
+name: elements
+type_: records
+data:
+  variants:
+  - id: name
+    question: 'Which element has the symbol {symbol}?'
+    answer: name
+  - id: number
+    question: 'What is the atomic number of {name}?'
+    answer: number
+items:
+- id: hydrogen
+  name: Hydrogen
+  symbol: H
+  number: 1
+- id: unknownium
+  name: Unknownium
+  symbol: Uk
+";
+
+#[test]
+fn every_record_is_asked_in_every_variant_it_has_fields_for() {
+    let models = parse_models(&[ELEMENTS]).unwrap();
+    assert_eq!(validate_models(&models), Vec::<String>::new());
+    let mut names = models
+        .questions
+        .iter()
+        .map(|q| q.name.as_str())
+        .collect::<Vec<_>>();
+    names.sort();
+    assert_eq!(
+        names,
+        ["hydrogen-name", "hydrogen-number", "unknownium-name"]
+    );
+}
+
+#[tokio::test]
+async fn variants_ask_for_their_field() {
+    let repo = memory_repository().await.unwrap();
+    import_fixture(&repo, &[ELEMENTS]).await.unwrap();
+    let service = Service::new(&repo).await.unwrap();
+    let question = |name: &str| {
+        let q = service
+            .get_questions()
+            .into_iter()
+            .find(|q| q.name == name)
+            .unwrap();
+        &q.runner
+    };
+    let number = question("hydrogen-number");
+    assert_eq!(
+        number.question_text(),
+        "What is the atomic number of Hydrogen?"
+    );
+    assert_eq!(number.answer_text(), "1");
+    Scripted::new(&["1", "hydrogen", "He"]).install();
+    assert_eq!(number.run().unwrap(), Score::CORRECT);
+    let name = question("hydrogen-name");
+    assert_eq!(name.run().unwrap(), Score::CORRECT);
+    assert_eq!(name.run().unwrap(), Score::WRONG);
+}
+
+#[test]
+fn answers_can_be_templates_too() {
+    let capitals = "
+name: capitals
+type_: template
+data:
+  variants:
+  - id: located
+    question: 'Where is {capital}?'
+    answer: '{country}, {continent}'
+items:
+- {id: france, country: France, capital: Paris, continent: Europe}
+- {id: nowhere, capital: Atlantis}
+";
+    let models = parse_models(&[capitals]).unwrap();
+    assert_eq!(validate_models(&models), Vec::<String>::new());
+    assert_eq!(models.questions.len(), 1);
+    assert_eq!(models.questions[0].name, "france-located");
+}
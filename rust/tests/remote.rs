@@ -0,0 +1,93 @@
+use rust::functionality::parse_models;
+use rust::remote::init_cache;
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::thread;
+
+const SET: &str = "
+name: teachers-capitals
+type_: default
+data:
+  question_prefix: 'What is the capital of '
+items:
+- id: france
+  question: France
+  answers: [Paris]
+";
+
+/// Serves the set to a single request, returning the URL.
+fn serve_once(set: &str) -> String {
+    let set = set.to_string();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/capitals.yaml", listener.local_addr().unwrap());
+    thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut request = [0; 1024];
+        let _ = stream.read(&mut request).unwrap();
+        write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            set.len(),
+            set
+        )
+        .unwrap();
+    });
+    url
+}
+
+#[test]
+fn remote_sets_are_fetched_and_cached() {
+    let cache = std::env::temp_dir().join(format!("trivial-remote-{}", std::process::id()));
+    init_cache(cache.clone());
+    let url = serve_once(SET);
+    let remote = |max_age: f64| {
+        format!(
+            "name: capitals\ntype_: remote\ndata:\n  url: {}\n  max_age_hours: {}\n",
+            url, max_age
+        )
+    };
+
+    let models = parse_models(&[remote(24.)]).unwrap();
+    assert_eq!(models.questions.len(), 1);
+    assert_eq!(models.questions[0].factory, "capitals");
+    assert!(models.sets.contains_key("capitals"));
+
+    // The server only answered once, so these come from the cache, the
+    // second after failing to fetch it again.
+    assert_eq!(parse_models(&[remote(24.)]).unwrap().questions.len(), 1);
+    assert_eq!(parse_models(&[remote(0.)]).unwrap().questions.len(), 1);
+
+    std::fs::remove_dir_all(&cache).unwrap();
+    assert!(parse_models(&[remote(0.)]).is_err());
+}
+
+#[test]
+fn remote_sets_cant_run_commands() {
+    let script = "
+name: primes
+type_: script
+data:
+  interpreter: [sh, -c]
+  script: touch owned
+items:
+- id: prime
+  question: Name a prime
+";
+    let speaking = SET.replace(
+        "data:\n",
+        "data:\n  speech:\n    command: [sh, -c, touch owned]\n",
+    );
+    init_cache(std::env::temp_dir().join(format!("trivial-remote-{}", std::process::id())));
+    for set in [script, &speaking] {
+        let url = serve_once(set);
+        let remote = format!("name: sneaky\ntype_: remote\ndata:\n  url: {}\n", url);
+        let Err(err) = parse_models(&[remote]) else {
+            panic!("{} was fetched", set);
+        };
+        assert!(
+            format!("{:#}", err).contains("can't be fetched"),
+            "{:#}",
+            err
+        );
+    }
+}
@@ -0,0 +1,88 @@
+use rust::functionality::{Score, Service};
+use rust::room::{score_reply, RoomQuestion, Rooms, Scored};
+use rust::testing::{import_fixture, memory_repository};
+use std::time::{Duration, Instant};
+
+const CAPITALS: &str = "
+name: capitals
+type_: default
+data:
+  question_prefix: 'The capital of '
+items:
+- id: france
+  question: France
+  answers:
+  - Paris
+";
+
+fn question(id: i64) -> RoomQuestion {
+    RoomQuestion {
+        id,
+        text: format!("question {}", id),
+        answer: format!("answer {}", id),
+    }
+}
+
+#[test]
+fn faster_correct_answers_get_more_points() {
+    let mut rooms = Rooms::new();
+    let limit = Duration::from_secs(20);
+    let (code, host) = rooms
+        .create("set", vec![question(1), question(2)], limit)
+        .unwrap();
+    let ana = rooms.join(&code, "ana").unwrap();
+    let ben = rooms.join(&code.to_lowercase(), "ben").unwrap();
+    assert!(rooms.join(&code, "ana").is_err());
+
+    let start = Instant::now();
+    assert!(rooms.question(&code, &ana, start).is_err());
+    assert!(rooms.next(&code, &ana, start).is_err());
+    rooms.next(&code, &host, start).unwrap();
+    assert_eq!(rooms.question(&code, &ana, start).unwrap(), 1);
+    let view = rooms.view(&code, start).unwrap();
+    assert_eq!(view.question.as_deref(), Some("question 1"));
+    assert_eq!(view.previous, None);
+
+    let first = rooms
+        .answer(&code, &ana, Score::CORRECT, start + Duration::from_secs(2))
+        .unwrap();
+    let second = rooms
+        .answer(&code, &ben, Score::CORRECT, start + Duration::from_secs(10))
+        .unwrap();
+    assert_eq!(
+        first,
+        Scored {
+            correct: true,
+            points: 950
+        }
+    );
+    assert_eq!(second.points, 750);
+    assert!(rooms.answer(&code, &ana, Score::CORRECT, start).is_err());
+
+    // Everyone answered, so the answer is shown before time is up.
+    let view = rooms.view(&code, start + Duration::from_secs(11)).unwrap();
+    assert_eq!(view.question, None);
+    assert_eq!(view.previous, Some(question(1)));
+    assert_eq!(
+        view.scores,
+        [("ana".to_string(), 950), ("ben".to_string(), 750)]
+    );
+
+    rooms.next(&code, &host, start + limit).unwrap();
+    let late = start + limit * 2;
+    assert!(rooms.answer(&code, &ben, Score::CORRECT, late).is_err());
+    let view = rooms.view(&code, late).unwrap();
+    assert!(view.finished);
+    assert_eq!(view.previous, Some(question(2)));
+    assert!(rooms.next(&code, &host, late).is_err());
+}
+
+#[tokio::test]
+async fn replies_are_scored_like_answers() {
+    let repo = memory_repository().await.unwrap();
+    import_fixture(&repo, &[CAPITALS]).await.unwrap();
+    let service = Service::new(&repo).await.unwrap();
+    let runner = &service.get_questions()[0].runner;
+    assert!(score_reply(runner.as_ref(), "Paris").is_correct());
+    assert!(!score_reply(runner.as_ref(), "Lyon").is_correct());
+}
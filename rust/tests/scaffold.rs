@@ -0,0 +1,12 @@
+use rust::functionality::{parse_models, validate_models};
+use rust::scaffold::{scaffold, types};
+
+#[test]
+fn every_template_is_a_valid_set() {
+    for type_ in types() {
+        let set = scaffold(type_, &format!("new_{}", type_)).unwrap();
+        let models = parse_models(&[set]).unwrap();
+        assert_eq!(validate_models(&models), Vec::<String>::new(), "{}", type_);
+        assert!(models.sets.contains_key(&format!("new_{}", type_)));
+    }
+}
@@ -0,0 +1,59 @@
+use rust::functionality::{Score, Service};
+use rust::testing::{import_fixture, memory_repository, Scripted};
+
+#[tokio::test]
+async fn scripts_validate_and_score_answers() {
+    let words = r#"
+name: words
+type_: script
+data:
+  interpreter: [python3, -c]
+  script: |
+    import json, sys
+    request = json.load(sys.stdin)
+    words = request["answer"].split()
+    if not words:
+        print(json.dumps({"invalid": "Give at least one word"}))
+    else:
+        long = [w for w in words if len(w) >= request["item"]["length"]]
+        print(json.dumps({"score": len(long) / len(words)}))
+items:
+- id: long-words
+  question: Name long words
+  length: 8
+"#;
+    let repo = memory_repository().await.unwrap();
+    import_fixture(&repo, &[words]).await.unwrap();
+    let service = Service::new(&repo).await.unwrap();
+    let question = &service.get_questions()[0].runner;
+    Scripted::new(&[" ", "elephant wonderful", "elephant cat"]).install();
+    assert_eq!(question.run().unwrap(), Score::CORRECT);
+    assert_eq!(question.run().unwrap(), Score::new(0.5));
+}
+
+#[tokio::test]
+async fn scripts_can_write_the_prompt() {
+    let echo = r#"
+name: echo
+type_: script
+data:
+  interpreter: [sh, -c]
+  prompt: true
+  script: |
+    read -r request
+    case "$request" in
+      *'"answer":null'*) echo '{"prompt": "Say yes"}' ;;
+      *'"answer":"yes"'*) echo '{"score": 1}' ;;
+      *) echo '{"score": 0, "feedback": "Say yes!"}' ;;
+    esac
+items:
+- id: yes
+"#;
+    let repo = memory_repository().await.unwrap();
+    import_fixture(&repo, &[echo]).await.unwrap();
+    let service = Service::new(&repo).await.unwrap();
+    let question = &service.get_questions()[0].runner;
+    Scripted::new(&["yes", "no"]).install();
+    assert_eq!(question.run().unwrap(), Score::CORRECT);
+    assert_eq!(question.run().unwrap(), Score::WRONG);
+}
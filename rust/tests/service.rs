@@ -0,0 +1,1083 @@
+use rust::db::Load;
+use rust::functionality::{
+    diff_models, load_models, validate_models, weighted_shares, Method, Score, Selection, Service,
+};
+use rust::input::{interrupted, take_details, Shortcut};
+use rust::model::ModelParams;
+use rust::storage::{MemoryStorage, Storage};
+use rust::testing::{fixture, import_fixture, memory_repository, Scripted};
+
+const CAPITALS: &str = "
+name: capitals
+type_: default
+data:
+  question_prefix: 'The capital of '
+items:
+- id: france
+  question: France
+  answers:
+  - Paris
+- id: spain
+  question: Spain
+  answers:
+  - Madrid
+- id: italy
+  question: Italy
+  answers:
+  - Rome
+";
+
+const CARDS: &str = "
+name: cards
+type_: flashcard
+data: {}
+items:
+- id: hello
+  front: hej
+  back: hello
+";
+
+const EVERYTHING: &str = "
+name: everything
+type_: union
+data:
+  sets:
+  - capitals
+  - cards
+";
+
+fn find(service: &Service, name: &str) -> i64 {
+    service
+        .get_questions()
+        .into_iter()
+        .find(|q| q.name == name)
+        .unwrap()
+        .id
+}
+
+#[tokio::test]
+async fn import_builds_sets_and_unions() {
+    let repo = memory_repository().await.unwrap();
+    import_fixture(&repo, &[CAPITALS, CARDS, EVERYTHING])
+        .await
+        .unwrap();
+
+    let service = Service::new(&repo).await.unwrap();
+    assert_eq!(service.get_set("capitals").len(), 3);
+    assert_eq!(service.get_set("cards").len(), 1);
+    assert_eq!(service.get_set("everything").len(), 4);
+}
+
+#[tokio::test]
+async fn reimporting_the_same_sets_changes_nothing() {
+    let repo = memory_repository().await.unwrap();
+    import_fixture(&repo, &[CAPITALS]).await.unwrap();
+
+    let mut service = Service::new(&repo).await.unwrap();
+    let report = service
+        .import(&fixture(&[CAPITALS]).unwrap(), false)
+        .await
+        .unwrap();
+    assert!(report.diff.is_empty());
+}
+
+#[tokio::test]
+async fn diffs_show_questions_joining_sets() {
+    let repo = memory_repository().await.unwrap();
+    import_fixture(&repo, &[CAPITALS, CARDS]).await.unwrap();
+
+    let diff = diff_models(&repo, &fixture(&[CAPITALS, CARDS, EVERYTHING]).unwrap())
+        .await
+        .unwrap();
+    assert!(diff.added.is_empty() && diff.updated.is_empty());
+    assert!(!diff.is_empty());
+    let joined = diff
+        .joined
+        .iter()
+        .map(|(set, keys)| (set.as_str(), keys.len()))
+        .collect::<Vec<_>>();
+    assert_eq!(joined, [("everything", 4)]);
+    assert!(diff.left.is_empty());
+}
+
+#[tokio::test]
+async fn renamed_question_keeps_its_answers() {
+    let repo = memory_repository().await.unwrap();
+    import_fixture(&repo, &[CAPITALS]).await.unwrap();
+    let mut service = Service::new(&repo).await.unwrap();
+    let id = find(&service, "france");
+    service.add_answer(id, Score::CORRECT).await.unwrap();
+
+    let renamed = CAPITALS.replace("id: france", "id: fr");
+    let report = service
+        .import(&fixture(&[&renamed]).unwrap(), false)
+        .await
+        .unwrap();
+    assert_eq!(report.diff.renamed.len(), 1);
+    assert_eq!(find(&service, "fr"), id);
+    assert_eq!(repo.get_answers(id).await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn renamed_question_stays_in_its_sets() {
+    let repo = memory_repository().await.unwrap();
+    import_fixture(&repo, &[CAPITALS, CARDS, EVERYTHING])
+        .await
+        .unwrap();
+    let mut service = Service::new(&repo).await.unwrap();
+    let id = find(&service, "france");
+
+    let renamed = CAPITALS.replace("id: france", "id: fr");
+    let report = service
+        .import(&fixture(&[&renamed, CARDS, EVERYTHING]).unwrap(), false)
+        .await
+        .unwrap();
+    assert_eq!(report.diff.renamed.len(), 1);
+    assert_eq!(find(&service, "fr"), id);
+    assert!(service.get_set("capitals").contains(&id));
+    assert!(service.get_set("everything").contains(&id));
+    assert_eq!(service.get_set("everything").len(), 4);
+}
+
+#[tokio::test]
+async fn questions_with_the_same_content_are_renamed_one_each() {
+    let twins = "
+name: twins
+type_: default
+data:
+  question_prefix: ''
+items:
+- id: a
+  question: Same?
+  answers:
+  - Yes
+- id: b
+  question: Same?
+  answers:
+  - Yes
+";
+    let repo = memory_repository().await.unwrap();
+    import_fixture(&repo, &[twins]).await.unwrap();
+    let mut service = Service::new(&repo).await.unwrap();
+    let a = find(&service, "a");
+    let b = find(&service, "b");
+
+    let renamed = twins
+        .replace("id: a", "id: c")
+        .replace("- id: b", "- id: d");
+    let diff = diff_models(&repo, &fixture(&[&renamed]).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(diff.renamed.len(), 2);
+    assert!(diff.orphaned.is_empty());
+
+    let one = twins.replace("id: a", "id: c");
+    let one = &one[..one.find("- id: b").unwrap()];
+    let report = service
+        .import(&fixture(&[one]).unwrap(), false)
+        .await
+        .unwrap();
+    assert_eq!(report.diff.renamed.len(), 1);
+    assert_eq!(find(&service, "c"), a);
+    let orphaned = report
+        .diff
+        .orphaned
+        .iter()
+        .map(|q| q.id)
+        .collect::<Vec<_>>();
+    assert_eq!(orphaned, vec![b]);
+}
+
+#[tokio::test]
+async fn scripted_answers_are_scored_and_recorded() {
+    let repo = memory_repository().await.unwrap();
+    import_fixture(&repo, &[CAPITALS, CARDS]).await.unwrap();
+    let mut service = Service::new(&repo).await.unwrap();
+    let script = Scripted::new(&["paris", "Barcelona", "Good"]);
+    script.install();
+
+    for (name, correct) in [("france", true), ("spain", false), ("hello", true)] {
+        let id = find(&service, name);
+        let score = service.get(id).runner.run().unwrap();
+        assert_eq!(score.is_correct(), correct, "{}", name);
+        service.add_answer(id, score).await.unwrap();
+    }
+    assert_eq!(script.remaining(), 0);
+
+    // Everything recorded must survive loading the service again.
+    let service = Service::new(&repo).await.unwrap();
+    let france = service.get(find(&service, "france"));
+    let spain = service.get(find(&service, "spain"));
+    assert!(france.probability > spain.probability);
+    assert_eq!(repo.get_answers(france.id).await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn bottom_selection_picks_the_least_known() {
+    let repo = memory_repository().await.unwrap();
+    import_fixture(&repo, &[CAPITALS]).await.unwrap();
+    let mut service = Service::new(&repo).await.unwrap();
+    for name in ["france", "italy"] {
+        let id = find(&service, name);
+        service.add_answer(id, Score::CORRECT).await.unwrap();
+    }
+
+    let selected = service.select("capitals", Method::Bottom, 1, Selection::All);
+    assert_eq!(selected, vec![find(&service, "spain")]);
+}
+
+#[tokio::test]
+async fn seeded_selection_is_deterministic() {
+    let repo = memory_repository().await.unwrap();
+    import_fixture(&repo, &[CAPITALS]).await.unwrap();
+    let mut service = Service::new(&repo).await.unwrap();
+
+    let mut runs = Vec::new();
+    for _ in 0..2 {
+        service.seed(7);
+        runs.push(service.select("capitals", Method::UniformRandom, 2, Selection::All));
+    }
+    assert_eq!(runs[0], runs[1]);
+}
+
+#[tokio::test]
+async fn seeded_weighted_selection_keeps_the_order_drawn() {
+    let repo = memory_repository().await.unwrap();
+    import_fixture(&repo, &[CAPITALS]).await.unwrap();
+
+    let mut runs = Vec::new();
+    for _ in 0..5 {
+        let mut service = Service::new(&repo).await.unwrap();
+        service.seed(3);
+        runs.push(service.select("capitals", Method::WeightedRandom, 3, Selection::All));
+    }
+    assert!(runs.iter().all(|run| *run == runs[0]), "{:?}", runs);
+}
+
+#[tokio::test]
+async fn archived_sets_are_hidden() {
+    let repo = memory_repository().await.unwrap();
+    import_fixture(&repo, &[CAPITALS, CARDS]).await.unwrap();
+    assert!(repo.set_archived("cards", true).await.unwrap());
+
+    let service = Service::new(&repo).await.unwrap();
+    assert_eq!(service.get_sets(), vec!["capitals"]);
+    assert!(service.is_archived("cards"));
+    assert_eq!(service.get_set("cards").len(), 1);
+}
+
+#[tokio::test]
+async fn shortcuts_interrupt_and_answers_can_be_undone() {
+    let repo = memory_repository().await.unwrap();
+    import_fixture(&repo, &[CAPITALS]).await.unwrap();
+    let mut service = Service::new(&repo).await.unwrap();
+    let id = find(&service, "france");
+    Scripted::new(&[":s"]).install();
+    let err = service.get(id).runner.run().unwrap_err();
+    assert_eq!(interrupted(&err), Some(Shortcut::Skip));
+
+    let before = service.get(id).probability;
+    service.add_answer(id, Score::CORRECT).await.unwrap();
+    assert!(service.undo_answer(id).await.unwrap());
+    assert!(!service.undo_answer(id).await.unwrap());
+    assert_eq!(service.get(id).probability, before);
+    let q = repo.get_question_by_id(id).await.unwrap();
+    assert_eq!((q.num_correct, q.last_answered_at), (1, None));
+    assert!(repo.get_answers(id).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn multiline_answers_are_compared_normalized() {
+    let definitions = "
+name: definitions
+type_: default
+data:
+  question_prefix: 'Define '
+  multiline: true
+items:
+- id: monad
+  question: a monad
+  answers:
+  - |
+    a monoid in the
+    category of endofunctors
+";
+    let repo = memory_repository().await.unwrap();
+    import_fixture(&repo, &[definitions]).await.unwrap();
+    let service = Service::new(&repo).await.unwrap();
+    let id = find(&service, "monad");
+    Scripted::new(&["A monoid in the category\n  of endofunctors\n\n"]).install();
+    assert_eq!(service.get(id).runner.run().unwrap(), Score::CORRECT);
+    Scripted::new(&["a monoid"]).install();
+    assert_eq!(service.get(id).runner.run().unwrap(), Score::WRONG);
+}
+
+#[tokio::test]
+async fn regex_answers_accept_variants() {
+    let colors = "
+name: colors
+type_: default
+data:
+  question_prefix: 'Spell '
+items:
+- id: color
+  question: color
+  answers:
+  - color
+  - 're:^colou?r$'
+";
+    let repo = memory_repository().await.unwrap();
+    import_fixture(&repo, &[colors]).await.unwrap();
+    let service = Service::new(&repo).await.unwrap();
+    let id = find(&service, "color");
+    assert_eq!(service.get(id).runner.answer_text(), "color");
+    Scripted::new(&["Colour", "colours"]).install();
+    assert_eq!(service.get(id).runner.run().unwrap(), Score::CORRECT);
+    assert_eq!(service.get(id).runner.run().unwrap(), Score::WRONG);
+}
+
+#[tokio::test]
+async fn patterns_are_never_shown_as_the_answer() {
+    let colors = "
+name: colors
+type_: default
+data:
+  question_prefix: 'Spell '
+  reverse:
+    question_prefix: 'Spelled '
+items:
+- id: color
+  question: color
+  answers:
+  - 're:^colou?r$'
+  display: colour (or color)
+- id: grey
+  question: grey
+  answers:
+  - 're:^gr[ae]y$'
+";
+    let repo = memory_repository().await.unwrap();
+    import_fixture(&repo, &[colors]).await.unwrap();
+    let service = Service::new(&repo).await.unwrap();
+    // The reversed questions keep the names of the originals.
+    let answer = |name: &str| {
+        let questions = service.get_questions();
+        let q = questions
+            .iter()
+            .find(|q| q.factory == "colors" && q.name == name);
+        q.unwrap().runner.answer_text()
+    };
+    assert_eq!(answer("color"), "colour (or color)");
+    assert_eq!(answer("grey"), "");
+    // Only the question with something to show can be asked the other way.
+    assert_eq!(service.get_set("colors-reversed").len(), 1);
+}
+
+#[tokio::test]
+async fn patterns_see_numbers_as_typed() {
+    let wonders = "
+name: wonders
+type_: default
+data:
+  question_prefix: 'How many '
+  normalize:
+    spelled_numbers: true
+items:
+- id: wonders
+  question: wonders of the ancient world were there?
+  answers:
+  - 're:^seven$'
+- id: dwarfs
+  question: dwarfs did Snow White meet?
+  answers:
+  - seven
+";
+    let repo = memory_repository().await.unwrap();
+    import_fixture(&repo, &[wonders]).await.unwrap();
+    let service = Service::new(&repo).await.unwrap();
+    let wonders = &service.get(find(&service, "wonders")).runner;
+    let dwarfs = &service.get(find(&service, "dwarfs")).runner;
+    Scripted::new(&["Seven", "7", "7"]).install();
+    assert_eq!(wonders.run().unwrap(), Score::CORRECT);
+    assert_eq!(wonders.run().unwrap(), Score::WRONG);
+    assert_eq!(dwarfs.run().unwrap(), Score::CORRECT);
+}
+
+#[tokio::test]
+async fn questions_can_compare_answers_their_own_way() {
+    let elements = "
+name: elements
+type_: default
+data:
+  question_prefix: 'The symbol of '
+  normalize:
+    ignore_punctuation: true
+items:
+- id: sodium
+  question: sodium
+  answers:
+  - Na
+  normalize:
+    case_fold: false
+- id: mister
+  question: mister
+  answers:
+  - Mr.
+";
+    let repo = memory_repository().await.unwrap();
+    import_fixture(&repo, &[elements]).await.unwrap();
+    let service = Service::new(&repo).await.unwrap();
+    let sodium = &service.get(find(&service, "sodium")).runner;
+    let mister = &service.get(find(&service, "mister")).runner;
+    Scripted::new(&["NA", "Na.", "mr", "MR!"]).install();
+    assert_eq!(sodium.run().unwrap(), Score::WRONG);
+    assert_eq!(sodium.run().unwrap(), Score::CORRECT);
+    assert_eq!(mister.run().unwrap(), Score::CORRECT);
+    assert_eq!(mister.run().unwrap(), Score::CORRECT);
+}
+
+#[tokio::test]
+async fn questions_can_ask_for_several_answers() {
+    let government = "
+name: government
+type_: default
+data:
+  question_prefix: 'Name the branches of '
+items:
+- id: branches
+  question: government
+  required: 3
+  answers:
+  - legislative
+  - executive
+  - re:^judicia(l|ry)$
+";
+    let repo = memory_repository().await.unwrap();
+    import_fixture(&repo, &[government]).await.unwrap();
+    let service = Service::new(&repo).await.unwrap();
+    let branches = &service.get(find(&service, "branches")).runner;
+    Scripted::new(&["Executive", "executive", "judiciary"]).install();
+    assert!((branches.run().unwrap().value() - 2. / 3.).abs() < 1e-9);
+    Scripted::new(&["judicial", "legislative", "executive"]).install();
+    assert_eq!(branches.run().unwrap(), Score::CORRECT);
+}
+
+#[tokio::test]
+async fn numeric_guesses_record_their_error() {
+    let areas = "
+name: areas
+type_: numeric_range
+data:
+  question_prefix: 'The area of '
+  range: 0.2
+items:
+- id: denmark
+  question: Denmark
+  answer: 40000
+";
+    let repo = memory_repository().await.unwrap();
+    import_fixture(&repo, &[areas]).await.unwrap();
+    let mut service = Service::new(&repo).await.unwrap();
+    let id = find(&service, "denmark");
+    Scripted::new(&["44k", "30k"]).install();
+    for _ in 0..2 {
+        let score = service.get(id).runner.run().unwrap();
+        let details = take_details();
+        service.add_answer_with(id, score, &details).await.unwrap();
+    }
+    assert_eq!(take_details().error, None);
+    let (mean, count) = service.average_error(id).await.unwrap().unwrap();
+    assert_eq!(count, 2);
+    assert!((mean - 0.175).abs() < 1e-9);
+    let errors = repo.get_answers(id).await.unwrap();
+    assert_eq!(errors[1].error, Some(-0.25));
+    assert_eq!(errors[1].text.as_deref(), Some("30k"));
+}
+
+#[tokio::test]
+async fn numeric_ranges_tighten_with_the_streak() {
+    let areas = "
+name: areas
+type_: numeric_range
+data:
+  question_prefix: 'The area of '
+  range: 0.2
+  tighten: [0.3, 0.1, 0.05]
+items:
+- id: denmark
+  question: Denmark
+  answer: 40000
+";
+    let repo = memory_repository().await.unwrap();
+    import_fixture(&repo, &[areas]).await.unwrap();
+    let mut service = Service::new(&repo).await.unwrap();
+    let id = find(&service, "denmark");
+    Scripted::new(&["50k", "44k", "42.5k", "50k"]).install();
+    let mut scores = Vec::new();
+    for _ in 0..4 {
+        let score = service.get(id).runner.run().unwrap();
+        let details = take_details();
+        service.add_answer_with(id, score, &details).await.unwrap();
+        scores.push(score.is_correct());
+    }
+    assert_eq!(scores, [true, true, false, true]);
+    let tolerances = repo
+        .get_answers(id)
+        .await
+        .unwrap()
+        .iter()
+        .map(|a| a.tolerance.unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(tolerances, [0.3, 0.1, 0.05, 0.3]);
+
+    // The streak carries over to the next session.
+    let service = Service::new(&repo).await.unwrap();
+    Scripted::new(&["45k"]).install();
+    assert_eq!(service.get(id).runner.run().unwrap(), Score::WRONG);
+}
+
+#[tokio::test]
+async fn numeric_guesses_can_be_given_in_other_units() {
+    let areas = "
+name: areas
+type_: numeric_range
+data:
+  question_prefix: 'The area of '
+  range: 0.1
+  unit: km²
+  units:
+    mi²: 2.59
+items:
+- id: denmark
+  question: Denmark
+  answer: 43094
+";
+    let repo = memory_repository().await.unwrap();
+    import_fixture(&repo, &[areas]).await.unwrap();
+    let service = Service::new(&repo).await.unwrap();
+    let denmark = &service.get(find(&service, "denmark")).runner;
+    Scripted::new(&["16.6k mi²", "16,000", "40 acres", "43k km²"]).install();
+    assert_eq!(denmark.run().unwrap(), Score::CORRECT);
+    assert_eq!(denmark.run().unwrap(), Score::WRONG);
+    assert_eq!(denmark.run().unwrap(), Score::CORRECT);
+}
+
+#[tokio::test]
+async fn recently_wrong_comes_first() {
+    let repo = memory_repository().await.unwrap();
+    import_fixture(&repo, &[CAPITALS]).await.unwrap();
+    let mut service = Service::new(&repo).await.unwrap();
+    let (france, spain, italy) = (
+        find(&service, "france"),
+        find(&service, "spain"),
+        find(&service, "italy"),
+    );
+    let now = chrono::Utc::now();
+    for _ in 0..3 {
+        service
+            .add_answer_at(spain, Score::WRONG, now - chrono::Duration::days(30))
+            .await
+            .unwrap();
+    }
+    service
+        .add_answer_at(italy, Score::WRONG, now)
+        .await
+        .unwrap();
+    service
+        .add_answer_at(france, Score::CORRECT, now)
+        .await
+        .unwrap();
+    let selected = service.select("capitals", Method::RecentlyWrong, 3, Selection::All);
+    assert_eq!(selected, vec![italy, spain, france]);
+}
+
+#[tokio::test]
+async fn reversed_questions_are_a_separate_set() {
+    let capitals = CAPITALS.replace(
+        "data:\n",
+        "data:\n  reverse:\n    question_prefix: 'Which country has the capital '\n",
+    );
+    let repo = memory_repository().await.unwrap();
+    import_fixture(&repo, &[capitals.as_str()]).await.unwrap();
+    let service = Service::new(&repo).await.unwrap();
+    assert_eq!(service.get_set("capitals").len(), 3);
+    let reversed = service.get_set("capitals-reversed");
+    assert_eq!(reversed.len(), 3);
+
+    let france = service
+        .get_questions()
+        .into_iter()
+        .filter(|q| q.name == "france")
+        .collect::<Vec<_>>();
+    assert_eq!(france.len(), 2);
+    let question = france
+        .into_iter()
+        .find(|q| q.factory == "capitals-reversed")
+        .unwrap();
+    assert_eq!(
+        question.runner.question_text(),
+        "Which country has the capital Paris?"
+    );
+    Scripted::new(&["france"]).install();
+    assert_eq!(question.runner.run().unwrap(), Score::CORRECT);
+}
+
+#[tokio::test]
+async fn sequences_score_their_steps_together() {
+    let proof = "
+name: proofs
+type_: sequence
+data: {}
+items:
+- id: sqrt2
+  question: Prove that the square root of 2 is irrational.
+  steps:
+  - question: Assume
+    answers:
+    - sqrt(2) = p/q
+  - question: Square
+    answers:
+    - 2q^2 = p^2
+  - question: Conclude
+    answers:
+    - p and q are even
+";
+    let repo = memory_repository().await.unwrap();
+    import_fixture(&repo, &[proof]).await.unwrap();
+    let mut service = Service::new(&repo).await.unwrap();
+    let id = find(&service, "sqrt2");
+    Scripted::new(&["sqrt(2) = p/q", "p^2 = 2", "P and Q are even"]).install();
+    let score = service.get(id).runner.run().unwrap();
+    assert!((score.value() - 2. / 3.).abs() < 1e-9);
+    let details = take_details();
+    service.add_answer_with(id, score, &details).await.unwrap();
+    let answers = repo.get_answers(id).await.unwrap();
+    assert_eq!(answers[0].steps.as_deref(), Some("+-+"));
+}
+
+#[test]
+fn shares_follow_the_weights() {
+    assert_eq!(weighted_shares(6, &[(2., 10), (1., 10)]), vec![4, 2]);
+    assert_eq!(weighted_shares(6, &[(2., 1), (1., 10)]), vec![1, 5]);
+    assert_eq!(weighted_shares(6, &[(0., 10), (1., 3)]), vec![0, 3]);
+}
+
+#[tokio::test]
+async fn weighted_unions_draw_by_weight() {
+    let weighted = "
+name: weighted
+type_: union
+data:
+  sets:
+  - capitals
+  - cards
+  weights:
+    capitals: 2
+";
+    let repo = memory_repository().await.unwrap();
+    import_fixture(&repo, &[CAPITALS, CARDS, weighted])
+        .await
+        .unwrap();
+    let mut service = Service::new(&repo).await.unwrap();
+    assert_eq!(
+        service.union_shares("weighted", 3, Selection::All),
+        Some(vec![("capitals".to_string(), 2), ("cards".to_string(), 1)])
+    );
+    let ids = service.select("weighted", Method::Bottom, 3, Selection::All);
+    assert_eq!(ids.len(), 3);
+    assert!(ids.contains(&find(&service, "hello")));
+    assert_eq!(service.union_shares("capitals", 3, Selection::All), None);
+}
+
+#[tokio::test]
+async fn empty_members_of_weighted_unions_get_no_share() {
+    let empty = "
+name: empty
+type_: default
+data:
+  question_prefix: ''
+items: []
+";
+    let weighted = "
+name: weighted
+type_: union
+data:
+  sets:
+  - empty
+  - capitals
+  weights:
+    empty: 5
+";
+    let repo = memory_repository().await.unwrap();
+    import_fixture(&repo, &[CAPITALS, empty, weighted])
+        .await
+        .unwrap();
+    let mut service = Service::new(&repo).await.unwrap();
+    assert!(service.get_set("empty").is_empty());
+    assert_eq!(
+        service.union_shares("weighted", 2, Selection::All),
+        Some(vec![("empty".to_string(), 0), ("capitals".to_string(), 2)])
+    );
+    let ids = service.select("weighted", Method::Bottom, 2, Selection::All);
+    assert_eq!(ids.len(), 2);
+}
+
+#[tokio::test]
+async fn threshold_sets_follow_the_probabilities() {
+    let weak = "
+name: weak
+type_: threshold
+data:
+  set: capitals
+  below: 0.6
+";
+    let repo = memory_repository().await.unwrap();
+    import_fixture(&repo, &[CAPITALS, weak]).await.unwrap();
+    let mut service = Service::new(&repo).await.unwrap();
+    assert_eq!(service.get_set("weak").len(), 3);
+    let france = find(&service, "france");
+    for _ in 0..5 {
+        service.add_answer(france, Score::CORRECT).await.unwrap();
+    }
+    assert_eq!(service.get_set("weak").len(), 3);
+    service.refresh_thresholds();
+    assert_eq!(service.get_set("weak").len(), 2);
+    assert!(!service.get_set("weak").contains(&france));
+}
+
+#[tokio::test]
+async fn excluded_questions_are_taken_out_of_sets() {
+    let repo = memory_repository().await.unwrap();
+    import_fixture(&repo, &[CAPITALS, CARDS, EVERYTHING])
+        .await
+        .unwrap();
+    let mut service = Service::new(&repo).await.unwrap();
+    assert_eq!(service.get_set("everything").len(), 4);
+
+    let excluding = format!(
+        "{}exclude:\n  sets: [cards]\n  questions: [capitals/france]\n",
+        EVERYTHING
+    );
+    service
+        .import(
+            &fixture(&[CAPITALS, CARDS, excluding.as_str()]).unwrap(),
+            false,
+        )
+        .await
+        .unwrap();
+    let everything = service.get_set("everything");
+    assert_eq!(everything.len(), 2);
+    assert!(!everything.contains(&find(&service, "france")));
+    assert!(!everything.contains(&find(&service, "hello")));
+}
+
+#[tokio::test]
+async fn questions_wait_for_their_prerequisites() {
+    let verbs = "
+name: verbs
+type_: default
+data:
+  question_prefix: 'Translate '
+items:
+- id: ser
+  question: to be
+  answers:
+  - ser
+- id: fue
+  question: was
+  answers:
+  - fue
+  requires:
+  - ser
+- id: era
+  question: used to be
+  answers:
+  - era
+  requires:
+  - verbs/fue
+";
+    let repo = memory_repository().await.unwrap();
+    import_fixture(&repo, &[verbs]).await.unwrap();
+    let mut service = Service::new(&repo).await.unwrap();
+    let (ser, fue) = (find(&service, "ser"), find(&service, "fue"));
+    assert_eq!(service.get_set_size("verbs", Selection::All), 1);
+    assert!(service.is_locked(fue));
+    for _ in 0..5 {
+        service.add_answer(ser, Score::CORRECT).await.unwrap();
+    }
+    assert!(!service.is_locked(fue));
+    assert_eq!(
+        service
+            .select("verbs", Method::Bottom, 3, Selection::All)
+            .len(),
+        2
+    );
+    service.set_prerequisite_probability(0.99);
+    assert!(service.is_locked(fue));
+}
+
+#[tokio::test]
+async fn day_streaks_count_days_in_a_row() {
+    let repo = memory_repository().await.unwrap();
+    import_fixture(&repo, &[CAPITALS]).await.unwrap();
+    let mut service = Service::new(&repo).await.unwrap();
+    let id = find(&service, "france");
+    let today = chrono::Local::now();
+    for days in [4, 2, 1] {
+        let time = today - chrono::Duration::days(days);
+        service
+            .add_answer_at(id, Score::CORRECT, time.with_timezone(&chrono::Utc))
+            .await
+            .unwrap();
+    }
+    assert_eq!(service.day_streak(today.date_naive()), 2);
+    service.add_answer(id, Score::WRONG).await.unwrap();
+    assert_eq!(service.day_streak(today.date_naive()), 3);
+}
+
+#[tokio::test]
+async fn loads_record_what_changed() {
+    let repo = memory_repository().await.unwrap();
+    let mut service = Service::new(&repo).await.unwrap();
+    let report = service
+        .import(&fixture(&[CAPITALS]).unwrap(), false)
+        .await
+        .unwrap();
+    repo.record_load(&report.to_load(&["capitals.yaml".into()]))
+        .await
+        .unwrap();
+    let changed = CAPITALS
+        .replace("id: france", "id: fr")
+        .replace("Rome", "Roma");
+    let report = service
+        .import(&fixture(&[&changed]).unwrap(), false)
+        .await
+        .unwrap();
+    repo.record_load(&report.to_load(&["capitals.yaml".into()]))
+        .await
+        .unwrap();
+
+    let loads = repo.get_loads().await.unwrap();
+    assert_eq!(loads.len(), 2);
+    assert_eq!(Load::entries(&loads[0].added).count(), 3);
+    assert_eq!(loads[0].files, "capitals.yaml\n");
+    assert_eq!(loads[1].updated, "capitals/italy\n");
+    assert_eq!(loads[1].renamed, "capitals/france -> fr\n");
+    assert!(loads[1].added.is_empty());
+}
+
+#[tokio::test]
+async fn broken_rows_are_skipped() {
+    let repo = MemoryStorage::new();
+    import_fixture(&repo, &[CAPITALS]).await.unwrap();
+    repo.insert_question("gone", "q", b"").await.unwrap();
+    let gone = repo.get_question_by_name("gone", "q").await.unwrap().id;
+    repo.insert_question_in_set("capitals", gone).await.unwrap();
+    let france = repo
+        .get_question_by_name("capitals", "france")
+        .await
+        .unwrap();
+    repo.insert_question("capitals", "broken", b"{not: [valid")
+        .await
+        .unwrap();
+
+    let service = Service::new(&repo).await.unwrap();
+    let skipped = service.skipped();
+    assert_eq!(skipped.questions, ["gone/q", "capitals/broken"]);
+    assert_eq!(skipped.memberships, 1);
+    assert_eq!(service.get_questions().len(), 3);
+    assert!(service.get_set("capitals").contains(&france.id));
+}
+
+#[tokio::test]
+async fn broken_sets_are_skipped() {
+    let repo = MemoryStorage::new();
+    import_fixture(&repo, &[CAPITALS, CARDS]).await.unwrap();
+    repo.insert_question_factory("plugged", "not-installed", b"{}")
+        .await
+        .unwrap();
+    repo.insert_question("plugged", "q", b"{}").await.unwrap();
+    repo.insert_question_factory("broken", "default", b"{not: [valid")
+        .await
+        .unwrap();
+    repo.insert_question("broken", "q", b"{}").await.unwrap();
+
+    let service = Service::new(&repo).await.unwrap();
+    let skipped = service.skipped();
+    let mut factories = skipped.factories.clone();
+    factories.sort();
+    assert_eq!(factories, ["broken", "plugged"]);
+    assert!(skipped.questions.contains(&"plugged/q".to_string()));
+    assert!(skipped.questions.contains(&"broken/q".to_string()));
+    assert!(!service.get_questions().is_empty());
+}
+
+#[tokio::test]
+async fn sets_can_have_their_own_model_params() {
+    let repo = memory_repository().await.unwrap();
+    let tuned = CAPITALS.replace(
+        "  question_prefix: 'The capital of '",
+        "  question_prefix: 'The capital of '\n  model:\n    prior_correct: 0\n    prior_total: 1",
+    );
+    import_fixture(&repo, &[&tuned, CARDS]).await.unwrap();
+    let mut service = Service::new(&repo).await.unwrap();
+    let france = repo
+        .get_question_by_name("capitals", "france")
+        .await
+        .unwrap();
+    let hello = repo.get_question_by_name("cards", "hello").await.unwrap();
+    assert_eq!(service.get(france.id).probability, 0.);
+    assert_eq!(service.get(hello.id).probability, 0.5);
+
+    service.add_answer(france.id, Score::CORRECT).await.unwrap();
+    assert_eq!(service.get(france.id).probability, 0.5);
+    let answers = repo.get_answers(france.id).await.unwrap();
+    let params: ModelParams = serde_json::from_str(answers[0].params.as_ref().unwrap()).unwrap();
+    assert_eq!(params.prior_total, 1.);
+    assert_eq!(params.decay, ModelParams::default().decay);
+}
+
+#[tokio::test]
+async fn hardest_tags_come_first() {
+    let repo = memory_repository().await.unwrap();
+    let tagged = CAPITALS
+        .replace("  - Paris", "  - Paris\n  tags: [west]")
+        .replace("  - Madrid", "  - Madrid\n  tags: [west]")
+        .replace("  - Rome", "  - Rome\n  tags: [south]");
+    import_fixture(&repo, &[&tagged]).await.unwrap();
+    let mut service = Service::new(&repo).await.unwrap();
+    let mut ids = Vec::new();
+    for name in ["france", "spain", "italy"] {
+        ids.push(
+            repo.get_question_by_name("capitals", name)
+                .await
+                .unwrap()
+                .id,
+        );
+    }
+    let [france, spain, italy] = ids[..] else {
+        unreachable!()
+    };
+    service.add_answer(france, Score::WRONG).await.unwrap();
+    service.add_answer(spain, Score::CORRECT).await.unwrap();
+    service.add_answer(italy, Score::WRONG).await.unwrap();
+    service.add_answer(italy, Score::WRONG).await.unwrap();
+
+    let ids = service.get_set("capitals");
+    let tags = service.difficulty_by_tag(ids);
+    assert_eq!(tags[0].name, "south");
+    assert_eq!(tags[1].name, "west");
+    assert_eq!(tags[1].questions, 2);
+    assert_eq!(tags[1].first_try_accuracy, Some(0.5));
+    assert_eq!(
+        service.select("capitals", Method::HardestTags, 3, Selection::All),
+        [italy, france, spain]
+    );
+}
+
+#[tokio::test]
+async fn linked_questions_share_answers() {
+    let repo = memory_repository().await.unwrap();
+    let reversed = CAPITALS.replace("name: capitals", "name: reversed");
+    import_fixture(&repo, &[CAPITALS, &reversed]).await.unwrap();
+    let mut ids = Vec::new();
+    for factory in ["capitals", "reversed"] {
+        ids.push(
+            repo.get_question_by_name(factory, "france")
+                .await
+                .unwrap()
+                .id,
+        );
+    }
+    let italy = repo
+        .get_question_by_name("reversed", "italy")
+        .await
+        .unwrap()
+        .id;
+    repo.link_questions(ids[0], ids[1]).await.unwrap();
+    repo.link_questions(italy, ids[1]).await.unwrap();
+
+    let mut service = Service::new(&repo).await.unwrap();
+    assert_eq!(service.linked(ids[0]).len(), 2);
+    service.add_answer(ids[0], Score::CORRECT).await.unwrap();
+    let answered = service.get(ids[0]).probability;
+    assert!(answered > 0.5);
+    assert_eq!(service.get(ids[1]).probability, answered);
+    assert_eq!(service.get(italy).probability, answered);
+    let stored = repo.get_question_by_id(italy).await.unwrap();
+    assert_eq!(stored.probability, answered);
+
+    service.undo_answer(ids[0]).await.unwrap();
+    assert_eq!(service.get(italy).probability, 0.5);
+    // Loading again gives the same as answering.
+    service.add_answer(ids[1], Score::WRONG).await.unwrap();
+    let answered = service.get(ids[0]).probability;
+    let service = Service::new(&repo).await.unwrap();
+    assert_eq!(service.get(ids[0]).probability, answered);
+}
+
+#[tokio::test]
+async fn sets_can_be_followed_by_the_next() {
+    let repo = memory_repository().await.unwrap();
+    let chained = CAPITALS.replace(
+        "  question_prefix: 'The capital of '",
+        "  question_prefix: 'The capital of '\n  next: cards",
+    );
+    import_fixture(&repo, &[&chained, CARDS]).await.unwrap();
+    let service = Service::new(&repo).await.unwrap();
+    assert_eq!(service.next_set("capitals"), Some("cards"));
+    assert_eq!(service.next_set("cards"), None);
+
+    let problems = validate_models(&fixture(&[&chained]).unwrap());
+    assert_eq!(problems, ["capitals: next set \"cards\" doesn't exist"]);
+}
+
+async fn rename_referred_sets(repo: &dyn Storage) {
+    let chained = CARDS.replace("data: {}", "data:\n  next: capitals");
+    let weighted = "
+name: weighted
+type_: union
+data:
+  sets:
+  - capitals
+  - cards
+  weights:
+    capitals: 2
+";
+    let weak = "
+name: weak
+type_: threshold
+data:
+  set: capitals
+  below: 0.6
+";
+    import_fixture(repo, &[CAPITALS, &chained, weighted, weak])
+        .await
+        .unwrap();
+    repo.rename_set("capitals", "caps").await.unwrap();
+    let service = Service::new(repo).await.unwrap();
+    assert_eq!(
+        service.union_shares("weighted", 3, Selection::All),
+        Some(vec![("caps".to_string(), 2), ("cards".to_string(), 1)])
+    );
+    assert_eq!(service.get_set("weighted").len(), 4);
+    assert_eq!(service.get_set("weak").len(), 3);
+    assert_eq!(service.next_set("cards"), Some("caps"));
+}
+
+#[tokio::test]
+async fn renaming_a_set_updates_the_sets_referring_to_it() {
+    rename_referred_sets(&memory_repository().await.unwrap()).await;
+    rename_referred_sets(&MemoryStorage::new()).await;
+}
+
+#[test]
+fn unknown_types_are_reported_with_their_file() {
+    let path = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("unknown.yaml");
+    let unknown = CARDS.replace("type_: flashcard", "type_: flashcards");
+    std::fs::write(&path, unknown).unwrap();
+    let err = load_models(&[path]).err().unwrap();
+    let message = format!("{:#}", err);
+    assert!(message.contains("unknown.yaml"), "{}", message);
+    assert!(message.contains("\"flashcards\""), "{}", message);
+}
@@ -0,0 +1,67 @@
+use rust::functionality::{Method, Selection, Service};
+use rust::spacing::space;
+use rust::testing::{import_fixture, memory_repository};
+
+#[test]
+fn related_items_are_kept_apart() {
+    // Items are related when they have the same parity.
+    let spaced = space(&[1, 3, 5, 2, 4, 6], 2, |a, b| a % 2 == b % 2);
+    assert_eq!(spaced, vec![1, 2, 3, 4, 5, 6]);
+    // Impossible to satisfy, so the order is kept where nothing fits.
+    assert_eq!(space(&[1, 3, 2], 3, |a, b| a % 2 == b % 2), vec![1, 2, 3]);
+    assert_eq!(space(&[1, 3, 5], 1, |_, _| true), vec![1, 3, 5]);
+}
+
+const COUNTRIES: &str = "
+name: countries
+type_: default
+data:
+  question_prefix: ''
+items:
+- id: capital_of_france
+  question: The capital of France
+  answers: [Paris]
+- id: paris
+  question: Paris is the capital of
+  answers: [France]
+- id: denmark
+  question: The capital of Denmark
+  answers: [Copenhagen]
+  tags: [nordic]
+- id: norway
+  question: The capital of Norway
+  answers: [Oslo]
+  tags: [nordic]
+";
+
+#[tokio::test]
+async fn reverse_questions_and_shared_tags_are_siblings() {
+    let repo = memory_repository().await.unwrap();
+    import_fixture(&repo, &[COUNTRIES]).await.unwrap();
+    let mut service = Service::new(&repo).await.unwrap();
+    let id = |name: &str| {
+        service
+            .get_questions()
+            .iter()
+            .find(|q| q.name == name)
+            .unwrap()
+            .id
+    };
+    let (france, paris, denmark, norway) = (
+        id("capital_of_france"),
+        id("paris"),
+        id("denmark"),
+        id("norway"),
+    );
+    assert!(service.are_siblings(france, paris));
+    assert!(service.are_siblings(denmark, norway));
+    assert!(!service.are_siblings(france, denmark));
+
+    let mut ids = service.select("countries", Method::Bottom, 4, Selection::All);
+    for _ in 0..20 {
+        service.shuffle(&mut ids, 2);
+        for pair in ids.windows(2) {
+            assert!(!service.are_siblings(pair[0], pair[1]));
+        }
+    }
+}
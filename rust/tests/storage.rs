@@ -0,0 +1,163 @@
+use chrono::{NaiveDate, TimeZone, Utc};
+use rust::db::{AnswerDetails, Goal, SetMembership};
+use rust::functionality::{Score, Service};
+use rust::storage::{MemoryStorage, Storage};
+use rust::testing::{fixture, import_fixture, memory_repository};
+
+const CAPITALS: &str = "
+name: capitals
+type_: default
+data:
+  question_prefix: 'The capital of '
+items:
+- id: france
+  question: France
+  answers:
+  - Paris
+- id: spain
+  question: Spain
+  answers:
+  - Madrid
+";
+
+/// Imports, answers, renames and merges questions and renames the set, returning what ends up
+/// stored with ids and timestamps left out.
+async fn exercise(repo: &dyn Storage) -> Vec<String> {
+    import_fixture(repo, &[CAPITALS]).await.unwrap();
+    let mut service = Service::new(repo).await.unwrap();
+    let ids = service
+        .get_questions()
+        .iter()
+        .map(|q| q.id)
+        .collect::<Vec<_>>();
+    for id in ids {
+        service.add_answer(id, Score::CORRECT).await.unwrap();
+    }
+    let renamed = CAPITALS.replace("id: spain", "id: es");
+    service
+        .import(&fixture(&[&renamed]).unwrap(), false)
+        .await
+        .unwrap();
+    repo.insert_question("capitals", "spain", b"old")
+        .await
+        .unwrap();
+    let old = repo
+        .get_question_by_name("capitals", "spain")
+        .await
+        .unwrap();
+    repo.add_answer(
+        old.id,
+        Utc.timestamp_opt(0, 0).unwrap(),
+        false,
+        0.,
+        &AnswerDetails::default(),
+        0.4,
+    )
+    .await
+    .unwrap();
+    let es = repo.get_question_by_name("capitals", "es").await.unwrap();
+    repo.merge_questions(es.id, &[old.id]).await.unwrap();
+    repo.set_goal(&Goal {
+        set_name: "capitals".to_string(),
+        target: 0.9,
+        deadline: NaiveDate::from_ymd_opt(2030, 1, 1).unwrap(),
+    })
+    .await
+    .unwrap();
+    repo.rename_set("capitals", "caps").await.unwrap();
+    assert!(repo.rename_set("caps", "caps").await.is_err());
+    repo.rename_question("caps", "es", "spain").await.unwrap();
+
+    let mut stored = Vec::new();
+    for q in repo.get_all_questions().await.unwrap() {
+        let answers = repo.get_answers(q.id).await.unwrap();
+        stored.push(format!(
+            "{}/{} {}+{} p={:.3} sets={:?} answers={:?}",
+            q.factory,
+            q.name,
+            q.num_correct,
+            q.num_incorrect,
+            q.probability,
+            repo.get_sets_of_question(q.id).await.unwrap(),
+            answers.iter().map(|a| a.correct).collect::<Vec<_>>()
+        ));
+    }
+    for g in repo.get_all_goals().await.unwrap() {
+        stored.push(format!("goal {}", g.set_name));
+    }
+    stored.sort();
+    stored
+}
+
+#[tokio::test]
+async fn memory_storage_behaves_like_sqlite() {
+    let sqlite = exercise(&memory_repository().await.unwrap()).await;
+    let memory = exercise(&MemoryStorage::new()).await;
+    assert_eq!(memory, sqlite);
+    assert_eq!(memory.len(), 3);
+    assert!(memory[0].starts_with("caps/france"), "{:?}", memory);
+    // The merged question counts the answers of both, and its start once.
+    assert_eq!(
+        memory[1],
+        "caps/spain 2+2 p=0.513 sets=[\"caps\"] answers=[false, true]"
+    );
+}
+
+/// Takes France out of the set and prunes Spain in the import, returning
+/// what's left.
+async fn prune(repo: &dyn Storage) -> Vec<String> {
+    import_fixture(repo, &[CAPITALS]).await.unwrap();
+    let spain = repo
+        .get_question_by_name("capitals", "spain")
+        .await
+        .unwrap();
+    repo.add_answer(
+        spain.id,
+        Utc::now(),
+        true,
+        1.,
+        &AnswerDetails::default(),
+        0.6,
+    )
+    .await
+    .unwrap();
+    let models = fixture(&[CAPITALS]).unwrap();
+    let france = SetMembership {
+        set: "capitals".to_string(),
+        factory: "capitals".to_string(),
+        name: "france".to_string(),
+    };
+    repo.import(
+        &models.questions[..1],
+        &models.factories,
+        &[],
+        &[],
+        &[france],
+        &[spain.id],
+    )
+    .await
+    .unwrap();
+
+    let mut left = Vec::new();
+    for q in repo.get_all_questions().await.unwrap() {
+        left.push(format!(
+            "{}/{} sets={:?}",
+            q.factory,
+            q.name,
+            repo.get_sets_of_question(q.id).await.unwrap()
+        ));
+    }
+    left.push(format!(
+        "answers={}",
+        repo.get_all_answers().await.unwrap().len()
+    ));
+    left
+}
+
+#[tokio::test]
+async fn imports_take_questions_out_of_sets_and_prune() {
+    let sqlite = prune(&memory_repository().await.unwrap()).await;
+    let memory = prune(&MemoryStorage::new()).await;
+    assert_eq!(memory, sqlite);
+    assert_eq!(memory, ["capitals/france sets=[]", "answers=0"]);
+}
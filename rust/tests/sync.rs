@@ -0,0 +1,99 @@
+use rust::db::Repository;
+use rust::functionality::{Score, Service};
+use rust::storage::Storage;
+use rust::sync::{read_log, sync};
+use rust::testing::{import_fixture, memory_repository};
+use sqlx::sqlite::SqliteConnectOptions;
+use sqlx::ConnectOptions;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+const CAPITALS: &str = "
+name: capitals
+type_: default
+data:
+  question_prefix: 'The capital of '
+items:
+- id: france
+  question: France
+  answers:
+  - Paris
+- id: spain
+  question: Spain
+  answers:
+  - Madrid
+";
+
+#[tokio::test]
+async fn answers_travel_through_the_log_once() {
+    let dir = std::env::temp_dir().join(format!("trivial-sync-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let log = dir.join("answers.jsonl");
+    let laptop = memory_repository().await.unwrap();
+    let phone = memory_repository().await.unwrap();
+    for repo in [&laptop, &phone] {
+        import_fixture(repo, &[CAPITALS]).await.unwrap();
+        let mut service = Service::new(repo).await.unwrap();
+        for q in service
+            .get_questions()
+            .iter()
+            .map(|q| q.id)
+            .collect::<Vec<_>>()
+        {
+            service.add_answer(q, Score::CORRECT).await.unwrap();
+        }
+    }
+
+    let counts = sync(&laptop, &log, "laptop").await.unwrap();
+    assert_eq!((counts.imported, counts.exported), (0, 2));
+    let counts = sync(&phone, &log, "phone").await.unwrap();
+    assert_eq!((counts.imported, counts.exported), (2, 2));
+    let counts = sync(&laptop, &log, "laptop").await.unwrap();
+    assert_eq!((counts.imported, counts.exported), (2, 0));
+    let counts = sync(&phone, &log, "phone").await.unwrap();
+    assert_eq!((counts.imported, counts.exported), (0, 0));
+
+    assert_eq!(read_log(&log).unwrap().len(), 4);
+    assert_eq!(laptop.get_all_answers().await.unwrap().len(), 4);
+    assert_eq!(phone.get_all_answers().await.unwrap().len(), 4);
+    let service = Service::new(&laptop).await.unwrap();
+    let q = service.get_questions()[0].id;
+    assert_eq!(service.streak(q), 2);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+/// Answers left behind by deleting a question by hand, see `trivial fsck`.
+#[tokio::test]
+async fn answers_to_missing_questions_are_skipped() {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("sync-orphans");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let url = format!("sqlite://{}?mode=rwc", dir.join("trivial.db").display());
+    let repo = Repository::new(&url).await.unwrap();
+    import_fixture(&repo, &[CAPITALS]).await.unwrap();
+    let mut service = Service::new(&repo).await.unwrap();
+    for q in service
+        .get_questions()
+        .iter()
+        .map(|q| q.id)
+        .collect::<Vec<_>>()
+    {
+        service.add_answer(q, Score::CORRECT).await.unwrap();
+    }
+    let mut conn = SqliteConnectOptions::from_str(&url)
+        .unwrap()
+        .foreign_keys(false)
+        .connect()
+        .await
+        .unwrap();
+    sqlx::query("DELETE FROM questions WHERE name = 'france';")
+        .execute(&mut conn)
+        .await
+        .unwrap();
+
+    let log = dir.join("answers.jsonl");
+    let counts = sync(&repo, &log, "laptop").await.unwrap();
+    assert_eq!((counts.exported, counts.orphaned), (1, 1));
+    assert_eq!(read_log(&log).unwrap()[0].question, "spain");
+}
@@ -0,0 +1,95 @@
+use anyhow::{bail, Result};
+use rust::frontend::{self, Frontend, Validator};
+use rust::functionality::{parse_models, validate_models, Score, Service};
+use rust::testing::{import_fixture, memory_repository};
+use std::rc::Rc;
+use std::thread::sleep;
+use std::time::Duration;
+
+const WORDS: &str = "
+name: words
+type_: vocab
+data:
+  time_limit: 0.2
+items:
+- id: pernicious
+  word: pernicious
+  definition: harmful
+  example: a pernicious influence
+  translations:
+  - skadelig
+";
+
+/// Answers right, taking its time to answer and to read the definition.
+struct Slow {
+    answering: Duration,
+    reading: Duration,
+}
+
+impl Frontend for Slow {
+    fn text(&self, _: &str, _: Option<&str>, _: Option<Validator>) -> Result<String> {
+        sleep(self.answering);
+        Ok("skadelig".to_string())
+    }
+
+    fn editor(&self, message: &str, _: Option<&str>) -> Result<String> {
+        bail!("unexpected editor {:?}", message)
+    }
+
+    fn confirm(&self, _: &str) -> Result<bool> {
+        Ok(true)
+    }
+
+    fn select(&self, message: &str, _: &[String]) -> Result<usize> {
+        bail!("unexpected select {:?}", message)
+    }
+
+    fn pause(&self, _: &str) -> Result<()> {
+        sleep(self.reading);
+        Ok(())
+    }
+}
+
+async fn answer(answering: f64, reading: f64) -> Score {
+    let repo = memory_repository().await.unwrap();
+    import_fixture(&repo, &[WORDS]).await.unwrap();
+    let service = Service::new(&repo).await.unwrap();
+    let id = service.get_questions()[0].id;
+    let previous = frontend::set(Rc::new(Slow {
+        answering: Duration::from_secs_f64(answering),
+        reading: Duration::from_secs_f64(reading),
+    }));
+    let score = service.get(id).runner.run().unwrap();
+    frontend::set(previous);
+    score
+}
+
+#[tokio::test]
+async fn reading_the_feedback_doesnt_count_towards_the_time_limit() {
+    assert_eq!(answer(0., 0.4).await, Score::CORRECT);
+}
+
+#[tokio::test]
+async fn answers_after_the_time_limit_get_the_late_score() {
+    assert_eq!(answer(0.4, 0.).await, Score::WRONG);
+}
+
+#[test]
+fn time_limits_and_late_scores_are_checked() {
+    for (from, to, problem) in [
+        (
+            "time_limit: 0.2",
+            "time_limit: -1",
+            "set words: time_limit -1 isn't a number of seconds",
+        ),
+        (
+            "time_limit: 0.2",
+            "time_limit: 0.2\n  late_score: 2",
+            "set words: late_score 2 isn't between 0 and 1",
+        ),
+    ] {
+        let models = parse_models(&[WORDS.replace(from, to)]).unwrap();
+        assert_eq!(validate_models(&models), [problem]);
+    }
+    assert!(validate_models(&parse_models(&[WORDS]).unwrap()).is_empty());
+}
@@ -0,0 +1,47 @@
+use rust::functionality::Score;
+use rust::storage::Storage;
+use rust::testing::memory_repository;
+use rust::versus::Game;
+
+fn players(names: &[&str]) -> Vec<String> {
+    names.iter().map(|n| n.to_string()).collect()
+}
+
+#[tokio::test]
+async fn players_take_turns_and_keep_their_totals() {
+    let repo = memory_repository().await.unwrap();
+    assert!(Game::new(&players(&["ana"])).is_err());
+    assert!(Game::new(&players(&["ana", "ana"])).is_err());
+
+    let mut game = Game::new(&players(&["ana", "ben"])).unwrap();
+    assert!(!game.started());
+    for score in [
+        Score::CORRECT,
+        Score::WRONG,
+        Score::new(0.5),
+        Score::CORRECT,
+    ] {
+        game.record(score);
+    }
+    game.pass();
+    assert_eq!(game.current().name, "ben");
+    let winners = game.winners();
+    assert_eq!(winners.len(), 1);
+    assert_eq!(winners[0].name, "ana");
+    assert_eq!(winners[0].points, 1.5);
+    game.save(&repo).await.unwrap();
+
+    // A draw is no one's win.
+    let mut game = Game::new(&players(&["ben", "ana"])).unwrap();
+    game.record(Score::CORRECT);
+    game.record(Score::CORRECT);
+    assert_eq!(game.winners().len(), 2);
+    game.save(&repo).await.unwrap();
+
+    let users = repo.get_users().await.unwrap();
+    let (ana, ben) = (&users[0], &users[1]);
+    assert_eq!((ana.name.as_str(), ana.games, ana.wins), ("ana", 2, 1));
+    assert_eq!((ana.answers, ana.correct, ana.points), (3, 2, 2.5));
+    assert_eq!((ben.name.as_str(), ben.games, ben.wins), ("ben", 2, 0));
+    assert_eq!((ben.answers, ben.correct, ben.points), (3, 2, 2.));
+}
@@ -0,0 +1,27 @@
+use rust::worksheet::Worksheet;
+
+fn sheet() -> Worksheet {
+    Worksheet {
+        title: "capitals".to_string(),
+        items: vec![
+            ("The capital of France?".to_string(), "Paris".to_string()),
+            ("The capital of Spain?".to_string(), "Madrid".to_string()),
+        ],
+    }
+}
+
+#[test]
+fn markdown_worksheets_end_with_the_answers() {
+    let markdown = sheet().to_markdown();
+    assert!(markdown.starts_with("# capitals\n\n1. The capital of France?\n"));
+    let key = markdown.split("## Answer key").nth(1).unwrap();
+    assert_eq!(key, "\n\n1. Paris\n2. Madrid\n");
+}
+
+#[test]
+fn pdf_worksheets_have_a_page_for_the_answers() {
+    let pdf = sheet().to_pdf();
+    assert!(pdf.starts_with(b"%PDF-"));
+    let text = String::from_utf8_lossy(&pdf);
+    assert!(text.contains("/Count 2"));
+}
@@ -0,0 +1,2 @@
+ALTER TABLE answers ADD COLUMN score REAL NOT NULL DEFAULT 0;
+UPDATE answers SET score = correct;
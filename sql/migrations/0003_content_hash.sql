@@ -0,0 +1,2 @@
+ALTER TABLE questions ADD COLUMN content_hash TEXT;
+CREATE INDEX IF NOT EXISTS index_questions_content_hash ON questions(factory, content_hash);
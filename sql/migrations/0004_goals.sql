@@ -0,0 +1,5 @@
+CREATE TABLE IF NOT EXISTS goals (
+    set_name TEXT PRIMARY KEY,
+    target REAL NOT NULL,
+    deadline TEXT NOT NULL
+);
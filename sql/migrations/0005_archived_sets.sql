@@ -0,0 +1,4 @@
+CREATE TABLE IF NOT EXISTS archived_sets (
+    name TEXT PRIMARY KEY,
+    archived_at INTEGER NOT NULL
+);
@@ -0,0 +1 @@
+ALTER TABLE answers ADD COLUMN error REAL;
@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS loads (
+    id INTEGER PRIMARY KEY,
+    time TIMESTAMP NOT NULL,
+    files TEXT NOT NULL,
+    added TEXT NOT NULL,
+    updated TEXT NOT NULL,
+    renamed TEXT NOT NULL,
+    pruned TEXT NOT NULL
+);
@@ -0,0 +1,52 @@
+-- SQLite can't add foreign keys to existing tables, so they are rebuilt.
+-- Migrations run with foreign keys off, rows referring to missing rows are
+-- kept for `trivial fsck` to report.
+CREATE TABLE new_questions (
+    id INTEGER PRIMARY KEY,
+    factory TEXT NOT NULL REFERENCES question_factories(name) ON UPDATE CASCADE,
+    name TEXT NOT NULL,
+    created_at INTEGER NOT NULL,
+    last_answered_at INTEGER,
+    probability REAL NOT NULL,
+    num_correct INTEGER NOT NULL,
+    num_incorrect INTEGER NOT NULL,
+    data BLOB NOT NULL,
+    content_hash TEXT,
+    UNIQUE(factory, name)
+);
+INSERT INTO new_questions(id, factory, name, created_at, last_answered_at, probability, num_correct, num_incorrect, data, content_hash)
+SELECT id, factory, name, created_at, last_answered_at, probability, num_correct, num_incorrect, data, content_hash FROM questions;
+
+CREATE TABLE new_answers (
+    id INTEGER PRIMARY KEY,
+    question_id INTEGER REFERENCES questions(id) ON DELETE CASCADE,
+    time INTEGER,
+    correct INTEGER,
+    score REAL NOT NULL DEFAULT 0,
+    error REAL,
+    text TEXT,
+    steps TEXT
+);
+INSERT INTO new_answers(id, question_id, time, correct, score, error, text, steps)
+SELECT id, question_id, time, correct, score, error, text, steps FROM answers;
+
+CREATE TABLE new_question_sets (
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL,
+    question_id INTEGER NOT NULL REFERENCES questions(id) ON DELETE CASCADE,
+    UNIQUE(name, question_id)
+);
+INSERT INTO new_question_sets(id, name, question_id)
+SELECT id, name, question_id FROM question_sets;
+
+DROP TABLE answers;
+DROP TABLE question_sets;
+DROP TABLE questions;
+ALTER TABLE new_questions RENAME TO questions;
+ALTER TABLE new_answers RENAME TO answers;
+ALTER TABLE new_question_sets RENAME TO question_sets;
+
+CREATE INDEX index_questions ON questions(factory, name);
+CREATE INDEX index_questions_content_hash ON questions(factory, content_hash);
+CREATE INDEX index_answers ON answers(question_id, time);
+CREATE INDEX index_question_sets_question ON question_sets(question_id);
@@ -0,0 +1,8 @@
+-- Broken rows moved aside by `trivial fsck --quarantine`, as JSON objects of
+-- their columns with blobs in hex.
+CREATE TABLE IF NOT EXISTS quarantine (
+    id INTEGER PRIMARY KEY,
+    time TIMESTAMP NOT NULL,
+    source TEXT NOT NULL,
+    row TEXT NOT NULL
+);
@@ -0,0 +1 @@
+ALTER TABLE answers ADD COLUMN params TEXT;
@@ -0,0 +1,8 @@
+-- Questions asking the same thing in different sets, whose answers count for
+-- each other. Stored with a < b.
+CREATE TABLE IF NOT EXISTS question_links (
+    a INTEGER NOT NULL REFERENCES questions(id) ON DELETE CASCADE,
+    b INTEGER NOT NULL REFERENCES questions(id) ON DELETE CASCADE,
+    PRIMARY KEY(a, b)
+);
+CREATE INDEX IF NOT EXISTS index_question_links_b ON question_links(b);
@@ -0,0 +1,7 @@
+-- Files in the assets directory that questions refer to, with their hashes
+-- as of the last load, see `assets::verify`.
+CREATE TABLE IF NOT EXISTS assets (
+    path TEXT PRIMARY KEY NOT NULL,
+    sha256 TEXT NOT NULL,
+    size INTEGER NOT NULL
+);